@@ -282,6 +282,9 @@ async function generateTerrain() {
     
     const atlasWidth = config.cols * grid.innerSize;
     const atlasHeight = config.rows * grid.innerSize;
+    if (!grid.atlas) {
+      throw new Error("Tile grid was generated without an atlas");
+    }
     const heightAtlas = RawTexture.CreateRTexture(grid.atlas, atlasWidth, atlasHeight, scene, false, false, Texture.NEAREST_SAMPLINGMODE);
     
     currentMaterial = createAtlasMaterial(scene, {
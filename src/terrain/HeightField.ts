@@ -2,8 +2,13 @@ export class HeightField {
   readonly size: number; // N x N
   readonly data: Float32Array;
 
-  constructor(size: number, fill = 0) {
+  // `fill` may also be an existing size × size array, which is wrapped without copying
+  constructor(size: number, fill: number | Float32Array = 0) {
     this.size = size;
+    if (fill instanceof Float32Array) {
+      this.data = fill;
+      return;
+    }
     this.data = new Float32Array(size * size);
     if (fill !== 0) this.data.fill(fill);
   }
@@ -2,14 +2,28 @@
 import init, { 
     HeightField as WasmHeightField, 
     BiomeType,
+    TileGridOutput,
     generate_continuous_tile_grid,
     init as initWasm 
 } from 'genesis-terrain-wasm';
 
 // Import the existing types for compatibility
-import type { BiomeParams, ContinuousGrid } from './types';
+import type { BiomeParams, ContinuousGrid, WaterFeatures } from './types';
 import { HeightField } from './HeightField';
 
+// Plain object generate_continuous_tile_grid returns; `atlas` and `waterFeatures` are missing
+// with TileGridOutput.TilesOnly and `tiles` is empty with TileGridOutput.AtlasOnly
+interface WasmTileGrid {
+    tiles: Array<{ size: number; data: Float32Array }>;
+    innerSize: number;
+    atlas?: Float32Array;
+    atlasSize: number;
+    rects: Array<{ u0: number; v0: number; u1: number; v1: number }>;
+    skirtDepths: Float32Array;
+    edgeErrors: Float32Array;
+    waterFeatures?: WaterFeatures;
+}
+
 export class WasmTerrainGenerator {
     private static initialized = false;
 
@@ -57,9 +71,18 @@ export class WasmTerrainGenerator {
         tileSize: number;
         overlap: number;
         baseSize?: number;
+        steps?: number;
+        // Pick just enough steps to cover the atlas instead of `steps` (faster on large grids)
+        autoSteps?: boolean;
         seed: number;
         seaLevel?: number;
         erosionYears?: number;
+        // Rerun the water system at atlas resolution for crisp rivers (slower)
+        recomputeWater?: boolean;
+        // Leave the atlas or the tile copies out of the result to save memory on large grids
+        output?: TileGridOutput;
+        // Called with each tile as soon as it is cut, before the whole grid is done
+        onTile?: (tile: { size: number; data: Float32Array }, index: number, rect: { u0: number; v0: number; u1: number; v1: number }) => void;
     }, _biome: BiomeParams, biomeName?: string): Promise<ContinuousGrid> {
         await this.initialize();
 
@@ -77,35 +100,25 @@ export class WasmTerrainGenerator {
                 cfg.tileSize,
                 cfg.overlap,
                 cfg.baseSize ?? 64,
-                4, // steps
+                cfg.steps ?? 4,
+                cfg.autoSteps ?? true,
                 cfg.seed,
                 biomeType,
                 cfg.seaLevel ?? 0.0,
-                cfg.erosionYears ?? 0.0
-            ) as any; // Type assertion for JS interop
+                cfg.erosionYears ?? 0.0,
+                cfg.recomputeWater ?? false,
+                cfg.onTile,
+                cfg.output ?? TileGridOutput.TilesAndAtlas
+            ) as WasmTileGrid;
 
             const wasmTime = performance.now() - startTime;
             console.log(`⚡ WASM terrain generation took: ${wasmTime.toFixed(2)}ms`);
             
             const conversionStart = performance.now();
 
-            // Convert WASM result to JavaScript format
-            const tiles: HeightField[] = [];
-            const tilesArray = result.tiles;
-            
-            for (let i = 0; i < tilesArray.length; i++) {
-                const wasmTile = tilesArray[i];
-                const jsTile = new HeightField(wasmTile.size);
-                const data = wasmTile.data;
-                
-                // Use set() for fast bulk copy instead of element-by-element
-                jsTile.data.set(data);
-                
-                tiles.push(jsTile);
-            }
-
-            // Use the Float32Array directly instead of copying
-            const atlasArray = result.atlas;
+            // Tile data already lives in JS-owned Float32Arrays, so the tiles wrap them
+            // instead of copying
+            const tiles = result.tiles.map(tile => new HeightField(tile.size, tile.data));
 
             // Convert rects array
             const rects: Array<{u0: number; v0: number; u1: number; v1: number}> = [];
@@ -122,9 +135,11 @@ export class WasmTerrainGenerator {
             const grid: ContinuousGrid = {
                 tiles,
                 innerSize: result.innerSize,
-                atlas: atlasArray,
+                atlas: result.atlas,
                 atlasSize: result.atlasSize,
-                rects
+                rects,
+                skirtDepths: result.skirtDepths,
+                edgeErrors: result.edgeErrors
             };
 
             // Add water features if available
@@ -11,11 +11,16 @@ export interface WaterFeatures {
 }
 
 export interface ContinuousGrid {
+  // Empty when generated with TileGridOutput.AtlasOnly; cut tiles from the atlas with `rects`
   tiles: HeightField[];
   innerSize: number;
-  atlas: Float32Array;
+  // Missing when generated with TileGridOutput.TilesOnly
+  atlas?: Float32Array;
   atlasSize: number;
   rects: Array<{u0: number; v0: number; u1: number; v1: number}>;
+  // Per tile: skirt depth, and max error vs. the next LOD level per edge (N, E, S, W)
+  skirtDepths?: Float32Array;
+  edgeErrors?: Float32Array;
   waterFeatures?: WaterFeatures;
   geologicalResult?: {
     waterFeatures?: WaterFeatures;
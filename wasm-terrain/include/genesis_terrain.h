@@ -0,0 +1,69 @@
+/*
+ * C declarations for the `ffi` feature of genesis-terrain-wasm (src/ffi.rs). Link against the
+ * library built with `cargo build --profile ffi --features ffi`.
+ */
+#ifndef GENESIS_TERRAIN_H
+#define GENESIS_TERRAIN_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Status codes */
+#define GENESIS_OK 0
+/* A handle or buffer pointer was null */
+#define GENESIS_NULL_POINTER (-1)
+/* The output buffer length doesn't match the layer (size x size values) */
+#define GENESIS_LENGTH_MISMATCH (-2)
+/* The terrain has no such layer, e.g. water masks of a terrain generated without erosion */
+#define GENESIS_MISSING_LAYER (-3)
+
+/* Per-cell layers genesis_terrain_copy_layer can read */
+#define GENESIS_LAYER_HEIGHT 0
+#define GENESIS_LAYER_WATER 1
+#define GENESIS_LAYER_RIVER 2
+#define GENESIS_LAYER_BEACH 3
+#define GENESIS_LAYER_FLOW 4
+
+/* A generated terrain; free with genesis_terrain_free */
+typedef struct GenesisTerrain GenesisTerrain;
+
+/*
+ * Copy the message of the last failure on this thread into `out` as UTF-8 (truncated to `len`
+ * bytes, not NUL-terminated) and return its full length; call with len 0 to size a buffer.
+ */
+size_t genesis_last_error(uint8_t *out, size_t len);
+
+/*
+ * Generate a terrain. biome_id is a NUL-terminated biome id: "desert", "alpine", "temperate",
+ * "volcanic", "tundra" or "badlands". sea_level is in height field
+ * units, as genesis_terrain_sea_level reports it. Out-of-range settings are clamped; returns
+ * NULL (see genesis_last_error) for settings the pipeline can't run with or a panic during
+ * generation.
+ */
+GenesisTerrain *genesis_generate(uint32_t base_size, uint32_t steps, uint32_t seed, const char *biome_id, float sea_level,
+                                 float erosion_years);
+
+void genesis_terrain_free(GenesisTerrain *terrain);
+
+/* Side of the map in cells; 0 for a null handle */
+uint32_t genesis_terrain_size(const GenesisTerrain *terrain);
+
+/* Sea level in height field units */
+float genesis_terrain_sea_level(const GenesisTerrain *terrain);
+
+/* Copy one of the GENESIS_LAYER_* rasters (row-major, size x size) into `out`; returns a status code */
+int32_t genesis_terrain_copy_layer(const GenesisTerrain *terrain, uint32_t layer, float *out, size_t len);
+
+/* Buffers in the module's own memory, for wasm hosts that can't pass pointers to host memory */
+float *genesis_alloc_f32(size_t len);
+void genesis_free_f32(float *buffer, size_t len);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* GENESIS_TERRAIN_H */
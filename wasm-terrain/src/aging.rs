@@ -0,0 +1,71 @@
+use crate::erosion::apply_geological_erosion;
+use crate::oasis::place_oases;
+use crate::TerrainGenerationResult;
+use wasm_bindgen::prelude::*;
+
+// Age a finished map by `additional_years` of the erosion it was generated with, instead of
+// regenerating it with a longer erosion time. Rivers and lakes are recomputed on the aged
+// heights; surface age and terraces carry on where the new erosion left a cell alone, and
+// oases are dug again afterwards as generation does. Everything else on the result (lava,
+// permafrost, stages) is kept as it was.
+#[wasm_bindgen]
+pub fn continue_erosion(result: &TerrainGenerationResult, additional_years: f32) -> Result<TerrainGenerationResult, JsValue> {
+    if !(additional_years >= 0.0 && additional_years.is_finite()) {
+        return Err(crate::error::TerrainError::InvalidParameter(format!(
+            "additional_years must be a non-negative number, got {}",
+            additional_years
+        ))
+        .into());
+    }
+    let mut height_field = result.height_field.clone();
+    let mut params = result.erosion_params;
+    params.time_years = additional_years;
+    let mut water_features = apply_geological_erosion(&mut height_field, &params);
+
+    // Cells the new erosion didn't renew have been weathering for the whole span on top of
+    // their old age, and keep any terrace they stood on
+    if let Some(previous) = &result.water_features {
+        let renewed: Vec<bool> = water_features.surface_age().iter().map(|&age| age < additional_years).collect();
+        let age = water_features
+            .surface_age()
+            .iter()
+            .zip(previous.surface_age())
+            .zip(&renewed)
+            .map(|((&new, &old), &renewed)| if renewed { new } else { old + additional_years })
+            .collect();
+        let terraces = water_features
+            .terrace_age()
+            .iter()
+            .zip(previous.terrace_age())
+            .zip(&renewed)
+            .map(|((&new, &old), &renewed)| if renewed { new } else { new.max(old) })
+            .collect();
+        water_features.set_surface_age(age);
+        water_features.set_terrace_age(terraces);
+    }
+
+    // The pools erosion ran through are dug again on the aged heights
+    let oases = result.oases.as_ref().map(|_| {
+        let params = result.biome_params.oasis_params();
+        let oases = place_oases(&mut height_field, result.units.sea_level_height(), &params);
+        water_features.add_standing_water(oases.water_mask());
+        oases
+    });
+
+    let mut erosion_params = result.erosion_params;
+    erosion_params.time_years += additional_years;
+    Ok(TerrainGenerationResult {
+        height_field,
+        water_features: Some(water_features),
+        erosion_params,
+        stages: result.stages.clone(),
+        completed_stages: result.completed_stages.clone(),
+        skipped_stages: result.skipped_stages.clone(),
+        adjustments: result.adjustments.clone(),
+        lava_mask: result.lava_mask.clone(),
+        permafrost_depth: result.permafrost_depth.clone(),
+        oases,
+        biome_id: result.biome_id.clone(),
+        ..*result
+    })
+}
@@ -0,0 +1,926 @@
+use crate::filters::{gradient_at, slope_at};
+use crate::height_field::HeightField;
+use crate::polygons::region_polygons;
+use crate::water_system::WaterFeatures;
+use wasm_bindgen::prelude::*;
+
+// Slopes this many times steeper than the walkable limit are treated as cliffs
+const CLIFF_SLOPE_FACTOR: f32 = 3.0;
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TerrainClass {
+    Water = 0,
+    Beach = 1,
+    Buildable = 2,
+    Walkable = 3,
+    Steep = 4,
+    Cliff = 5,
+}
+
+// Classify every cell by traversability, water first, then beach, then slope bands
+pub fn classify_cells(
+    height_field: &HeightField,
+    water_features: &WaterFeatures,
+    max_walk_slope: f32,
+    max_build_slope: f32,
+) -> Vec<TerrainClass> {
+    let n = height_field.size();
+    let water_mask = water_features.water_mask();
+    let beach_mask = water_features.beach_mask();
+    let cliff_slope = max_walk_slope * CLIFF_SLOPE_FACTOR;
+
+    let mut classes = vec![TerrainClass::Water; n * n];
+    for y in 0..n {
+        for x in 0..n {
+            let idx = y * n + x;
+            let slope = slope_at(height_field, x, y);
+
+            classes[idx] = if water_mask.get(idx).is_some_and(|&w| w > 0.5) {
+                TerrainClass::Water
+            } else if beach_mask.get(idx).is_some_and(|&b| b > 0.5) && slope <= max_walk_slope {
+                TerrainClass::Beach
+            } else if slope <= max_build_slope {
+                TerrainClass::Buildable
+            } else if slope <= max_walk_slope {
+                TerrainClass::Walkable
+            } else if slope <= cliff_slope {
+                TerrainClass::Steep
+            } else {
+                TerrainClass::Cliff
+            };
+        }
+    }
+
+    classes
+}
+
+#[wasm_bindgen]
+pub fn classify_terrain(
+    height_field: &HeightField,
+    water_features: &WaterFeatures,
+    max_walk_slope: f32,
+    max_build_slope: f32,
+) -> js_sys::Uint8Array {
+    let classes = classify_cells(height_field, water_features, max_walk_slope, max_build_slope);
+    let raster: Vec<u8> = classes.iter().map(|&c| c as u8).collect();
+
+    let array = js_sys::Uint8Array::new_with_length(raster.len() as u32);
+    array.copy_from(&raster);
+    array
+}
+
+// Resolution of the slope histogram and hypsometric curve
+const SLOPE_BINS: usize = 16;
+const HYPSOMETRIC_SAMPLES: usize = 21;
+
+#[wasm_bindgen]
+#[derive(Clone)]
+pub struct TerrainStatistics {
+    pub min: f32,
+    pub max: f32,
+    pub mean: f32,
+    pub std_dev: f32,
+    pub below_sea_fraction: f32,
+    pub slope_bin_width: f32,
+    slope_histogram: Vec<f32>,
+    hypsometric_curve: Vec<f32>,
+}
+
+#[wasm_bindgen]
+impl TerrainStatistics {
+    // Fraction of cells per slope bin; bin i covers [i, i + 1) * slope_bin_width
+    #[wasm_bindgen]
+    pub fn get_slope_histogram(&self) -> js_sys::Float32Array {
+        let array = js_sys::Float32Array::new_with_length(self.slope_histogram.len() as u32);
+        array.copy_from(&self.slope_histogram);
+        array
+    }
+
+    // Fraction of area above each evenly spaced relative elevation from min (0) to max (1)
+    #[wasm_bindgen]
+    pub fn get_hypsometric_curve(&self) -> js_sys::Float32Array {
+        let array = js_sys::Float32Array::new_with_length(self.hypsometric_curve.len() as u32);
+        array.copy_from(&self.hypsometric_curve);
+        array
+    }
+
+    // Hypsometric integral: area under the hypsometric curve (young terrain ~1, old ~0)
+    #[wasm_bindgen]
+    pub fn hypsometric_integral(&self) -> f32 {
+        if self.max > self.min {
+            (self.mean - self.min) / (self.max - self.min)
+        } else {
+            0.0
+        }
+    }
+}
+
+pub fn compute_statistics(height_field: &HeightField, sea_level: f32) -> TerrainStatistics {
+    let n = height_field.size();
+    let data = height_field.data();
+
+    if data.is_empty() {
+        return TerrainStatistics {
+            min: 0.0,
+            max: 0.0,
+            mean: 0.0,
+            std_dev: 0.0,
+            below_sea_fraction: 0.0,
+            slope_bin_width: 0.0,
+            slope_histogram: vec![0.0; SLOPE_BINS],
+            hypsometric_curve: vec![0.0; HYPSOMETRIC_SAMPLES],
+        };
+    }
+
+    let count = data.len() as f64;
+    let mut min = f32::INFINITY;
+    let mut max = f32::NEG_INFINITY;
+    let mut sum = 0.0f64;
+    let mut below = 0usize;
+    for &h in data {
+        min = min.min(h);
+        max = max.max(h);
+        sum += h as f64;
+        if h <= sea_level {
+            below += 1;
+        }
+    }
+    let mean = sum / count;
+    let variance = data.iter().map(|&h| (h as f64 - mean).powi(2)).sum::<f64>() / count;
+
+    // Slope histogram binned over the observed slope range
+    let mut slopes = Vec::with_capacity(data.len());
+    for y in 0..n {
+        for x in 0..n {
+            slopes.push(slope_at(height_field, x, y));
+        }
+    }
+    let max_slope = slopes.iter().fold(0.0f32, |m, &s| m.max(s));
+    let slope_bin_width = if max_slope > 0.0 { max_slope / SLOPE_BINS as f32 } else { 1.0 };
+    let mut slope_counts = [0usize; SLOPE_BINS];
+    for &s in &slopes {
+        let bin = ((s / slope_bin_width) as usize).min(SLOPE_BINS - 1);
+        slope_counts[bin] += 1;
+    }
+    let slope_histogram: Vec<f32> = slope_counts.iter().map(|&c| (c as f64 / count) as f32).collect();
+
+    // Hypsometric curve from a fine elevation histogram
+    let span = max - min;
+    let mut hypsometric_curve = vec![0.0f32; HYPSOMETRIC_SAMPLES];
+    if span > 0.0 {
+        const FINE_BINS: usize = 1024;
+        let mut fine = vec![0usize; FINE_BINS];
+        for &h in data {
+            let bin = (((h - min) / span) * (FINE_BINS - 1) as f32) as usize;
+            fine[bin.min(FINE_BINS - 1)] += 1;
+        }
+        // above[b] = cells in bins >= b
+        let mut above = vec![0usize; FINE_BINS + 1];
+        for b in (0..FINE_BINS).rev() {
+            above[b] = above[b + 1] + fine[b];
+        }
+        for (k, value) in hypsometric_curve.iter_mut().enumerate() {
+            let rel = k as f32 / (HYPSOMETRIC_SAMPLES - 1) as f32;
+            let bin = ((rel * (FINE_BINS - 1) as f32).ceil() as usize).min(FINE_BINS);
+            *value = above[bin] as f32 / count as f32;
+        }
+    } else {
+        hypsometric_curve[0] = 1.0;
+    }
+
+    TerrainStatistics {
+        min,
+        max,
+        mean: mean as f32,
+        std_dev: variance.sqrt() as f32,
+        below_sea_fraction: below as f32 / count as f32,
+        slope_bin_width,
+        slope_histogram,
+        hypsometric_curve,
+    }
+}
+
+// Height below which (1 - fraction) of the cells lie, so `fraction` of the map is land
+// (strictly above it). Binary search over a cumulative elevation histogram.
+pub fn sea_level_for_land_fraction(data: &[f32], fraction: f32) -> f32 {
+    const BINS: usize = 4096;
+    if data.is_empty() {
+        return 0.0;
+    }
+    let min = data.iter().fold(f32::INFINITY, |m, &h| m.min(h));
+    let max = data.iter().fold(f32::NEG_INFINITY, |m, &h| m.max(h));
+    let span = max - min;
+    let fraction = fraction.clamp(0.0, 1.0);
+    if span <= 0.0 || fraction >= 1.0 {
+        return min - span.max(1.0) * f32::EPSILON;
+    }
+    if fraction <= 0.0 {
+        return max;
+    }
+
+    let mut cumulative = vec![0usize; BINS];
+    for &h in data {
+        let bin = (((h - min) / span) * BINS as f32) as usize;
+        cumulative[bin.min(BINS - 1)] += 1;
+    }
+    for b in 1..BINS {
+        cumulative[b] += cumulative[b - 1];
+    }
+
+    // Find the bin holding the target rank, then resolve the exact height inside it
+    let target = ((1.0 - fraction) * data.len() as f32).ceil() as usize;
+    let bin = cumulative.partition_point(|&c| c < target).min(BINS - 1);
+    let before = if bin > 0 { cumulative[bin - 1] } else { 0 };
+    let rank = target.saturating_sub(before);
+    if rank == 0 {
+        return min + bin as f32 / BINS as f32 * span;
+    }
+    let mut in_bin: Vec<f32> = data
+        .iter()
+        .copied()
+        .filter(|&h| ((((h - min) / span) * BINS as f32) as usize).min(BINS - 1) == bin)
+        .collect();
+    in_bin.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    in_bin[(rank - 1).min(in_bin.len() - 1)]
+}
+
+// 1 inside [min, max], easing to 0 (smoothstep) within `feather` outside either end
+pub(crate) fn band_weight(value: f32, min: f32, max: f32, feather: f32) -> f32 {
+    let outside = (min - value).max(value - max);
+    if outside <= 0.0 {
+        return 1.0;
+    }
+    let t = if feather > 0.0 { (1.0 - outside / feather).max(0.0) } else { 0.0 };
+    t * t * (3.0 - 2.0 * t)
+}
+
+// Per-cell weight of the height band [min, max], feathered by `feather` height units
+pub fn height_range_mask(height_field: &HeightField, min: f32, max: f32, feather: f32) -> Vec<f32> {
+    height_field.data().iter().map(|&h| band_weight(h, min, max, feather)).collect()
+}
+
+// Per-cell weight of the slope band [min, max] (height units per cell, as slope_at), feathered
+// by `feather`
+pub fn slope_range_mask(height_field: &HeightField, min: f32, max: f32, feather: f32) -> Vec<f32> {
+    let n = height_field.size();
+    (0..n * n).map(|idx| band_weight(slope_at(height_field, idx % n, idx / n), min, max, feather)).collect()
+}
+
+// Side of the windows the hillshade similarity is measured over (cells)
+const SSIM_WINDOW: usize = 8;
+// SSIM stabilizers for values in [0, 1]
+const SSIM_C1: f64 = 0.01 * 0.01;
+const SSIM_C2: f64 = 0.03 * 0.03;
+
+// How far apart two height fields of the same size are, for golden tests and for measuring
+// how much outputs shifted between versions
+#[wasm_bindgen]
+#[derive(Clone, Copy)]
+pub struct HeightFieldComparison {
+    pub rmse: f32,
+    pub max_abs_diff: f32,
+    pub changed_fraction: f32, // cells whose heights differ at all
+    // 1 - mean SSIM of the two hillshades: 0 = looks identical, ~1 = unrelated relief.
+    // Tracks what a viewer notices (shifted ridges, new gullies) rather than raw offsets.
+    pub hillshade_difference: f32,
+}
+
+// None if the sizes differ
+pub fn compare_height_fields(a: &HeightField, b: &HeightField) -> Option<HeightFieldComparison> {
+    let n = a.size();
+    if b.size() != n {
+        return None;
+    }
+    let (da, db) = (a.data(), b.data());
+    let cells = da.len().max(1) as f64;
+
+    let mut squared = 0.0f64;
+    let mut max_abs_diff = 0.0f32;
+    let mut changed = 0usize;
+    for (&ha, &hb) in da.iter().zip(db) {
+        let diff = (ha - hb).abs();
+        squared += diff as f64 * diff as f64;
+        max_abs_diff = max_abs_diff.max(diff);
+        changed += usize::from(ha.to_bits() != hb.to_bits());
+    }
+
+    // Both shaded with the map spanning one height unit, so relief reads the same at any size
+    let shade = |hf: &HeightField| -> Vec<f64> {
+        (0..n * n).map(|i| crate::render::hillshade_at(hf, i % n, i / n, n as f32) as f64).collect()
+    };
+    let (sa, sb) = (shade(a), shade(b));
+    let mut ssim_sum = 0.0f64;
+    let mut windows = 0usize;
+    for wy in (0..n).step_by(SSIM_WINDOW) {
+        for wx in (0..n).step_by(SSIM_WINDOW) {
+            let window: Vec<usize> = (wy..(wy + SSIM_WINDOW).min(n))
+                .flat_map(|y| (wx..(wx + SSIM_WINDOW).min(n)).map(move |x| y * n + x))
+                .collect();
+            let count = window.len() as f64;
+            let mean_a = window.iter().map(|&i| sa[i]).sum::<f64>() / count;
+            let mean_b = window.iter().map(|&i| sb[i]).sum::<f64>() / count;
+            let (mut var_a, mut var_b, mut covariance) = (0.0, 0.0, 0.0);
+            for &i in &window {
+                var_a += (sa[i] - mean_a).powi(2);
+                var_b += (sb[i] - mean_b).powi(2);
+                covariance += (sa[i] - mean_a) * (sb[i] - mean_b);
+            }
+            let (var_a, var_b, covariance) = (var_a / count, var_b / count, covariance / count);
+            ssim_sum += ((2.0 * mean_a * mean_b + SSIM_C1) * (2.0 * covariance + SSIM_C2))
+                / ((mean_a * mean_a + mean_b * mean_b + SSIM_C1) * (var_a + var_b + SSIM_C2));
+            windows += 1;
+        }
+    }
+    let hillshade_difference = if windows > 0 { 1.0 - ssim_sum / windows as f64 } else { 0.0 };
+
+    Some(HeightFieldComparison {
+        rmse: (squared / cells).sqrt() as f32,
+        max_abs_diff,
+        changed_fraction: (changed as f64 / cells) as f32,
+        hillshade_difference: hillshade_difference.max(0.0) as f32,
+    })
+}
+
+// Label 8-connected regions of `mask`; returns per-cell labels (0 = unset, 1..=count) and count
+pub(crate) fn label_components(mask: &[bool], size: usize) -> (Vec<u32>, u32) {
+    let mut labels = vec![0u32; mask.len()];
+    let mut count = 0u32;
+    let mut stack = Vec::new();
+
+    for start in 0..mask.len() {
+        if !mask[start] || labels[start] != 0 {
+            continue;
+        }
+        count += 1;
+        labels[start] = count;
+        stack.push(start);
+
+        while let Some(idx) = stack.pop() {
+            let x = (idx % size) as i32;
+            let y = (idx / size) as i32;
+            for dy in -1i32..=1 {
+                for dx in -1i32..=1 {
+                    let nx = x + dx;
+                    let ny = y + dy;
+                    if nx < 0 || ny < 0 || nx as usize >= size || ny as usize >= size {
+                        continue;
+                    }
+                    let n_idx = ny as usize * size + nx as usize;
+                    if mask[n_idx] && labels[n_idx] == 0 {
+                        labels[n_idx] = count;
+                        stack.push(n_idx);
+                    }
+                }
+            }
+        }
+    }
+
+    (labels, count)
+}
+
+#[derive(Clone)]
+pub struct CliffFace {
+    pub centroid_x: f32,
+    pub centroid_y: f32,
+    pub orientation: f32, // radians, direction the face looks towards (downslope)
+    pub height: f32,
+    pub cell_count: usize,
+}
+
+impl CliffFace {
+    // Convert to JS object for interop
+    pub fn to_js_object(&self) -> js_sys::Object {
+        let obj = js_sys::Object::new();
+
+        js_sys::Reflect::set(&obj, &"x".into(), &self.centroid_x.into()).unwrap();
+        js_sys::Reflect::set(&obj, &"y".into(), &self.centroid_y.into()).unwrap();
+        js_sys::Reflect::set(&obj, &"orientation".into(), &self.orientation.into()).unwrap();
+        js_sys::Reflect::set(&obj, &"height".into(), &self.height.into()).unwrap();
+        js_sys::Reflect::set(&obj, &"cellCount".into(), &(self.cell_count as f32).into()).unwrap();
+
+        obj
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Clone)]
+pub struct CliffFaces {
+    mask: Vec<f32>,
+    faces: Vec<CliffFace>,
+    size: usize,
+}
+
+#[wasm_bindgen]
+impl CliffFaces {
+    #[wasm_bindgen(getter)]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    #[wasm_bindgen]
+    pub fn get_mask(&self) -> js_sys::Float32Array {
+        let array = js_sys::Float32Array::new_with_length(self.mask.len() as u32);
+        array.copy_from(&self.mask);
+        array
+    }
+
+    #[wasm_bindgen]
+    pub fn get_faces(&self) -> js_sys::Array {
+        let faces = js_sys::Array::new();
+        for face in &self.faces {
+            faces.push(&face.to_js_object());
+        }
+        faces
+    }
+}
+
+#[wasm_bindgen]
+pub fn find_cliffs(height_field: &HeightField, slope_threshold: f32, min_face_height: f32) -> CliffFaces {
+    let n = height_field.size();
+    let data = height_field.data();
+
+    let steep: Vec<bool> = (0..n * n)
+        .map(|idx| slope_at(height_field, idx % n, idx / n) > slope_threshold)
+        .collect();
+    let (labels, count) = label_components(&steep, n);
+
+    // Accumulate per-component extents and aspect vectors
+    let components = count as usize;
+    let mut min_h = vec![f32::INFINITY; components];
+    let mut max_h = vec![f32::NEG_INFINITY; components];
+    // Sums run over up to every cell of the map, beyond what f32 adds exactly
+    let mut sum_x = vec![0.0f64; components];
+    let mut sum_y = vec![0.0f64; components];
+    let mut aspect_x = vec![0.0f64; components];
+    let mut aspect_y = vec![0.0f64; components];
+    let mut cells = vec![0usize; components];
+
+    for (idx, &label) in labels.iter().enumerate() {
+        if label == 0 {
+            continue;
+        }
+        let c = label as usize - 1;
+        let x = idx % n;
+        let y = idx / n;
+        let (gx, gy) = gradient_at(height_field, x, y);
+
+        min_h[c] = min_h[c].min(data[idx]);
+        max_h[c] = max_h[c].max(data[idx]);
+        sum_x[c] += x as f64;
+        sum_y[c] += y as f64;
+        aspect_x[c] -= gx as f64;
+        aspect_y[c] -= gy as f64;
+        cells[c] += 1;
+    }
+
+    let mut keep = vec![false; components];
+    let mut faces = Vec::new();
+    for c in 0..components {
+        let height = max_h[c] - min_h[c];
+        if height < min_face_height {
+            continue;
+        }
+        keep[c] = true;
+        faces.push(CliffFace {
+            centroid_x: (sum_x[c] / cells[c] as f64) as f32,
+            centroid_y: (sum_y[c] / cells[c] as f64) as f32,
+            orientation: crate::det_math::atan2(aspect_y[c] as f32, aspect_x[c] as f32),
+            height,
+            cell_count: cells[c],
+        });
+    }
+
+    let mask = labels
+        .iter()
+        .map(|&label| if label != 0 && keep[label as usize - 1] { 1.0 } else { 0.0 })
+        .collect();
+
+    CliffFaces { mask, faces, size: n }
+}
+
+#[derive(Clone)]
+pub struct Landmass {
+    pub id: u32,
+    pub area: usize,      // cells
+    pub perimeter: usize, // land cell edges facing water
+    pub highest_x: usize,
+    pub highest_y: usize,
+    pub highest_height: f32,
+    pub centroid_x: f32,
+    pub centroid_y: f32,
+    pub touches_edge: bool, // may continue beyond the map, so not a true island
+}
+
+impl Landmass {
+    // Convert to JS object for interop
+    pub fn to_js_object(&self) -> js_sys::Object {
+        let obj = js_sys::Object::new();
+
+        js_sys::Reflect::set(&obj, &"id".into(), &self.id.into()).unwrap();
+        js_sys::Reflect::set(&obj, &"area".into(), &(self.area as f32).into()).unwrap();
+        js_sys::Reflect::set(&obj, &"perimeter".into(), &(self.perimeter as f32).into()).unwrap();
+        js_sys::Reflect::set(&obj, &"highestX".into(), &(self.highest_x as f32).into()).unwrap();
+        js_sys::Reflect::set(&obj, &"highestY".into(), &(self.highest_y as f32).into()).unwrap();
+        js_sys::Reflect::set(&obj, &"highestHeight".into(), &self.highest_height.into()).unwrap();
+        js_sys::Reflect::set(&obj, &"centroidX".into(), &self.centroid_x.into()).unwrap();
+        js_sys::Reflect::set(&obj, &"centroidY".into(), &self.centroid_y.into()).unwrap();
+        js_sys::Reflect::set(&obj, &"touchesEdge".into(), &self.touches_edge.into()).unwrap();
+
+        obj
+    }
+}
+
+// Connected land regions above sea level, largest first
+pub fn landmass_inventory(height_field: &HeightField, sea_level: f32) -> Vec<Landmass> {
+    let n = height_field.size();
+    let data = height_field.data();
+    let land: Vec<bool> = data.iter().map(|&h| h > sea_level).collect();
+    let (labels, count) = label_components(&land, n);
+
+    let mut landmasses: Vec<Landmass> = (1..=count)
+        .map(|id| Landmass {
+            id,
+            area: 0,
+            perimeter: 0,
+            highest_x: 0,
+            highest_y: 0,
+            highest_height: f32::NEG_INFINITY,
+            centroid_x: 0.0,
+            centroid_y: 0.0,
+            touches_edge: false,
+        })
+        .collect();
+    // Coordinate sums for the centroids, in f64 as a landmass can cover the whole map
+    let mut sums = vec![(0.0f64, 0.0f64); landmasses.len()];
+
+    for (idx, &label) in labels.iter().enumerate() {
+        if label == 0 {
+            continue;
+        }
+        let x = idx % n;
+        let y = idx / n;
+        let mass = &mut landmasses[label as usize - 1];
+
+        mass.area += 1;
+        let sum = &mut sums[label as usize - 1];
+        sum.0 += x as f64;
+        sum.1 += y as f64;
+        if data[idx] > mass.highest_height {
+            mass.highest_height = data[idx];
+            mass.highest_x = x;
+            mass.highest_y = y;
+        }
+        if x == 0 || y == 0 || x == n - 1 || y == n - 1 {
+            mass.touches_edge = true;
+        }
+
+        let neighbors = [
+            (x > 0, idx.wrapping_sub(1)),
+            (x + 1 < n, idx + 1),
+            (y > 0, idx.wrapping_sub(n)),
+            (y + 1 < n, idx + n),
+        ];
+        for (inside, n_idx) in neighbors {
+            if inside && !land[n_idx] {
+                mass.perimeter += 1;
+            }
+        }
+    }
+
+    for (mass, (sum_x, sum_y)) in landmasses.iter_mut().zip(sums) {
+        mass.centroid_x = (sum_x / mass.area as f64) as f32;
+        mass.centroid_y = (sum_y / mass.area as f64) as f32;
+    }
+    landmasses.sort_by_key(|mass| std::cmp::Reverse(mass.area));
+    landmasses
+}
+
+#[wasm_bindgen]
+pub fn find_landmasses(height_field: &HeightField, sea_level: f32, min_area: u32) -> js_sys::Array {
+    let result = js_sys::Array::new();
+    for mass in landmass_inventory(height_field, sea_level) {
+        if mass.area >= min_area as usize {
+            result.push(&mass.to_js_object());
+        }
+    }
+    result
+}
+
+// Largest distance (cells) a simplified walkable outline may stray from the cell boundary
+const WALKABLE_OUTLINE_TOLERANCE: f32 = 0.75;
+
+// A contiguous patch of walkable ground. Polygons are flat x, y pairs in cell units with cell
+// centers on integer coordinates; holes are water, steep ground or cliffs inside the patch.
+#[derive(Clone)]
+pub struct WalkableRegion {
+    pub area: u32, // cells
+    pub outline: Vec<f32>,
+    pub holes: Vec<Vec<f32>>,
+}
+
+impl WalkableRegion {
+    // Convert to JS object for interop
+    pub fn to_js_object(&self) -> js_sys::Object {
+        let obj = js_sys::Object::new();
+
+        let outline = js_sys::Float32Array::new_with_length(self.outline.len() as u32);
+        outline.copy_from(&self.outline);
+        let holes = js_sys::Array::new();
+        for hole in &self.holes {
+            let ring = js_sys::Float32Array::new_with_length(hole.len() as u32);
+            ring.copy_from(hole);
+            holes.push(&ring);
+        }
+
+        js_sys::Reflect::set(&obj, &"area".into(), &self.area.into()).unwrap();
+        js_sys::Reflect::set(&obj, &"outline".into(), &outline).unwrap();
+        js_sys::Reflect::set(&obj, &"holes".into(), &holes).unwrap();
+
+        obj
+    }
+}
+
+// Dry cells no steeper than `max_slope` (height units per cell), grouped into 8-connected
+// regions of at least `min_area` cells, largest first
+pub fn walkable_polygons(
+    height_field: &HeightField,
+    water_features: &WaterFeatures,
+    max_slope: f32,
+    min_area: u32,
+) -> Vec<WalkableRegion> {
+    let n = height_field.size();
+    let water_mask = water_features.water_mask();
+    let walkable: Vec<bool> = (0..n * n)
+        .map(|idx| water_mask.get(idx).is_none_or(|&w| w <= 0.5) && slope_at(height_field, idx % n, idx / n) <= max_slope)
+        .collect();
+    let (labels, count) = label_components(&walkable, n);
+
+    let mut area = vec![0u32; count as usize];
+    for &label in labels.iter().filter(|&&l| l != 0) {
+        area[label as usize - 1] += 1;
+    }
+    let polygons = region_polygons(&walkable, &labels, count, n, WALKABLE_OUTLINE_TOLERANCE);
+    let mut regions: Vec<WalkableRegion> = polygons
+        .into_iter()
+        .zip(area)
+        .filter(|&(_, area)| area >= min_area.max(1))
+        .map(|((outline, holes), area)| WalkableRegion { area, outline, holes })
+        .collect();
+    regions.sort_by_key(|region| std::cmp::Reverse(region.area));
+    regions
+}
+
+// Walkable ground above water as polygons with holes, ready to seed navmesh generation
+#[wasm_bindgen]
+pub fn extract_walkable_polygons(
+    height_field: &HeightField,
+    water_features: &WaterFeatures,
+    max_slope: f32,
+    min_area: u32,
+) -> js_sys::Array {
+    let result = js_sys::Array::new();
+    for region in walkable_polygons(height_field, water_features, max_slope, min_area) {
+        result.push(&region.to_js_object());
+    }
+    result
+}
+
+// How far (cells) from a cliff face a cave mouth may sit
+const CAVE_BASE_DISTANCE: f32 = 3.0;
+
+#[derive(Clone)]
+pub struct CaveEntrance {
+    pub x: usize,
+    pub y: usize,
+    pub height: f32,
+    pub facing: f32, // radians, direction the mouth opens towards (downslope)
+    pub score: f32,
+}
+
+impl CaveEntrance {
+    // Convert to JS object for interop
+    pub fn to_js_object(&self) -> js_sys::Object {
+        let obj = js_sys::Object::new();
+
+        js_sys::Reflect::set(&obj, &"x".into(), &(self.x as f32).into()).unwrap();
+        js_sys::Reflect::set(&obj, &"y".into(), &(self.y as f32).into()).unwrap();
+        js_sys::Reflect::set(&obj, &"height".into(), &self.height.into()).unwrap();
+        js_sys::Reflect::set(&obj, &"facing".into(), &self.facing.into()).unwrap();
+        js_sys::Reflect::set(&obj, &"score".into(), &self.score.into()).unwrap();
+
+        obj
+    }
+}
+
+// Steep, concave cells just below a cliff face and above water, best first
+pub fn cave_entrance_sites(
+    height_field: &HeightField,
+    sea_level: f32,
+    slope_threshold: f32,
+    max_count: usize,
+    min_spacing: f32,
+) -> Vec<CaveEntrance> {
+    let n = height_field.size();
+    let data = height_field.data();
+
+    let cliff: Vec<bool> = (0..n * n)
+        .map(|idx| slope_at(height_field, idx % n, idx / n) > slope_threshold)
+        .collect();
+    let dist_cliff = crate::filters::distance_to_mask(&cliff, n);
+
+    let mut candidates = Vec::new();
+    for y in 1..n.saturating_sub(1) {
+        for x in 1..n.saturating_sub(1) {
+            let idx = y * n + x;
+            let h = data[idx];
+            if cliff[idx] || h <= sea_level || dist_cliff[idx] > CAVE_BASE_DISTANCE {
+                continue;
+            }
+
+            let slope = slope_at(height_field, x, y);
+            if slope < slope_threshold * 0.5 {
+                continue;
+            }
+
+            // Concave: neighbors sit higher than the cell on average
+            let concavity = data[idx - 1] + data[idx + 1] + data[idx - n] + data[idx + n] - 4.0 * h;
+            if concavity <= 0.0 {
+                continue;
+            }
+
+            // The cliff has to rise above the cell, not drop away below it
+            let (gx, gy) = gradient_at(height_field, x, y);
+            let ux = x as f32 + gx / slope * CAVE_BASE_DISTANCE;
+            let uy = y as f32 + gy / slope * CAVE_BASE_DISTANCE;
+            let uphill = height_field.get_clamped(ux.round() as i32, uy.round() as i32);
+            if uphill <= h {
+                continue;
+            }
+
+            let proximity = 1.0 - dist_cliff[idx] / (CAVE_BASE_DISTANCE + 1.0);
+            let score = concavity / slope_threshold.max(f32::EPSILON) * proximity * (slope / slope_threshold).min(1.0);
+
+            candidates.push(CaveEntrance {
+                x,
+                y,
+                height: h,
+                facing: crate::det_math::atan2(-gy, -gx),
+                score,
+            });
+        }
+    }
+
+    candidates.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+
+    let min_spacing_sq = min_spacing * min_spacing;
+    let mut sites: Vec<CaveEntrance> = Vec::with_capacity(max_count.min(candidates.len()));
+    for candidate in candidates {
+        if sites.len() >= max_count {
+            break;
+        }
+        let too_close = sites.iter().any(|s| {
+            let dx = s.x as f32 - candidate.x as f32;
+            let dy = s.y as f32 - candidate.y as f32;
+            dx * dx + dy * dy < min_spacing_sq
+        });
+        if !too_close {
+            sites.push(candidate);
+        }
+    }
+
+    sites
+}
+
+#[wasm_bindgen]
+pub fn find_cave_entrances(
+    height_field: &HeightField,
+    sea_level: f32,
+    slope_threshold: f32,
+    max_count: u32,
+    min_spacing: f32,
+) -> js_sys::Array {
+    let result = js_sys::Array::new();
+    for site in cave_entrance_sites(height_field, sea_level, slope_threshold, max_count as usize, min_spacing) {
+        result.push(&site.to_js_object());
+    }
+    result
+}
+
+// Summary of a water system for tests and UIs
+#[wasm_bindgen]
+#[derive(Clone)]
+pub struct HydrologyReport {
+    pub river_count: u32,
+    pub total_river_length: f32, // cells, following the channels
+    pub largest_lake_area: u32,  // cells of the largest water body not touching the map edge
+    pub drainage_density: f32,   // river length per land cell
+    pub longest_river_length: f32,
+    longest_river_path: Vec<f32>,
+}
+
+#[wasm_bindgen]
+impl HydrologyReport {
+    // Polyline of the longest river from source to mouth as flat x, y cell coordinates
+    #[wasm_bindgen]
+    pub fn get_longest_river_path(&self) -> js_sys::Float32Array {
+        let array = js_sys::Float32Array::new_with_length(self.longest_river_path.len() as u32);
+        array.copy_from(&self.longest_river_path);
+        array
+    }
+}
+
+// Rivers are 8-connected components of strong river cells; each river cell drains into its
+// neighbor with the most flow, which orders channels from source to mouth.
+pub fn hydrology_report(water_features: &WaterFeatures) -> HydrologyReport {
+    let n = water_features.size();
+    let water_mask = water_features.water_mask();
+    let river_mask = water_features.river_mask();
+    let flow = water_features.flow_accumulation();
+
+    let river: Vec<bool> = river_mask.iter().map(|&r| r > 0.5).collect();
+    let (_, river_count) = label_components(&river, n);
+
+    // Downstream neighbor and step length for every river cell
+    let mut downstream = vec![usize::MAX; n * n];
+    let mut step = vec![0.0f32; n * n];
+    for idx in (0..n * n).filter(|&i| river[i]) {
+        let (x, y) = ((idx % n) as i32, (idx / n) as i32);
+        let mut best = flow[idx];
+        for dy in -1i32..=1 {
+            for dx in -1i32..=1 {
+                let nx = x + dx;
+                let ny = y + dy;
+                if (dx == 0 && dy == 0) || nx < 0 || ny < 0 || nx as usize >= n || ny as usize >= n {
+                    continue;
+                }
+                let n_idx = ny as usize * n + nx as usize;
+                if river[n_idx] && flow[n_idx] > best {
+                    best = flow[n_idx];
+                    downstream[idx] = n_idx;
+                    step[idx] = ((dx * dx + dy * dy) as f32).sqrt();
+                }
+            }
+        }
+    }
+    let total_river_length = (0..n * n)
+        .filter(|&i| river[i])
+        .map(|i| if downstream[i] == usize::MAX { 1.0 } else { step[i] as f64 })
+        .sum::<f64>() as f32;
+
+    // Channel length to the mouth, resolved downstream first (flow strictly increases downstream)
+    let mut order: Vec<usize> = (0..n * n).filter(|&i| river[i]).collect();
+    order.sort_by(|&a, &b| flow[b].partial_cmp(&flow[a]).unwrap_or(std::cmp::Ordering::Equal));
+    let mut to_mouth = vec![0.0f32; n * n];
+    for &idx in &order {
+        if downstream[idx] != usize::MAX {
+            to_mouth[idx] = step[idx] + to_mouth[downstream[idx]];
+        }
+    }
+    let source = order
+        .iter()
+        .copied()
+        .max_by(|&a, &b| to_mouth[a].partial_cmp(&to_mouth[b]).unwrap_or(std::cmp::Ordering::Equal));
+    let mut longest_river_path = Vec::new();
+    let mut longest_river_length = 0.0;
+    if let Some(mut idx) = source {
+        longest_river_length = to_mouth[idx];
+        loop {
+            longest_river_path.push((idx % n) as f32);
+            longest_river_path.push((idx / n) as f32);
+            if downstream[idx] == usize::MAX {
+                break;
+            }
+            idx = downstream[idx];
+        }
+    }
+
+    // Lakes: standing water away from the map edge (edge-connected water is treated as sea)
+    let standing: Vec<bool> = (0..n * n).map(|i| water_mask[i] > 0.5 && !river[i]).collect();
+    let (labels, count) = label_components(&standing, n);
+    let mut area = vec![0u32; count as usize + 1];
+    let mut touches_edge = vec![false; count as usize + 1];
+    for (idx, &label) in labels.iter().enumerate() {
+        if label == 0 {
+            continue;
+        }
+        area[label as usize] += 1;
+        let (x, y) = (idx % n, idx / n);
+        if x == 0 || y == 0 || x == n - 1 || y == n - 1 {
+            touches_edge[label as usize] = true;
+        }
+    }
+    let largest_lake_area = (1..=count as usize).filter(|&l| !touches_edge[l]).map(|l| area[l]).max().unwrap_or(0);
+
+    let land_cells = water_mask.iter().filter(|&&w| w <= 0.5).count();
+    let drainage_density = if land_cells > 0 { total_river_length / land_cells as f32 } else { 0.0 };
+
+    HydrologyReport {
+        river_count,
+        total_river_length,
+        largest_lake_area,
+        drainage_density,
+        longest_river_length,
+        longest_river_path,
+    }
+}
@@ -0,0 +1,113 @@
+use crate::height_field::HeightField;
+use crate::noise::{hash_cell, hash_to_unit};
+use crate::strata::{bed_erodibility, StrataField, HARD_BED};
+use crate::water_system::calculate_flow_accumulation;
+use wasm_bindgen::prelude::*;
+
+// Stream-power coefficient and drainage-area exponent (incision = K * A^m * S * erodibility)
+const INCISION_K: f32 = 0.02;
+const AREA_EXPONENT: f32 = 0.5;
+// Incision never takes more than this fraction of the drop to the downstream cell,
+// so channels stay graded instead of digging pits
+const MAX_DROP_FRACTION: f32 = 0.9;
+// Hoodoo spacing (cells) and pillar radius relative to the spacing
+const HOODOO_SPACING: f32 = 5.0;
+const HOODOO_RADIUS: f32 = 0.22;
+// Incision depth (height units) that exposes enough of the section for hoodoos
+const HOODOO_MIN_CUT: f32 = 0.01;
+
+// Steepest-descent neighbor height, None for pits
+fn downstream_height(height_field: &HeightField, x: usize, y: usize) -> Option<(f32, f32)> {
+    let h = height_field.get(x, y);
+    let mut best: Option<(f32, f32)> = None;
+    for dy in -1i32..=1 {
+        for dx in -1i32..=1 {
+            if dx == 0 && dy == 0 {
+                continue;
+            }
+            let nx = x as i32 + dx;
+            let ny = y as i32 + dy;
+            let n = height_field.size() as i32;
+            if nx < 0 || ny < 0 || nx >= n || ny >= n {
+                continue;
+            }
+            let nh = height_field.get(nx as usize, ny as usize);
+            let distance = ((dx * dx + dy * dy) as f32).sqrt();
+            let slope = (h - nh) / distance;
+            if slope > 0.0 && best.is_none_or(|(_, s)| slope > s) {
+                best = Some((nh, slope));
+            }
+        }
+    }
+    best
+}
+
+// Badlands dissection: exaggerated stream-power incision through stratified beds turns the
+// drainage network into slot canyons, then hoodoo pillars are left standing where a hard bed
+// capped a deep enough cut. `intensity` 0-1 controls both the number of incision passes and
+// their strength; 0 is a no-op.
+#[wasm_bindgen]
+pub fn apply_badlands_dissection(height_field: &mut HeightField, intensity: f32, seed: u32) {
+    badlands_dissection(height_field, intensity, None, seed);
+}
+
+// Badlands dissection through tilted strata, so cut faces show dipping bedding planes.
+// `strata` must match the height field's size; otherwise the beds stay horizontal.
+#[wasm_bindgen]
+pub fn apply_badlands_dissection_with_strata(height_field: &mut HeightField, intensity: f32, strata: &StrataField, seed: u32) {
+    let strata = (strata.size() == height_field.size()).then_some(strata);
+    badlands_dissection(height_field, intensity, strata, seed);
+}
+
+pub(crate) fn badlands_dissection(height_field: &mut HeightField, intensity: f32, strata: Option<&StrataField>, seed: u32) {
+    let n = height_field.size();
+    let intensity = intensity.clamp(0.0, 1.0);
+    if n < 3 || intensity <= 0.0 {
+        return;
+    }
+    let original = height_field.data().to_vec();
+    let passes = 2 + (intensity * 10.0) as u32;
+    let k = INCISION_K * intensity * 4.0;
+
+    for _pass in 0..passes {
+        let flow = calculate_flow_accumulation(height_field);
+        let mut cut = vec![0.0f32; n * n];
+        for y in 0..n {
+            for x in 0..n {
+                let Some((down, slope)) = downstream_height(height_field, x, y) else {
+                    continue;
+                };
+                let idx = y * n + x;
+                let h = height_field.get(x, y);
+                let power = k * crate::det_math::powf(flow[idx], AREA_EXPONENT) * slope * bed_erodibility(h, x, y, strata, seed);
+                cut[idx] = power.min((h - down) * MAX_DROP_FRACTION);
+            }
+        }
+        for (h, c) in height_field.data_mut().iter_mut().zip(&cut) {
+            *h -= c;
+        }
+    }
+
+    // Hoodoos: hard-capped pillars on a jittered grid keep part of their pre-incision height
+    for y in 0..n {
+        for x in 0..n {
+            let idx = y * n + x;
+            let depth = original[idx] - height_field.get(x, y);
+            // Only a resistant cap protects the soft beds beneath a pillar
+            if depth < HOODOO_MIN_CUT || bed_erodibility(original[idx], x, y, strata, seed) != HARD_BED {
+                continue;
+            }
+            let px = x as f32 / HOODOO_SPACING;
+            let py = y as f32 / HOODOO_SPACING;
+            let (cx, cy) = (px.floor() as i32, py.floor() as i32);
+            let h = hash_cell(cx, cy, seed ^ 0x5eed);
+            let fx = cx as f32 + 0.25 + hash_to_unit(h) * 0.5;
+            let fy = cy as f32 + 0.25 + hash_to_unit(h.rotate_left(16)) * 0.5;
+            let d = ((px - fx).powi(2) + (py - fy).powi(2)).sqrt() / HOODOO_RADIUS;
+            if d < 1.0 {
+                let standing = (1.0 - d * d).sqrt() * intensity;
+                height_field.set(x, y, height_field.get(x, y) + depth * standing);
+            }
+        }
+    }
+}
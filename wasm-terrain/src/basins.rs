@@ -0,0 +1,260 @@
+use crate::height_field::HeightField;
+use crate::polygons::region_polygons;
+use crate::water_system::{cells_by_height, flow_receivers};
+use wasm_bindgen::prelude::*;
+
+// Largest distance (cells) a simplified basin outline may stray from the cell boundary
+const BASIN_OUTLINE_TOLERANCE: f32 = 1.0;
+
+// The catchment of one stream of the Horton-Strahler network: everything draining into the
+// stream, including the basins of its lower-order tributaries
+#[derive(Clone)]
+pub struct Basin {
+    pub order: u32,  // Strahler order of the stream; 0 for the whole-map root
+    pub parent: i32, // index of the enclosing basin, -1 for the root
+    pub area: u32,   // cells
+    pub outlet_x: f32,
+    pub outlet_y: f32,
+    pub outlet_height: f32,
+    children: Vec<u32>,
+    outline: Vec<f32>,
+    holes: Vec<Vec<f32>>,
+}
+
+impl Basin {
+    // Convert to JS object for interop
+    pub fn to_js_object(&self) -> js_sys::Object {
+        let obj = js_sys::Object::new();
+
+        let children = js_sys::Uint32Array::new_with_length(self.children.len() as u32);
+        children.copy_from(&self.children);
+        let outline = js_sys::Float32Array::new_with_length(self.outline.len() as u32);
+        outline.copy_from(&self.outline);
+        let holes = js_sys::Array::new();
+        for hole in &self.holes {
+            let ring = js_sys::Float32Array::new_with_length(hole.len() as u32);
+            ring.copy_from(hole);
+            holes.push(&ring);
+        }
+
+        js_sys::Reflect::set(&obj, &"order".into(), &self.order.into()).unwrap();
+        js_sys::Reflect::set(&obj, &"parent".into(), &self.parent.into()).unwrap();
+        js_sys::Reflect::set(&obj, &"area".into(), &self.area.into()).unwrap();
+        js_sys::Reflect::set(&obj, &"outletX".into(), &self.outlet_x.into()).unwrap();
+        js_sys::Reflect::set(&obj, &"outletY".into(), &self.outlet_y.into()).unwrap();
+        js_sys::Reflect::set(&obj, &"outletHeight".into(), &self.outlet_height.into()).unwrap();
+        js_sys::Reflect::set(&obj, &"children".into(), &children).unwrap();
+        js_sys::Reflect::set(&obj, &"outline".into(), &outline).unwrap();
+        js_sys::Reflect::set(&obj, &"holes".into(), &holes).unwrap();
+
+        obj
+    }
+}
+
+// Nested drainage basins from single streams up to the whole map. Basin 0 is the map itself;
+// every other basin comes after its parent, and siblings are sorted largest first. Outlines
+// run along cell edges (the dual grid) as flat x, y pairs with cell centers on integers.
+#[wasm_bindgen]
+#[derive(Clone)]
+pub struct BasinHierarchy {
+    basins: Vec<Basin>,
+    labels: Vec<u32>,
+    size: usize,
+}
+
+#[wasm_bindgen]
+impl BasinHierarchy {
+    #[wasm_bindgen(getter)]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn basin_count(&self) -> usize {
+        self.basins.len()
+    }
+
+    // Strahler order of the largest river
+    #[wasm_bindgen(getter)]
+    pub fn max_order(&self) -> u32 {
+        self.basins.iter().map(|b| b.order).max().unwrap_or(0)
+    }
+
+    #[wasm_bindgen]
+    pub fn get_basins(&self) -> js_sys::Array {
+        let basins = js_sys::Array::new();
+        for basin in &self.basins {
+            basins.push(&basin.to_js_object());
+        }
+        basins
+    }
+
+    // Innermost basin of every cell; walk `parent` for the larger ones
+    #[wasm_bindgen]
+    pub fn get_labels(&self) -> js_sys::Uint32Array {
+        let array = js_sys::Uint32Array::new_with_length(self.labels.len() as u32);
+        array.copy_from(&self.labels);
+        array
+    }
+}
+
+// Land cells draining at least `channel_area` cells form the stream network. Strahler orders
+// follow the D8 flow: sources are order 1 and two streams of order k meet to form order k + 1.
+// Each stream (a run of one order) gets the basin above its outlet; streams end where they
+// join a higher-order stream, reach the sea or leave the map.
+pub fn basin_hierarchy(height_field: &HeightField, sea_level: f32, channel_area: f32) -> BasinHierarchy {
+    let n = height_field.size();
+    let data = height_field.data();
+    let receivers = flow_receivers(height_field);
+    let order_by_height = cells_by_height(height_field);
+
+    let mut flow = vec![1.0f32; n * n];
+    for &idx in &order_by_height {
+        if receivers[idx] != usize::MAX {
+            flow[receivers[idx]] += flow[idx];
+        }
+    }
+    let channel: Vec<bool> = (0..n * n).map(|i| flow[i] >= channel_area && data[i] > sea_level).collect();
+
+    // Upstream first: a cell's order and stream follow from the channel cells feeding it
+    let mut strahler = vec![0u32; n * n];
+    let mut top_order = vec![(0u32, 0u32); n * n]; // highest donor order and how many donors have it
+    let mut stream = vec![usize::MAX; n * n];
+    let mut donor_stream = vec![usize::MAX; n * n]; // stream of the donor with the cell's own order
+    let mut streams: Vec<usize> = Vec::new(); // outlet cell of each stream
+    for &idx in &order_by_height {
+        if !channel[idx] {
+            continue;
+        }
+        let (top, count) = top_order[idx];
+        strahler[idx] = match (top, count) {
+            (0, _) => 1,
+            (k, c) if c >= 2 => k + 1,
+            (k, _) => k,
+        };
+        // Two donors of the same order can't both match, since they would raise the order
+        stream[idx] = if strahler[idx] == top && count == 1 {
+            donor_stream[idx]
+        } else {
+            streams.push(idx);
+            streams.len() - 1
+        };
+        streams[stream[idx]] = idx;
+
+        let down = receivers[idx];
+        if down != usize::MAX && channel[down] {
+            let (k, c) = top_order[down];
+            if strahler[idx] > k {
+                top_order[down] = (strahler[idx], 1);
+                donor_stream[down] = stream[idx];
+            } else if strahler[idx] == k {
+                top_order[down] = (k, c + 1);
+            }
+        }
+    }
+
+    // A stream's parent is the stream its outlet drains into; node 0 is the whole map
+    let stream_parent: Vec<usize> = streams
+        .iter()
+        .map(|&outlet| {
+            let down = receivers[outlet];
+            if down != usize::MAX && channel[down] { stream[down] + 1 } else { 0 }
+        })
+        .collect();
+
+    // Innermost basin per cell: the stream of the first channel cell downstream (downhill last)
+    let mut innermost = vec![0usize; n * n];
+    for &idx in order_by_height.iter().rev() {
+        innermost[idx] = if channel[idx] {
+            stream[idx] + 1
+        } else if receivers[idx] != usize::MAX {
+            innermost[receivers[idx]]
+        } else {
+            0
+        };
+    }
+
+    // Renumber breadth-first from the root, largest sibling first, so parents precede children
+    let node_count = streams.len() + 1;
+    let area_of = |node: usize| if node == 0 { (n * n) as f32 } else { flow[streams[node - 1]] };
+    let mut children: Vec<Vec<usize>> = vec![Vec::new(); node_count];
+    for (s, &parent) in stream_parent.iter().enumerate() {
+        children[parent].push(s + 1);
+    }
+    for list in &mut children {
+        list.sort_by(|&a, &b| area_of(b).partial_cmp(&area_of(a)).unwrap_or(std::cmp::Ordering::Equal));
+    }
+    let mut sequence = vec![0usize];
+    let mut next = 0;
+    while next < sequence.len() {
+        let node = sequence[next];
+        sequence.extend_from_slice(&children[node]);
+        next += 1;
+    }
+    let mut index_of = vec![0u32; node_count];
+    for (i, &node) in sequence.iter().enumerate() {
+        index_of[node] = i as u32;
+    }
+
+    let max_order = strahler.iter().copied().max().unwrap_or(0);
+    let mut basins: Vec<Basin> = sequence
+        .iter()
+        .map(|&node| {
+            let (order, parent, (outlet_x, outlet_y, outlet_height)) = if node == 0 {
+                (0, -1, (f32::NAN, f32::NAN, f32::NAN))
+            } else {
+                let outlet = streams[node - 1];
+                let point = ((outlet % n) as f32, (outlet / n) as f32, data[outlet]);
+                (strahler[outlet], index_of[stream_parent[node - 1]] as i32, point)
+            };
+            Basin {
+                order,
+                parent,
+                area: area_of(node) as u32,
+                outlet_x,
+                outlet_y,
+                outlet_height,
+                children: children[node].iter().map(|&c| index_of[c]).collect(),
+                outline: Vec::new(),
+                holes: Vec::new(),
+            }
+        })
+        .collect();
+    let labels: Vec<u32> = innermost.iter().map(|&node| index_of[node]).collect();
+
+    // Basins of one order never overlap, so each order is traced in a single pass
+    for order in 1..=max_order {
+        let mut members: Vec<usize> = Vec::new();
+        let mut level = vec![0u32; basins.len()];
+        for i in (0..basins.len()).filter(|&i| basins[i].order == order) {
+            members.push(i);
+            level[i] = members.len() as u32;
+        }
+        // Climb to the enclosing basin of this order, if the cell has one
+        let cell_labels: Vec<u32> = labels
+            .iter()
+            .map(|&label| {
+                let mut b = label as usize;
+                while b != 0 && basins[b].order < order {
+                    b = basins[b].parent as usize;
+                }
+                if basins[b].order == order { level[b] } else { 0 }
+            })
+            .collect();
+        let mask: Vec<bool> = cell_labels.iter().map(|&l| l > 0).collect();
+        let polygons = region_polygons(&mask, &cell_labels, members.len() as u32, n, BASIN_OUTLINE_TOLERANCE);
+        for (&i, (outline, holes)) in members.iter().zip(polygons) {
+            basins[i].outline = outline;
+            basins[i].holes = holes;
+        }
+    }
+
+    BasinHierarchy { basins, labels, size: n }
+}
+
+// Basin tree for naming regions and level-of-detail hydrology. `channel_area` is the
+// catchment (cells) at which a stream begins; larger values give a coarser tree.
+#[wasm_bindgen]
+pub fn extract_basin_hierarchy(height_field: &HeightField, sea_level: f32, channel_area: f32) -> BasinHierarchy {
+    basin_hierarchy(height_field, sea_level, channel_area)
+}
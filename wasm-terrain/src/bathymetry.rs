@@ -0,0 +1,168 @@
+use crate::filters::{apply_smoothing, distance_to_mask};
+use crate::height_field::HeightField;
+use crate::noise::{hash_cell, hash_to_unit, value_noise_2d};
+use wasm_bindgen::prelude::*;
+
+// Smoothing passes that separate the seafloor's small-scale relief from its broad shape
+const DETAIL_SMOOTHING_PASSES: u32 = 4;
+// Shape of the shelf's depth profile: above 1 it stays shallow near the coast and deepens
+// faster towards the shelf break
+const SHELF_PROFILE_EXPONENT: f32 = 1.5;
+// Abyssal hill noise: cycles across the map, octaves, and how much longer the hills run along
+// their strike than across it
+const HILL_FREQUENCY: f32 = 12.0;
+const HILL_OCTAVES: u32 = 3;
+const HILL_ELONGATION: f32 = 3.0;
+// Hills keep this share of their amplitude on the shelf, where sediment buries them
+const SHELF_HILL_SHARE: f32 = 0.2;
+// Closest any seafloor cell comes to the surface (height units), so shaping never drains the sea
+const MIN_WATER_DEPTH: f32 = 1e-4;
+
+// Ocean floor profile by distance from the coast: a gently sloping continental shelf, a steep
+// continental slope past the shelf break, and a smoothed abyssal plain with low elongated hills.
+// Widths are in cells, depths in height units below sea level.
+#[wasm_bindgen]
+#[derive(Clone, Copy)]
+pub struct BathymetryParams {
+    pub shelf_width: f32,
+    pub shelf_depth: f32, // at the shelf break
+    pub slope_width: f32, // from the shelf break down to the abyssal plain
+    pub abyssal_depth: f32,
+    pub abyssal_smoothing: f32, // 0-1, share of the original seafloor relief the plain loses
+    pub hill_amplitude: f32,    // abyssal hills, height units
+    // Trenches along converging plate boundaries in deep water, this far below the plain;
+    // 0 turns them off
+    pub trench_depth: f32,
+    pub trench_width: f32, // cells, half-width of the trench
+    pub plate_count: u32,
+    pub seed: u32,
+}
+
+#[wasm_bindgen]
+impl BathymetryParams {
+    #[wasm_bindgen(constructor)]
+    pub fn new(shelf_width: f32, shelf_depth: f32, slope_width: f32, abyssal_depth: f32) -> Self {
+        Self {
+            shelf_width,
+            shelf_depth,
+            slope_width,
+            abyssal_depth,
+            abyssal_smoothing: 0.8,
+            hill_amplitude: abyssal_depth * 0.05,
+            trench_depth: 0.0,
+            trench_width: 6.0,
+            plate_count: 6,
+            seed: 0,
+        }
+    }
+}
+
+// Smooth step from 0 at `edge0` to 1 at `edge1`
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0).max(f32::EPSILON)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+// Signed -1..1 fractal noise stretched along `strike` (radians)
+fn abyssal_hills(x: f32, y: f32, strike: f32, seed: u32) -> f32 {
+    let (sin, cos) = (crate::det_math::sin(strike), crate::det_math::cos(strike));
+    let along = (x * cos + y * sin) / HILL_ELONGATION;
+    let across = -x * sin + y * cos;
+    let offset = seed as f32 * 0.37;
+    let (mut freq, mut amp, mut sum, mut norm) = (HILL_FREQUENCY, 1.0, 0.0, 0.0);
+    for _ in 0..HILL_OCTAVES {
+        sum += (value_noise_2d(along * freq + offset, across * freq - offset) * 2.0 - 1.0) * amp;
+        norm += amp;
+        freq *= 2.0;
+        amp *= 0.5;
+    }
+    sum / norm
+}
+
+// Depth (height units below the abyssal plain) of trenches at every cell. Plates are Voronoi
+// cells around seeded centers, each drifting in a seeded direction; where a plate moves towards
+// its neighbor the boundary gets a trench as deep as the convergence is head-on.
+fn trench_depths(n: usize, params: &BathymetryParams) -> Vec<f32> {
+    let plates: Vec<([f32; 2], [f32; 2])> = (0..params.plate_count.max(2) as i32)
+        .map(|i| {
+            let center = [
+                hash_to_unit(hash_cell(i, 0, params.seed)) * n as f32,
+                hash_to_unit(hash_cell(i, 1, params.seed)) * n as f32,
+            ];
+            let heading = hash_to_unit(hash_cell(i, 2, params.seed)) * std::f32::consts::TAU;
+            (center, [crate::det_math::cos(heading), crate::det_math::sin(heading)])
+        })
+        .collect();
+
+    (0..n * n)
+        .map(|idx| {
+            let p = [(idx % n) as f32, (idx / n) as f32];
+            let dist2 = |c: [f32; 2]| (p[0] - c[0]).powi(2) + (p[1] - c[1]).powi(2);
+            let (mut first, mut second) = (0, 1);
+            if dist2(plates[second].0) < dist2(plates[first].0) {
+                std::mem::swap(&mut first, &mut second);
+            }
+            for (i, plate) in plates.iter().enumerate().skip(2) {
+                if dist2(plate.0) < dist2(plates[first].0) {
+                    second = first;
+                    first = i;
+                } else if dist2(plate.0) < dist2(plates[second].0) {
+                    second = i;
+                }
+            }
+            let ((c1, v1), (c2, v2)) = (plates[first], plates[second]);
+            let span = ((c2[0] - c1[0]).powi(2) + (c2[1] - c1[1]).powi(2)).sqrt().max(f32::EPSILON);
+            let normal = [(c2[0] - c1[0]) / span, (c2[1] - c1[1]) / span];
+            // Distance to the bisector between the two centers
+            let boundary = (dist2(c2) - dist2(c1)) / (2.0 * span);
+            let convergence = ((v1[0] - v2[0]) * normal[0] + (v1[1] - v2[1]) * normal[1]) * 0.5;
+            if convergence <= 0.0 {
+                return 0.0;
+            }
+            let across = boundary / params.trench_width.max(1.0);
+            params.trench_depth * convergence * crate::det_math::exp(-across * across)
+        })
+        .collect()
+}
+
+// Reshape every cell at or below `sea_level` to the shelf, slope and plain profile, keeping the
+// original seafloor's small-scale relief on the shelf and `1 - abyssal_smoothing` of it on the
+// plain. Land is untouched.
+#[wasm_bindgen]
+pub fn apply_bathymetry(height_field: &mut HeightField, sea_level: f32, params: &BathymetryParams) {
+    let n = height_field.size();
+    if n == 0 {
+        return;
+    }
+    let land: Vec<bool> = height_field.data().iter().map(|&h| h > sea_level).collect();
+    if land.iter().all(|&l| l) {
+        return;
+    }
+    let distance_to_land = distance_to_mask(&land, n);
+    let mut smoothed = height_field.clone();
+    apply_smoothing(&mut smoothed, DETAIL_SMOOTHING_PASSES, 1.0);
+    let trenches = (params.trench_depth > 0.0).then(|| trench_depths(n, params));
+    let strike = hash_to_unit(hash_cell(0, 0, params.seed ^ 0x5eaf_100d)) * std::f32::consts::PI;
+
+    let shelf_width = params.shelf_width.max(1.0);
+    for idx in 0..n * n {
+        if land[idx] {
+            continue;
+        }
+        // Without any land the whole map is open ocean
+        let distance = distance_to_land[idx].min(shelf_width + params.slope_width + 1.0);
+        let shelf = crate::det_math::powf((distance / shelf_width).min(1.0), SHELF_PROFILE_EXPONENT) * params.shelf_depth;
+        let abyss = smoothstep(shelf_width, shelf_width + params.slope_width, distance);
+        let mut depth = shelf + (params.abyssal_depth - params.shelf_depth).max(0.0) * abyss;
+
+        let detail = height_field.data()[idx] - smoothed.data()[idx];
+        depth -= detail * (1.0 - params.abyssal_smoothing.clamp(0.0, 1.0) * abyss);
+        let (u, v) = ((idx % n) as f32 / n as f32, (idx / n) as f32 / n as f32);
+        let hills = abyssal_hills(u, v, strike, params.seed) * params.hill_amplitude;
+        depth -= hills * (SHELF_HILL_SHARE + (1.0 - SHELF_HILL_SHARE) * abyss);
+        if let Some(trenches) = &trenches {
+            depth += trenches[idx] * abyss;
+        }
+        height_field.data_mut()[idx] = sea_level - depth.max(MIN_WATER_DEPTH);
+    }
+}
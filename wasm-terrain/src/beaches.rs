@@ -0,0 +1,134 @@
+use crate::filters::{distance_to_mask, slope_at};
+use crate::height_field::HeightField;
+use crate::water_system::{wave_exposure, WaterFeatures, DEFAULT_BEACH_MAX_SLOPE};
+use wasm_bindgen::prelude::*;
+
+// Land this close to the sea (cells) can be rocky shore where it is too steep for a beach
+const ROCK_SHORE_REACH: f32 = 3.0;
+// Distance (cells) along the coast over which a river mouth's sediment keeps beaches sandy
+const SEDIMENT_REACH: f32 = 24.0;
+
+// Shore material weights per cell. Sand, gravel and rock add up to how much of the cell is
+// shore (the beach mask, plus steep ground right at the waterline); all are 0 in the water
+// and inland.
+#[wasm_bindgen]
+#[derive(Clone)]
+pub struct BeachMaterials {
+    sand: Vec<f32>,
+    gravel: Vec<f32>,
+    rock: Vec<f32>,
+    size: usize,
+}
+
+#[wasm_bindgen]
+impl BeachMaterials {
+    #[wasm_bindgen(getter)]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    #[wasm_bindgen]
+    pub fn get_sand(&self) -> js_sys::Float32Array {
+        let array = js_sys::Float32Array::new_with_length(self.sand.len() as u32);
+        array.copy_from(&self.sand);
+        array
+    }
+
+    #[wasm_bindgen]
+    pub fn get_gravel(&self) -> js_sys::Float32Array {
+        let array = js_sys::Float32Array::new_with_length(self.gravel.len() as u32);
+        array.copy_from(&self.gravel);
+        array
+    }
+
+    #[wasm_bindgen]
+    pub fn get_rock(&self) -> js_sys::Float32Array {
+        let array = js_sys::Float32Array::new_with_length(self.rock.len() as u32);
+        array.copy_from(&self.rock);
+        array
+    }
+}
+
+// River cells touching the sea
+pub(crate) fn river_mouths(river: &[f32], sea: &[bool], n: usize) -> Vec<bool> {
+    (0..n * n)
+        .map(|idx| {
+            let (x, y) = ((idx % n) as i32, (idx / n) as i32);
+            river[idx] > 0.5
+                && !sea[idx]
+                && [(-1, 0), (1, 0), (0, -1), (0, 1)].iter().any(|&(dx, dy)| {
+                    let (nx, ny) = (x + dx, y + dy);
+                    nx >= 0 && ny >= 0 && (nx as usize) < n && (ny as usize) < n && sea[ny as usize * n + nx as usize]
+                })
+        })
+        .collect()
+}
+
+// Steep shores are rock, the more so where waves hit them. What remains is gravel in
+// proportion to wave energy, which river sediment nearby damps back into sand, so sheltered
+// bays and deltas get sand and open headlands shingle.
+pub fn beach_materials(height_field: &HeightField, water_features: &WaterFeatures, sea_level: f32, wind_direction: f32) -> BeachMaterials {
+    let n = height_field.size();
+    let data = height_field.data();
+    let beach = water_features.beach_mask();
+    let river = water_features.river_mask();
+    let exposure = wave_exposure(height_field, sea_level, wind_direction);
+
+    let sea: Vec<bool> = data.iter().map(|&h| h <= sea_level).collect();
+    let distance_to_sea = distance_to_mask(&sea, n);
+    let distance_to_mouth = distance_to_mask(&river_mouths(river, &sea, n), n);
+
+    let mut sand = vec![0.0f32; n * n];
+    let mut gravel = vec![0.0f32; n * n];
+    let mut rock = vec![0.0f32; n * n];
+    for y in 0..n {
+        for x in 0..n {
+            let idx = y * n + x;
+            if sea[idx] {
+                continue;
+            }
+            let steepness = (slope_at(height_field, x, y) / (2.0 * DEFAULT_BEACH_MAX_SLOPE)).min(1.0);
+            let waterline = (1.0 - distance_to_sea[idx] / (ROCK_SHORE_REACH + 1.0)).max(0.0);
+            let coverage = beach[idx].max(waterline * steepness);
+            if coverage <= 0.0 {
+                continue;
+            }
+
+            // Without any mouth the distance is infinite and the supply 0
+            let supply = crate::det_math::exp(-distance_to_mouth[idx] / SEDIMENT_REACH);
+            let rocky = steepness * (0.5 + 0.5 * exposure[idx]);
+            let energy = exposure[idx] * (1.0 - supply);
+            rock[idx] = coverage * rocky;
+            gravel[idx] = coverage * (1.0 - rocky) * energy;
+            sand[idx] = coverage * (1.0 - rocky) * (1.0 - energy);
+        }
+    }
+
+    BeachMaterials { sand, gravel, rock, size: n }
+}
+
+// Sand / gravel / rocky-shore channels for coast texturing; `wind_direction` (radians, the
+// way the prevailing wind blows) sets which shores face the waves
+#[wasm_bindgen]
+pub fn classify_beach_materials(
+    height_field: &HeightField,
+    water_features: &WaterFeatures,
+    sea_level: f32,
+    wind_direction: f32,
+) -> BeachMaterials {
+    beach_materials(height_field, water_features, sea_level, wind_direction)
+}
+
+#[cfg(all(test, feature = "strict_determinism"))]
+mod tests {
+    use crate::biomes::BiomeType;
+    use crate::det_math::tests::{hex, terrain};
+
+    #[test]
+    fn beach_materials_are_pinned() {
+        let base = terrain(BiomeType::Temperate);
+        let features = base.water_features.as_ref().unwrap();
+        let materials = super::beach_materials(&base.height_field, features, base.units.sea_level_height(), 0.7);
+        assert_eq!(hex(&[&materials.sand, &materials.gravel, &materials.rock]), "d4a2a847b398c56d");
+    }
+}
@@ -0,0 +1,138 @@
+use crate::biomes::{BiomeParams, BiomeType};
+use std::sync::Mutex;
+use wasm_bindgen::prelude::*;
+
+// String ids of the built-in biomes. New APIs take these instead of BiomeType, so biomes can be
+// added without changing the enum callers compile against; each enum value is an alias for its id.
+const BUILTIN_BIOMES: [(&str, BiomeType); 6] = [
+    ("desert", BiomeType::Desert),
+    ("alpine", BiomeType::Alpine),
+    ("temperate", BiomeType::Temperate),
+    ("volcanic", BiomeType::Volcanic),
+    ("tundra", BiomeType::Tundra),
+    ("badlands", BiomeType::Badlands),
+];
+
+// Ids registered at runtime with their parameters. Entries are never removed, so a position
+// in the list (a registry slot) names a registered biome for good.
+static REGISTERED: Mutex<Vec<(String, BiomeParams)>> = Mutex::new(Vec::new());
+
+fn registered() -> std::sync::MutexGuard<'static, Vec<(String, BiomeParams)>> {
+    REGISTERED.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+impl BiomeType {
+    pub(crate) fn id(self) -> &'static str {
+        BUILTIN_BIOMES.iter().find(|(_, b)| *b == self).map_or("", |(id, _)| id)
+    }
+}
+
+// Ids compare case-insensitively and ignore surrounding whitespace
+fn normalize(id: &str) -> String {
+    id.trim().to_ascii_lowercase()
+}
+
+// The biome `id` is based on and, for registered ids, its registry slot
+pub(crate) fn lookup(id: &str) -> Option<(BiomeType, Option<usize>)> {
+    let id = normalize(id);
+    if let Some((_, biome)) = BUILTIN_BIOMES.iter().find(|(builtin, _)| *builtin == id) {
+        return Some((*biome, None));
+    }
+    let registered = registered();
+    let slot = registered.iter().position(|(name, _)| *name == id)?;
+    Some((registered[slot].1.biome_type(), Some(slot)))
+}
+
+// Built-in biome whose generation `id` builds on, for built-in and registered ids alike
+pub(crate) fn resolve(id: &str) -> Option<BiomeType> {
+    lookup(id).map(|(biome, _)| biome)
+}
+
+// Id and parameters of a registry slot from lookup
+pub(crate) fn registered_biome(slot: usize) -> Option<(String, BiomeParams)> {
+    registered().get(slot).cloned()
+}
+
+// The BiomeType behind a biome id; undefined for unknown ids
+#[wasm_bindgen]
+pub fn resolve_biome(id: &str) -> Option<BiomeType> {
+    resolve(id)
+}
+
+// The string id of a BiomeType value
+#[wasm_bindgen]
+pub fn biome_id(biome_type: BiomeType) -> String {
+    biome_type.id().to_string()
+}
+
+// Add `id` as a biome generated with `params`: start from BiomeParams.for_id of the biome it
+// is based on and override the relief settings. Select it with TerrainConfig.set_biome_id.
+// False when `id` is empty or already taken.
+#[wasm_bindgen]
+pub fn register_biome(id: &str, params: &BiomeParams) -> bool {
+    let id = normalize(id);
+    if id.is_empty() || lookup(&id).is_some() {
+        return false;
+    }
+    registered().push((id, *params));
+    true
+}
+
+// Every known biome id: the built-ins in enum order, then registered ones in registration order
+#[wasm_bindgen]
+pub fn biome_ids() -> js_sys::Array {
+    let ids = js_sys::Array::new();
+    for (id, _) in BUILTIN_BIOMES {
+        ids.push(&id.into());
+    }
+    for (id, _) in registered().iter() {
+        ids.push(&id.as_str().into());
+    }
+    ids
+}
+
+#[wasm_bindgen]
+impl BiomeParams {
+    // Parameters of the biome with string id `id`, as registered for registered ids; undefined
+    // for unknown ids
+    #[wasm_bindgen]
+    pub fn for_id(id: &str) -> Option<BiomeParams> {
+        match lookup(id)? {
+            (_, Some(slot)) => registered_biome(slot).map(|(_, params)| params),
+            (biome, None) => Some(BiomeParams::new(biome)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::TerrainConfig;
+
+    #[test]
+    fn registered_params_reach_generation() {
+        let mut params = BiomeParams::new(BiomeType::Temperate);
+        params.set_fbm_params(crate::noise::FBMParams::new(0.5, 3.0, 4, 2.0, 0.5, 0.0, 0));
+        params.set_dissection_intensity(0.4);
+        assert!(register_biome(" Rugged ", &params));
+        assert!(!register_biome("rugged", &params));
+        assert!(!register_biome("temperate", &params));
+        assert_eq!(BiomeParams::for_id("RUGGED").map(|p| p.fbm_params().frequency), Some(3.0));
+        assert_eq!(BiomeParams::for_id("rugged").map(|p| p.dissection_intensity()), Some(0.4));
+
+        let generate = |config: &TerrainConfig| crate::run_pipeline(config, Vec::new(), crate::budget::StageBudget::new(None));
+        let mut config = TerrainConfig::new(32, 1, 5, BiomeType::Desert, 0.0, 0.0);
+        assert!(config.set_biome_id("rugged"));
+        assert!(config.biome_type == BiomeType::Temperate);
+        assert_eq!(config.biome_id(), "rugged");
+        let custom = generate(&config);
+        assert_eq!(custom.biome_id, "rugged");
+        assert_eq!(custom.biome_params().dissection_intensity(), 0.4);
+
+        assert!(config.set_biome_id("temperate"));
+        let base = generate(&config);
+        assert_eq!(base.biome_id, "temperate");
+        assert_eq!(base.biome_params().dissection_intensity(), BiomeParams::new(BiomeType::Temperate).dissection_intensity());
+        assert_ne!(custom.height_field.data(), base.height_field.data());
+    }
+}
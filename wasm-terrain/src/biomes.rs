@@ -1,5 +1,8 @@
 use crate::noise::FBMParams;
 use crate::filters::{SlopeBlurParams, DuneParams};
+use crate::volcanic::LavaFlowParams;
+use crate::periglacial::PatternedGroundParams;
+use crate::oasis::OasisParams;
 use wasm_bindgen::prelude::*;
 
 #[wasm_bindgen]
@@ -8,27 +11,72 @@ pub enum BiomeType {
     Desert = 0,
     Alpine = 1,
     Temperate = 2,
+    Volcanic = 3,
+    Tundra = 4,
+    Badlands = 5,
 }
 
 #[wasm_bindgen]
+#[derive(Clone, Copy)]
 pub struct BiomeParams {
     biome_type: BiomeType,
+    // Relief settings that replace the biome's own, for biomes added with register_biome
+    fbm: Option<FBMParams>,
+    slope_blur: Option<SlopeBlurParams>,
+    ridge_sharpen: Option<f32>,
+    dissection: Option<f32>,
 }
 
 #[wasm_bindgen]
 impl BiomeParams {
     #[wasm_bindgen(constructor)]
     pub fn new(biome_type: BiomeType) -> Self {
-        Self { biome_type }
+        Self {
+            biome_type,
+            fbm: None,
+            slope_blur: None,
+            ridge_sharpen: None,
+            dissection: None,
+        }
     }
 
     #[wasm_bindgen]
     pub fn for_biome(biome_type: BiomeType) -> Self {
-        Self { biome_type }
+        Self::new(biome_type)
+    }
+
+    // The built-in biome these parameters start from; everything not overridden comes from it
+    #[wasm_bindgen(getter)]
+    pub fn biome_type(&self) -> BiomeType {
+        self.biome_type
+    }
+
+    #[wasm_bindgen]
+    pub fn set_fbm_params(&mut self, params: FBMParams) {
+        self.fbm = Some(params);
+    }
+
+    #[wasm_bindgen]
+    pub fn set_slope_blur_params(&mut self, params: SlopeBlurParams) {
+        self.slope_blur = Some(params);
+    }
+
+    #[wasm_bindgen]
+    pub fn set_ridge_sharpen_strength(&mut self, strength: f32) {
+        self.ridge_sharpen = Some(strength);
+    }
+
+    // Badlands dissection for any biome, e.g. a temperate biome with gullied slopes
+    #[wasm_bindgen]
+    pub fn set_dissection_intensity(&mut self, intensity: f32) {
+        self.dissection = Some(intensity);
     }
 
     #[wasm_bindgen]
     pub fn fbm_params(&self) -> FBMParams {
+        if let Some(params) = self.fbm {
+            return params;
+        }
         match self.biome_type {
             BiomeType::Desert => FBMParams {
                 amplitude: 0.15,
@@ -57,11 +105,41 @@ impl BiomeParams {
                 warp: 0.1,
                 seed: 0,
             },
+            BiomeType::Volcanic => FBMParams {
+                amplitude: 0.3,
+                frequency: 1.4,
+                octaves: 6,
+                lacunarity: 2.0,
+                gain: 0.5,
+                warp: 0.08,
+                seed: 0,
+            },
+            BiomeType::Tundra => FBMParams {
+                amplitude: 0.12,
+                frequency: 1.8,
+                octaves: 5,
+                lacunarity: 2.0,
+                gain: 0.5,
+                warp: 0.1,
+                seed: 0,
+            },
+            BiomeType::Badlands => FBMParams {
+                amplitude: 0.2,
+                frequency: 2.2,
+                octaves: 5,
+                lacunarity: 2.0,
+                gain: 0.5,
+                warp: 0.12,
+                seed: 0,
+            },
         }
     }
 
     #[wasm_bindgen]
     pub fn slope_blur_params(&self) -> SlopeBlurParams {
+        if let Some(params) = self.slope_blur {
+            return params;
+        }
         match self.biome_type {
             BiomeType::Desert => SlopeBlurParams {
                 radius: 2.0,
@@ -78,23 +156,133 @@ impl BiomeParams {
                 k: 0.4,
                 iterations: 2,
             },
+            BiomeType::Volcanic => SlopeBlurParams {
+                radius: 1.0,
+                k: 0.3,
+                iterations: 1,
+            },
+            BiomeType::Tundra => SlopeBlurParams {
+                radius: 2.0,
+                k: 0.5,
+                iterations: 2,
+            },
+            BiomeType::Badlands => SlopeBlurParams {
+                radius: 1.0,
+                k: 0.3,
+                iterations: 1,
+            },
         }
     }
 
     #[wasm_bindgen]
     pub fn ridge_sharpen_strength(&self) -> f32 {
+        if let Some(strength) = self.ridge_sharpen {
+            return strength;
+        }
         match self.biome_type {
             BiomeType::Desert => 0.2,
             BiomeType::Alpine => 0.6,
             BiomeType::Temperate => 0.35,
+            BiomeType::Volcanic => 0.45,
+            BiomeType::Tundra => 0.15,
+            BiomeType::Badlands => 0.3,
         }
     }
 
     #[wasm_bindgen]
     pub fn has_dunes(&self) -> bool {
+        matches!(self.biome_type, BiomeType::Desert | BiomeType::Temperate)
+    }
+
+    // Strength of the slot-canyon / hoodoo dissection pass, 0 = none
+    #[wasm_bindgen]
+    pub fn dissection_intensity(&self) -> f32 {
+        if let Some(intensity) = self.dissection {
+            return intensity;
+        }
+        match self.biome_type {
+            BiomeType::Badlands => 0.6,
+            _ => 0.0,
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn has_oases(&self) -> bool {
         matches!(self.biome_type, BiomeType::Desert)
     }
 
+    #[wasm_bindgen]
+    pub fn oasis_params(&self) -> OasisParams {
+        match self.biome_type {
+            BiomeType::Desert => OasisParams {
+                count: 3,
+                radius: 8.0,
+                pool_depth: 0.01,
+                dune_softening: 0.6,
+            },
+            _ => OasisParams {
+                count: 0,
+                radius: 0.0,
+                pool_depth: 0.0,
+                dune_softening: 0.0,
+            },
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn has_lava_flows(&self) -> bool {
+        matches!(self.biome_type, BiomeType::Volcanic)
+    }
+
+    #[wasm_bindgen]
+    pub fn has_patterned_ground(&self) -> bool {
+        matches!(self.biome_type, BiomeType::Tundra)
+    }
+
+    #[wasm_bindgen]
+    pub fn patterned_ground_params(&self) -> PatternedGroundParams {
+        match self.biome_type {
+            BiomeType::Tundra => PatternedGroundParams {
+                polygon_size: 14.0,
+                crack_depth: 0.004,
+                pingo_count: 4,
+                pingo_radius: 6.0,
+                pingo_height: 0.015,
+                lobe_amplitude: 0.004,
+                supersample: false,
+            },
+            _ => PatternedGroundParams {
+                polygon_size: 0.0,
+                crack_depth: 0.0,
+                pingo_count: 0,
+                pingo_radius: 0.0,
+                pingo_height: 0.0,
+                lobe_amplitude: 0.0,
+                supersample: false,
+            },
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn lava_flow_params(&self) -> LavaFlowParams {
+        match self.biome_type {
+            BiomeType::Volcanic => LavaFlowParams {
+                vent_count: 3,
+                volume: 3.0,
+                steps: 200,
+                yield_strength: 0.004,
+                cooling_rate: 0.02,
+            },
+            _ => LavaFlowParams {
+                vent_count: 0,
+                volume: 0.0,
+                steps: 0,
+                yield_strength: 0.0,
+                cooling_rate: 0.0,
+            },
+        }
+    }
+
     #[wasm_bindgen]
     pub fn dunes_params(&self) -> DuneParams {
         match self.biome_type {
@@ -102,11 +290,19 @@ impl BiomeParams {
                 scale: 16.0,
                 amplitude: 0.03,
                 direction: std::f32::consts::PI * 0.25,
+                asymmetry: 0.7,
+            },
+            BiomeType::Temperate => DuneParams {
+                scale: 16.0,
+                amplitude: 0.012,
+                direction: std::f32::consts::PI * 0.25,
+                asymmetry: 0.4,
             },
             _ => DuneParams {
                 scale: 0.0,
                 amplitude: 0.0,
                 direction: 0.0,
+                asymmetry: 0.0,
             },
         }
     }
@@ -117,6 +313,59 @@ impl BiomeParams {
             BiomeType::Desert => 600.0,
             BiomeType::Alpine => 1800.0,
             BiomeType::Temperate => 900.0,
+            BiomeType::Volcanic => 1500.0,
+            BiomeType::Tundra => 500.0,
+            BiomeType::Badlands => 700.0,
+        }
+    }
+
+    // Climate parameters
+    #[wasm_bindgen]
+    pub fn mean_temperature(&self) -> f32 {
+        match self.biome_type {
+            BiomeType::Desert => 24.0,
+            BiomeType::Alpine => 6.0,
+            BiomeType::Temperate => 12.0,
+            BiomeType::Volcanic => 14.0,
+            BiomeType::Tundra => -8.0,
+            BiomeType::Badlands => 15.0,
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn seasonal_temperature_range(&self) -> f32 {
+        match self.biome_type {
+            BiomeType::Desert => 10.0,
+            BiomeType::Alpine => 14.0,
+            BiomeType::Temperate => 12.0,
+            BiomeType::Volcanic => 8.0,
+            BiomeType::Tundra => 18.0,
+            BiomeType::Badlands => 16.0,
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn summer_dryness(&self) -> f32 {
+        match self.biome_type {
+            BiomeType::Desert => 0.7,
+            BiomeType::Alpine => 0.1,
+            BiomeType::Temperate => 0.3,
+            BiomeType::Volcanic => 0.4,
+            BiomeType::Tundra => 0.2,
+            BiomeType::Badlands => 0.6,
+        }
+    }
+
+    // Baseline air and ground moisture (0 arid - 1 humid)
+    #[wasm_bindgen]
+    pub fn humidity(&self) -> f32 {
+        match self.biome_type {
+            BiomeType::Desert => 0.1,
+            BiomeType::Alpine => 0.6,
+            BiomeType::Temperate => 0.7,
+            BiomeType::Volcanic => 0.5,
+            BiomeType::Tundra => 0.4,
+            BiomeType::Badlands => 0.25,
         }
     }
 
@@ -127,6 +376,9 @@ impl BiomeParams {
             BiomeType::Desert => 0.1,
             BiomeType::Alpine => 0.05,
             BiomeType::Temperate => 0.08,
+            BiomeType::Volcanic => 0.06,
+            BiomeType::Tundra => 0.07,
+            BiomeType::Badlands => 0.1,
         }
     }
 
@@ -136,6 +388,9 @@ impl BiomeParams {
             BiomeType::Desert => 0.2,
             BiomeType::Alpine => 0.15,
             BiomeType::Temperate => 0.12,
+            BiomeType::Volcanic => 0.18,
+            BiomeType::Tundra => 0.14,
+            BiomeType::Badlands => 0.1,
         }
     }
 
@@ -145,6 +400,9 @@ impl BiomeParams {
             BiomeType::Desert => 2.0,
             BiomeType::Alpine => 1.5,
             BiomeType::Temperate => 3.0,
+            BiomeType::Volcanic => 2.0,
+            BiomeType::Tundra => 2.5,
+            BiomeType::Badlands => 1.5,
         }
     }
 
@@ -154,6 +412,9 @@ impl BiomeParams {
             BiomeType::Desert => 0.03,
             BiomeType::Alpine => 0.04,
             BiomeType::Temperate => 0.025,
+            BiomeType::Volcanic => 0.035,
+            BiomeType::Tundra => 0.02,
+            BiomeType::Badlands => 0.04,
         }
     }
 
@@ -163,6 +424,9 @@ impl BiomeParams {
             BiomeType::Desert => 0.05,
             BiomeType::Alpine => 0.03,
             BiomeType::Temperate => 0.04,
+            BiomeType::Volcanic => 0.03,
+            BiomeType::Tundra => 0.02,
+            BiomeType::Badlands => 0.05,
         }
     }
 
@@ -172,6 +436,9 @@ impl BiomeParams {
             BiomeType::Desert => 8.0,
             BiomeType::Alpine => 6.0,
             BiomeType::Temperate => 10.0,
+            BiomeType::Volcanic => 5.0,
+            BiomeType::Tundra => 4.0,
+            BiomeType::Badlands => 5.0,
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,76 @@
+use crate::error::TerrainError;
+
+// One parameter moved into the range the generator supports. Lists of these replace the caps
+// that used to be applied silently, so callers can see what actually ran.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParamAdjustment {
+    pub field: &'static str,
+    pub requested: f64,
+    pub applied: f64,
+    pub reason: String,
+}
+
+impl ParamAdjustment {
+    // Convert to JS object for interop
+    pub fn to_js_object(&self) -> js_sys::Object {
+        let obj = js_sys::Object::new();
+
+        js_sys::Reflect::set(&obj, &"field".into(), &self.field.into()).unwrap();
+        js_sys::Reflect::set(&obj, &"requested".into(), &self.requested.into()).unwrap();
+        js_sys::Reflect::set(&obj, &"applied".into(), &self.applied.into()).unwrap();
+        js_sys::Reflect::set(&obj, &"reason".into(), &self.reason.as_str().into()).unwrap();
+
+        obj
+    }
+}
+
+pub(crate) fn to_js_array(adjustments: &[ParamAdjustment]) -> js_sys::Array {
+    let array = js_sys::Array::new();
+    for adjustment in adjustments {
+        array.push(&adjustment.to_js_object());
+    }
+    array
+}
+
+// Clamp `value` into [min, max], recording the change when there is one
+pub(crate) fn clamp_f32(adjustments: &mut Vec<ParamAdjustment>, field: &'static str, value: &mut f32, min: f32, max: f32) {
+    let applied = value.clamp(min, max);
+    if applied != *value {
+        adjustments.push(ParamAdjustment {
+            field,
+            requested: *value as f64,
+            applied: applied as f64,
+            reason: format!("{} must lie in [{}, {}]", field, min, max),
+        });
+        *value = applied;
+    }
+}
+
+pub(crate) fn clamp_u32(adjustments: &mut Vec<ParamAdjustment>, field: &'static str, value: &mut u32, min: u32, max: u32, reason: &str) {
+    let applied = (*value).clamp(min, max);
+    if applied != *value {
+        adjustments.push(ParamAdjustment { field, requested: *value as f64, applied: applied as f64, reason: reason.to_string() });
+        *value = applied;
+    }
+}
+
+// Tile grid layout the generator can cut: rows, columns and tile size are rejected when zero,
+// tiles too small to keep any inner cells are rejected, and an overlap that eats half a tile or
+// more is reduced to the largest that leaves one inner cell. Returns the overlap to use.
+pub(crate) fn tile_grid_overlap(
+    rows: u32,
+    cols: u32,
+    tile_size: u32,
+    overlap: u32,
+    adjustments: &mut Vec<ParamAdjustment>,
+) -> Result<u32, TerrainError> {
+    if rows == 0 || cols == 0 || tile_size == 0 {
+        return Err(TerrainError::InvalidParameter(format!(
+            "rows, cols and tile_size must be positive, got {}x{} tiles of {}",
+            rows, cols, tile_size
+        )));
+    }
+    let mut overlap = overlap;
+    clamp_u32(adjustments, "overlap", &mut overlap, 0, (tile_size - 1) / 2, "overlap must be less than half of tile_size");
+    Ok(overlap)
+}
@@ -0,0 +1,116 @@
+use crate::height_field::HeightField;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum BrushMode {
+    Raise = 0,
+    Lower = 1,
+    Flatten = 2, // pull toward the height under the brush center
+    Smooth = 3,
+    Noise = 4,
+}
+
+// Weight from the brush center (1) to its rim (0)
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum BrushFalloff {
+    Constant = 0,
+    Linear = 1,
+    Smooth = 2, // smoothstep
+    Sphere = 3, // hemispherical profile
+}
+
+impl BrushFalloff {
+    fn weight(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            BrushFalloff::Constant => 1.0,
+            BrushFalloff::Linear => 1.0 - t,
+            BrushFalloff::Smooth => {
+                let s = 1.0 - t;
+                s * s * (3.0 - 2.0 * s)
+            }
+            BrushFalloff::Sphere => (1.0 - t * t).sqrt(),
+        }
+    }
+}
+
+// Inclusive-exclusive cell rectangle [x0, x1) x [y0, y1)
+pub(crate) type CellRect = [usize; 4];
+
+// Apply one brush dab centered at (x, y) in cell coordinates. `strength` is the height change
+// at full weight for Raise/Lower/Noise and the blend fraction (0-1) for Flatten/Smooth.
+// Returns the rectangle of cells that may have changed.
+pub(crate) fn apply_brush(
+    height_field: &mut HeightField,
+    x: f32,
+    y: f32,
+    radius: f32,
+    strength: f32,
+    mode: BrushMode,
+    falloff: BrushFalloff,
+) -> Option<CellRect> {
+    let n = height_field.size();
+    if n == 0 || radius <= 0.0 {
+        return None;
+    }
+
+    let x0 = (x - radius).floor().max(0.0) as usize;
+    let y0 = (y - radius).floor().max(0.0) as usize;
+    let x1 = ((x + radius).ceil() + 1.0).clamp(0.0, n as f32) as usize;
+    let y1 = ((y + radius).ceil() + 1.0).clamp(0.0, n as f32) as usize;
+    if x0 >= x1 || y0 >= y1 {
+        return None;
+    }
+
+    let center = height_field.get_clamped(x.round() as i32, y.round() as i32);
+    // Smoothing reads the unmodified neighborhood: copy the dab rectangle plus a 1-cell ring
+    let (wx0, wy0) = (x0 as i32 - 1, y0 as i32 - 1);
+    let ww = x1 - x0 + 2;
+    let window: Vec<f32> = if mode == BrushMode::Smooth {
+        (0..(y1 - y0 + 2) * ww)
+            .map(|k| height_field.get_clamped(wx0 + (k % ww) as i32, wy0 + (k / ww) as i32))
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    for cy in y0..y1 {
+        for cx in x0..x1 {
+            let dx = cx as f32 - x;
+            let dy = cy as f32 - y;
+            let d = (dx * dx + dy * dy).sqrt();
+            if d > radius {
+                continue;
+            }
+            let w = falloff.weight(d / radius);
+            let h = height_field.get(cx, cy);
+
+            let value = match mode {
+                BrushMode::Raise => h + strength * w,
+                BrushMode::Lower => h - strength * w,
+                BrushMode::Flatten => h + (center - h) * (strength * w).min(1.0),
+                BrushMode::Smooth => {
+                    let mut sum = 0.0;
+                    for oy in 0..3 {
+                        let row = (cy - y0 + oy) * ww;
+                        for ox in 0..3 {
+                            sum += window[row + cx - x0 + ox];
+                        }
+                    }
+                    h + (sum / 9.0 - h) * (strength * w).min(1.0)
+                }
+                BrushMode::Noise => {
+                    // Features about a quarter of the brush wide, fixed in map space
+                    let scale = 4.0 / radius.max(1.0);
+                    let noise = crate::noise::value_noise_2d(cx as f32 * scale, cy as f32 * scale) * 2.0 - 1.0;
+                    h + noise * strength * w
+                }
+            };
+            height_field.set(cx, cy, value);
+        }
+    }
+
+    Some([x0, y0, x1, y1])
+}
@@ -0,0 +1,45 @@
+// Part of the budget that detail stages ahead of erosion may start within; the rest is kept
+// for erosion, which matters more to the finished look
+pub(crate) const DETAIL_SHARE: f64 = 0.5;
+// Stages that may start as long as any budget is left
+pub(crate) const FULL_SHARE: f64 = 1.0;
+
+// Decides which pipeline stages fit in a generation time budget and records the outcome.
+// Stages are asked in pipeline order; once the budget is spent every later stage is skipped,
+// while less important stages give up earlier so the important ones still get their turn.
+pub(crate) struct StageBudget {
+    start: f64,
+    budget_ms: Option<f64>, // None runs everything
+    completed: Vec<String>,
+    skipped: Vec<String>,
+}
+
+impl StageBudget {
+    pub(crate) fn new(budget_ms: Option<f64>) -> Self {
+        Self { start: crate::utils::now_ms(), budget_ms, completed: Vec::new(), skipped: Vec::new() }
+    }
+
+    // Whether `label` may run, given it has to start within `share` of the budget
+    pub(crate) fn admit(&mut self, label: &str, share: f64) -> bool {
+        let fits = match self.budget_ms {
+            Some(budget) => crate::utils::now_ms() - self.start < budget * share,
+            None => true,
+        };
+        if fits {
+            self.completed.push(label.to_string());
+        } else {
+            self.skipped.push(label.to_string());
+        }
+        fits
+    }
+
+    // Record a stage that runs whatever the budget says
+    pub(crate) fn always(&mut self, label: &str) {
+        self.completed.push(label.to_string());
+    }
+
+    // Completed and skipped stage labels, each in pipeline order
+    pub(crate) fn into_report(self) -> (Vec<String>, Vec<String>) {
+        (self.completed, self.skipped)
+    }
+}
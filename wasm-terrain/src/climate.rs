@@ -0,0 +1,158 @@
+use crate::biomes::{BiomeParams, BiomeType};
+use crate::height_field::HeightField;
+use crate::precipitation::{advected_rain_grid, climate_grid_size, grid_lookup};
+use crate::seasons::LAPSE_RATE;
+use crate::units::VerticalUnits;
+use crate::TerrainGenerationResult;
+use wasm_bindgen::prelude::*;
+
+// Annual mean temperature (°C) below which ground is classed tundra, and below which it is
+// alpine rather than lowland
+const TUNDRA_TEMPERATURE: f32 = -2.0;
+const ALPINE_TEMPERATURE: f32 = 4.0;
+// Moisture (biome humidity × relative precipitation) below which land is desert, and below
+// which it is badlands rather than temperate
+const DESERT_MOISTURE: f32 = 0.2;
+const BADLANDS_MOISTURE: f32 = 0.4;
+// Dry land warmer than this (°C) is desert; cooler dry land is badlands
+const DESERT_TEMPERATURE: f32 = 18.0;
+
+// Annual climate on a coarse grid of its own, independent of the height field resolution, so
+// it stays cheap on very large maps. Per-cell values are bilinear lookups into the grid, and
+// biomes are classified per cell from those looked-up values.
+#[wasm_bindgen]
+#[derive(Clone)]
+pub struct ClimateGrid {
+    temperature: Vec<f32>,   // °C, annual mean
+    precipitation: Vec<f32>, // relative to the map mean (1.0)
+    grid_size: usize,
+    size: usize,
+    humidity: f32,
+}
+
+#[wasm_bindgen]
+impl ClimateGrid {
+    // Cells per side of the height field the grid covers
+    #[wasm_bindgen(getter)]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    // Coarse cells per side
+    #[wasm_bindgen(getter)]
+    pub fn grid_size(&self) -> usize {
+        self.grid_size
+    }
+
+    // Coarse temperature grid, grid_size × grid_size
+    #[wasm_bindgen]
+    pub fn get_temperature(&self) -> js_sys::Float32Array {
+        let array = js_sys::Float32Array::new_with_length(self.temperature.len() as u32);
+        array.copy_from(&self.temperature);
+        array
+    }
+
+    // Coarse precipitation grid, grid_size × grid_size
+    #[wasm_bindgen]
+    pub fn get_precipitation(&self) -> js_sys::Float32Array {
+        let array = js_sys::Float32Array::new_with_length(self.precipitation.len() as u32);
+        array.copy_from(&self.precipitation);
+        array
+    }
+
+    #[wasm_bindgen]
+    pub fn temperature_at(&self, x: usize, y: usize) -> f32 {
+        grid_lookup(&self.temperature, self.grid_size, self.size, x, y)
+    }
+
+    #[wasm_bindgen]
+    pub fn precipitation_at(&self, x: usize, y: usize) -> f32 {
+        grid_lookup(&self.precipitation, self.grid_size, self.size, x, y)
+    }
+
+    #[wasm_bindgen]
+    pub fn biome_at(&self, x: usize, y: usize) -> BiomeType {
+        classify(self.temperature_at(x, y), self.humidity * self.precipitation_at(x, y))
+    }
+
+    // BiomeType per height field cell, size × size
+    #[wasm_bindgen]
+    pub fn get_biome_map(&self) -> js_sys::Uint8Array {
+        let biomes = self.biome_map();
+        let array = js_sys::Uint8Array::new_with_length(biomes.len() as u32);
+        array.copy_from(&biomes);
+        array
+    }
+
+    // Internal methods for Rust use
+    pub(crate) fn biome_map(&self) -> Vec<u8> {
+        let n = self.size;
+        (0..n * n).map(|idx| self.biome_at(idx % n, idx / n) as u8).collect()
+    }
+}
+
+// Climate biome of an annual mean temperature and moisture; volcanic ground is geology, not
+// climate, and is never picked
+fn classify(temperature: f32, moisture: f32) -> BiomeType {
+    if temperature < TUNDRA_TEMPERATURE {
+        BiomeType::Tundra
+    } else if temperature < ALPINE_TEMPERATURE {
+        BiomeType::Alpine
+    } else if moisture < DESERT_MOISTURE {
+        if temperature > DESERT_TEMPERATURE {
+            BiomeType::Desert
+        } else {
+            BiomeType::Badlands
+        }
+    } else if moisture < BADLANDS_MOISTURE {
+        BiomeType::Badlands
+    } else {
+        BiomeType::Temperate
+    }
+}
+
+// Temperature from the mean elevation of each coarse cell, precipitation from the moisture
+// advection model run on the same grid. `grid_size` 0 picks the default.
+pub fn climate_grid(
+    height_field: &HeightField,
+    units: &VerticalUnits,
+    biome_type: BiomeType,
+    wind_direction: f32,
+    grid_size: usize,
+) -> ClimateGrid {
+    let n = height_field.size();
+    let g = climate_grid_size(n, grid_size);
+    let sea = units.sea_level_height();
+    let biome = BiomeParams::new(biome_type);
+
+    let mut elevation = vec![0.0f32; g * g];
+    let mut count = vec![0u32; g * g];
+    for (idx, &h) in height_field.data().iter().enumerate() {
+        let c = (idx / n) * g / n * g + (idx % n) * g / n;
+        elevation[c] += (h - sea).max(0.0);
+        count[c] += 1;
+    }
+    let temperature = elevation
+        .iter()
+        .zip(&count)
+        .map(|(&e, &c)| {
+            let meters = units.to_meters(if c > 0 { e / c as f32 } else { 0.0 });
+            biome.mean_temperature() - meters / 1000.0 * LAPSE_RATE
+        })
+        .collect();
+
+    let mut precipitation = if n > 0 { advected_rain_grid(height_field, wind_direction, sea, g) } else { vec![0.0; g * g] };
+    let mean = precipitation.iter().sum::<f32>() / precipitation.len() as f32;
+    if mean > 0.0 {
+        precipitation.iter_mut().for_each(|r| *r /= mean);
+    }
+
+    ClimateGrid { temperature, precipitation, grid_size: g, size: n, humidity: biome.humidity() }
+}
+
+// Climate of a generated terrain on a `grid_size` × `grid_size` grid (0 = 64 cells per side),
+// with `wind_direction` (radians) the way the rain-bearing wind blows
+#[wasm_bindgen]
+pub fn compute_climate_grid(result: &TerrainGenerationResult, wind_direction: f32, grid_size: usize) -> ClimateGrid {
+    climate_grid(result.height_field_ref(), &result.vertical_units(), result.biome_type(), wind_direction, grid_size)
+}
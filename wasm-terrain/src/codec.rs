@@ -0,0 +1,223 @@
+use crate::height_field::HeightField;
+
+// Serialized form tag, bumped whenever the byte layout changes
+const CODEC_MAGIC: &[u8; 4] = b"HFC1";
+// Cells per block side; each block picks its own Rice parameter
+const BLOCK: usize = 32;
+// Finest quantization step relative to the height range, so level indices fit comfortably
+// in 32 bits whatever max_error asks for
+const MAX_LEVELS: f32 = (1u32 << 24) as f32;
+// Largest Rice parameter tried per block
+const MAX_RICE_K: u32 = 24;
+// Unary quotients this long switch to a raw 32-bit value
+const RICE_ESCAPE: u32 = 24;
+
+// Near-lossless codec: heights are quantized to steps of 2 · max_error (so every decoded
+// height is within max_error of the original), each level is predicted from its decoded
+// neighbors with the LOCO-I median edge detector, and the residuals are Rice coded with a
+// parameter chosen per 32×32 block. Smooth terrain costs a few bits per cell.
+pub(crate) fn compress(height_field: &HeightField, max_error: f32) -> Vec<u8> {
+    let n = height_field.size();
+    let data = height_field.data();
+    let min = data.iter().fold(f32::INFINITY, |m, &h| m.min(h));
+    let max = data.iter().fold(f32::NEG_INFINITY, |m, &h| m.max(h));
+    let (min, max) = if data.is_empty() { (0.0, 0.0) } else { (min, max) };
+    let step = (2.0 * max_error).max((max - min) / MAX_LEVELS).max(f32::MIN_POSITIVE);
+
+    let levels: Vec<i64> = data.iter().map(|&h| ((h - min) / step).round() as i64).collect();
+
+    let mut bytes = Vec::with_capacity(16 + n * n / 2);
+    bytes.extend_from_slice(CODEC_MAGIC);
+    bytes.extend_from_slice(&(n as u32).to_le_bytes());
+    bytes.extend_from_slice(&min.to_le_bytes());
+    bytes.extend_from_slice(&step.to_le_bytes());
+
+    let mut writer = BitWriter::new(bytes);
+    for_each_block([0, 0, n, n], |cells| {
+        let residuals: Vec<u32> = cells.iter().map(|&(x, y)| zigzag(levels[y * n + x] - predict(&levels, n, x, y))).collect();
+        writer.write_block(&residuals);
+    });
+    writer.finish()
+}
+
+// Inverse of compress; None on malformed input
+pub(crate) fn decompress(bytes: &[u8]) -> Option<HeightField> {
+    if bytes.get(0..4)? != CODEC_MAGIC {
+        return None;
+    }
+    let word = |at: usize| <[u8; 4]>::try_from(bytes.get(at..at + 4)?).ok();
+    let n = u32::from_le_bytes(word(4)?) as usize;
+    let min = f32::from_le_bytes(word(8)?);
+    let step = f32::from_le_bytes(word(12)?);
+    // Every cell takes at least one bit, which bounds the size a valid stream can claim
+    if n.checked_mul(n)? > (bytes.len() - 16) * 8 {
+        return None;
+    }
+
+    let mut reader = BitReader::new(&bytes[16..]);
+    let mut levels = vec![0i64; n * n];
+    let mut ok = true;
+    for_each_block([0, 0, n, n], |cells| {
+        let Some(residuals) = reader.read_block(cells.len()).filter(|_| ok) else {
+            ok = false;
+            return;
+        };
+        for (&(x, y), &r) in cells.iter().zip(&residuals) {
+            levels[y * n + x] = predict(&levels, n, x, y) + unzigzag(r);
+        }
+    });
+    if !ok {
+        return None;
+    }
+
+    let mut height_field = HeightField::new(n);
+    for (h, &q) in height_field.data_mut().iter_mut().zip(&levels) {
+        *h = min + q as f32 * step;
+    }
+    Some(height_field)
+}
+
+// Visit the cells of [x, y, width, height] block by block (blocks and the cells inside them
+// in row-major order). Every cell's left, upper and upper-left neighbors come before it.
+pub(crate) fn for_each_block([x0, y0, w, h]: [usize; 4], mut visit: impl FnMut(&[(usize, usize)])) {
+    let mut cells = Vec::with_capacity(BLOCK * BLOCK);
+    for by in (y0..y0 + h).step_by(BLOCK) {
+        for bx in (x0..x0 + w).step_by(BLOCK) {
+            cells.clear();
+            for y in by..(by + BLOCK).min(y0 + h) {
+                for x in bx..(bx + BLOCK).min(x0 + w) {
+                    cells.push((x, y));
+                }
+            }
+            visit(&cells);
+        }
+    }
+}
+
+// LOCO-I median edge detector: follows edges and falls back to the plane through the
+// three neighbors on smooth ground
+fn predict(levels: &[i64], n: usize, x: usize, y: usize) -> i64 {
+    match (x, y) {
+        (0, 0) => 0,
+        (0, _) => levels[(y - 1) * n],
+        (_, 0) => levels[x - 1],
+        _ => {
+            let a = levels[y * n + x - 1];
+            let b = levels[(y - 1) * n + x];
+            let c = levels[(y - 1) * n + x - 1];
+            if c >= a.max(b) {
+                a.min(b)
+            } else if c <= a.min(b) {
+                a.max(b)
+            } else {
+                a + b - c
+            }
+        }
+    }
+}
+
+pub(crate) fn zigzag(v: i64) -> u32 {
+    let v = v.clamp(i32::MIN as i64, i32::MAX as i64) as i32;
+    ((v << 1) ^ (v >> 31)) as u32
+}
+
+pub(crate) fn unzigzag(v: u32) -> i64 {
+    ((v >> 1) as i32 ^ -((v & 1) as i32)) as i64
+}
+
+fn rice_cost(v: u32, k: u32) -> u64 {
+    let q = v >> k;
+    if q < RICE_ESCAPE { (q + 1 + k) as u64 } else { (RICE_ESCAPE + 32) as u64 }
+}
+
+// Little-endian bit stream appended to an existing byte header
+pub(crate) struct BitWriter {
+    bytes: Vec<u8>,
+    current: u64,
+    filled: u32,
+}
+
+impl BitWriter {
+    pub(crate) fn new(header: Vec<u8>) -> Self {
+        Self { bytes: header, current: 0, filled: 0 }
+    }
+
+    // Append the low `count` bits of `value`, least significant first
+    fn write(&mut self, value: u32, count: u32) {
+        if count == 0 {
+            return;
+        }
+        self.current |= ((value as u64) & ((1u64 << count) - 1)) << self.filled;
+        self.filled += count;
+        while self.filled >= 8 {
+            self.bytes.push(self.current as u8);
+            self.current >>= 8;
+            self.filled -= 8;
+        }
+    }
+
+    fn write_rice(&mut self, v: u32, k: u32) {
+        let q = v >> k;
+        if q < RICE_ESCAPE {
+            self.write((1 << q) - 1, q + 1);
+            self.write(v, k);
+        } else {
+            self.write((1 << RICE_ESCAPE) - 1, RICE_ESCAPE);
+            self.write(v, 32);
+        }
+    }
+
+    // Rice parameter (5 bits) chosen for the block, then every value
+    pub(crate) fn write_block(&mut self, values: &[u32]) {
+        let k = (0..=MAX_RICE_K).min_by_key(|&k| values.iter().map(|&v| rice_cost(v, k)).sum::<u64>()).unwrap_or(0);
+        self.write(k, 5);
+        for &v in values {
+            self.write_rice(v, k);
+        }
+    }
+
+    pub(crate) fn finish(mut self) -> Vec<u8> {
+        if self.filled > 0 {
+            self.bytes.push(self.current as u8);
+        }
+        self.bytes
+    }
+}
+
+pub(crate) struct BitReader<'a> {
+    bytes: &'a [u8],
+    pos: usize, // in bits
+}
+
+impl<'a> BitReader<'a> {
+    pub(crate) fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, pos: 0 }
+    }
+
+    fn read(&mut self, count: u32) -> Option<u32> {
+        let mut value = 0u64;
+        for i in 0..count as usize {
+            let bit = (self.bytes.get((self.pos + i) / 8)? >> ((self.pos + i) % 8)) & 1;
+            value |= (bit as u64) << i;
+        }
+        self.pos += count as usize;
+        Some(value as u32)
+    }
+
+    fn read_rice(&mut self, k: u32) -> Option<u32> {
+        let mut q = 0;
+        while q < RICE_ESCAPE && self.read(1)? == 1 {
+            q += 1;
+        }
+        if q == RICE_ESCAPE {
+            return self.read(32);
+        }
+        Some((q << k) | self.read(k)?)
+    }
+
+    // Inverse of BitWriter::write_block
+    pub(crate) fn read_block(&mut self, count: usize) -> Option<Vec<u32>> {
+        let k = self.read(5)?;
+        (0..count).map(|_| self.read_rice(k)).collect()
+    }
+}
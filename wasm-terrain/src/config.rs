@@ -0,0 +1,295 @@
+use crate::bathymetry::BathymetryParams;
+use crate::biomes::{BiomeParams, BiomeType};
+use crate::bounds::{clamp_f32, clamp_u32, ParamAdjustment};
+use crate::error::TerrainError;
+use crate::region::WorldRegion;
+use crate::units::VerticalUnits;
+use wasm_bindgen::prelude::*;
+
+// Largest finished map (cells per side) a config may ask for; one f32 layer of it is 256 MB
+pub(crate) const MAX_MAP_SIZE: u64 = 8192;
+
+#[wasm_bindgen]
+#[derive(Clone, Copy)]
+pub struct TerrainConfig {
+    pub base_size: u32,
+    pub steps: u32,
+    pub seed: u32,
+    pub biome_type: BiomeType,
+    pub sea_level: f32, // 1/1000 of the height range; see set_vertical_units for meters
+    pub erosion_years: f32,
+    pub capture_stages: bool, // keep labeled copies of intermediate height fields
+    pub coastline_detail: f32, // -1 smooths the coastline, +1 adds fractal jitter, 0 leaves it raw
+    // Without a cell size, treat every map as the same world so a small preview matches the
+    // full-size result: per-cell radii, thresholds and iteration counts follow the resolution
+    pub auto_scale: bool,
+    pub shuffle_thermal: bool, // seeded neighbor order in thermal erosion over bedrock; see ErosionParams
+    // Quality flag: carve rivers and patterned ground at 2x and average back, so diagonal banks
+    // and trough edges don't stair-step
+    pub supersample_carving: bool,
+    pub(crate) land_fraction: Option<f32>,
+    pub(crate) vertical_units: Option<VerticalUnits>,
+    pub(crate) cell_size_m: Option<f32>,
+    pub(crate) bathymetry: Option<BathymetryParams>,
+    pub(crate) region: Option<WorldRegion>,
+    pub(crate) strata: Option<(f32, f32)>, // fault block size (cells) and largest dip
+    // Registry slot of a biome picked with set_biome_id; its parameters apply while
+    // `biome_type` is still the biome it is based on
+    pub(crate) biome_slot: Option<usize>,
+}
+
+#[wasm_bindgen]
+impl TerrainConfig {
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        base_size: u32,
+        steps: u32,
+        seed: u32,
+        biome_type: BiomeType,
+        sea_level: f32,
+        erosion_years: f32,
+    ) -> Self {
+        Self {
+            base_size,
+            steps,
+            seed,
+            biome_type,
+            sea_level,
+            erosion_years,
+            capture_stages: false,
+            coastline_detail: 0.0,
+            auto_scale: false,
+            shuffle_thermal: false,
+            supersample_carving: false,
+            land_fraction: None,
+            vertical_units: None,
+            cell_size_m: None,
+            bathymetry: None,
+            region: None,
+            strata: None,
+            biome_slot: None,
+        }
+    }
+
+    // Pick the biome by id (see biome_ids), including runtime-registered ones: `biome_type`
+    // becomes the biome it is based on and a registered biome's own parameters are used. False,
+    // leaving the biome as it was, for unknown ids.
+    #[wasm_bindgen]
+    pub fn set_biome_id(&mut self, id: &str) -> bool {
+        match crate::biome_registry::lookup(id) {
+            Some((biome_type, slot)) => {
+                self.biome_type = biome_type;
+                self.biome_slot = slot;
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Id of the biome generation uses: the registered one picked with set_biome_id, otherwise
+    // the id of `biome_type`
+    #[wasm_bindgen(getter)]
+    pub fn biome_id(&self) -> String {
+        match self.registered_biome() {
+            Some((id, _)) => id,
+            None => self.biome_type.id().to_string(),
+        }
+    }
+
+    // Choose sea level after relief generation so that `fraction` (0-1) of the map is land;
+    // overrides `sea_level`. The resolved value is reported on the result.
+    #[wasm_bindgen]
+    pub fn set_sea_level_by_fraction(&mut self, fraction: f32) {
+        self.land_fraction = Some(fraction);
+    }
+
+    // Go back to the absolute `sea_level`
+    #[wasm_bindgen]
+    pub fn clear_land_fraction(&mut self) {
+        self.land_fraction = None;
+    }
+
+    // Give heights and sea level in meters; overrides `sea_level` and the biome's height scale
+    // everywhere the pipeline converts between the two
+    #[wasm_bindgen]
+    pub fn set_vertical_units(&mut self, units: VerticalUnits) {
+        self.vertical_units = Some(units);
+    }
+
+    // Go back to `sea_level` on the biome's height scale
+    #[wasm_bindgen]
+    pub fn clear_vertical_units(&mut self) {
+        self.vertical_units = None;
+    }
+
+    // Meters between cells of the finished map. Slopes, talus angles and river and beach
+    // widths then keep their size in the world, so the same config gives equivalent terrain
+    // at any resolution; without it they are per cell.
+    #[wasm_bindgen]
+    pub fn set_cell_size_m(&mut self, cell_size_m: f32) {
+        self.cell_size_m = Some(cell_size_m);
+    }
+
+    #[wasm_bindgen]
+    pub fn clear_cell_size_m(&mut self) {
+        self.cell_size_m = None;
+    }
+
+    // Shape the ocean floor into shelf, slope and abyssal plain once the coastline is final.
+    // With a cell size its widths are taken at the reference cell size, like the beach width.
+    #[wasm_bindgen]
+    pub fn set_bathymetry(&mut self, params: BathymetryParams) {
+        self.bathymetry = Some(params);
+    }
+
+    // Leave the seafloor as the relief noise made it
+    #[wasm_bindgen]
+    pub fn clear_bathymetry(&mut self) {
+        self.bathymetry = None;
+    }
+
+    // Cut dissection and erosion into tilted strata: fault blocks of about `block_size` cells
+    // whose beds dip up to `max_dip` height units per cell (see generate_strata). Hard beds
+    // then hold up ledges along the dip. With a cell size both are taken at the reference cell
+    // size, like the beach width.
+    #[wasm_bindgen]
+    pub fn set_strata(&mut self, block_size: f32, max_dip: f32) {
+        self.strata = Some((block_size, max_dip));
+    }
+
+    // Erode uniform rock again
+    #[wasm_bindgen]
+    pub fn clear_strata(&mut self) {
+        self.strata = None;
+    }
+
+    // Bring every setting into the range the pipeline supports, as generation does, and list
+    // what changed as {field, requested, applied, reason} objects; empty when nothing did.
+    // Values that can't be clamped sensibly, like NaN, are left for generation to reject.
+    #[wasm_bindgen]
+    pub fn clamp_to_bounds(&mut self) -> js_sys::Array {
+        crate::bounds::to_js_array(&self.clamp_bounds())
+    }
+
+    // Generate only a window of the world: the square with top-left corner (u, v) and side
+    // `extent`, as fractions of the side of the map this config makes without it. The relief
+    // noise is sampled there at the full map size; see extract_world_region.
+    #[wasm_bindgen]
+    pub fn set_world_region(&mut self, u: f32, v: f32, extent: f32) {
+        self.region = Some(WorldRegion { u, v, extent });
+    }
+
+    // Generate the whole world again
+    #[wasm_bindgen]
+    pub fn clear_world_region(&mut self) {
+        self.region = None;
+    }
+
+    // Internal methods for Rust use
+    pub(crate) fn units(&self) -> VerticalUnits {
+        self.vertical_units.unwrap_or_else(|| VerticalUnits::from_legacy(self.biome_type, self.sea_level))
+    }
+
+    pub(crate) fn clamp_bounds(&mut self) -> Vec<ParamAdjustment> {
+        let mut adjustments = Vec::new();
+        let max_size = MAX_MAP_SIZE as u32;
+        let size_reason = format!("base_size must lie in [2, {}]", max_size);
+        clamp_u32(&mut adjustments, "base_size", &mut self.base_size, 2, max_size, &size_reason);
+        // Each step after the first doubles the map
+        let max_steps = 1 + (max_size / self.base_size).ilog2();
+        let steps_reason = format!(
+            "base_size {} doubled over more than {} steps exceeds the {}-cell map limit",
+            self.base_size, max_steps, max_size
+        );
+        clamp_u32(&mut adjustments, "steps", &mut self.steps, 1, max_steps, &steps_reason);
+        if self.erosion_years.is_finite() {
+            clamp_f32(&mut adjustments, "erosion_years", &mut self.erosion_years, 0.0, f32::MAX);
+        }
+        if self.coastline_detail.is_finite() {
+            clamp_f32(&mut adjustments, "coastline_detail", &mut self.coastline_detail, -1.0, 1.0);
+        }
+        if let Some(fraction) = self.land_fraction.as_mut().filter(|f| f.is_finite()) {
+            clamp_f32(&mut adjustments, "land_fraction", fraction, 0.0, 1.0);
+        }
+        if let Some((block_size, max_dip)) = self.strata.as_mut() {
+            if block_size.is_finite() {
+                clamp_f32(&mut adjustments, "strata.block_size", block_size, 1.0, f32::MAX);
+            }
+            if max_dip.is_finite() {
+                clamp_f32(&mut adjustments, "strata.max_dip", max_dip, 0.0, f32::MAX);
+            }
+        }
+        if let Some(bathymetry) = self.bathymetry.as_mut() {
+            clamp_f32(&mut adjustments, "bathymetry.shelf_width", &mut bathymetry.shelf_width, 1.0, f32::MAX);
+            clamp_f32(&mut adjustments, "bathymetry.slope_width", &mut bathymetry.slope_width, 0.0, f32::MAX);
+            clamp_f32(&mut adjustments, "bathymetry.abyssal_smoothing", &mut bathymetry.abyssal_smoothing, 0.0, 1.0);
+            let plates_reason = "plate boundaries need at least 2 plates";
+            clamp_u32(&mut adjustments, "bathymetry.plate_count", &mut bathymetry.plate_count, 2, u32::MAX, plates_reason);
+        }
+        adjustments
+    }
+
+    // Config for the C and Python APIs, which name biomes by id (see biome_ids) and give sea
+    // level in height field units, the units their results report it in
+    #[cfg(any(feature = "ffi", feature = "python"))]
+    pub(crate) fn for_biome_id(
+        base_size: u32,
+        steps: u32,
+        seed: u32,
+        biome_id: &str,
+        sea_level: f32,
+        erosion_years: f32,
+    ) -> Result<Self, TerrainError> {
+        let legacy_sea_level = sea_level * crate::units::LEGACY_SEA_LEVEL_SCALE;
+        let mut config = Self::new(base_size, steps, seed, BiomeType::Temperate, legacy_sea_level, erosion_years);
+        if !config.set_biome_id(biome_id) {
+            return Err(TerrainError::InvalidParameter(format!("unknown biome id {:?}", biome_id)));
+        }
+        Ok(config)
+    }
+
+    // Registered biome picked with set_biome_id, unless `biome_type` has been changed since
+    fn registered_biome(&self) -> Option<(String, BiomeParams)> {
+        let (id, params) = crate::biome_registry::registered_biome(self.biome_slot?)?;
+        (params.biome_type() == self.biome_type).then_some((id, params))
+    }
+
+    // Parameters generation runs with, a registered biome's own when one is picked
+    pub(crate) fn biome_params(&self) -> BiomeParams {
+        self.registered_biome().map_or_else(|| BiomeParams::new(self.biome_type), |(_, params)| params)
+    }
+
+    // Settings the pipeline can't run with even after clamp_bounds, before any work is done
+    pub(crate) fn check(&self) -> Result<(), TerrainError> {
+        let invalid = |message: String| Err(TerrainError::InvalidParameter(message));
+        let finite = [
+            ("sea_level", self.sea_level),
+            ("erosion_years", self.erosion_years),
+            ("coastline_detail", self.coastline_detail),
+            ("land_fraction", self.land_fraction.unwrap_or(0.0)),
+            ("strata.block_size", self.strata.map_or(0.0, |(block_size, _)| block_size)),
+            ("strata.max_dip", self.strata.map_or(0.0, |(_, max_dip)| max_dip)),
+        ];
+        if let Some((name, value)) = finite.iter().find(|(_, v)| !v.is_finite()) {
+            return invalid(format!("{} must be a finite number, got {}", name, value));
+        }
+        if let Some(units) = self.vertical_units {
+            if !(units.height_scale_m > 0.0 && units.sea_level_m.is_finite()) {
+                return invalid(format!(
+                    "vertical units need a positive height scale and a finite sea level, got {}m and {}m",
+                    units.height_scale_m, units.sea_level_m
+                ));
+            }
+        }
+        if let Some(cell_size_m) = self.cell_size_m.filter(|&m| !(m > 0.0 && m.is_finite())) {
+            return invalid(format!("cell_size_m must be positive, got {}", cell_size_m));
+        }
+        if let Some(region) = self.region.filter(|r| !(r.u.is_finite() && r.v.is_finite() && r.extent > 0.0 && r.extent.is_finite())) {
+            return invalid(format!(
+                "world region needs a finite corner and a positive extent, got ({}, {}) and {}",
+                region.u, region.v, region.extent
+            ));
+        }
+        Ok(())
+    }
+}
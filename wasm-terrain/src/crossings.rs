@@ -0,0 +1,160 @@
+use crate::filters::slope_at;
+use crate::height_field::HeightField;
+use crate::water_system::{river_paths, WaterFeatures};
+use crate::water_volumes::{probe_bank, standing_water, MAX_HALF_WIDTH};
+use wasm_bindgen::prelude::*;
+
+// Span (cells) at which a crossing scores half as well as a zero-width one
+const CROSSING_SPAN_SCALE: f32 = 4.0;
+// Bank height difference (height units) that halves the score
+const BANK_DIFFERENCE_SCALE: f32 = 0.005;
+// Ground slope on the approaches that halves the score; steeper banks slump and need ramps
+const APPROACH_SLOPE_SCALE: f32 = 0.01;
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum CrossingKind {
+    Ford = 0,
+    Bridge = 1,
+}
+
+#[derive(Clone)]
+pub struct CrossingSite {
+    pub kind: CrossingKind,
+    pub x: usize, // channel cell
+    pub y: usize,
+    pub left_x: f32, // bank ends of the crossing, left and right looking downstream
+    pub left_y: f32,
+    pub right_x: f32,
+    pub right_y: f32,
+    pub span: f32,  // cells from bank to bank
+    pub depth: f32, // below the lower bank (height units)
+    pub bank_difference: f32,
+    pub approach_slope: f32, // steeper of the two banks
+    pub score: f32,
+}
+
+impl CrossingSite {
+    // Convert to JS object for interop
+    pub fn to_js_object(&self) -> js_sys::Object {
+        let obj = js_sys::Object::new();
+
+        js_sys::Reflect::set(&obj, &"kind".into(), &(self.kind as u8).into()).unwrap();
+        js_sys::Reflect::set(&obj, &"x".into(), &(self.x as f32).into()).unwrap();
+        js_sys::Reflect::set(&obj, &"y".into(), &(self.y as f32).into()).unwrap();
+        js_sys::Reflect::set(&obj, &"leftX".into(), &self.left_x.into()).unwrap();
+        js_sys::Reflect::set(&obj, &"leftY".into(), &self.left_y.into()).unwrap();
+        js_sys::Reflect::set(&obj, &"rightX".into(), &self.right_x.into()).unwrap();
+        js_sys::Reflect::set(&obj, &"rightY".into(), &self.right_y.into()).unwrap();
+        js_sys::Reflect::set(&obj, &"span".into(), &self.span.into()).unwrap();
+        js_sys::Reflect::set(&obj, &"depth".into(), &self.depth.into()).unwrap();
+        js_sys::Reflect::set(&obj, &"bankDifference".into(), &self.bank_difference.into()).unwrap();
+        js_sys::Reflect::set(&obj, &"approachSlope".into(), &self.approach_slope.into()).unwrap();
+        js_sys::Reflect::set(&obj, &"score".into(), &self.score.into()).unwrap();
+
+        obj
+    }
+}
+
+// River crossings across the flow at every channel cell, best first: narrow channels between
+// banks of similar height with gentle approaches score highest. Crossings no deeper than
+// `max_ford_depth` are fords, the rest bridges.
+pub fn crossing_sites(
+    height_field: &HeightField,
+    water_features: &WaterFeatures,
+    sea_level: f32,
+    max_ford_depth: f32,
+    max_count: usize,
+    min_spacing: f32,
+) -> Vec<CrossingSite> {
+    let n = height_field.size();
+    let data = height_field.data();
+    let river_mask = water_features.river_mask();
+    let standing = standing_water(height_field, water_features, sea_level);
+    let river: Vec<bool> = (0..n * n).map(|i| river_mask[i] > 0.5 && !standing[i]).collect();
+
+    let mut candidates = Vec::new();
+    for path in river_paths(n, &river, &standing, water_features.flow_accumulation()) {
+        let last = path.len() - 1;
+        for i in (0..last).filter(|&i| river[path[i]]) {
+            let (x, y) = (path[i] % n, path[i] / n);
+            let (a, b) = (path[i.saturating_sub(1)], path[i + 1]);
+            let (dx, dy) = ((b % n) as f32 - (a % n) as f32, (b / n) as f32 - (a / n) as f32);
+            let d = (dx * dx + dy * dy).sqrt().max(f32::EPSILON);
+            let (ux, uy) = (-dy / d, dx / d);
+
+            let (left, left_bank) = probe_bank(height_field, river_mask, x, y, ux, uy);
+            let (right, right_bank) = probe_bank(height_field, river_mask, x, y, -ux, -uy);
+            // No bank within reach: the channel opens into something wider than a river
+            if left >= MAX_HALF_WIDTH || right >= MAX_HALF_WIDTH {
+                continue;
+            }
+            let (left_x, left_y) = (x as f32 + ux * left, y as f32 + uy * left);
+            let (right_x, right_y) = (x as f32 - ux * right, y as f32 - uy * right);
+            let bank_slope = |bx: f32, by: f32| {
+                slope_at(height_field, (bx.round().max(0.0) as usize).min(n - 1), (by.round().max(0.0) as usize).min(n - 1))
+            };
+
+            let span = left + right;
+            let depth = (left_bank.min(right_bank) - data[path[i]]).max(0.0);
+            let bank_difference = (left_bank - right_bank).abs();
+            let approach_slope = bank_slope(left_x, left_y).max(bank_slope(right_x, right_y));
+            let score = 1.0
+                / (1.0 + span / CROSSING_SPAN_SCALE)
+                / (1.0 + bank_difference / BANK_DIFFERENCE_SCALE)
+                / (1.0 + approach_slope / APPROACH_SLOPE_SCALE);
+
+            candidates.push(CrossingSite {
+                kind: if depth <= max_ford_depth { CrossingKind::Ford } else { CrossingKind::Bridge },
+                x,
+                y,
+                left_x,
+                left_y,
+                right_x,
+                right_y,
+                span,
+                depth,
+                bank_difference,
+                approach_slope,
+                score,
+            });
+        }
+    }
+
+    candidates.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+
+    let min_spacing_sq = min_spacing * min_spacing;
+    let mut sites: Vec<CrossingSite> = Vec::with_capacity(max_count.min(candidates.len()));
+    for candidate in candidates {
+        if sites.len() >= max_count {
+            break;
+        }
+        let too_close = sites.iter().any(|s| {
+            let dx = s.x as f32 - candidate.x as f32;
+            let dy = s.y as f32 - candidate.y as f32;
+            dx * dx + dy * dy < min_spacing_sq
+        });
+        if !too_close {
+            sites.push(candidate);
+        }
+    }
+
+    sites
+}
+
+// Ranked bridge and ford sites for road routing
+#[wasm_bindgen]
+pub fn find_crossing_sites(
+    height_field: &HeightField,
+    water_features: &WaterFeatures,
+    sea_level: f32,
+    max_ford_depth: f32,
+    max_count: u32,
+    min_spacing: f32,
+) -> js_sys::Array {
+    let result = js_sys::Array::new();
+    for site in crossing_sites(height_field, water_features, sea_level, max_ford_depth, max_count as usize, min_spacing) {
+        result.push(&site.to_js_object());
+    }
+    result
+}
@@ -0,0 +1,334 @@
+use crate::codec::{for_each_block, BitReader, BitWriter};
+use crate::error::TerrainError;
+use crate::height_field::HeightField;
+use wasm_bindgen::prelude::*;
+
+// Serialized form tag, bumped whenever the byte layout changes
+const DELTA_MAGIC: &[u8; 4] = b"HFD1";
+const UPDATE_MAGIC: &[u8; 4] = b"HFU1";
+// Update header: magic, size, region x, y, width, height, checksums before and after
+const UPDATE_HEADER: usize = 40;
+
+// A run of consecutive changed cells (row-major indices start..start + len)
+#[derive(Clone)]
+struct DeltaRun {
+    start: usize,
+    before: Vec<f32>,
+    after: Vec<f32>,
+}
+
+// Run-length encoded difference between two height fields of the same size. Both the old
+// and new values are stored so deltas invert and compose exactly (no float drift on undo).
+#[wasm_bindgen]
+#[derive(Clone)]
+pub struct HeightFieldDelta {
+    size: usize,
+    runs: Vec<DeltaRun>,
+}
+
+impl HeightFieldDelta {
+    // Expand into (index, before, after) triples in index order
+    fn cells(&self) -> Vec<(usize, f32, f32)> {
+        let mut cells = Vec::with_capacity(self.changed_cells());
+        for run in &self.runs {
+            for k in 0..run.before.len() {
+                cells.push((run.start + k, run.before[k], run.after[k]));
+            }
+        }
+        cells
+    }
+
+    // Delta between two equally sized row-major buffers
+    pub(crate) fn between_slices(size: usize, from: &[f32], to: &[f32]) -> Self {
+        let cells: Vec<(usize, f32, f32)> = from
+            .iter()
+            .zip(to)
+            .enumerate()
+            .filter(|(_, (a, b))| a.to_bits() != b.to_bits())
+            .map(|(idx, (&a, &b))| (idx, a, b))
+            .collect();
+
+        Self::from_cells(size, &cells)
+    }
+
+    // Group index-ordered cells back into runs
+    fn from_cells(size: usize, cells: &[(usize, f32, f32)]) -> Self {
+        let mut runs: Vec<DeltaRun> = Vec::new();
+        for &(idx, before, after) in cells {
+            match runs.last_mut() {
+                Some(run) if run.start + run.before.len() == idx => {
+                    run.before.push(before);
+                    run.after.push(after);
+                }
+                _ => runs.push(DeltaRun {
+                    start: idx,
+                    before: vec![before],
+                    after: vec![after],
+                }),
+            }
+        }
+        Self { size, runs }
+    }
+}
+
+#[wasm_bindgen]
+impl HeightFieldDelta {
+    // Delta that turns `from` into `to`; throws when the fields differ in size
+    #[wasm_bindgen]
+    pub fn between(from: &HeightField, to: &HeightField) -> Result<HeightFieldDelta, JsValue> {
+        if from.size() != to.size() {
+            return Err(TerrainError::SizeMismatch { expected: from.size(), actual: to.size() }.into());
+        }
+        Ok(Self::between_slices(from.size(), from.data(), to.data()))
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    #[wasm_bindgen]
+    pub fn changed_cells(&self) -> usize {
+        self.runs.iter().map(|run| run.before.len()).sum()
+    }
+
+    #[wasm_bindgen]
+    pub fn is_empty(&self) -> bool {
+        self.runs.is_empty()
+    }
+
+    // Write the new values into `height_field`; returns false when sizes differ
+    #[wasm_bindgen]
+    pub fn apply(&self, height_field: &mut HeightField) -> bool {
+        if height_field.size() != self.size {
+            return false;
+        }
+        let data = height_field.data_mut();
+        for run in &self.runs {
+            data[run.start..run.start + run.after.len()].copy_from_slice(&run.after);
+        }
+        true
+    }
+
+    // Delta that undoes this one
+    #[wasm_bindgen]
+    pub fn invert(&self) -> HeightFieldDelta {
+        let runs = self
+            .runs
+            .iter()
+            .map(|run| DeltaRun {
+                start: run.start,
+                before: run.after.clone(),
+                after: run.before.clone(),
+            })
+            .collect();
+        Self { size: self.size, runs }
+    }
+
+    // Single delta equivalent to applying `self` then `next`; throws when sizes differ
+    #[wasm_bindgen]
+    pub fn compose(&self, next: &HeightFieldDelta) -> Result<HeightFieldDelta, JsValue> {
+        if self.size != next.size {
+            return Err(TerrainError::SizeMismatch { expected: self.size, actual: next.size }.into());
+        }
+
+        let first = self.cells();
+        let second = next.cells();
+        let mut merged = Vec::with_capacity(first.len() + second.len());
+        let (mut i, mut j) = (0, 0);
+        while i < first.len() || j < second.len() {
+            let take_first = j >= second.len() || (i < first.len() && first[i].0 < second[j].0);
+            let take_second = i >= first.len() || (j < second.len() && second[j].0 < first[i].0);
+
+            let cell = if take_first {
+                i += 1;
+                first[i - 1]
+            } else if take_second {
+                j += 1;
+                second[j - 1]
+            } else {
+                // Changed by both: original value of the first, final value of the second
+                i += 1;
+                j += 1;
+                (first[i - 1].0, first[i - 1].1, second[j - 1].2)
+            };
+
+            if cell.1.to_bits() != cell.2.to_bits() {
+                merged.push(cell);
+            }
+        }
+
+        Ok(Self::from_cells(self.size, &merged))
+    }
+
+    // Compact little-endian encoding for network sync and undo persistence
+    #[wasm_bindgen]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(12 + self.runs.len() * 8 + self.changed_cells() * 8);
+        bytes.extend_from_slice(DELTA_MAGIC);
+        bytes.extend_from_slice(&(self.size as u32).to_le_bytes());
+        bytes.extend_from_slice(&(self.runs.len() as u32).to_le_bytes());
+        for run in &self.runs {
+            bytes.extend_from_slice(&(run.start as u32).to_le_bytes());
+            bytes.extend_from_slice(&(run.before.len() as u32).to_le_bytes());
+            for v in run.before.iter().chain(&run.after) {
+                bytes.extend_from_slice(&v.to_le_bytes());
+            }
+        }
+        bytes
+    }
+
+    // Decode bytes produced by to_bytes; throws on malformed input
+    #[wasm_bindgen]
+    pub fn from_bytes(bytes: &[u8]) -> Result<HeightFieldDelta, JsValue> {
+        Self::decode(bytes).ok_or_else(|| TerrainError::CorruptData("height field delta").into())
+    }
+}
+
+impl HeightFieldDelta {
+    fn decode(bytes: &[u8]) -> Option<HeightFieldDelta> {
+        let mut reader = ByteReader { bytes, pos: 0 };
+        if reader.take(4)? != DELTA_MAGIC {
+            return None;
+        }
+        let size = reader.u32()? as usize;
+        let run_count = reader.u32()? as usize;
+
+        let cells = size.checked_mul(size)?;
+        let mut runs = Vec::with_capacity(run_count.min(bytes.len() / 8));
+        // Runs come sorted and disjoint from to_bytes; anything else would apply order-dependently
+        let mut previous_end = 0;
+        for _ in 0..run_count {
+            let start = reader.u32()? as usize;
+            let len = reader.u32()? as usize;
+            let end = start.checked_add(len)?;
+            if start < previous_end || end > cells {
+                return None;
+            }
+            previous_end = end;
+            let before = (0..len).map(|_| reader.f32()).collect::<Option<Vec<f32>>>()?;
+            let after = (0..len).map(|_| reader.f32()).collect::<Option<Vec<f32>>>()?;
+            runs.push(DeltaRun { start, before, after });
+        }
+
+        Some(Self { size, runs })
+    }
+}
+
+// Heights of [x, y, width, height] in row-major order
+fn region_values(height_field: &HeightField, [x0, y0, w, h]: [usize; 4]) -> Vec<f32> {
+    let n = height_field.size();
+    let data = height_field.data();
+    (y0..y0 + h).flat_map(|y| data[y * n + x0..y * n + x0 + w].iter().copied()).collect()
+}
+
+// Patch that turns `prev` into `next` inside `region` ([x, y, width, height] in cells, e.g.
+// dirty_region after an edit). Cells are stored as the XOR of old and new bit patterns, Rice
+// coded per 32×32 block: untouched cells cost about a bit and edited ones mostly their
+// mantissa bits, so the result is exact at under half the size of a HeightFieldDelta. None when
+// the fields differ in size or the region is malformed or leaves the map.
+#[wasm_bindgen]
+pub fn encode_update(prev: &HeightField, next: &HeightField, region: &[u32]) -> Option<Vec<u8>> {
+    let region = <[u32; 4]>::try_from(region).ok()?.map(|v| v as usize);
+    let n = prev.size();
+    let [x0, y0, w, h] = region;
+    if next.size() != n || x0.checked_add(w).is_none_or(|x1| x1 > n) || y0.checked_add(h).is_none_or(|y1| y1 > n) {
+        return None;
+    }
+    let before = region_values(prev, region);
+    let after = region_values(next, region);
+
+    let mut bytes = Vec::with_capacity(UPDATE_HEADER + w * h / 4);
+    bytes.extend_from_slice(UPDATE_MAGIC);
+    for v in [n, x0, y0, w, h] {
+        bytes.extend_from_slice(&(v as u32).to_le_bytes());
+    }
+    bytes.extend_from_slice(&crate::det_math::checksum(&[&before]).to_le_bytes());
+    bytes.extend_from_slice(&crate::det_math::checksum(&[&after]).to_le_bytes());
+
+    let mut writer = BitWriter::new(bytes);
+    for_each_block(region, |cells| {
+        let flips: Vec<u32> = cells
+            .iter()
+            .map(|&(x, y)| {
+                let local = (y - y0) * w + (x - x0);
+                before[local].to_bits() ^ after[local].to_bits()
+            })
+            .collect();
+        writer.write_block(&flips);
+    });
+    Some(writer.finish())
+}
+
+// Apply a patch from encode_update. The region must hold exactly the heights the patch was
+// made against; on any mismatch or malformed input the field is left untouched and false
+// is returned, so a client that drifted can ask for a full resync.
+#[wasm_bindgen]
+pub fn apply_update(height_field: &mut HeightField, patch: &[u8]) -> bool {
+    let Some((region, values)) = decode_update(height_field, patch) else {
+        return false;
+    };
+    let [x0, y0, w, h] = region;
+    let n = height_field.size();
+    let data = height_field.data_mut();
+    for y in 0..h {
+        let row = (y0 + y) * n + x0;
+        data[row..row + w].copy_from_slice(&values[y * w..(y + 1) * w]);
+    }
+    true
+}
+
+// Region and its patched heights, after checking both checksums
+fn decode_update(height_field: &HeightField, patch: &[u8]) -> Option<([usize; 4], Vec<f32>)> {
+    let mut reader = ByteReader { bytes: patch, pos: 0 };
+    if reader.take(4)? != UPDATE_MAGIC {
+        return None;
+    }
+    let n = reader.u32()? as usize;
+    let region = [reader.u32()?, reader.u32()?, reader.u32()?, reader.u32()?].map(|v| v as usize);
+    let [x0, y0, w, h] = region;
+    if n != height_field.size() || x0.checked_add(w)? > n || y0.checked_add(h)? > n {
+        return None;
+    }
+    let checksum_before = u64::from_le_bytes(reader.take(8)?.try_into().ok()?);
+    let checksum_after = u64::from_le_bytes(reader.take(8)?.try_into().ok()?);
+
+    let mut values = region_values(height_field, region);
+    if crate::det_math::checksum(&[&values]) != checksum_before {
+        return None;
+    }
+    let mut bits = BitReader::new(&patch[UPDATE_HEADER..]);
+    let mut ok = true;
+    for_each_block(region, |cells| {
+        let Some(flips) = bits.read_block(cells.len()).filter(|_| ok) else {
+            ok = false;
+            return;
+        };
+        for (&(x, y), &flip) in cells.iter().zip(&flips) {
+            let local = (y - y0) * w + (x - x0);
+            values[local] = f32::from_bits(values[local].to_bits() ^ flip);
+        }
+    });
+    (ok && crate::det_math::checksum(&[&values]) == checksum_after).then_some((region, values))
+}
+
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        let slice = self.bytes.get(self.pos..self.pos.checked_add(len)?)?;
+        self.pos += len;
+        Some(slice)
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_le_bytes(self.take(4)?.try_into().ok()?))
+    }
+
+    fn f32(&mut self) -> Option<f32> {
+        Some(f32::from_le_bytes(self.take(4)?.try_into().ok()?))
+    }
+}
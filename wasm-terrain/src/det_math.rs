@@ -0,0 +1,316 @@
+// Transcendental functions used by the generation pipeline (sin, cos, atan2, exp, ln, powf
+// and hypot; sqrt and powi are exact or plain multiplication already). With the `strict_determinism`
+// feature they are evaluated with range reduction + polynomials built only from IEEE basic
+// operations (exactly rounded, so identical on every platform); otherwise they forward to std.
+
+#[cfg(feature = "strict_determinism")]
+mod poly {
+    use std::f64::consts::{FRAC_PI_2, LN_2, PI, SQRT_2, TAU};
+
+    // Taylor series for sin on [-π/2, π/2], Horner form (error < 1e-13)
+    fn sin_reduced(r: f64) -> f64 {
+        let r2 = r * r;
+        let mut term = 1.0;
+        // 1/(n(n-1)) factors for the odd terms up to r^17
+        for n in (3..=17).rev().step_by(2) {
+            term = 1.0 - r2 / ((n * (n - 1)) as f64) * term;
+        }
+        r * term
+    }
+
+    fn sin_f64(x: f64) -> f64 {
+        let mut r = x - (x / TAU).round() * TAU;
+        if r > FRAC_PI_2 {
+            r = PI - r;
+        } else if r < -FRAC_PI_2 {
+            r = -PI - r;
+        }
+        sin_reduced(r)
+    }
+
+    pub fn sin(x: f32) -> f32 {
+        sin_f64(x as f64) as f32
+    }
+
+    pub fn cos(x: f32) -> f32 {
+        sin_f64(x as f64 + FRAC_PI_2) as f32
+    }
+
+    // atan on [-1, 1] after halving the angle twice; sqrt is exactly rounded in IEEE 754
+    fn atan_unit(z: f64) -> f64 {
+        let half = z / (1.0 + (1.0 + z * z).sqrt());
+        let quarter = half / (1.0 + (1.0 + half * half).sqrt());
+        let q2 = quarter * quarter;
+        let mut sum = 0.0;
+        for k in (0..12).rev() {
+            let n = (2 * k + 1) as f64;
+            sum = 1.0 / n - q2 * sum;
+        }
+        4.0 * quarter * sum
+    }
+
+    pub fn atan2(y: f32, x: f32) -> f32 {
+        let (y, x) = (y as f64, x as f64);
+        if x == 0.0 && y == 0.0 {
+            return 0.0;
+        }
+        let angle = if x.abs() >= y.abs() {
+            let a = atan_unit(y / x);
+            if x > 0.0 {
+                a
+            } else if y >= 0.0 {
+                a + PI
+            } else {
+                a - PI
+            }
+        } else {
+            let a = atan_unit(x / y);
+            if y > 0.0 {
+                FRAC_PI_2 - a
+            } else {
+                -FRAC_PI_2 - a
+            }
+        };
+        angle as f32
+    }
+
+    // e^x: x = k·ln2 + r with |r| ≤ ln2/2, Taylor series for e^r (error < 1e-16) and the
+    // 2^k factor assembled from its exponent bits
+    fn exp_f64(x: f64) -> f64 {
+        if x.is_nan() {
+            return x;
+        }
+        if x > 709.0 {
+            return f64::INFINITY;
+        }
+        if x < -745.0 {
+            return 0.0;
+        }
+        let k = (x / LN_2).round();
+        let r = x - k * LN_2;
+        let mut sum = 1.0;
+        for n in (1..=16).rev() {
+            sum = 1.0 + r / n as f64 * sum;
+        }
+        // Split 2^k in two so both halves stay normal at the ends of the range
+        let half = (k / 2.0).trunc();
+        let pow2 = |e: f64| f64::from_bits(((e as i64 + 1023) as u64) << 52);
+        sum * pow2(half) * pow2(k - half)
+    }
+
+    // ln x = e·ln2 + ln m for x = m·2^e with m in [√½, √2), ln m from the atanh series of
+    // s = (m - 1)/(m + 1) (|s| < 0.18, error < 1e-16)
+    fn ln_f64(x: f64) -> f64 {
+        if x.is_nan() || x < 0.0 {
+            return f64::NAN;
+        }
+        if x == 0.0 {
+            return f64::NEG_INFINITY;
+        }
+        if x.is_infinite() {
+            return x;
+        }
+        // Inputs come from f32, so x is never subnormal as an f64
+        let bits = x.to_bits();
+        let mut e = ((bits >> 52) & 0x7ff) as i64 - 1023;
+        let mut m = f64::from_bits((bits & ((1u64 << 52) - 1)) | (1023u64 << 52));
+        if m > SQRT_2 {
+            m /= 2.0;
+            e += 1;
+        }
+        let s = (m - 1.0) / (m + 1.0);
+        let s2 = s * s;
+        let mut sum = 0.0;
+        for k in (0..12).rev() {
+            sum = 1.0 / (2 * k + 1) as f64 + s2 * sum;
+        }
+        e as f64 * LN_2 + 2.0 * s * sum
+    }
+
+    pub fn exp(x: f32) -> f32 {
+        exp_f64(x as f64) as f32
+    }
+
+    pub fn ln(x: f32) -> f32 {
+        ln_f64(x as f64) as f32
+    }
+
+    // x^y as e^(y·ln|x|), with std's results for zero, one and negative bases
+    pub fn powf(x: f32, y: f32) -> f32 {
+        if y == 0.0 || x == 1.0 {
+            return 1.0;
+        }
+        if x.is_nan() || y.is_nan() {
+            return f32::NAN;
+        }
+        if x == 0.0 {
+            return if y > 0.0 { 0.0 } else { f32::INFINITY };
+        }
+        let magnitude = exp_f64(y as f64 * ln_f64((x as f64).abs()));
+        if x > 0.0 {
+            magnitude as f32
+        } else if y.fract() != 0.0 {
+            f32::NAN
+        } else if (y / 2.0).fract() != 0.0 {
+            -magnitude as f32
+        } else {
+            magnitude as f32
+        }
+    }
+
+    // Exact in f64 for any pair of f32 values, so one rounding on the way back
+    pub fn hypot(x: f32, y: f32) -> f32 {
+        let (x, y) = (x as f64, y as f64);
+        (x * x + y * y).sqrt() as f32
+    }
+}
+
+#[cfg(feature = "strict_determinism")]
+pub(crate) use poly::{atan2, cos, exp, hypot, ln, powf, sin};
+
+#[cfg(not(feature = "strict_determinism"))]
+#[inline]
+pub(crate) fn sin(x: f32) -> f32 {
+    x.sin()
+}
+
+#[cfg(not(feature = "strict_determinism"))]
+#[inline]
+pub(crate) fn cos(x: f32) -> f32 {
+    x.cos()
+}
+
+#[cfg(not(feature = "strict_determinism"))]
+#[inline]
+pub(crate) fn atan2(y: f32, x: f32) -> f32 {
+    y.atan2(x)
+}
+
+#[cfg(not(feature = "strict_determinism"))]
+#[inline]
+pub(crate) fn exp(x: f32) -> f32 {
+    x.exp()
+}
+
+#[cfg(not(feature = "strict_determinism"))]
+#[inline]
+pub(crate) fn ln(x: f32) -> f32 {
+    x.ln()
+}
+
+#[cfg(not(feature = "strict_determinism"))]
+#[inline]
+pub(crate) fn powf(x: f32, y: f32) -> f32 {
+    x.powf(y)
+}
+
+#[cfg(not(feature = "strict_determinism"))]
+#[inline]
+pub(crate) fn hypot(x: f32, y: f32) -> f32 {
+    x.hypot(y)
+}
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+// FNV-1a over the exact bit patterns of a sequence of f32 buffers
+pub(crate) fn checksum(buffers: &[&[f32]]) -> u64 {
+    let mut hash = FNV_OFFSET;
+    for buffer in buffers {
+        for value in buffer.iter() {
+            for byte in value.to_bits().to_le_bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+        }
+    }
+    hash
+}
+
+#[cfg(all(test, feature = "strict_determinism"))]
+pub(crate) mod tests {
+    use crate::biomes::BiomeType;
+    use crate::config::TerrainConfig;
+    use crate::height_field::HeightField;
+    use crate::TerrainGenerationResult;
+
+    // Small eroded map, 60% land, the stage tests start from
+    pub(crate) fn terrain(biome_type: BiomeType) -> TerrainGenerationResult {
+        let mut config = TerrainConfig::new(16, 3, 7, biome_type, 0.0, 2000.0);
+        config.set_sea_level_by_fraction(0.6);
+        let adjustments = config.clamp_bounds();
+        crate::run_pipeline(&config, adjustments, crate::budget::StageBudget::new(None))
+    }
+
+    // Hex checksum of the buffers, in the form the pinned values below are written in
+    pub(crate) fn hex(buffers: &[&[f32]]) -> String {
+        format!("{:016x}", super::checksum(buffers))
+    }
+
+    #[test]
+    fn polynomials_match_std() {
+        for i in -400..400 {
+            let x = i as f32 * 0.0731;
+            assert!((super::exp(x) - x.exp()).abs() <= x.exp() * 1e-6, "exp({})", x);
+            assert!((super::sin(x) - x.sin()).abs() < 1e-6, "sin({})", x);
+            let y = (i as f32 * 0.37).abs() + 1e-3;
+            assert!((super::ln(y) - y.ln()).abs() < 1e-6 * y.ln().abs().max(1.0), "ln({})", y);
+            assert!((super::powf(y, 1.5) - y.powf(1.5)).abs() <= y.powf(1.5) * 1e-6, "powf({}, 1.5)", y);
+            assert_eq!(super::hypot(x, y), x.hypot(y), "hypot({}, {})", x, y);
+        }
+        assert_eq!(super::powf(-2.0, 3.0), -8.0);
+        assert!(super::powf(-2.0, 0.5).is_nan());
+        assert_eq!(super::ln(0.0), f32::NEG_INFINITY);
+    }
+
+    // Exact bits of every pipeline stage built on exp/ln/powf/hypot; a mismatch on any platform
+    // means a std transcendental slipped back into generation
+    #[test]
+    fn pipeline_is_pinned() {
+        let mut config = TerrainConfig::new(16, 3, 7, BiomeType::Badlands, 0.0, 2000.0);
+        config.set_sea_level_by_fraction(0.6);
+        config.set_bathymetry(crate::bathymetry::BathymetryParams::new(6.0, 0.1, 12.0, 0.4));
+        config.set_strata(12.0, 0.3);
+        config.supersample_carving = true;
+        let adjustments = config.clamp_bounds();
+        let badlands = crate::run_pipeline(&config, adjustments, crate::budget::StageBudget::new(None));
+        assert_eq!(badlands.determinism_checksum(), "aae655e01f38e186");
+
+        let tundra = terrain(BiomeType::Tundra);
+        let permafrost = tundra.permafrost_depth.as_deref().unwrap();
+        assert_eq!(hex(&[tundra.height_field.data(), permafrost]), "4f8aba88bf3530a6");
+
+        let desert = terrain(BiomeType::Desert);
+        let oases = desert.oases.as_ref().unwrap().water_mask();
+        assert_eq!(hex(&[desert.height_field.data(), oases]), "3f6700c6373a4c17");
+    }
+
+    #[test]
+    fn standalone_stages_are_pinned() {
+        let base = terrain(BiomeType::Temperate);
+        let sea = base.units.sea_level_height();
+        let size = base.height_field.size();
+
+        let strata = crate::strata::strata_field(size, 12.0, 0.3, 3);
+        let mut dissected = base.height_field.clone();
+        crate::badlands::apply_badlands_dissection_with_strata(&mut dissected, 0.6, &strata, 3);
+        assert_eq!(hex(&[dissected.data()]), "6013581a58d2c057");
+
+        let regolith = crate::outcrops::regolith_thickness(&base.height_field);
+        assert_eq!(hex(&[&regolith]), "d907d09981a69a6d");
+
+        let mut shaped = base.height_field.clone();
+        crate::spectral::spectral_shape(&mut shaped, 0.8);
+        let fractal = crate::spectral::synthesize_fractal(32, 0.7, 5);
+        assert_eq!(hex(&[shaped.data(), fractal.data()]), "1310d1c1d44ba465");
+
+        let rain = crate::precipitation::advected_precipitation(&base.height_field, 0.7, sea, 0);
+        assert_eq!(hex(&[&rain]), "96567c3106ae0638");
+
+        let mut reefed: HeightField = base.height_field.clone();
+        let params = crate::reefs::ReefParams::new(27.0, 0.2);
+        let water = base.water_features.clone().unwrap_or_else(|| crate::water_system::WaterFeatures::new(base.height_field.size()));
+        crate::reefs::reefs(&mut reefed, &water, sea, &params).unwrap();
+        assert_eq!(hex(&[reefed.data()]), "d60e6245153e68c0");
+    }
+}
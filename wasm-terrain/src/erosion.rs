@@ -1,17 +1,56 @@
+use crate::biomes::{BiomeParams, BiomeType};
 use crate::height_field::HeightField;
-use crate::water_system::{WaterFeatures, apply_water_system, WaterSystemParams};
+use crate::strata::StrataField;
+use crate::units::{WorldScale, LEGACY_SEA_LEVEL_SCALE};
+use crate::water_system::{WaterFeatures, apply_water_system, WaterSystemParams, DEFAULT_BEACH_MAX_SLOPE};
 use wasm_bindgen::prelude::*;
 
+// Named erosion settings for common landscape histories
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum GeologyPreset {
+    YoungMountains = 0, // wet, frost-shattered, rivers cutting hard rock into gorges
+    OldShield = 1,      // mild, worn-down basement rock with slow, shallow drainage
+    AridPlateau = 2,    // dry and windy, flash floods cutting cliffs into caprock
+}
+
 #[wasm_bindgen]
 #[derive(Clone, Copy)]
 pub struct ErosionParams {
     pub time_years: f32,
-    pub sea_level: f32,
+    pub sea_level: f32, // 1/1000 of the height range, like TerrainConfig::sea_level
     pub wind_strength: f32,
     pub rain_intensity: f32,
     pub temperature_cycles: f32,
+    pub use_precipitation: bool, // scale rain by the orographic precipitation raster
+    pub wind_direction: f32,     // radians, direction the rain-bearing wind blows towards
+    pub advect_moisture: bool,   // with use_precipitation: the moisture advection model instead
+    pub carve_floor: Option<f32>, // same units as sea_level; see WaterSystemParams::carve_floor
+    // Bedrock erosion rate relative to loose regolith: 1 = no bedrock, 0 = a hard floor only
+    // the strongest streams cut through. Erosion through strata lowers it further in hard beds.
+    pub bedrock_erodibility: f32,
+    pub min_channel_width: f32, // cells; see WaterSystemParams::min_channel_width
+    pub adaptive_erosion: bool,  // spend hydraulic iterations only where the terrain still changes
+    // Quality flag for thermal erosion over bedrock (bedrock_erodibility below 1 or strata):
+    // visit each cell's neighbors in a seeded random order instead of a fixed scan, so the
+    // neighbors visited first don't take all of the soft cover. Without bedrock every neighbor
+    // sees the same heights and the order makes no difference.
+    pub shuffle_thermal: bool,
+    pub seed: u32, // for shuffle_thermal
+    pub supersample_carving: bool, // see WaterSystemParams::supersample_carving
+    // Set: min_channel_width is meters, and the built-in talus slope and river and beach widths
+    // keep the size they have at the reference cell size, whatever the resolution
+    pub world_scale: Option<WorldScale>,
 }
 
+// Height (height units) of the loose layer over bedrock where regolith is thickest
+const REGOLITH_DEPTH: f32 = 0.02;
+// Stream power (normalized flow × slope) needed to cut into bedrock of zero erodibility; the
+// threshold falls linearly to zero as erodibility approaches 1
+const BEDROCK_STREAM_POWER: f32 = 0.002;
+// Removal or burial (height units) that replaces a cell's weathered skin with a fresh surface
+const RENEWAL_DEPTH: f32 = 1e-4;
+
 #[wasm_bindgen]
 impl ErosionParams {
     #[wasm_bindgen(constructor)]
@@ -28,17 +67,134 @@ impl ErosionParams {
             wind_strength,
             rain_intensity,
             temperature_cycles,
+            use_precipitation: false,
+            wind_direction: 0.0,
+            advect_moisture: false,
+            carve_floor: None,
+            bedrock_erodibility: 1.0,
+            min_channel_width: 0.0,
+            adaptive_erosion: false,
+            shuffle_thermal: false,
+            seed: 0,
+            supersample_carving: false,
+            world_scale: None,
+        }
+    }
+
+    // Parameters the terrain generator uses for a biome: frost cycles by climate and wind
+    // scaled to the biome's relief
+    #[wasm_bindgen]
+    pub fn for_biome(biome_type: BiomeType, time_years: f32, sea_level: f32) -> Self {
+        let temperature_cycles = match biome_type {
+            BiomeType::Alpine => 50.0,
+            BiomeType::Desert => 10.0,
+            BiomeType::Temperate => 25.0,
+            BiomeType::Volcanic => 30.0,
+            BiomeType::Tundra => 60.0,
+            BiomeType::Badlands => 20.0,
+        };
+        let wind_strength = BiomeParams::new(biome_type).fbm_params().amplitude * 0.5;
+        Self::new(time_years, sea_level, wind_strength, 1.0, temperature_cycles)
+    }
+
+    #[wasm_bindgen]
+    pub fn preset(preset: GeologyPreset, time_years: f32, sea_level: f32) -> Self {
+        match preset {
+            GeologyPreset::YoungMountains => Self {
+                bedrock_erodibility: 0.4,
+                ..Self::new(time_years, sea_level, 0.1, 1.5, 50.0)
+            },
+            GeologyPreset::OldShield => Self {
+                bedrock_erodibility: 0.2,
+                ..Self::new(time_years, sea_level, 0.05, 0.8, 25.0)
+            },
+            GeologyPreset::AridPlateau => Self {
+                bedrock_erodibility: 0.3,
+                min_channel_width: 2.0,
+                ..Self::new(time_years, sea_level, 0.2, 0.3, 10.0)
+            },
+        }
+    }
+}
+
+// Shorter spans only get the water system, no erosion passes
+const MIN_EROSION_YEARS: f32 = 10.0;
+// Largest per-cell height difference rockfall leaves standing, at the reference cell size
+const TALUS_SLOPE: f32 = 0.8;
+
+// Water system settings the erosion passes use to find and carve rivers
+fn erosion_water_params(params: &ErosionParams, river_threshold: f32) -> WaterSystemParams {
+    let mut water_params = WaterSystemParams::new(
+        params.sea_level / LEGACY_SEA_LEVEL_SCALE, // Convert to heightfield units
+        river_threshold,
+        8.0,  // River width
+        0.05, // River depth
+        0.04, // Coastal erosion
+        8.0,  // Beach width
+    );
+    water_params.carve_floor = params.carve_floor.map(|floor| floor / LEGACY_SEA_LEVEL_SCALE);
+    water_params.min_channel_width = params.min_channel_width;
+    water_params.supersample_carving = params.supersample_carving;
+    if let Some(scale) = params.world_scale {
+        water_params.river_width = scale.reference_cells(water_params.river_width);
+        water_params.beach_width = scale.reference_cells(water_params.beach_width);
+        water_params.beach_max_slope = scale.reference_slope(DEFAULT_BEACH_MAX_SLOPE);
+        water_params.min_channel_width = scale.to_cells(params.min_channel_width);
+    }
+    water_params
+}
+
+// The water settings the last water system pass of apply_geological_erosion ran with
+pub(crate) fn final_water_params(params: &ErosionParams) -> WaterSystemParams {
+    erosion_water_params(params, if params.time_years < MIN_EROSION_YEARS { 0.1 } else { 0.08 })
+}
+
+// Rock under the loose cover: its surface under the starting terrain, where the regolith
+// estimate from outcrops stands in for the depth of loose cover, and the beds it is made of
+struct Bedrock<'a> {
+    surface: Vec<f32>,
+    strata: Option<&'a StrataField>,
+}
+
+impl<'a> Bedrock<'a> {
+    fn new(height_field: &HeightField, strata: Option<&'a StrataField>) -> Self {
+        let regolith = crate::outcrops::regolith_thickness(height_field);
+        let surface = height_field.data().iter().zip(&regolith).map(|(&h, &r)| h - r * REGOLITH_DEPTH).collect();
+        Self { surface, strata }
+    }
+
+    // Erodibility of the rock at height h over cell (x, y): the params' rate, scaled by the
+    // hardness of the bed there when the rock is stratified
+    fn erodibility(&self, params: &ErosionParams, x: usize, y: usize, h: f32) -> f32 {
+        match self.strata {
+            Some(strata) => params.bedrock_erodibility * crate::strata::bed_erodibility(h, x, y, Some(strata), params.seed),
+            None => params.bedrock_erodibility,
         }
     }
 }
 
-// Apply wind erosion (affects exposed ridges and high areas)
+// How much of a cut of `amount` at a cell of `height` goes through: everything down to the
+// bedrock, then only `erodibility` of the rest and only where the stream is strong enough.
+// Reaches that stall on hard rock while the channel below keeps cutting become knickpoints.
+fn bedrock_limited(height: f32, bedrock: f32, amount: f32, erodibility: f32, stream_power: f32) -> f32 {
+    let soft = (height - bedrock).clamp(0.0, amount);
+    if stream_power < BEDROCK_STREAM_POWER * (1.0 - erodibility) {
+        soft
+    } else {
+        soft + (amount - soft) * erodibility
+    }
+}
+
+// Apply wind erosion (affects exposed ridges and high areas). Each iteration reads the heights
+// the previous one left and writes a separate buffer, so the result doesn't depend on scan order.
 fn apply_wind_erosion(height_field: &mut HeightField, params: &ErosionParams, iterations: u32) -> Vec<f32> {
     let size = height_field.size();
     let data = height_field.data_mut();
     let mut erosion_mask = vec![0.0f32; size * size];
+    let mut next = data.to_vec();
     
     for _i in 0..iterations {
+        next.copy_from_slice(data);
         for y in 1..size-1 {
             for x in 1..size-1 {
                 let idx = y * size + x;
@@ -58,22 +214,39 @@ fn apply_wind_erosion(height_field: &mut HeightField, params: &ErosionParams, it
                 let wind_erosion = params.wind_strength * exposure * 0.01;
                 
                 if wind_erosion > 0.0 {
-                    data[idx] -= wind_erosion;
+                    next[idx] -= wind_erosion;
                     erosion_mask[idx] += wind_erosion;
                 }
             }
         }
+        data.copy_from_slice(&next);
     }
     
     erosion_mask
 }
 
+// Neighbor offsets in scan order, row by row
+const THERMAL_NEIGHBORS: [(i32, i32); 8] = [(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)];
+
 // Apply thermal erosion (freeze-thaw, rockfall)
-fn apply_thermal_erosion(height_field: &mut HeightField, params: &ErosionParams, iterations: u32) -> Vec<f32> {
+fn apply_thermal_erosion(
+    height_field: &mut HeightField,
+    params: &ErosionParams,
+    bedrock: Option<&Bedrock>,
+    iterations: u32,
+) -> Vec<f32> {
+    use rand::seq::SliceRandom;
+    use rand::SeedableRng;
+
     let size = height_field.size();
     let data = height_field.data_mut();
     let mut erosion_mask = vec![0.0f32; size * size];
-    let talus_angle = 0.8; // Maximum stable slope
+    let talus_angle = params.world_scale.map_or(TALUS_SLOPE, |scale| scale.reference_slope(TALUS_SLOPE)); // Maximum stable slope
+    let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(params.seed as u64);
+    let mut neighbors = THERMAL_NEIGHBORS;
+    // Only the bedrock limit reads the heights this pass has already changed, so only there do
+    // the neighbors visited first take more of the soft cover
+    let shuffle = params.shuffle_thermal && bedrock.is_some();
     
     for _i in 0..iterations {
         let mut new_data = data.to_vec();
@@ -82,24 +255,29 @@ fn apply_thermal_erosion(height_field: &mut HeightField, params: &ErosionParams,
             for x in 1..size-1 {
                 let idx = y * size + x;
                 let height = data[idx];
+                if shuffle {
+                    neighbors.shuffle(&mut rng);
+                }
                 
                 // Check all neighbors for unstable slopes
-                for dy in -1i32..=1 {
-                    for dx in -1i32..=1 {
-                        if dx == 0 && dy == 0 { continue; }
-                        
-                        let n_idx = ((y as i32 + dy) as usize) * size + ((x as i32 + dx) as usize);
-                        let neighbor_height = data[n_idx];
-                        let height_diff = height - neighbor_height;
-                        
-                        if height_diff > talus_angle {
-                            // Slope is too steep - erode and deposit
-                            let erosion_amount = (height_diff - talus_angle) * params.temperature_cycles * 0.001;
-                            
-                            new_data[idx] -= erosion_amount * 0.5;
-                            new_data[n_idx] += erosion_amount * 0.5;
-                            erosion_mask[idx] += erosion_amount * 0.5;
+                for &(dx, dy) in &neighbors {
+                    let n_idx = ((y as i32 + dy) as usize) * size + ((x as i32 + dx) as usize);
+                    let neighbor_height = data[n_idx];
+                    let height_diff = height - neighbor_height;
+                    
+                    if height_diff > talus_angle {
+                        // Slope is too steep - erode and deposit
+                        let mut erosion_amount = (height_diff - talus_angle) * params.temperature_cycles * 0.001;
+                        // Rockfall has no stream behind it: bare bedrock only weathers
+                        if let Some(bedrock) = bedrock {
+                            let erodibility = bedrock.erodibility(params, x, y, new_data[idx]);
+                            let cut = bedrock_limited(new_data[idx], bedrock.surface[idx], erosion_amount * 0.5, erodibility, 0.0);
+                            erosion_amount = cut * 2.0;
                         }
+                        
+                        new_data[idx] -= erosion_amount * 0.5;
+                        new_data[n_idx] += erosion_amount * 0.5;
+                        erosion_mask[idx] += erosion_amount * 0.5;
                     }
                 }
             }
@@ -112,117 +290,265 @@ fn apply_thermal_erosion(height_field: &mut HeightField, params: &ErosionParams,
     erosion_mask
 }
 
+// Inputs shared by every cell update of one hydraulic erosion run
+struct HydraulicContext<'a> {
+    size: usize,
+    flow: &'a [f32],
+    river_mask: &'a [f32],
+    max_flow: f32,
+    rainfall: Option<&'a [f32]>,
+    bedrock: Option<&'a Bedrock<'a>>,
+    params: &'a ErosionParams,
+}
+
+// One hydraulic update of interior cell (x, y): erode it and deposit part of the sediment on
+// its steepest downhill neighbor. Reads the heights of the previous pass from `data` and writes
+// the changes to `next`. Returns the amount eroded and the cell that received sediment.
+#[allow(clippy::too_many_arguments)]
+fn hydraulic_cell(
+    ctx: &HydraulicContext,
+    data: &[f32],
+    next: &mut [f32],
+    erosion_mask: &mut [f32],
+    deposition_mask: &mut [f32],
+    x: usize,
+    y: usize,
+) -> (f32, Option<usize>) {
+    let size = ctx.size;
+    let idx = y * size + x;
+    
+    // Calculate erosion based on water flow and slope
+    let flow = ctx.flow[idx] / ctx.max_flow;
+    let river_strength = ctx.river_mask[idx];
+    
+    // Calculate local slope
+    let mut total_slope = 0.0f32;
+    let mut slope_count = 0;
+    for dy in -1i32..=1 {
+        for dx in -1i32..=1 {
+            if dx == 0 && dy == 0 { continue; }
+            let n_idx = ((y as i32 + dy) as usize) * size + ((x as i32 + dx) as usize);
+            total_slope += (data[idx] - data[n_idx]).abs();
+            slope_count += 1;
+        }
+    }
+    let avg_slope = total_slope / slope_count as f32;
+    
+    // Erosion is proportional to flow * slope * rain intensity
+    let params = ctx.params;
+    let rain = params.rain_intensity * ctx.rainfall.map_or(1.0, |r| r[idx]);
+    let hydraulic_erosion = flow * avg_slope * rain * 0.02;
+    let river_erosion = river_strength * avg_slope * rain * 0.05;
+    
+    let mut total_erosion = hydraulic_erosion + river_erosion;
+    if let Some(bedrock) = ctx.bedrock {
+        let stream_power = flow * avg_slope;
+        let erodibility = bedrock.erodibility(params, x, y, data[idx]);
+        total_erosion = bedrock_limited(data[idx], bedrock.surface[idx], total_erosion, erodibility, stream_power);
+    }
+    
+    if total_erosion <= 0.0 {
+        return (0.0, None);
+    }
+    next[idx] -= total_erosion;
+    erosion_mask[idx] += total_erosion;
+    let eroded_height = data[idx] - total_erosion;
+    
+    // Deposit sediment downstream (simplified)
+    // Find steepest downhill neighbor
+    let mut steepest_slope = 0.0f32;
+    let mut deposit_idx = None;
+    
+    for dy in -1i32..=1 {
+        for dx in -1i32..=1 {
+            if dx == 0 && dy == 0 { continue; }
+            let n_idx = ((y as i32 + dy) as usize) * size + ((x as i32 + dx) as usize);
+            let slope = eroded_height - data[n_idx];
+            
+            if slope > steepest_slope {
+                steepest_slope = slope;
+                deposit_idx = Some(n_idx);
+            }
+        }
+    }
+    
+    if let Some(dep_idx) = deposit_idx {
+        let deposition_amount = total_erosion * 0.3; // Not all sediment deposits immediately
+        next[dep_idx] += deposition_amount;
+        deposition_mask[dep_idx] += deposition_amount;
+    }
+    (total_erosion, deposit_idx)
+}
+
 // Apply hydraulic erosion (water-based)
 fn apply_hydraulic_erosion(
     height_field: &mut HeightField,
     water_features: &WaterFeatures,
     params: &ErosionParams,
+    rainfall: Option<&[f32]>,
+    bedrock: Option<&Bedrock>,
     iterations: u32,
 ) -> (Vec<f32>, Vec<f32>) {
     let size = height_field.size();
     let data = height_field.data_mut();
-    let river_mask = water_features.get_river_mask();
-    let flow_accumulation = water_features.get_flow_accumulation();
+    let flow = water_features.flow_accumulation();
     
     let mut erosion_mask = vec![0.0f32; size * size];
     let mut deposition_mask = vec![0.0f32; size * size];
     
     // Find max flow for normalization
-    let mut max_flow = 0.0f32;
-    for i in 0..flow_accumulation.length() {
-        let flow = flow_accumulation.get_index(i);
-        if flow > max_flow {
-            max_flow = flow;
-        }
+    let max_flow = flow.iter().fold(0.0f32, |max, &f| max.max(f));
+    if max_flow == 0.0 || size < 3 {
+        return (erosion_mask, deposition_mask);
     }
+    let ctx = HydraulicContext {
+        size,
+        flow,
+        river_mask: water_features.river_mask(),
+        max_flow,
+        rainfall,
+        bedrock,
+        params,
+    };
     
-    if max_flow == 0.0 {
+    if params.adaptive_erosion {
+        adaptive_hydraulic_passes(&ctx, data, &mut erosion_mask, &mut deposition_mask, iterations);
         return (erosion_mask, deposition_mask);
     }
     
+    // Every pass reads the heights of the previous one, like the wind pass
+    let mut next = data.to_vec();
     for _i in 0..iterations {
+        next.copy_from_slice(data);
         for y in 1..size-1 {
             for x in 1..size-1 {
-                let idx = y * size + x;
-                
-                // Calculate erosion based on water flow and slope
-                let flow = flow_accumulation.get_index(idx as u32) / max_flow;
-                let river_strength = river_mask.get_index(idx as u32);
-                
-                // Calculate local slope
-                let mut total_slope = 0.0f32;
-                let mut slope_count = 0;
-                for dy in -1i32..=1 {
-                    for dx in -1i32..=1 {
-                        if dx == 0 && dy == 0 { continue; }
-                        let n_idx = ((y as i32 + dy) as usize) * size + ((x as i32 + dx) as usize);
-                        total_slope += (data[idx] - data[n_idx]).abs();
-                        slope_count += 1;
-                    }
-                }
-                let avg_slope = total_slope / slope_count as f32;
-                
-                // Erosion is proportional to flow * slope * rain intensity
-                let hydraulic_erosion = flow * avg_slope * params.rain_intensity * 0.02;
-                let river_erosion = river_strength * avg_slope * params.rain_intensity * 0.05;
-                
-                let total_erosion = hydraulic_erosion + river_erosion;
-                
-                if total_erosion > 0.0 {
-                    data[idx] -= total_erosion;
-                    erosion_mask[idx] += total_erosion;
-                    
-                    // Deposit sediment downstream (simplified)
-                    // Find steepest downhill neighbor
-                    let mut steepest_slope = 0.0f32;
-                    let mut deposit_idx = None;
-                    
-                    for dy in -1i32..=1 {
-                        for dx in -1i32..=1 {
-                            if dx == 0 && dy == 0 { continue; }
-                            let n_idx = ((y as i32 + dy) as usize) * size + ((x as i32 + dx) as usize);
-                            let slope = data[idx] - data[n_idx];
-                            
-                            if slope > steepest_slope {
-                                steepest_slope = slope;
-                                deposit_idx = Some(n_idx);
-                            }
-                        }
-                    }
-                    
-                    if let Some(dep_idx) = deposit_idx {
-                        let deposition_amount = total_erosion * 0.3; // Not all sediment deposits immediately
-                        data[dep_idx] += deposition_amount;
-                        deposition_mask[dep_idx] += deposition_amount;
-                    }
-                }
+                hydraulic_cell(&ctx, data, &mut next, &mut erosion_mask, &mut deposition_mask, x, y);
             }
         }
+        data.copy_from_slice(&next);
     }
     
     (erosion_mask, deposition_mask)
 }
 
+// Side of the blocks adaptive erosion schedules (cells)
+const ADAPTIVE_BLOCK: usize = 32;
+// Blocks whose largest per-pass cut falls below this fraction of the largest cut seen anywhere
+// are considered settled and skipped
+const ADAPTIVE_TOLERANCE: f32 = 0.02;
+
+// Up to `iterations` hydraulic passes per block, most active block first. After each pass a
+// block is requeued with its largest cut as priority, or dropped once that is negligible;
+// sediment spilling into a settled neighbor wakes it up again. Quiet lowlands and flats stop
+// costing time after a pass or two while channels get their full count.
+fn adaptive_hydraulic_passes(
+    ctx: &HydraulicContext,
+    data: &mut [f32],
+    erosion_mask: &mut [f32],
+    deposition_mask: &mut [f32],
+    iterations: u32,
+) {
+    let size = ctx.size;
+    let columns = size.div_ceil(ADAPTIVE_BLOCK);
+    let count = columns * columns;
+    let block_of = |idx: usize| (idx / size / ADAPTIVE_BLOCK) * columns + (idx % size) / ADAPTIVE_BLOCK;
+
+    // Non-negative f32 priorities order like their bit patterns
+    let mut queue: std::collections::BinaryHeap<(u32, std::cmp::Reverse<usize>)> =
+        (0..count).map(|b| (f32::INFINITY.to_bits(), std::cmp::Reverse(b))).collect();
+    let mut queued = vec![true; count];
+    let mut passes = vec![0u32; count];
+    let mut largest_cut = 0.0f32;
+    // Heights written by a block pass; only the block and its one-cell margin of deposit
+    // targets are copied in and back
+    let mut next = data.to_vec();
+
+    while let Some((_, std::cmp::Reverse(block))) = queue.pop() {
+        queued[block] = false;
+        let (bx, by) = ((block % columns) * ADAPTIVE_BLOCK, (block / columns) * ADAPTIVE_BLOCK);
+        let (x0, x1) = (bx.saturating_sub(1), (bx + ADAPTIVE_BLOCK + 1).min(size));
+        let rows = by.saturating_sub(1)..(by + ADAPTIVE_BLOCK + 1).min(size);
+        for y in rows.clone() {
+            next[y * size + x0..y * size + x1].copy_from_slice(&data[y * size + x0..y * size + x1]);
+        }
+        let mut cut = 0.0f32;
+        let mut spills: Vec<(usize, f32)> = Vec::new();
+        for y in by.max(1)..(by + ADAPTIVE_BLOCK).min(size - 1) {
+            for x in bx.max(1)..(bx + ADAPTIVE_BLOCK).min(size - 1) {
+                let (eroded, deposit) = hydraulic_cell(ctx, data, &mut next, erosion_mask, deposition_mask, x, y);
+                cut = cut.max(eroded);
+                if let Some(target) = deposit.map(block_of).filter(|&b| b != block) {
+                    spills.push((target, eroded * 0.3));
+                }
+            }
+        }
+        for y in rows {
+            data[y * size + x0..y * size + x1].copy_from_slice(&next[y * size + x0..y * size + x1]);
+        }
+        passes[block] += 1;
+        largest_cut = largest_cut.max(cut);
+
+        let settled = |amount: f32| amount <= largest_cut * ADAPTIVE_TOLERANCE;
+        let mut requeue = |b: usize, priority: f32, queue: &mut std::collections::BinaryHeap<_>| {
+            if !queued[b] && passes[b] < iterations && !settled(priority) {
+                queued[b] = true;
+                queue.push((priority.to_bits(), std::cmp::Reverse(b)));
+            }
+        };
+        requeue(block, cut, &mut queue);
+        for (target, amount) in spills {
+            requeue(target, amount, &mut queue);
+        }
+    }
+}
+
 #[wasm_bindgen]
 pub fn apply_geological_erosion(
     height_field: &mut HeightField,
     params: &ErosionParams,
+) -> WaterFeatures {
+    geological_erosion(height_field, params, None)
+}
+
+// Geological erosion of stratified rock: hard beds resist rivers and rockfall, so cuts stall
+// into ledges and knickpoints that follow the tilt of the strata. `strata` must match the
+// height field's size; otherwise it is ignored.
+#[wasm_bindgen]
+pub fn apply_geological_erosion_with_strata(
+    height_field: &mut HeightField,
+    params: &ErosionParams,
+    strata: &StrataField,
+) -> WaterFeatures {
+    let strata = (strata.size() == height_field.size()).then_some(strata);
+    geological_erosion(height_field, params, strata)
+}
+
+pub(crate) fn geological_erosion(
+    height_field: &mut HeightField,
+    params: &ErosionParams,
+    strata: Option<&StrataField>,
 ) -> WaterFeatures {
     crate::utils::console_log!("Applying {} years of geological erosion...", params.time_years);
     
     // Early exit for very small time scales to save performance
-    if params.time_years < 10.0 {
+    if params.time_years < MIN_EROSION_YEARS {
         crate::utils::console_log!("Skipping erosion (time too small), generating basic water features...");
-        return apply_water_system(height_field, &WaterSystemParams::new(
-            params.sea_level / 1000.0,
-            0.1, 8.0, 0.05, 0.04, 8.0
-        ));
+        let water_params = final_water_params(params);
+        let mut water_features = apply_water_system(height_field, &water_params);
+        water_features.set_surface_age(vec![params.time_years; height_field.size() * height_field.size()]);
+        return water_features;
     }
     
     // Calculate erosion iterations based on time scale with limits for performance
     let wind_iterations = ((params.time_years / 100.0).ceil() as u32).min(20); // Cap at 20 iterations
     let thermal_iterations = ((params.time_years / 50.0).ceil() as u32).min(40); // Cap at 40 iterations  
     let hydraulic_iterations = ((params.time_years / 25.0).ceil() as u32).min(80); // Cap at 80 iterations
+    // Rockfall diffuses and sediment moves a cell per pass, so finer cells need more of both to
+    // shape the same ground; wind works cell by cell and needs no more
+    let (thermal_iterations, hydraulic_iterations) = match params.world_scale {
+        Some(scale) => (scale.scale_iterations(thermal_iterations, 2), scale.scale_iterations(hydraulic_iterations, 1)),
+        None => (thermal_iterations, hydraulic_iterations),
+    };
     
     crate::utils::console_log!(
         "Iterations: Wind={}, Thermal={}, Hydraulic={}",
@@ -230,36 +556,29 @@ pub fn apply_geological_erosion(
     );
     
     // Step 1: Calculate initial water flow patterns on base terrain
-    let water_params = WaterSystemParams::new(
-        params.sea_level / 1000.0, // Convert to heightfield units
-        0.08, // Lower threshold for more rivers
-        8.0,  // River width
-        0.05, // River depth
-        0.04, // Coastal erosion
-        8.0,  // Beach width
-    );
+    let water_params = erosion_water_params(params, 0.08); // Lower threshold for more rivers
     
     let mut water_features = apply_water_system(height_field, &water_params);
     
+    let bedrock = (params.bedrock_erodibility < 1.0 || strata.is_some()).then(|| Bedrock::new(height_field, strata));
+
     // Step 2: Apply erosion processes in geological order
-    let mut _total_erosion_mask = vec![0.0f32; height_field.size() * height_field.size()];
-    let mut _total_deposition_mask = vec![0.0f32; height_field.size() * height_field.size()];
+    // Material stripped or buried per cell, which resets the weathering of its surface
+    let mut renewal = vec![0.0f32; height_field.size() * height_field.size()];
     
-    // Wind erosion (affects ridges and exposed areas)
+    // Wind erosion (affects ridges and exposed areas); it lowers land almost uniformly and
+    // polishes rather than exposes fresh rock, so it doesn't count as renewal
     if params.wind_strength > 0.0 {
         crate::utils::console_log!("Applying wind erosion...");
-        let wind_erosion = apply_wind_erosion(height_field, params, wind_iterations);
-        for i in 0.._total_erosion_mask.len() {
-            _total_erosion_mask[i] += wind_erosion[i];
-        }
+        apply_wind_erosion(height_field, params, wind_iterations);
     }
     
     // Thermal erosion (freeze-thaw, rockfall)
     if params.temperature_cycles > 0.0 {
         crate::utils::console_log!("Applying thermal erosion...");
-        let thermal_erosion = apply_thermal_erosion(height_field, params, thermal_iterations);
-        for i in 0.._total_erosion_mask.len() {
-            _total_erosion_mask[i] += thermal_erosion[i];
+        let thermal_erosion = apply_thermal_erosion(height_field, params, bedrock.as_ref(), thermal_iterations);
+        for i in 0..renewal.len() {
+            renewal[i] += thermal_erosion[i];
         }
     }
     
@@ -270,16 +589,24 @@ pub fn apply_geological_erosion(
         // Recalculate water flow on modified terrain
         water_features = apply_water_system(height_field, &water_params);
         
+        let rainfall = params.use_precipitation.then(|| {
+            if params.advect_moisture {
+                crate::precipitation::advected_precipitation(height_field, params.wind_direction, water_params.sea_level, 0)
+            } else {
+                crate::precipitation::orographic_precipitation(height_field, params.wind_direction, water_params.sea_level)
+            }
+        });
         let (erosion_mask, deposition_mask) = apply_hydraulic_erosion(
             height_field, 
             &water_features, 
             params, 
+            rainfall.as_deref(),
+            bedrock.as_ref(),
             hydraulic_iterations
         );
         
-        for i in 0.._total_erosion_mask.len() {
-            _total_erosion_mask[i] += erosion_mask[i];
-            _total_deposition_mask[i] += deposition_mask[i];
+        for i in 0..renewal.len() {
+            renewal[i] += erosion_mask[i] + deposition_mask[i];
         }
         
         // Update final water mask
@@ -288,5 +615,328 @@ pub fn apply_geological_erosion(
     
     crate::utils::console_log!("Geological erosion complete");
     
+    water_features.set_surface_age(surface_age(&renewal, params.time_years));
     water_features
-}
\ No newline at end of file
+}
+
+// Years since each cell's surface was last renewed. Erosion and deposition are taken as spread
+// evenly over `years`, so a cell that went through k renewal depths was last fresh years / k
+// ago; cells that saw less than one keep the full span.
+fn surface_age(renewal: &[f32], years: f32) -> Vec<f32> {
+    renewal.iter().map(|&r| years * (RENEWAL_DEPTH / r.max(f32::MIN_POSITIVE)).min(1.0)).collect()
+}
+// Droplet constants for detail erosion, tuned for heights in roughly [0, 1]
+const DROPLET_LIFETIME: u32 = 30;
+const DROPLET_INERTIA: f32 = 0.05;
+const DROPLET_CAPACITY: f32 = 4.0;
+const DROPLET_MIN_SLOPE: f32 = 0.0005;
+const DROPLET_ERODE: f32 = 0.3;
+const DROPLET_DEPOSIT: f32 = 0.3;
+const DROPLET_EVAPORATE: f32 = 0.02;
+const DROPLET_GRAVITY: f32 = 4.0;
+// One droplet per this many cells per pass
+const DROPLET_SPACING: usize = 64;
+
+// Bilinear height and gradient at a fractional position (x, y must be inside [0, n-1))
+fn height_and_gradient(data: &[f32], n: usize, x: f32, y: f32) -> (f32, f32, f32) {
+    let cx = x as usize;
+    let cy = y as usize;
+    let fx = x - cx as f32;
+    let fy = y - cy as f32;
+    let idx = cy * n + cx;
+
+    let h00 = data[idx];
+    let h10 = data[idx + 1];
+    let h01 = data[idx + n];
+    let h11 = data[idx + n + 1];
+
+    let gx = (h10 - h00) * (1.0 - fy) + (h11 - h01) * fy;
+    let gy = (h01 - h00) * (1.0 - fx) + (h11 - h10) * fx;
+    let h = h00 * (1.0 - fx) * (1.0 - fy) + h10 * fx * (1.0 - fy) + h01 * (1.0 - fx) * fy + h11 * fx * fy;
+    (h, gx, gy)
+}
+
+// Spread a height change over the four cells around a fractional position
+fn splat(data: &mut [f32], n: usize, x: f32, y: f32, amount: f32) {
+    let cx = x as usize;
+    let cy = y as usize;
+    let fx = x - cx as f32;
+    let fy = y - cy as f32;
+    let idx = cy * n + cx;
+
+    data[idx] += amount * (1.0 - fx) * (1.0 - fy);
+    data[idx + 1] += amount * fx * (1.0 - fy);
+    data[idx + n] += amount * (1.0 - fx) * fy;
+    data[idx + n + 1] += amount * fx * fy;
+}
+
+// Trace one droplet downhill, eroding where it speeds up and depositing where it slows
+fn run_droplet(data: &mut [f32], n: usize, start_x: f32, start_y: f32) {
+    let limit = (n - 1) as f32;
+    let (mut x, mut y) = (start_x, start_y);
+    let (mut dir_x, mut dir_y) = (0.0f32, 0.0f32);
+    let mut speed = 1.0f32;
+    let mut water = 1.0f32;
+    let mut sediment = 0.0f32;
+
+    for _ in 0..DROPLET_LIFETIME {
+        let (h, gx, gy) = height_and_gradient(data, n, x, y);
+
+        dir_x = dir_x * DROPLET_INERTIA - gx * (1.0 - DROPLET_INERTIA);
+        dir_y = dir_y * DROPLET_INERTIA - gy * (1.0 - DROPLET_INERTIA);
+        let len = (dir_x * dir_x + dir_y * dir_y).sqrt();
+        if len <= f32::EPSILON {
+            break;
+        }
+        dir_x /= len;
+        dir_y /= len;
+
+        let (old_x, old_y) = (x, y);
+        x += dir_x;
+        y += dir_y;
+        if x < 0.0 || y < 0.0 || x >= limit || y >= limit {
+            break;
+        }
+
+        let (new_h, _, _) = height_and_gradient(data, n, x, y);
+        let dh = new_h - h;
+        let capacity = (-dh).max(DROPLET_MIN_SLOPE) * speed * water * DROPLET_CAPACITY;
+
+        if sediment > capacity || dh > 0.0 {
+            // Fill the pit when moving uphill, otherwise drop the surplus
+            let deposit = if dh > 0.0 {
+                dh.min(sediment)
+            } else {
+                (sediment - capacity) * DROPLET_DEPOSIT
+            };
+            sediment -= deposit;
+            splat(data, n, old_x, old_y, deposit);
+        } else {
+            let erode = ((capacity - sediment) * DROPLET_ERODE).min(-dh);
+            sediment += erode;
+            splat(data, n, old_x, old_y, -erode);
+        }
+
+        speed = (speed * speed + dh.abs() * DROPLET_GRAVITY).sqrt();
+        water *= 1.0 - DROPLET_EVAPORATE;
+    }
+}
+
+// Lightweight erosion for streamed tiles: a few droplet passes plus one thermal relaxation,
+// aiming at 10-30ms for a 512² tile instead of the multi-second geological pipeline.
+#[wasm_bindgen]
+pub fn apply_detail_erosion(height_field: &mut HeightField, iterations: u32, seed: u32) {
+    detail_erosion(height_field, None, iterations, seed);
+}
+
+// Detail erosion with droplets spawned in proportion to `rainfall` (one value per cell, e.g.
+// from compute_precipitation), so wet windward slopes gully more than the rain shadow
+#[wasm_bindgen]
+pub fn apply_rainfall_detail_erosion(height_field: &mut HeightField, rainfall: &[f32], iterations: u32, seed: u32) {
+    let n = height_field.size();
+    let rainfall = (rainfall.len() == n * n).then_some(rainfall);
+    detail_erosion(height_field, rainfall, iterations, seed);
+}
+
+// Rejection-sampling attempts per droplet before giving up on a dry map
+const SPAWN_ATTEMPTS: u32 = 8;
+
+fn detail_erosion(height_field: &mut HeightField, rainfall: Option<&[f32]>, iterations: u32, seed: u32) {
+    use rand::{Rng, SeedableRng};
+
+    let n = height_field.size();
+    if n < 3 {
+        return;
+    }
+
+    let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(seed as u64);
+    let droplets = (n * n / DROPLET_SPACING).max(1);
+    let limit = (n - 1) as f32;
+    let max_rain = rainfall.map_or(0.0, |r| r.iter().fold(0.0f32, |m, &v| m.max(v)));
+
+    {
+        let data = height_field.data_mut();
+        for _pass in 0..iterations {
+            for _ in 0..droplets {
+                let mut spawn = None;
+                for _attempt in 0..SPAWN_ATTEMPTS {
+                    let x = rng.gen_range(0.0..limit);
+                    let y = rng.gen_range(0.0..limit);
+                    let accept = match rainfall {
+                        Some(rain) if max_rain > 0.0 => {
+                            rng.gen::<f32>() * max_rain < rain[y as usize * n + x as usize]
+                        }
+                        _ => true,
+                    };
+                    if accept {
+                        spawn = Some((x, y));
+                        break;
+                    }
+                }
+                if let Some((x, y)) = spawn {
+                    run_droplet(data, n, x, y);
+                }
+            }
+        }
+    }
+
+    // Relax slopes steeper than ~2 height units across the map
+    crate::filters::apply_thermal_erosion(height_field, 1, 2.0 / n as f32);
+}
+
+// Border strips copied from neighboring tiles (ghost cells) for edge-matched erosion.
+// north/south are `width` rows of `size` values (top to bottom), west/east are `size`
+// rows of `width` values (left to right). Empty strips replicate the tile's own edge.
+#[wasm_bindgen]
+#[derive(Clone)]
+pub struct TileBorders {
+    width: usize,
+    north: Vec<f32>,
+    south: Vec<f32>,
+    west: Vec<f32>,
+    east: Vec<f32>,
+}
+
+#[wasm_bindgen]
+impl TileBorders {
+    #[wasm_bindgen(constructor)]
+    pub fn new(width: usize, north: Vec<f32>, south: Vec<f32>, west: Vec<f32>, east: Vec<f32>) -> Self {
+        Self {
+            width,
+            north,
+            south,
+            west,
+            east,
+        }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+}
+
+// Tile plus ghost ring as one (size + 2 * width)² buffer
+fn build_ghosted(tile: &HeightField, borders: &TileBorders) -> Vec<f32> {
+    let n = tile.size();
+    let g = borders.width;
+    let m = n + 2 * g;
+    let row_strip = |strip: &Vec<f32>| strip.len() == g * n;
+
+    let mut out = vec![0.0f32; m * m];
+    for y in 0..m {
+        for x in 0..m {
+            // Position relative to the tile, clamped per axis for strips that don't cover it
+            let tx = x as i32 - g as i32;
+            let ty = y as i32 - g as i32;
+            let cx = tx.clamp(0, n as i32 - 1) as usize;
+            let cy = ty.clamp(0, n as i32 - 1) as usize;
+
+            out[y * m + x] = if ty < 0 && row_strip(&borders.north) {
+                borders.north[y * n + cx]
+            } else if ty >= n as i32 && row_strip(&borders.south) {
+                borders.south[(ty as usize - n) * n + cx]
+            } else if tx < 0 && row_strip(&borders.west) {
+                borders.west[cy * g + x]
+            } else if tx >= n as i32 && row_strip(&borders.east) {
+                borders.east[cy * g + (tx as usize - n)]
+            } else {
+                tile.get(cx, cy)
+            };
+        }
+    }
+    out
+}
+
+// Erosion that sees `borders.width` cells of each neighbor so material moves across tile
+// seams consistently. Droplets are spawned from world-space hashes, so two adjacent tiles
+// simulate identical droplets wherever their ghosted regions overlap.
+#[wasm_bindgen]
+pub fn apply_edge_matched_erosion(
+    tile: &mut HeightField,
+    borders: &TileBorders,
+    world_x: i32,
+    world_y: i32,
+    iterations: u32,
+    seed: u32,
+) {
+    let n = tile.size();
+    let g = borders.width;
+    let m = n + 2 * g;
+    if n < 3 {
+        return;
+    }
+
+    let mut ghosted = HeightField::new(m);
+    ghosted.data_mut().copy_from_slice(&build_ghosted(tile, borders));
+
+    // One droplet per block of DROPLET_SPACING cells, jittered by a world-space hash
+    let block = (DROPLET_SPACING as f32).sqrt() as i32;
+    let origin_x = world_x - g as i32;
+    let origin_y = world_y - g as i32;
+    let limit = (m - 1) as f32;
+    {
+        let data = ghosted.data_mut();
+        for pass in 0..iterations {
+            let salt = seed ^ pass.wrapping_mul(0x9e37_79b9);
+            for by in origin_y.div_euclid(block)..=(origin_y + m as i32).div_euclid(block) {
+                for bx in origin_x.div_euclid(block)..=(origin_x + m as i32).div_euclid(block) {
+                    let h = crate::noise::hash_cell(bx, by, salt);
+                    let jx = crate::noise::hash_to_unit(h);
+                    let jy = crate::noise::hash_to_unit(h.rotate_left(13) ^ 0x5bd1_e995);
+                    let x = (bx * block - origin_x) as f32 + jx * block as f32;
+                    let y = (by * block - origin_y) as f32 + jy * block as f32;
+                    if x >= 0.0 && y >= 0.0 && x < limit && y < limit {
+                        run_droplet(data, m, x, y);
+                    }
+                }
+            }
+        }
+    }
+
+    crate::filters::apply_thermal_erosion(&mut ghosted, 1, 2.0 / n as f32);
+
+    // Keep only the tile's own cells
+    let src = ghosted.data();
+    let dst = tile.data_mut();
+    for y in 0..n {
+        dst[y * n..(y + 1) * n].copy_from_slice(&src[(y + g) * m + g..(y + g) * m + g + n]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Rough relief, steeper than the talus slope almost everywhere
+    fn rough(size: usize) -> HeightField {
+        let mut height_field = HeightField::new(size);
+        for (idx, h) in height_field.data_mut().iter_mut().enumerate() {
+            *h = 3.0 * crate::noise::hash_to_unit(crate::noise::hash_cell(idx as i32, 0, 9));
+        }
+        height_field
+    }
+
+    fn thermal(params: &ErosionParams, with_bedrock: bool) -> Vec<f32> {
+        let mut height_field = rough(24);
+        let bedrock = with_bedrock.then(|| Bedrock::new(&height_field, None));
+        apply_thermal_erosion(&mut height_field, params, bedrock.as_ref(), 4);
+        height_field.data().to_vec()
+    }
+
+    #[test]
+    fn shuffle_thermal_reorders_bedrock_rockfall() {
+        let mut params = ErosionParams::new(1000.0, 0.0, 0.0, 0.0, 50.0);
+        params.bedrock_erodibility = 0.3;
+        params.seed = 4;
+        let fixed = thermal(&params, true);
+        params.shuffle_thermal = true;
+        assert_ne!(thermal(&params, true), fixed);
+
+        // Without bedrock every neighbor reads the heights before the pass
+        params.shuffle_thermal = false;
+        let fixed = thermal(&params, false);
+        params.shuffle_thermal = true;
+        assert_eq!(thermal(&params, false), fixed);
+    }
+}
@@ -0,0 +1,51 @@
+use std::fmt;
+use wasm_bindgen::prelude::*;
+
+// Failure of a fallible API. Crosses into JS as an Error named "TerrainError" whose `code`
+// property holds a stable string (see code()) and whose message describes the problem.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TerrainError {
+    // A parameter outside what the call can work with; the message names it
+    InvalidParameter(String),
+    // An array of the wrong length for the map it belongs to
+    LengthMismatch { what: &'static str, expected: usize, actual: usize },
+    // Two maps or deltas that have to be the same size and aren't
+    SizeMismatch { expected: usize, actual: usize },
+    // Bytes that don't decode as what they claim to be
+    CorruptData(&'static str),
+}
+
+impl TerrainError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            TerrainError::InvalidParameter(_) => "invalid_parameter",
+            TerrainError::LengthMismatch { .. } => "length_mismatch",
+            TerrainError::SizeMismatch { .. } => "size_mismatch",
+            TerrainError::CorruptData(_) => "corrupt_data",
+        }
+    }
+}
+
+impl fmt::Display for TerrainError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TerrainError::InvalidParameter(message) => write!(f, "{}", message),
+            TerrainError::LengthMismatch { what, expected, actual } => {
+                write!(f, "{} needs {} values, got {}", what, expected, actual)
+            }
+            TerrainError::SizeMismatch { expected, actual } => {
+                write!(f, "expected a {}x{} map, got {}x{}", expected, expected, actual, actual)
+            }
+            TerrainError::CorruptData(what) => write!(f, "malformed or truncated {}", what),
+        }
+    }
+}
+
+impl From<TerrainError> for JsValue {
+    fn from(error: TerrainError) -> Self {
+        let js_error = js_sys::Error::new(&error.to_string());
+        js_error.set_name("TerrainError");
+        js_sys::Reflect::set(&js_error, &"code".into(), &error.code().into()).unwrap();
+        js_error.into()
+    }
+}
@@ -0,0 +1,231 @@
+// C ABI for embedders without JS glue: game engines linking the library natively, or running
+// the module under wasmtime/wasmer. Everything crosses as numbers, pointers and lengths; a
+// generated terrain is an opaque handle the caller frees with genesis_terrain_free. Functions
+// that can fail return one of the GENESIS_* status codes (or null) and leave a message for
+// genesis_last_error. include/genesis_terrain.h declares all of it for C. With this feature
+// generation logs nothing and reads no JS clock, so under a wasm runtime it makes no JS calls;
+// the wasm-bindgen imports of the other exports still have to be stubbed. Build native
+// libraries with the `ffi` profile, whose panics unwind and are reported as failures.
+use crate::config::TerrainConfig;
+use crate::TerrainGenerationResult;
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr};
+
+pub const GENESIS_OK: i32 = 0;
+// A handle or buffer pointer was null
+pub const GENESIS_NULL_POINTER: i32 = -1;
+// The output buffer length doesn't match the layer (size × size values)
+pub const GENESIS_LENGTH_MISMATCH: i32 = -2;
+// The terrain has no such layer, e.g. water masks of a terrain generated without erosion
+pub const GENESIS_MISSING_LAYER: i32 = -3;
+
+// Per-cell layers genesis_terrain_copy_layer can read
+pub const GENESIS_LAYER_HEIGHT: u32 = 0;
+pub const GENESIS_LAYER_WATER: u32 = 1;
+pub const GENESIS_LAYER_RIVER: u32 = 2;
+pub const GENESIS_LAYER_BEACH: u32 = 3;
+pub const GENESIS_LAYER_FLOW: u32 = 4;
+
+thread_local! {
+    static LAST_ERROR: RefCell<String> = const { RefCell::new(String::new()) };
+}
+
+fn fail<T>(status: T, message: String) -> T {
+    LAST_ERROR.with(|last| *last.borrow_mut() = message);
+    status
+}
+
+// Copy the message of the last failure on this thread into `out` as UTF-8 (truncated to
+// `len` bytes, not NUL-terminated) and return its full length, so callers can size a buffer
+// with a first call of len 0
+#[no_mangle]
+pub unsafe extern "C" fn genesis_last_error(out: *mut u8, len: usize) -> usize {
+    LAST_ERROR.with(|last| {
+        let message = last.borrow();
+        if !out.is_null() {
+            let count = message.len().min(len);
+            std::ptr::copy_nonoverlapping(message.as_ptr(), out, count);
+        }
+        message.len()
+    })
+}
+
+// generate_terrain for C: biome_id is a NUL-terminated biome id (see biome_ids) and sea_level
+// is in height field units, as genesis_terrain_sea_level reports it. Out-of-range settings are clamped as in generate_terrain_with_config; returns
+// null for settings the pipeline can't run with, and for a panic during generation when
+// panics unwind.
+#[no_mangle]
+pub unsafe extern "C" fn genesis_generate(
+    base_size: u32,
+    steps: u32,
+    seed: u32,
+    biome_id: *const c_char,
+    sea_level: f32,
+    erosion_years: f32,
+) -> *mut TerrainGenerationResult {
+    if biome_id.is_null() {
+        return fail(std::ptr::null_mut(), "biome id is null".to_string());
+    }
+    let Ok(biome_id) = CStr::from_ptr(biome_id).to_str() else {
+        return fail(std::ptr::null_mut(), "biome id is not UTF-8".to_string());
+    };
+    let mut config = match TerrainConfig::for_biome_id(base_size, steps, seed, biome_id, sea_level, erosion_years) {
+        Ok(config) => config,
+        Err(error) => return fail(std::ptr::null_mut(), error.to_string()),
+    };
+    let adjustments = config.clamp_bounds();
+    if let Err(error) = config.check() {
+        return fail(std::ptr::null_mut(), error.to_string());
+    }
+    let generated = std::panic::catch_unwind(|| crate::run_pipeline(&config, adjustments, crate::budget::StageBudget::new(None)));
+    match generated {
+        Ok(result) => Box::into_raw(Box::new(result)),
+        Err(payload) => fail(std::ptr::null_mut(), format!("generation panicked: {}", panic_message(payload.as_ref()))),
+    }
+}
+
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> &str {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown panic")
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn genesis_terrain_free(terrain: *mut TerrainGenerationResult) {
+    if !terrain.is_null() {
+        drop(Box::from_raw(terrain));
+    }
+}
+
+// Side of the map in cells; 0 for a null handle
+#[no_mangle]
+pub unsafe extern "C" fn genesis_terrain_size(terrain: *const TerrainGenerationResult) -> u32 {
+    terrain.as_ref().map_or(0, |terrain| terrain.height_field_ref().size() as u32)
+}
+
+// Sea level in height field units, for splitting the heights into land and sea
+#[no_mangle]
+pub unsafe extern "C" fn genesis_terrain_sea_level(terrain: *const TerrainGenerationResult) -> f32 {
+    terrain.as_ref().map_or(0.0, |terrain| terrain.vertical_units().sea_level_height())
+}
+
+// Copy one of the GENESIS_LAYER_* rasters (row-major, size × size) into `out`
+#[no_mangle]
+pub unsafe extern "C" fn genesis_terrain_copy_layer(
+    terrain: *const TerrainGenerationResult,
+    layer: u32,
+    out: *mut f32,
+    len: usize,
+) -> i32 {
+    let Some(terrain) = terrain.as_ref() else {
+        return fail(GENESIS_NULL_POINTER, "terrain handle is null".to_string());
+    };
+    if out.is_null() {
+        return fail(GENESIS_NULL_POINTER, "output buffer is null".to_string());
+    }
+    let water = terrain.water_features_ref();
+    let values = match layer {
+        GENESIS_LAYER_HEIGHT => Some(terrain.height_field_ref().data()),
+        GENESIS_LAYER_WATER => water.map(|w| w.water_mask()),
+        GENESIS_LAYER_RIVER => water.map(|w| w.river_mask()),
+        GENESIS_LAYER_BEACH => water.map(|w| w.beach_mask()),
+        GENESIS_LAYER_FLOW => water.map(|w| w.flow_accumulation()),
+        _ => None,
+    };
+    let Some(values) = values else {
+        return fail(GENESIS_MISSING_LAYER, format!("terrain has no layer {}", layer));
+    };
+    if values.len() != len {
+        return fail(GENESIS_LENGTH_MISMATCH, format!("layer {} needs {} values, got {}", layer, values.len(), len));
+    }
+    std::slice::from_raw_parts_mut(out, len).copy_from_slice(values);
+    GENESIS_OK
+}
+
+// Buffers in the module's own memory, for wasm runtimes whose host can't hand it pointers to
+// host memory: allocate `len` floats, pass the pointer to genesis_terrain_copy_layer, read them
+// out of linear memory and release them with genesis_free_f32 and the same length
+#[no_mangle]
+pub extern "C" fn genesis_alloc_f32(len: usize) -> *mut f32 {
+    Box::into_raw(vec![0.0f32; len].into_boxed_slice()) as *mut f32
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn genesis_free_f32(buffer: *mut f32, len: usize) {
+    if !buffer.is_null() {
+        drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(buffer, len)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn last_error() -> String {
+        let len = unsafe { genesis_last_error(std::ptr::null_mut(), 0) };
+        let mut message = vec![0u8; len];
+        unsafe { genesis_last_error(message.as_mut_ptr(), len) };
+        String::from_utf8(message).unwrap()
+    }
+
+    #[test]
+    fn generate_copy_and_free() {
+        let terrain = unsafe { genesis_generate(32, 1, 7, c"Temperate".as_ptr(), 0.0, 200.0) };
+        assert!(!terrain.is_null(), "{}", last_error());
+        let size = unsafe { genesis_terrain_size(terrain) } as usize;
+        assert_eq!(size, 32);
+
+        let mut heights = vec![f32::NAN; size * size];
+        let status = unsafe { genesis_terrain_copy_layer(terrain, GENESIS_LAYER_HEIGHT, heights.as_mut_ptr(), heights.len()) };
+        assert_eq!(status, GENESIS_OK);
+        assert!(heights.iter().all(|h| h.is_finite()));
+        assert_eq!(heights, unsafe { &*terrain }.height_field_ref().data());
+
+        let mut rivers = vec![0.0f32; size * size];
+        let status = unsafe { genesis_terrain_copy_layer(terrain, GENESIS_LAYER_RIVER, rivers.as_mut_ptr(), rivers.len()) };
+        assert_eq!(status, GENESIS_OK);
+        let status = unsafe { genesis_terrain_copy_layer(terrain, GENESIS_LAYER_RIVER, rivers.as_mut_ptr(), rivers.len() - 1) };
+        assert_eq!(status, GENESIS_LENGTH_MISMATCH);
+        let status = unsafe { genesis_terrain_copy_layer(terrain, 99, rivers.as_mut_ptr(), rivers.len()) };
+        assert_eq!(status, GENESIS_MISSING_LAYER);
+        assert!(last_error().contains("99"));
+
+        unsafe { genesis_terrain_free(terrain) };
+        unsafe { genesis_terrain_free(std::ptr::null_mut()) };
+    }
+
+    #[test]
+    fn rejects_bad_input() {
+        assert!(unsafe { genesis_generate(32, 1, 7, c"swamp".as_ptr(), 0.0, 0.0) }.is_null());
+        assert!(last_error().contains("swamp"));
+        assert!(unsafe { genesis_generate(32, 1, 7, std::ptr::null(), 0.0, 0.0) }.is_null());
+        let mut out = [0.0f32; 4];
+        let status = unsafe { genesis_terrain_copy_layer(std::ptr::null(), GENESIS_LAYER_HEIGHT, out.as_mut_ptr(), out.len()) };
+        assert_eq!(status, GENESIS_NULL_POINTER);
+        assert_eq!(unsafe { genesis_terrain_size(std::ptr::null()) }, 0);
+    }
+
+    // The C header restates the status and layer codes; keep both sides in step
+    #[test]
+    fn header_matches_constants() {
+        let header = include_str!("../include/genesis_terrain.h");
+        let defines = [
+            ("GENESIS_OK", GENESIS_OK as i64),
+            ("GENESIS_NULL_POINTER", GENESIS_NULL_POINTER as i64),
+            ("GENESIS_LENGTH_MISMATCH", GENESIS_LENGTH_MISMATCH as i64),
+            ("GENESIS_MISSING_LAYER", GENESIS_MISSING_LAYER as i64),
+            ("GENESIS_LAYER_HEIGHT", GENESIS_LAYER_HEIGHT as i64),
+            ("GENESIS_LAYER_WATER", GENESIS_LAYER_WATER as i64),
+            ("GENESIS_LAYER_RIVER", GENESIS_LAYER_RIVER as i64),
+            ("GENESIS_LAYER_BEACH", GENESIS_LAYER_BEACH as i64),
+            ("GENESIS_LAYER_FLOW", GENESIS_LAYER_FLOW as i64),
+        ];
+        for (name, value) in defines {
+            let line = header.lines().find(|line| line.starts_with(&format!("#define {} ", name)));
+            let defined = line.and_then(|line| line.split_whitespace().nth(2)).map(|v| v.trim_matches(|c| c == '(' || c == ')'));
+            assert_eq!(defined.and_then(|v| v.parse::<i64>().ok()), Some(value), "{}", name);
+        }
+    }
+}
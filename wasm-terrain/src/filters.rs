@@ -23,34 +23,58 @@ pub struct DuneParams {
     pub scale: f32,
     pub amplitude: f32,
     pub direction: f32, // radians
+    pub asymmetry: f32, // 0 symmetric ridges; towards 1 the crest moves downwind over a steep lee face
 }
 
 #[wasm_bindgen]
 impl DuneParams {
     #[wasm_bindgen(constructor)]
     pub fn new(scale: f32, amplitude: f32, direction: f32) -> Self {
-        Self { scale, amplitude, direction }
+        Self { scale, amplitude, direction, asymmetry: 0.0 }
     }
 }
 
-// Calculate slope at a point
-fn slope_at(height_field: &HeightField, x: usize, y: usize) -> f32 {
+// Steepest slope dry sand holds (height units per cell): tan 34° at 30 m cells on a 900 m
+// height scale. Steeper lee faces avalanche down to it.
+const DUNE_REPOSE_SLOPE: f32 = 0.022;
+const DUNE_AVALANCHE_ITERATIONS: u32 = 32;
+// Furthest downwind the crest moves at full asymmetry, as a share of the wavelength
+const DUNE_MAX_CREST_SHIFT: f32 = 0.4;
+// Lee slope, relative to the windward slope, where slip faces start and where they are fully so
+const SLIP_FACE_START: f32 = 1.5;
+const SLIP_FACE_FULL: f32 = 3.0;
+// Share of the repose slope from which a lee face counts as slipping in any case
+const SLIP_FACE_REPOSE_SHARE: f32 = 0.8;
+
+// Calculate height gradient (central differences) at a point
+pub(crate) fn gradient_at(height_field: &HeightField, x: usize, y: usize) -> (f32, f32) {
     let dx = (height_field.get_clamped(x as i32 + 1, y as i32) - 
               height_field.get_clamped(x as i32 - 1, y as i32)) * 0.5;
     let dy = (height_field.get_clamped(x as i32, y as i32 + 1) - 
               height_field.get_clamped(x as i32, y as i32 - 1)) * 0.5;
+    (dx, dy)
+}
+
+// Calculate slope at a point
+pub(crate) fn slope_at(height_field: &HeightField, x: usize, y: usize) -> f32 {
+    let (dx, dy) = gradient_at(height_field, x, y);
     (dx * dx + dy * dy).sqrt()
 }
 
 #[wasm_bindgen]
 pub fn apply_slope_blur(height_field: &mut HeightField, params: &SlopeBlurParams) {
+    slope_blur(height_field, params, 1.0);
+}
+
+// Slope blur with slopes measured in units of `slope_scale` per cell
+pub(crate) fn slope_blur(height_field: &mut HeightField, params: &SlopeBlurParams, slope_scale: f32) {
     let n = height_field.size();
     let mut tmp = vec![0.0f32; n * n];
     
     for _it in 0..params.iterations {
         for y in 0..n {
             for x in 0..n {
-                let s = slope_at(height_field, x, y);
+                let s = slope_at(height_field, x, y) / slope_scale;
                 let r = (params.radius * (1.0 - params.k * (s * 10.0).min(1.0))).max(1.0) as i32;
                 
                 let mut sum = 0.0;
@@ -99,18 +123,174 @@ pub fn apply_ridge_sharpen(height_field: &mut HeightField, strength: f32) {
 
 #[wasm_bindgen]
 pub fn apply_dunes(height_field: &mut HeightField, params: &DuneParams) {
+    dunes(height_field, params);
+}
+
+// Dunes as in apply_dunes; returns the slip-face mask (0-1): the steep lee faces sand
+// avalanches down, for texturing and for keeping vegetation off them
+#[wasm_bindgen]
+pub fn apply_dunes_with_slip_faces(height_field: &mut HeightField, params: &DuneParams) -> js_sys::Float32Array {
+    let mask = dunes(height_field, params);
+    let array = js_sys::Float32Array::new_with_length(mask.len() as u32);
+    array.copy_from(&mask);
+    array
+}
+
+// Transverse dunes across the wind (params.direction, the way it blows). With asymmetry the
+// crest moves downwind, so each ridge rises slowly on the windward side and drops on the lee;
+// lee faces steeper than sand can stand avalanche until they rest at DUNE_REPOSE_SLOPE.
+// Returns the slip-face mask.
+pub(crate) fn dunes(height_field: &mut HeightField, params: &DuneParams) -> Vec<f32> {
     let n = height_field.size();
-    let dx = params.direction.cos();
-    let dy = params.direction.sin();
-    
+    let dx = crate::det_math::cos(params.direction);
+    let dy = crate::det_math::sin(params.direction);
+    let crest = 0.5 + DUNE_MAX_CREST_SHIFT * params.asymmetry.clamp(0.0, 1.0);
+
+    // Trough at phase 0, crest at `crest`; at asymmetry 0 this is a plain sine
+    let mut offsets = vec![0.0f32; n * n];
     for y in 0..n {
         for x in 0..n {
             let u = (x as f32 * dx + y as f32 * dy) / n as f32;
-            let w = (u * params.scale * std::f32::consts::PI * 2.0).sin() * params.amplitude;
-            let current = height_field.get(x, y);
-            height_field.set(x, y, current + w);
+            let phase = u * params.scale + 0.25;
+            let t = phase - phase.floor();
+            let warped = if t < crest { 0.5 * t / crest } else { 0.5 + 0.5 * (t - crest) / (1.0 - crest) };
+            offsets[y * n + x] = -crate::det_math::cos(warped * std::f32::consts::PI * 2.0) * params.amplitude;
+        }
+    }
+
+    if params.asymmetry > 0.0 {
+        relax_to_repose(&mut offsets, n, DUNE_REPOSE_SLOPE, DUNE_AVALANCHE_ITERATIONS);
+    }
+
+    // Lee faces: ground falling downwind, steep against the mean windward slope of the profile
+    let wavelength = n as f32 / params.scale.max(f32::EPSILON);
+    let windward = (2.0 * params.amplitude.abs() / (crest * wavelength)).max(f32::EPSILON);
+    let at = |x: f32, y: f32| {
+        let xi = (x.round().max(0.0) as usize).min(n - 1);
+        let yi = (y.round().max(0.0) as usize).min(n - 1);
+        offsets[yi * n + xi]
+    };
+    let mut slip = vec![0.0f32; n * n];
+    for y in 0..n {
+        for x in 0..n {
+            let (fx, fy) = (x as f32, y as f32);
+            let lee = (at(fx - dx, fy - dy) - at(fx + dx, fy + dy)) * 0.5;
+            let steep = (lee / windward - SLIP_FACE_START) / (SLIP_FACE_FULL - SLIP_FACE_START);
+            // Faces the avalanche left at the angle of repose are slip faces however tall the dunes
+            let resting = (lee / DUNE_REPOSE_SLOPE - SLIP_FACE_REPOSE_SHARE) / (1.0 - SLIP_FACE_REPOSE_SHARE);
+            slip[y * n + x] = steep.max(resting).clamp(0.0, 1.0);
+        }
+    }
+
+    for (h, o) in height_field.data_mut().iter_mut().zip(&offsets) {
+        *h += o;
+    }
+    slip
+}
+
+// Avalanche relaxation: wherever a cell stands more than `repose` per cell of distance above a
+// neighbor, half the excess slides down to it
+fn relax_to_repose(data: &mut [f32], n: usize, repose: f32, iterations: u32) {
+    const NEIGHBORS: [(i32, i32, f32); 8] = [
+        (-1, -1, std::f32::consts::SQRT_2), (0, -1, 1.0), (1, -1, std::f32::consts::SQRT_2), (-1, 0, 1.0),
+        (1, 0, 1.0), (-1, 1, std::f32::consts::SQRT_2), (0, 1, 1.0), (1, 1, std::f32::consts::SQRT_2),
+    ];
+    let mut next = data.to_vec();
+    for _ in 0..iterations {
+        next.copy_from_slice(data);
+        let mut moved = false;
+        for y in 0..n {
+            for x in 0..n {
+                let idx = y * n + x;
+                // Steepest overloaded neighbor takes the slide
+                let mut best: Option<(usize, f32)> = None;
+                for &(ox, oy, dist) in &NEIGHBORS {
+                    let (nx, ny) = (x as i32 + ox, y as i32 + oy);
+                    if nx < 0 || ny < 0 || nx >= n as i32 || ny >= n as i32 {
+                        continue;
+                    }
+                    let n_idx = ny as usize * n + nx as usize;
+                    let excess = data[idx] - data[n_idx] - repose * dist;
+                    if excess > 0.0 && best.is_none_or(|(_, e)| excess > e) {
+                        best = Some((n_idx, excess));
+                    }
+                }
+                if let Some((n_idx, excess)) = best {
+                    // A quarter each way halves the excess; later passes settle the face
+                    next[idx] -= excess * 0.25;
+                    next[n_idx] += excess * 0.25;
+                    moved = true;
+                }
+            }
+        }
+        data.copy_from_slice(&next);
+        if !moved {
+            break;
+        }
+    }
+}
+
+// Spacing (cells) between foredune ridges and how many rows form behind a beach
+const FOREDUNE_SPACING: f32 = 6.0;
+const FOREDUNE_ROWS: f32 = 3.0;
+// Ground steeper than this (height units per cell) holds no loose sand
+const FOREDUNE_MAX_SLOPE: f32 = 0.02;
+// Share of the dune height that forms even when the wind blows along or off the shore
+const FOREDUNE_CALM_SHARE: f32 = 0.3;
+
+// Coastal foredunes: ridges parallel to the shoreline in a band behind sandy beaches
+// (`beach_mask` > 0 on land), tallest at the beach and fading inland over a few rows.
+// Onshore wind (params.direction, radians, the direction the wind blows towards) builds them
+// up and gives each ridge a gentle windward and a steep lee face. Steep ground blocks the
+// band, so nothing forms on rocky shores or further up into the hills.
+#[wasm_bindgen]
+pub fn apply_coastal_dunes(height_field: &mut HeightField, beach_mask: &[f32], sea_level: f32, params: &DuneParams) {
+    let n = height_field.size();
+    if n < 3 || beach_mask.len() != n * n {
+        return;
+    }
+    let data = height_field.data();
+    let sand: Vec<bool> = (0..n * n).map(|idx| beach_mask[idx] > 0.0 && data[idx] > sea_level).collect();
+    let blocked: Vec<bool> = (0..n * n)
+        .map(|idx| data[idx] <= sea_level || slope_at(height_field, idx % n, idx / n) > FOREDUNE_MAX_SLOPE)
+        .collect();
+    let distance = distance_to_mask_avoiding(&sand, &blocked, n);
+    let band = FOREDUNE_SPACING * FOREDUNE_ROWS;
+    let wind_x = crate::det_math::cos(params.direction);
+    let wind_y = crate::det_math::sin(params.direction);
+
+    let mut offsets = vec![0.0f32; n * n];
+    for y in 0..n {
+        for x in 0..n {
+            let idx = y * n + x;
+            let d = distance[idx];
+            if sand[idx] || !d.is_finite() || d > band {
+                continue;
+            }
+            // Inland direction is where the distance from the beach grows
+            let at = |i: i32, j: i32| {
+                let v = distance[(j.clamp(0, n as i32 - 1) as usize) * n + i.clamp(0, n as i32 - 1) as usize];
+                if v.is_finite() { v } else { d }
+            };
+            let (xi, yi) = (x as i32, y as i32);
+            let ix = at(xi + 1, yi) - at(xi - 1, yi);
+            let iy = at(xi, yi + 1) - at(xi, yi - 1);
+            let len = (ix * ix + iy * iy).sqrt();
+            let onshore = if len > f32::EPSILON { ((wind_x * ix + wind_y * iy) / len).max(0.0) } else { 0.0 };
+            let build = FOREDUNE_CALM_SHARE + (1.0 - FOREDUNE_CALM_SHARE) * onshore;
+
+            // Ridge profile: symmetric in calm air, long windward rise and short lee drop when onshore
+            let phase = d / FOREDUNE_SPACING;
+            let t = phase - phase.floor();
+            let crest = 0.5 + 0.25 * onshore;
+            let profile = if t < crest { t / crest } else { (1.0 - t) / (1.0 - crest) };
+            offsets[idx] = params.amplitude * build * profile * (1.0 - d / band);
         }
     }
+
+    for (h, o) in height_field.data_mut().iter_mut().zip(&offsets) {
+        *h += o;
+    }
 }
 
 // Additional optimized filters for WASM
@@ -187,4 +367,154 @@ pub fn apply_smoothing(height_field: &mut HeightField, iterations: u32, strength
         
         height_field.data_mut().copy_from_slice(&tmp);
     }
-}
\ No newline at end of file
+}
+
+// Height band around sea level (fraction of the height range) that coastline detail touches
+const COAST_BAND: f32 = 0.05;
+const COAST_NOISE_OCTAVES: u32 = 4;
+
+// Shape the land/sea boundary. `detail` < 0 smooths it: cells mostly surrounded by the other
+// medium flip (removing single-pixel islands and lakes) and heights near sea level are
+// blurred. `detail` > 0 adds fractal jitter that fades out away from sea level. Range [-1, 1].
+#[wasm_bindgen]
+pub fn apply_coastline_detail(height_field: &mut HeightField, sea_level: f32, detail: f32, seed: u32) {
+    let n = height_field.size();
+    let detail = detail.clamp(-1.0, 1.0);
+    if n < 3 || detail == 0.0 {
+        return;
+    }
+
+    let data = height_field.data();
+    let min = data.iter().fold(f32::INFINITY, |m, &h| m.min(h));
+    let max = data.iter().fold(f32::NEG_INFINITY, |m, &h| m.max(h));
+    let band = ((max - min) * COAST_BAND).max(f32::EPSILON);
+    let falloff = |h: f32| (1.0 - (h - sea_level).abs() / band).max(0.0);
+
+    if detail < 0.0 {
+        let strength = -detail;
+        let passes = (strength * 3.0).ceil() as u32;
+        for _pass in 0..passes {
+            let mut tmp = height_field.data().to_vec();
+            for y in 0..n {
+                for x in 0..n {
+                    let h = height_field.get(x, y);
+                    let w = falloff(h);
+                    if w <= 0.0 {
+                        continue;
+                    }
+                    let mut sum = 0.0;
+                    let mut land = 0;
+                    for dy in -1i32..=1 {
+                        for dx in -1i32..=1 {
+                            if dx == 0 && dy == 0 {
+                                continue;
+                            }
+                            let nh = height_field.get_clamped(x as i32 + dx, y as i32 + dy);
+                            sum += nh;
+                            if nh > sea_level {
+                                land += 1;
+                            }
+                        }
+                    }
+
+                    let mut value = h + (sum / 8.0 - h) * w * strength;
+                    // Majority vote: lone land/sea pixels move just across the waterline
+                    if h > sea_level && land <= 1 {
+                        value = value.min(sea_level - band * 0.01);
+                    } else if h <= sea_level && land >= 7 {
+                        value = value.max(sea_level + band * 0.01);
+                    }
+                    tmp[y * n + x] = value;
+                }
+            }
+            height_field.data_mut().copy_from_slice(&tmp);
+        }
+    } else {
+        let seed_f = seed as f32;
+        for y in 0..n {
+            for x in 0..n {
+                let h = height_field.get(x, y);
+                let w = falloff(h);
+                if w <= 0.0 {
+                    continue;
+                }
+                let u = x as f32 / n as f32;
+                let v = y as f32 / n as f32;
+                // Octaves from n/32 up to n/4 cycles across the map: pixel-scale coast jitter
+                let mut freq = n as f32 / 32.0;
+                let mut amp = 0.5;
+                let mut sum = 0.0;
+                for _o in 0..COAST_NOISE_OCTAVES {
+                    sum += (crate::noise::value_noise_2d(u * freq + seed_f * 3.1, v * freq - seed_f * 1.3) * 2.0 - 1.0) * amp;
+                    freq *= 2.0;
+                    amp *= 0.5;
+                }
+                height_field.set(x, y, h + sum * band * detail * w);
+            }
+        }
+    }
+}
+
+// Two-pass chamfer distance (in cells) from every cell to the nearest set cell of `mask`.
+// Cells are unreachable (f32::INFINITY) only when the mask is empty.
+pub(crate) fn distance_to_mask(mask: &[bool], size: usize) -> Vec<f32> {
+    distance_to_mask_avoiding(mask, &[], size)
+}
+
+// Same as distance_to_mask, but paths may not cross `blocked` cells (which stay unreachable).
+// Distances behind an obstacle are approximate since only two sweeps are made.
+pub(crate) fn distance_to_mask_avoiding(mask: &[bool], blocked: &[bool], size: usize) -> Vec<f32> {
+    const DIAG: f32 = std::f32::consts::SQRT_2;
+    let is_blocked = |idx: usize| blocked.get(idx).copied().unwrap_or(false);
+    let mut dist: Vec<f32> = mask.iter().map(|&m| if m { 0.0 } else { f32::INFINITY }).collect();
+
+    // Forward pass: top-left to bottom-right
+    for y in 0..size {
+        for x in 0..size {
+            let idx = y * size + x;
+            if is_blocked(idx) {
+                continue;
+            }
+            let mut d = dist[idx];
+            if x > 0 {
+                d = d.min(dist[idx - 1] + 1.0);
+            }
+            if y > 0 {
+                d = d.min(dist[idx - size] + 1.0);
+                if x > 0 {
+                    d = d.min(dist[idx - size - 1] + DIAG);
+                }
+                if x + 1 < size {
+                    d = d.min(dist[idx - size + 1] + DIAG);
+                }
+            }
+            dist[idx] = d;
+        }
+    }
+
+    // Backward pass: bottom-right to top-left
+    for y in (0..size).rev() {
+        for x in (0..size).rev() {
+            let idx = y * size + x;
+            if is_blocked(idx) {
+                continue;
+            }
+            let mut d = dist[idx];
+            if x + 1 < size {
+                d = d.min(dist[idx + 1] + 1.0);
+            }
+            if y + 1 < size {
+                d = d.min(dist[idx + size] + 1.0);
+                if x + 1 < size {
+                    d = d.min(dist[idx + size + 1] + DIAG);
+                }
+                if x > 0 {
+                    d = d.min(dist[idx + size - 1] + DIAG);
+                }
+            }
+            dist[idx] = d;
+        }
+    }
+
+    dist
+}
@@ -1,3 +1,5 @@
+use crate::brush::{BrushFalloff, BrushMode, CellRect};
+use crate::error::TerrainError;
 use wasm_bindgen::prelude::*;
 
 #[wasm_bindgen]
@@ -5,6 +7,7 @@ use wasm_bindgen::prelude::*;
 pub struct HeightField {
     size: usize,
     data: Vec<f32>,
+    dirty: Option<CellRect>, // cells touched by brushes since the last clear_dirty
 }
 
 #[wasm_bindgen]
@@ -14,6 +17,7 @@ impl HeightField {
         Self {
             size,
             data: vec![0.0; size * size],
+            dirty: None,
         }
     }
 
@@ -22,6 +26,7 @@ impl HeightField {
         Self {
             size,
             data: vec![fill; size * size],
+            dirty: None,
         }
     }
 
@@ -53,11 +58,13 @@ impl HeightField {
     }
 
     #[wasm_bindgen]
-    pub fn set_data(&mut self, data: &js_sys::Float32Array) {
+    pub fn set_data(&mut self, data: &js_sys::Float32Array) -> Result<(), JsValue> {
         let len = data.length() as usize;
-        if len == self.data.len() {
-            data.copy_to(&mut self.data);
+        if len != self.data.len() {
+            return Err(TerrainError::LengthMismatch { what: "height data", expected: self.data.len(), actual: len }.into());
         }
+        data.copy_to(&mut self.data);
+        Ok(())
     }
 
     #[wasm_bindgen]
@@ -98,6 +105,68 @@ impl HeightField {
         out
     }
 
+    // Upscale like resample_to but re-draw the river network (cells draining at least
+    // `channel_area` cells of this field) at the new resolution, so eroded channels stay
+    // connected instead of blurring into pits
+    #[wasm_bindgen]
+    pub fn resample_preserving_drainage(&self, new_size: usize, channel_area: f32) -> HeightField {
+        crate::resample::resample_preserving_drainage(self, new_size, channel_area)
+    }
+
+    // Cut out and rescale `src_rect` ([x, y, width, height] in cell-center coordinates,
+    // fractional allowed) to `dst_size` × `dst_size` in one pass, filtering properly when
+    // shrinking. Use instead of resampling a whole atlas and cropping. The rect is clamped to
+    // the map; throws if it is malformed or dst_size is above the largest map size.
+    #[wasm_bindgen]
+    pub fn resample_window(
+        &self,
+        src_rect: &[f32],
+        dst_size: usize,
+        kernel: crate::resample::ResampleKernel,
+    ) -> Result<HeightField, JsValue> {
+        let rect = <[f32; 4]>::try_from(src_rect).ok().filter(|rect| rect.iter().all(|v| v.is_finite())).ok_or_else(|| {
+            TerrainError::InvalidParameter(format!("src_rect must be 4 finite numbers [x, y, width, height], got {:?}", src_rect))
+        })?;
+        if dst_size as u64 > crate::config::MAX_MAP_SIZE {
+            return Err(TerrainError::InvalidParameter(format!(
+                "dst_size must be at most {}, got {}",
+                crate::config::MAX_MAP_SIZE,
+                dst_size
+            ))
+            .into());
+        }
+        Ok(crate::resample::resample_window(self, rect, dst_size, kernel))
+    }
+
+    // Zoom into `region` ([x, y, width, height] in cells, as returned by dirty_region) at
+    // `target_resolution` × `target_resolution`, adding seeded high-frequency detail that is
+    // consistent between overlapping zooms instead of a bilinear blur. Throws if the region
+    // is malformed or leaves the map.
+    #[wasm_bindgen]
+    pub fn add_detail(&self, region: &[u32], target_resolution: usize, seed: u32) -> Result<HeightField, JsValue> {
+        let Ok(region) = <[u32; 4]>::try_from(region) else {
+            return Err(TerrainError::InvalidParameter(format!("region must be [x, y, width, height], got {} values", region.len())).into());
+        };
+        let [x, y, w, h] = region.map(|v| v as usize);
+        let outside = |start: usize, len: usize| start.checked_add(len).is_none_or(|end| end > self.size);
+        if w < 2 || h < 2 || outside(x, w) || outside(y, h) {
+            return Err(TerrainError::InvalidParameter(format!(
+                "region {:?} must be at least 2x2 cells and lie inside the {}x{} map",
+                region, self.size, self.size
+            ))
+            .into());
+        }
+        if target_resolution < 2 || target_resolution as u64 > crate::config::MAX_MAP_SIZE {
+            return Err(TerrainError::InvalidParameter(format!(
+                "target_resolution must lie in [2, {}], got {}",
+                crate::config::MAX_MAP_SIZE,
+                target_resolution
+            ))
+            .into());
+        }
+        Ok(crate::noise::upsample_with_detail(self, [x, y, w, h], target_resolution, seed))
+    }
+
     #[wasm_bindgen]
     pub fn clone_field(&self) -> HeightField {
         self.clone()
@@ -129,7 +198,186 @@ impl HeightField {
         }
     }
 
+    // Normalize to [0, 1] while keeping the coastline: the sea range [min, sea_level] maps
+    // to [0, sea_level] and the land range [sea_level, max] to [sea_level, 1], each linearly,
+    // so cells at sea_level stay there and nothing crosses the waterline. sea_level must be
+    // in [0, 1]; otherwise this falls back to a plain normalize.
+    #[wasm_bindgen]
+    pub fn normalize_preserving(&mut self, sea_level: f32) {
+        if self.data.is_empty() {
+            return;
+        }
+        if !(0.0..=1.0).contains(&sea_level) {
+            self.normalize();
+            return;
+        }
+
+        let min = self.data.iter().fold(f32::INFINITY, |m, &h| m.min(h));
+        let max = self.data.iter().fold(f32::NEG_INFINITY, |m, &h| m.max(h));
+        let sea_span = sea_level - min;
+        let land_span = max - sea_level;
+
+        for value in &mut self.data {
+            if *value <= sea_level {
+                if sea_span > 0.0 {
+                    *value = (*value - min) / sea_span * sea_level;
+                }
+            } else if land_span > 0.0 {
+                *value = sea_level + (*value - sea_level) / land_span * (1.0 - sea_level);
+            }
+        }
+    }
+
+    // Hash of the exact height bits (hex), identical across platforms for identical data
+    #[wasm_bindgen]
+    pub fn determinism_checksum(&self) -> String {
+        format!("{:016x}", crate::det_math::checksum(&[&self.data]))
+    }
+
+    // 16-bit copy with min/max scale metadata for memory-bound atlases and transfers
+    #[wasm_bindgen]
+    pub fn quantize(&self) -> crate::quantized::QuantizedHeightField {
+        crate::quantized::QuantizedHeightField::from_height_field(self)
+    }
+
+    // Lossy transfer encoding: every decoded height is within `max_error` of this field (up to
+    // float rounding). Smooth terrain at 1/1000 of its height range takes 3-4 bits per cell.
+    #[wasm_bindgen]
+    pub fn compress(&self, max_error: f32) -> Vec<u8> {
+        crate::codec::compress(self, max_error)
+    }
+
+    // Decode bytes produced by compress; throws on malformed input
+    #[wasm_bindgen]
+    pub fn decompress(bytes: &[u8]) -> Result<HeightField, JsValue> {
+        crate::codec::decompress(bytes).ok_or_else(|| TerrainError::CorruptData("compressed height field").into())
+    }
+
+    // Progressive transfer encoding for preview streaming: coarsest level first, each further
+    // level doubling the resolution, with every height of the full stream within `max_error`
+    // of this field like compress. A server can send the bytes as they are and the client
+    // decode whatever has arrived. Throws unless the size is a power of two.
+    #[wasm_bindgen]
+    pub fn encode_progressive(&self, max_error: f32) -> Result<Vec<u8>, JsValue> {
+        Ok(crate::progressive::encode_progressive(self, max_error)?)
+    }
+
+    // Decode a prefix of encode_progressive bytes: the field at the resolution of the finest
+    // level received in full (resample_to brings it to the final size). Throws until the
+    // coarsest level is complete, and on malformed input.
+    #[wasm_bindgen]
+    pub fn decode_progressive(bytes: &[u8]) -> Result<HeightField, JsValue> {
+        crate::progressive::decode_progressive(bytes).ok_or_else(|| TerrainError::CorruptData("progressive height field").into())
+    }
+
+    // Physics-engine layouts (Rapier, PhysX, Bullet) of the same surface: heights are
+    // multiplied by `scale` (e.g. the biome height_scale) and cells are `cell_spacing` apart
+    #[wasm_bindgen]
+    pub fn to_physics_heightfield(&self, scale: f32, cell_spacing: f32) -> crate::physics::PhysicsHeightfield {
+        crate::physics::PhysicsHeightfield::from_height_field(self, scale, cell_spacing)
+    }
+
+    // Per-level min/max height bounds for ray marching, horizon culling and tile bounding boxes
+    #[wasm_bindgen]
+    pub fn build_minmax_pyramid(&self) -> crate::pyramid::MinMaxPyramid {
+        crate::pyramid::MinMaxPyramid::build(self)
+    }
+
+    // Bilinear height at fractional cell coordinates (cell centers on integers), clamped to
+    // the field. Matches the surface raycast and the physics layouts use.
+    #[wasm_bindgen]
+    pub fn sample_height(&self, fx: f32, fy: f32) -> f32 {
+        crate::query::bilinear(self, fx, fy)
+    }
+
+    // Catmull-Rom height: smooth across cells, may overshoot slightly at sharp breaks
+    #[wasm_bindgen]
+    pub fn sample_height_bicubic(&self, fx: f32, fy: f32) -> f32 {
+        crate::noise::sample_bicubic(self, fx, fy)
+    }
+
+    // Unit normal [x, y, height] in cell and height units. For world space with heights
+    // scaled by s and cells c apart, scale x and y by s / c and renormalize.
+    #[wasm_bindgen]
+    pub fn sample_normal(&self, fx: f32, fy: f32) -> Vec<f32> {
+        crate::query::normal(self, fx, fy).to_vec()
+    }
+
+    // First hit of the ray origin + t · direction on the bilinear surface, both given as
+    // [x, y, height] in cell and height units. Returns [x, y, height, t], or undefined on a
+    // miss or malformed input.
+    #[wasm_bindgen]
+    pub fn raycast(&self, origin: &[f32], direction: &[f32]) -> Option<Vec<f32>> {
+        let origin = <[f32; 3]>::try_from(origin).ok()?.map(f64::from);
+        let direction = <[f32; 3]>::try_from(direction).ok()?.map(f64::from);
+        let hit = crate::query::raycast(self, origin, direction)?;
+        Some(hit.iter().map(|&v| v as f32).collect())
+    }
+
+    #[wasm_bindgen]
+    pub fn statistics(&self, sea_level: f32) -> crate::analysis::TerrainStatistics {
+        crate::analysis::compute_statistics(self, sea_level)
+    }
+
+    // RMSE, largest difference, share of changed cells and hillshade dissimilarity against
+    // `other`; throws if the sizes differ
+    #[wasm_bindgen]
+    pub fn compare(&self, other: &HeightField) -> Result<crate::analysis::HeightFieldComparison, JsValue> {
+        crate::analysis::compare_height_fields(self, other)
+            .ok_or_else(|| TerrainError::SizeMismatch { expected: self.size, actual: other.size }.into())
+    }
+
+    // Texturing / placement weight per cell: 1 for heights in [min, max], fading to 0 over
+    // `feather` height units beyond either end
+    #[wasm_bindgen]
+    pub fn mask_by_height_range(&self, min: f32, max: f32, feather: f32) -> Vec<f32> {
+        crate::analysis::height_range_mask(self, min, max, feather)
+    }
+
+    // Same for slope (height units per cell, central differences)
+    #[wasm_bindgen]
+    pub fn mask_by_slope_range(&self, min: f32, max: f32, feather: f32) -> Vec<f32> {
+        crate::analysis::slope_range_mask(self, min, max, feather)
+    }
+
+    // Sea level that leaves `fraction` (0-1) of the cells above water
+    #[wasm_bindgen]
+    pub fn find_sea_level_for_land_fraction(&self, fraction: f32) -> f32 {
+        crate::analysis::sea_level_for_land_fraction(&self.data, fraction)
+    }
+
+    // Sculpt with one brush dab at (x, y) in cell coordinates and grow the dirty region.
+    // Raise/Lower/Noise: `strength` is the height change at the center; Flatten/Smooth: blend 0-1.
+    #[wasm_bindgen]
+    pub fn brush(&mut self, x: f32, y: f32, radius: f32, strength: f32, mode: BrushMode, falloff: BrushFalloff) {
+        if let Some(rect) = crate::brush::apply_brush(self, x, y, radius, strength, mode, falloff) {
+            self.mark_dirty(rect);
+        }
+    }
+
+    // Bounding box of brushed cells as [x, y, width, height]; empty when nothing changed,
+    // so downstream stages can be re-run on just that region
+    #[wasm_bindgen]
+    pub fn dirty_region(&self) -> Vec<u32> {
+        match self.dirty {
+            Some([x0, y0, x1, y1]) => vec![x0 as u32, y0 as u32, (x1 - x0) as u32, (y1 - y0) as u32],
+            None => Vec::new(),
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn clear_dirty(&mut self) {
+        self.dirty = None;
+    }
+
     // Internal methods for Rust use
+    pub(crate) fn mark_dirty(&mut self, [x0, y0, x1, y1]: CellRect) {
+        self.dirty = Some(match self.dirty {
+            Some([a0, b0, a1, b1]) => [a0.min(x0), b0.min(y0), a1.max(x1), b1.max(y1)],
+            None => [x0, y0, x1, y1],
+        });
+    }
+
     pub(crate) fn data(&self) -> &[f32] {
         &self.data
     }
@@ -5,6 +5,61 @@ mod filters;
 mod water_system;
 mod erosion;
 mod biomes;
+mod settlements;
+mod analysis;
+mod volcanic;
+mod seasons;
+mod delta;
+mod snapshots;
+mod config;
+mod quantized;
+mod tiles;
+mod det_math;
+mod render;
+mod stamp;
+mod precipitation;
+mod brush;
+mod terraform;
+mod periglacial;
+mod oasis;
+mod badlands;
+mod outcrops;
+mod spectral;
+mod pyramid;
+mod physics;
+mod polygons;
+mod water_volumes;
+mod query;
+mod placement;
+mod codec;
+mod validation;
+mod basins;
+mod strata;
+mod resample;
+mod budget;
+mod weathering;
+mod river_graph;
+mod crossings;
+mod beaches;
+mod units;
+mod supersample;
+mod rock_features;
+mod climate;
+mod bathymetry;
+mod reefs;
+mod sea_ice;
+mod biome_registry;
+mod error;
+mod bounds;
+mod morph;
+mod region;
+mod aging;
+mod memory;
+mod progressive;
+#[cfg(feature = "ffi")]
+mod ffi;
+#[cfg(feature = "python")]
+mod python;
 
 use wasm_bindgen::prelude::*;
 
@@ -23,12 +78,62 @@ pub fn init() {
 // Export main public API
 pub use height_field::HeightField;
 pub use biomes::{BiomeType, BiomeParams};
-pub use water_system::{WaterFeatures, WaterSystemParams};
+pub use water_system::{MapEdge, WaterFeatures, WaterPreset, WaterSystemParams};
+pub use settlements::SettlementConstraints;
+pub use analysis::{CliffFaces, HeightFieldComparison, HydrologyReport, TerrainClass, TerrainStatistics};
+pub use volcanic::{LavaField, LavaFlowParams};
+pub use periglacial::PatternedGroundParams;
+pub use oasis::{OasisField, OasisParams};
+pub use outcrops::RockOutcrops;
+pub use pyramid::MinMaxPyramid;
+pub use physics::PhysicsHeightfield;
+pub use water_volumes::WaterVolumes;
+pub use placement::{PlacementRule, PlacementRules, Placements};
+pub use validation::ValidationSeverity;
+pub use basins::BasinHierarchy;
+pub use strata::StrataField;
+pub use resample::ResampleKernel;
+pub use weathering::WeatheringMap;
+pub use river_graph::{RiverGraph, RiverNodeKind};
+pub use crossings::CrossingKind;
+pub use beaches::BeachMaterials;
+pub use rock_features::RockFeatureKind;
+pub use climate::ClimateGrid;
+pub use bathymetry::BathymetryParams;
+pub use reefs::{ReefField, ReefParams};
+pub use sea_ice::{SeaIceField, SeaIceParams};
+pub use seasons::{Hemisphere, Microclimate, Season, SeasonalVariant};
+pub use delta::HeightFieldDelta;
+pub use snapshots::SnapshotStack;
+pub use erosion::{ErosionParams, GeologyPreset, TileBorders};
+pub use config::TerrainConfig;
+pub use units::{VerticalUnits, WorldScale};
+pub use quantized::QuantizedHeightField;
+pub use brush::{BrushFalloff, BrushMode};
+pub use stamp::MergeAxis;
+pub use tiles::TileGridOutput;
+pub use error::TerrainError;
+pub use morph::TerrainMorph;
+pub use region::RegionExport;
 
 #[wasm_bindgen]
 pub struct TerrainGenerationResult {
     height_field: HeightField,
     water_features: Option<WaterFeatures>,
+    lava_mask: Option<Vec<f32>>,
+    permafrost_depth: Option<Vec<f32>>,
+    oases: Option<OasisField>,
+    biome_type: BiomeType,
+    biome_id: String,
+    // Parameters generation ran with, a registered biome's own when the config picked one
+    biome_params: BiomeParams,
+    units: VerticalUnits,
+    stages: Vec<(String, HeightField)>,
+    completed_stages: Vec<String>,
+    skipped_stages: Vec<String>,
+    adjustments: Vec<bounds::ParamAdjustment>,
+    // Erosion settings of the pipeline, with time_years the total erosion applied so far
+    erosion_params: erosion::ErosionParams,
 }
 
 #[wasm_bindgen]
@@ -42,6 +147,129 @@ impl TerrainGenerationResult {
     pub fn water_features(&self) -> Option<WaterFeatures> {
         self.water_features.clone()
     }
+
+    #[wasm_bindgen]
+    pub fn get_lava_mask(&self) -> Option<js_sys::Float32Array> {
+        self.lava_mask.as_ref().map(|mask| {
+            let array = js_sys::Float32Array::new_with_length(mask.len() as u32);
+            array.copy_from(mask);
+            array
+        })
+    }
+
+    // Desert oasis pools, vegetation and sites (None for other biomes)
+    #[wasm_bindgen(getter)]
+    pub fn oases(&self) -> Option<OasisField> {
+        self.oases.clone()
+    }
+
+    // Depth (m) of the permafrost table per cell for tundra maps, 0 where the ground is unfrozen
+    #[wasm_bindgen]
+    pub fn get_permafrost_depth(&self) -> Option<js_sys::Float32Array> {
+        self.permafrost_depth.as_ref().map(|depth| {
+            let array = js_sys::Float32Array::new_with_length(depth.len() as u32);
+            array.copy_from(depth);
+            array
+        })
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn biome_type(&self) -> BiomeType {
+        self.biome_type
+    }
+
+    // Id of the biome generated, a registered one when the config picked it (biome_type is then
+    // the biome it is based on)
+    #[wasm_bindgen(getter)]
+    pub fn biome_id(&self) -> String {
+        self.biome_id.clone()
+    }
+
+    // Parameters the terrain was generated with, including a registered biome's overrides
+    #[wasm_bindgen(getter)]
+    pub fn biome_params(&self) -> BiomeParams {
+        self.biome_params
+    }
+
+    // In the legacy 1/1000 units of TerrainConfig::sea_level
+    #[wasm_bindgen(getter)]
+    pub fn sea_level(&self) -> f32 {
+        self.units.legacy_sea_level()
+    }
+
+    // Vertical scale and resolved sea level in meters
+    #[wasm_bindgen(getter)]
+    pub fn vertical_units(&self) -> VerticalUnits {
+        self.units
+    }
+
+    // Hash of the exact height and water mask bits, for lockstep peers to compare results
+    #[wasm_bindgen]
+    pub fn determinism_checksum(&self) -> String {
+        let mut buffers: Vec<&[f32]> = vec![self.height_field.data()];
+        if let Some(features) = &self.water_features {
+            buffers.push(features.water_mask());
+            buffers.push(features.river_mask());
+            buffers.push(features.beach_mask());
+        }
+        format!("{:016x}", det_math::checksum(&buffers))
+    }
+
+    // Post-generation sanity checks as {code, severity, message, value} objects, most severe
+    // first; pass the config the result was generated from
+    #[wasm_bindgen]
+    pub fn validate(&self, config: &TerrainConfig) -> js_sys::Array {
+        let issues = js_sys::Array::new();
+        for issue in validation::validate_result(self, config) {
+            issues.push(&issue.to_js_object());
+        }
+        issues
+    }
+
+    // Labels of captured intermediate stages, in pipeline order (empty unless capture_stages)
+    #[wasm_bindgen]
+    pub fn stage_labels(&self) -> Vec<String> {
+        self.stages.iter().map(|(label, _)| label.clone()).collect()
+    }
+
+    #[wasm_bindgen]
+    pub fn stage(&self, label: &str) -> Option<HeightField> {
+        self.stages.iter().find(|(l, _)| l == label).map(|(_, hf)| hf.clone())
+    }
+
+    // Pipeline stages that ran, in order; "relief_<step>" is the noise and filters of one step
+    #[wasm_bindgen]
+    pub fn completed_stages(&self) -> Vec<String> {
+        self.completed_stages.clone()
+    }
+
+    // Stages left out because the time budget ran out (empty without a budget)
+    #[wasm_bindgen]
+    pub fn skipped_stages(&self) -> Vec<String> {
+        self.skipped_stages.clone()
+    }
+
+    // Years of erosion behind the map, including any added by continue_erosion
+    #[wasm_bindgen(getter)]
+    pub fn erosion_years(&self) -> f32 {
+        self.erosion_params.time_years
+    }
+
+    // Config values brought into range before generation, as {field, requested, applied,
+    // reason} objects (see TerrainConfig::clamp_to_bounds)
+    #[wasm_bindgen]
+    pub fn get_adjustments(&self) -> js_sys::Array {
+        bounds::to_js_array(&self.adjustments)
+    }
+
+    // Internal methods for Rust use
+    pub(crate) fn height_field_ref(&self) -> &HeightField {
+        &self.height_field
+    }
+
+    pub(crate) fn water_features_ref(&self) -> Option<&WaterFeatures> {
+        self.water_features.as_ref()
+    }
 }
 
 #[wasm_bindgen]
@@ -52,12 +280,86 @@ pub fn generate_terrain(
     biome_type: BiomeType,
     sea_level: f32,
     erosion_years: f32,
+) -> Result<TerrainGenerationResult, JsValue> {
+    generate_terrain_with_config(&TerrainConfig::new(
+        base_size,
+        steps,
+        seed,
+        biome_type,
+        sea_level,
+        erosion_years,
+    ))
+}
+
+// Out-of-range settings are clamped and listed on the result (get_adjustments); throws a
+// TerrainError for settings the pipeline can't run with at all
+#[wasm_bindgen]
+pub fn generate_terrain_with_config(config: &TerrainConfig) -> Result<TerrainGenerationResult, JsValue> {
+    let mut config = *config;
+    let adjustments = config.clamp_bounds();
+    config.check()?;
+    Ok(run_pipeline(&config, adjustments, budget::StageBudget::new(None)))
+}
+
+// Anytime generation for loading screens: stages are dropped once `ms_budget` is spent, the
+// least important first (relief detail steps and biome dressing before erosion), and the
+// result always has the full size. Stages already running are not interrupted, so the budget
+// can be overshot by one stage. See completed_stages / skipped_stages on the result.
+#[wasm_bindgen]
+pub fn generate_within_budget(config: &TerrainConfig, ms_budget: f64) -> Result<TerrainGenerationResult, JsValue> {
+    let mut config = *config;
+    let adjustments = config.clamp_bounds();
+    config.check()?;
+    Ok(run_pipeline(&config, adjustments, budget::StageBudget::new(Some(ms_budget.max(0.0)))))
+}
+
+fn run_pipeline(
+    config: &TerrainConfig,
+    adjustments: Vec<bounds::ParamAdjustment>,
+    mut budget: budget::StageBudget,
 ) -> TerrainGenerationResult {
-    use web_sys::console;
+    use budget::{DETAIL_SHARE, FULL_SHARE};
+    
+    let TerrainConfig {
+        base_size,
+        steps,
+        seed,
+        biome_type,
+        sea_level: _,
+        erosion_years,
+        capture_stages,
+        coastline_detail,
+        auto_scale,
+        shuffle_thermal,
+        supersample_carving,
+        land_fraction,
+        vertical_units: _,
+        cell_size_m,
+        bathymetry,
+        region,
+        strata,
+        biome_slot: _,
+    } = *config;
+    // Sea level in height field units from here on
+    let mut units = config.units();
+    let mut sea = units.sea_level_height();
+    // Size of a cell of the finished map, when the config works in world units
+    let full_size = (base_size as usize) << steps.saturating_sub(1);
+    let world = match cell_size_m {
+        Some(m) => Some(WorldScale::new(m, units.height_scale_m)),
+        None if auto_scale => Some(WorldScale::for_resolution(full_size, units.height_scale_m)),
+        None => None,
+    };
+    let mut stages: Vec<(String, HeightField)> = Vec::new();
+    let mut capture = |label: String, hf: &HeightField| {
+        if capture_stages {
+            stages.push((label, hf.clone()));
+        }
+    };
     
-    console::log_1(&format!("🌱 Starting terrain generation: base_size={}, steps={}", base_size, steps).into());
+    utils::console_log!("🌱 Starting terrain generation: base_size={}, steps={}", base_size, steps);
     
-    let biome_params = BiomeParams::for_biome(biome_type);
+    let biome_params = config.biome_params();
     
     // Generate base terrain
     let mut height_field = height_field::HeightField::new(base_size as usize);
@@ -65,181 +367,318 @@ pub fn generate_terrain(
     // Apply multi-level generation
     let mut current_size = base_size;
     for step in 0..steps {
-        let step_start = js_sys::Date::now();
+        let step_start = utils::now_ms();
         
         if current_size > base_size {
-            let resample_start = js_sys::Date::now();
+            let resample_start = utils::now_ms();
             height_field = height_field.resample_to(current_size as usize);
-            let resample_time = js_sys::Date::now() - resample_start;
-            console::log_1(&format!("  🔄 Step {} resample to {}: {:.2}ms", step, current_size, resample_time).into());
+            let resample_time = utils::now_ms() - resample_start;
+            utils::console_log!("  🔄 Step {} resample to {}: {:.2}ms", step, current_size, resample_time);
+        }
+        
+        // The first step is the relief itself; later steps only refine it
+        let relief_label = format!("relief_{}", step);
+        if step == 0 {
+            budget.always(&relief_label);
+        } else if !budget.admit(&relief_label, FULL_SHARE) {
+            current_size *= 2;
+            continue;
         }
         
         // Apply FBM noise
-        let fbm_start = js_sys::Date::now();
-        noise::apply_fbm(
-            &mut height_field, 
-            &biome_params.fbm_params(),
-            seed,
-            None // Use default world UV mapping
-        );
-        let fbm_time = js_sys::Date::now() - fbm_start;
-        console::log_1(&format!("  🌊 Step {} FBM noise: {:.2}ms", step, fbm_time).into());
+        let fbm_start = utils::now_ms();
+        match region {
+            // The region is a tile of a grid with cells `extent` wide
+            Some(r) => {
+                let (row, col) = (r.v / r.extent, r.u / r.extent);
+                noise::apply_fbm_for_tile(&mut height_field, &biome_params.fbm_params(), seed, row, col, r.extent)
+            }
+            None => noise::apply_fbm(
+                &mut height_field, 
+                &biome_params.fbm_params(),
+                seed,
+                None // Use default world UV mapping
+            ),
+        }
+        let fbm_time = utils::now_ms() - fbm_start;
+        utils::console_log!("  🌊 Step {} FBM noise: {:.2}ms", step, fbm_time);
+        capture(format!("fbm_{}", step), &height_field);
         
         // Apply filters
-        let filter_start = js_sys::Date::now();
-        filters::apply_slope_blur(&mut height_field, &biome_params.slope_blur_params());
-        
-        if biome_params.has_dunes() && current_size >= 256 {
-            filters::apply_dunes(&mut height_field, &biome_params.dunes_params());
+        let filter_start = utils::now_ms();
+        let mut blur = biome_params.slope_blur_params();
+        let mut slope_scale = 1.0;
+        if let Some(scale) = world.map(|w| w.at_size(current_size as usize, full_size)) {
+            blur.radius = scale.reference_cells(blur.radius);
+            slope_scale = scale.reference_slope(1.0);
         }
-        let filter_time = js_sys::Date::now() - filter_start;
-        console::log_1(&format!("  🏔️  Step {} filters: {:.2}ms", step, filter_time).into());
+        filters::slope_blur(&mut height_field, &blur, slope_scale);
+        let filter_time = utils::now_ms() - filter_start;
+        utils::console_log!("  🏔️  Step {} filters: {:.2}ms", step, filter_time);
+        capture(format!("filters_{}", step), &height_field);
         
         current_size *= 2;
         
-        let step_time = js_sys::Date::now() - step_start;
-        console::log_1(&format!("  ✅ Step {} total: {:.2}ms", step, step_time).into());
+        let step_time = utils::now_ms() - step_start;
+        utils::console_log!("  ✅ Step {} total: {:.2}ms", step, step_time);
     }
     
     // Apply ridge sharpening
-    let ridge_start = js_sys::Date::now();
-    filters::apply_ridge_sharpen(&mut height_field, biome_params.ridge_sharpen_strength());
-    let ridge_time = js_sys::Date::now() - ridge_start;
-    console::log_1(&format!("🗻 Ridge sharpening: {:.2}ms", ridge_time).into());
+    if budget.admit("ridge", DETAIL_SHARE) {
+        let ridge_start = utils::now_ms();
+        filters::apply_ridge_sharpen(&mut height_field, biome_params.ridge_sharpen_strength());
+        let ridge_time = utils::now_ms() - ridge_start;
+        utils::console_log!("🗻 Ridge sharpening: {:.2}ms", ridge_time);
+        capture("ridge".to_string(), &height_field);
+    }
+    
+    // Resolve a land-fraction target against the finished relief
+    if let Some(fraction) = land_fraction {
+        sea = height_field.find_sea_level_for_land_fraction(fraction);
+        units.sea_level_m = units.to_meters(sea);
+        utils::console_log!("🌊 Sea level for {:.0}% land: {:.1}m", fraction * 100.0, units.sea_level_m);
+    }
+    
+    if coastline_detail != 0.0 && budget.admit("coastline", DETAIL_SHARE) {
+        filters::apply_coastline_detail(&mut height_field, sea, coastline_detail, seed);
+        capture("coastline".to_string(), &height_field);
+    }
+    
+    // Ocean floor after the coastline so the shelf follows the final shore
+    if let Some(mut params) = bathymetry {
+        if budget.admit("bathymetry", DETAIL_SHARE) {
+            if let Some(w) = world {
+                params.shelf_width = w.reference_cells(params.shelf_width);
+                params.slope_width = w.reference_cells(params.slope_width);
+                params.trench_width = w.reference_cells(params.trench_width);
+            }
+            bathymetry::apply_bathymetry(&mut height_field, sea, &params);
+            capture("bathymetry".to_string(), &height_field);
+        }
+    }
+    
+    // Tilted beds the dissection and erosion cut through
+    let strata = strata.map(|(block_size, max_dip)| {
+        let (block_size, max_dip) = match world {
+            Some(w) => (w.reference_cells(block_size), w.reference_slope(max_dip)),
+            None => (block_size, max_dip),
+        };
+        strata::strata_field(height_field.size(), block_size, max_dip, seed)
+    });
+    
+    // Badlands: incise the drainage network into slot canyons and hoodoo fields
+    let dissection_intensity = biome_params.dissection_intensity();
+    if dissection_intensity > 0.0 && budget.admit("dissection", DETAIL_SHARE) {
+        let dissection_start = utils::now_ms();
+        badlands::badlands_dissection(&mut height_field, dissection_intensity, strata.as_ref(), seed);
+        let dissection_time = utils::now_ms() - dissection_start;
+        utils::console_log!("🏜️ Badlands dissection: {:.2}ms", dissection_time);
+        capture("dissection".to_string(), &height_field);
+    }
+    
+    // Tundra gets periglacial microrelief once the large-scale relief is settled
+    if biome_params.has_patterned_ground() && budget.admit("patterned_ground", DETAIL_SHARE) {
+        let params = periglacial::PatternedGroundParams {
+            supersample: supersample_carving,
+            ..biome_params.patterned_ground_params()
+        };
+        periglacial::apply_patterned_ground(&mut height_field, &params, sea, seed);
+        capture("patterned_ground".to_string(), &height_field);
+    }
+    
+    // Volcanic biomes get lava flows before erosion so rivers cut into the fresh flows
+    let lava_mask = if biome_params.has_lava_flows() && budget.admit("lava", DETAIL_SHARE) {
+        let lava_start = utils::now_ms();
+        let lava = volcanic::simulate_lava_flows(&mut height_field, &[], &biome_params.lava_flow_params());
+        let lava_time = utils::now_ms() - lava_start;
+        utils::console_log!("🌋 Lava flows: {:.2}ms", lava_time);
+        capture("lava".to_string(), &height_field);
+        Some(lava.lava_mask().to_vec())
+    } else {
+        None
+    };
     
     // Apply erosion if specified
-    let erosion_start = js_sys::Date::now();
-    let water_features = if erosion_years > 0.0 {
-        console::log_1(&format!("🌊 Starting erosion simulation: {} years", erosion_years).into());
-        let erosion_params = erosion::ErosionParams {
-            time_years: erosion_years,
-            sea_level,
-            wind_strength: biome_params.fbm_params().amplitude * 0.5,
-            rain_intensity: 1.0,
-            temperature_cycles: match biome_type {
-                BiomeType::Alpine => 50.0,
-                BiomeType::Desert => 10.0,
-                BiomeType::Temperate => 25.0,
-            },
+    let erosion_start = utils::now_ms();
+    let mut erosion_params = erosion::ErosionParams::for_biome(biome_type, erosion_years, units.legacy_sea_level());
+    erosion_params.world_scale = world;
+    erosion_params.shuffle_thermal = shuffle_thermal;
+    erosion_params.supersample_carving = supersample_carving;
+    erosion_params.seed = seed;
+    let mut water_features = if erosion_years > 0.0 && budget.admit("erosion", FULL_SHARE) {
+        utils::console_log!("🌊 Starting erosion simulation: {} years", erosion_years);
+        Some(erosion::geological_erosion(&mut height_field, &erosion_params, strata.as_ref()))
+    } else {
+        utils::console_log!("⏭️ Skipping erosion simulation");
+        erosion_params.time_years = 0.0;
+        None
+    };
+    let erosion_time = utils::now_ms() - erosion_start;
+    utils::console_log!("🌊 Erosion total: {:.2}ms", erosion_time);
+    if water_features.is_some() {
+        capture("erosion".to_string(), &height_field);
+    }
+    
+    // Foredunes follow the sandy coast, so they need the final shoreline
+    if biome_params.has_dunes() && budget.admit("dunes", FULL_SHARE) {
+        let beach_mask = match water_features.as_ref() {
+            Some(features) => features.beach_mask().to_vec(),
+            None => water_system::generate_beach_mask(
+                &height_field,
+                sea,
+                world.map_or(biome_params.beach_width(), |w| w.reference_cells(biome_params.beach_width())),
+                world.map_or(f32::INFINITY, |w| w.reference_slope(water_system::DEFAULT_BEACH_MAX_SLOPE)),
+                None,
+            ),
         };
-        
-        Some(erosion::apply_geological_erosion(&mut height_field, &erosion_params))
+        filters::apply_coastal_dunes(&mut height_field, &beach_mask, sea, &biome_params.dunes_params());
+        capture("dunes".to_string(), &height_field);
+    }
+    
+    // Oases go in last so erosion doesn't silt up the pools
+    let oases = if biome_params.has_oases() && budget.admit("oases", FULL_SHARE) {
+        let field = oasis::place_oases(&mut height_field, sea, &biome_params.oasis_params());
+        if let Some(features) = water_features.as_mut() {
+            features.add_standing_water(field.water_mask());
+        }
+        capture("oases".to_string(), &height_field);
+        Some(field)
+    } else {
+        None
+    };
+    
+    let permafrost_depth = if biome_params.has_patterned_ground() && budget.admit("permafrost", FULL_SHARE) {
+        Some(periglacial::permafrost_depth(&height_field, sea, units.height_scale_m, &biome_params))
     } else {
-        console::log_1(&"⏭️ Skipping erosion simulation".into());
         None
     };
-    let erosion_time = js_sys::Date::now() - erosion_start;
-    console::log_1(&format!("🌊 Erosion total: {:.2}ms", erosion_time).into());
+    let (completed_stages, skipped_stages) = budget.into_report();
     
     TerrainGenerationResult {
         height_field,
         water_features,
+        lava_mask,
+        permafrost_depth,
+        oases,
+        biome_type,
+        biome_id: config.biome_id(),
+        biome_params,
+        units,
+        stages,
+        completed_stages,
+        skipped_stages,
+        adjustments,
+        erosion_params,
     }
 }
 
+// With `recompute_water` the water masks are found again on the full-resolution atlas, with the
+// water settings of the core terrain's erosion, for crisp rivers (slower; nothing is carved a
+// second time); otherwise the masks of the core terrain are resampled to the atlas. The core
+// terrain is generated with `steps`, clamped like TerrainConfig::steps, unless `auto_steps`
+// picks just enough steps to cover the atlas (at most AUTO_TILE_GRID_STEPS, trading detail for
+// speed on large grids). With `on_tile`, each tile is passed to it as `on_tile(tile, index,
+// rect)` as soon as it is cut, before the atlas is assembled; the tiles are in the result as
+// well unless `output` leaves them out. Cutting only starts once the core terrain, its erosion
+// and the atlas water are done, since those work on the whole map, so streaming overlaps
+// uploads with tile cutting and atlas assembly, not with generation. An exception thrown by
+// `on_tile` aborts generation and is rethrown.
 #[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
 pub fn generate_continuous_tile_grid(
     rows: u32,
     cols: u32,
     tile_size: u32,
     overlap: u32,
     base_size: u32,
-    _steps: u32,
+    steps: u32,
+    auto_steps: bool,
     seed: u32,
     biome_type: BiomeType,
     sea_level: f32,
     erosion_years: f32,
-) -> js_sys::Object {
-    use web_sys::console;
+    recompute_water: bool,
+    on_tile: Option<js_sys::Function>,
+    output: TileGridOutput,
+) -> Result<js_sys::Object, JsValue> {
     
-    let start_time = js_sys::Date::now();
-    console::log_1(&format!("🦀 Starting WASM terrain generation: {}x{} tiles", rows, cols).into());
+    let mut adjustments = Vec::new();
+    let overlap = bounds::tile_grid_overlap(rows, cols, tile_size, overlap, &mut adjustments)?;
+    let start_time = utils::now_ms();
+    utils::console_log!("🦀 Starting WASM terrain generation: {}x{} tiles", rows, cols);
     
-    let _biome_params = BiomeParams::for_biome(biome_type);
     let inner_size = tile_size - 2 * overlap;
     
     // Calculate total size for atlas
-    let atlas_w = (cols * inner_size) as usize;
-    let atlas_h = (rows * inner_size) as usize;
+    let too_large = || TerrainError::InvalidParameter(format!("{}x{} tiles of {} cells overflow the atlas", rows, cols, tile_size));
+    let atlas_w = cols.checked_mul(inner_size).ok_or_else(too_large)? as usize;
+    let atlas_h = rows.checked_mul(inner_size).ok_or_else(too_large)? as usize;
     let atlas_size = std::cmp::max(atlas_w, atlas_h);
     
-    console::log_1(&format!("📐 Atlas size: {}x{}, max: {}", atlas_w, atlas_h, atlas_size).into());
+    utils::console_log!("📐 Atlas size: {}x{}, max: {}", atlas_w, atlas_h, atlas_size);
     
-    let terrain_start = js_sys::Date::now();
+    let terrain_start = utils::now_ms();
     
-    // Generate terrain directly at the atlas size to avoid expensive resampling
+    let steps = if auto_steps { auto_tile_grid_steps(atlas_size, base_size) } else { steps };
+    utils::console_log!("🪜 Core terrain steps: {}{}", steps, if auto_steps { " (auto)" } else { "" });
     let terrain_result = generate_terrain(
         base_size,
-        // Reduce steps for performance - instead of going step by step, target size directly
-        ((atlas_size as f32 / base_size as f32).log2().ceil() as u32 + 1).min(6), // Cap at 6 steps max
+        steps,
         seed,
         biome_type,
         sea_level,
         erosion_years,
-    );
+    )?;
+    adjustments.extend(terrain_result.adjustments.iter().cloned());
     
-    let terrain_time = js_sys::Date::now() - terrain_start;
-    console::log_1(&format!("⛰️  Core terrain generation: {:.2}ms", terrain_time).into());
+    let terrain_time = utils::now_ms() - terrain_start;
+    utils::console_log!("⛰️  Core terrain generation: {:.2}ms", terrain_time);
     
-    let resample_start = js_sys::Date::now();
+    let resample_start = utils::now_ms();
     
-    let atlas_hf = terrain_result.height_field.resample_to(atlas_size);
+    // The atlas-sized field is only built for the atlas itself or to find water on it; tiles
+    // on their own are resampled straight from their windows of the core terrain
+    let core = &terrain_result.height_field;
+    let core_water = terrain_result.water_features.as_ref();
+    let atlas_hf = (output != TileGridOutput::TilesOnly || (recompute_water && core_water.is_some())).then(|| {
+        let mut atlas_hf = HeightField::new(atlas_size);
+        atlas_hf.data_mut().copy_from_slice(&resample_raster(core.data(), core.size(), atlas_size));
+        atlas_hf
+    });
     
-    let resample_time = js_sys::Date::now() - resample_start;
-    console::log_1(&format!("🔄 Resampling: {:.2}ms", resample_time).into());
+    let resample_time = utils::now_ms() - resample_start;
+    utils::console_log!("🔄 Resampling: {:.2}ms", resample_time);
     
-    let extraction_start = js_sys::Date::now();
-
-    // Extract tiles directly from the atlas-sized heightfield
-    let mut tiles = Vec::with_capacity((rows * cols) as usize);
-    for r in 0..rows {
-        for c in 0..cols {
-            let mut tile = HeightField::new(tile_size as usize);
-            
-            // Calculate source region in atlas heightfield
-            let src_x = c * inner_size;
-            let src_y = r * inner_size;
-            
-            // Copy data from atlas heightfield to tile - use bulk operations where possible
-            let tile_data = tile.data_mut();
-            let atlas_data = atlas_hf.data();
-            let atlas_size_actual = atlas_hf.size();
-            
-            for y in 0..tile_size {
-                for x in 0..tile_size {
-                    let src_pixel_x = (src_x + x) as usize;
-                    let src_pixel_y = (src_y + y) as usize;
-                    let tile_idx = (y * tile_size + x) as usize;
-                    
-                    if src_pixel_x < atlas_size_actual && src_pixel_y < atlas_size_actual {
-                        let src_idx = src_pixel_y * atlas_size_actual + src_pixel_x;
-                        tile_data[tile_idx] = atlas_data[src_idx];
-                    } else {
-                        tile_data[tile_idx] = 0.0;
-                    }
-                }
-            }
-            
-            tiles.push(tile);
+    // Water is brought to the atlas once, before tiles are cut, so every tile's masks line up
+    // with its heights and rivers run on across tile seams
+    let water_start = utils::now_ms();
+    let water_features = core_water.zip(atlas_hf.as_ref()).map(|(features, atlas_hf)| {
+        if !recompute_water {
+            return features.map_rasters(atlas_size, |raster| resample_raster(raster, features.size(), atlas_size));
         }
-    }
-    
-    let extraction_time = js_sys::Date::now() - extraction_start;
-    console::log_1(&format!("📦 Tile extraction: {:.2}ms", extraction_time).into());
-    
-    let atlas_build_start = js_sys::Date::now();
-
-    // Create atlas directly from the generated heightfield
-    let mut atlas = vec![0.0f32; atlas_w * atlas_h];
-    for y in 0..atlas_h {
-        for x in 0..atlas_w {
-            atlas[y * atlas_w + x] = atlas_hf.get(x, y);
+        // Erosion already cut the channels into these heights, so the masks are found again at
+        // the atlas resolution with generation's own water settings, without carving twice
+        let mut params = terrain_result.erosion_params;
+        params.world_scale = params.world_scale.map(|scale| scale.at_size(atlas_size, features.size()));
+        let mut water_params = erosion::final_water_params(&params);
+        if params.world_scale.is_none() {
+            let factor = atlas_size as f32 / features.size() as f32;
+            water_params.river_width *= factor;
+            water_params.beach_width *= factor;
+            water_params.min_channel_width *= factor;
+            water_params.beach_max_slope /= factor;
         }
+        let mut atlas_features = water_system::water_masks(atlas_hf, &water_params);
+        // Terrace and surface age come from the erosion history, so they are resampled
+        atlas_features.set_terrace_age(resample_raster(features.terrace_age(), features.size(), atlas_size));
+        atlas_features.set_surface_age(resample_raster(features.surface_age(), features.size(), atlas_size));
+        atlas_features
+    });
+    if water_features.is_some() {
+        let water_time = utils::now_ms() - water_start;
+        utils::console_log!("💧 Atlas water ({}): {:.2}ms", if recompute_water { "recomputed" } else { "resampled" }, water_time);
     }
     
-    let atlas_build_time = js_sys::Date::now() - atlas_build_start;
-    console::log_1(&format!("🖼️  Atlas building: {:.2}ms", atlas_build_time).into());
-
     // Generate UV rects
     let mut rects = Vec::new();
     for r in 0..rows {
@@ -258,9 +697,89 @@ pub fn generate_continuous_tile_grid(
         }
     }
 
-    // Convert atlas to Float32Array
-    let atlas_array = js_sys::Float32Array::new_with_length(atlas.len() as u32);
-    atlas_array.copy_from(&atlas);
+    let extraction_start = utils::now_ms();
+
+    // LOD stitching metadata: one skirt depth and four edge errors (N, E, S, W) per tile
+    let tile_count = rows.checked_mul(cols).ok_or_else(too_large)? as usize;
+    let mut skirt_depths = Vec::with_capacity(tile_count);
+    let mut edge_errors = Vec::with_capacity(tile_count * 4);
+    let mut measure_tile = |tile: &HeightField| {
+        let errors = tiles::lod_edge_errors(tile);
+        skirt_depths.push(tiles::skirt_depth(&errors));
+        edge_errors.extend_from_slice(&errors);
+    };
+    let keep_tiles = output != TileGridOutput::AtlasOnly;
+    let tiles_array = js_sys::Array::new();
+    let tile_object = |tile: &HeightField, water: Option<&WaterFeatures>| {
+        let obj = tile.to_js_object();
+        if let Some(water) = water {
+            js_sys::Reflect::set(&obj, &"waterFeatures".into(), &water.to_js_object()).unwrap();
+        }
+        if keep_tiles {
+            tiles_array.push(&obj);
+        }
+        obj
+    };
+    let (tile_size, inner_size_cells) = (tile_size as usize, inner_size as usize);
+    let cut_tile = |i: usize| {
+        let (row, col) = (i / cols as usize, i % cols as usize);
+        match &atlas_hf {
+            Some(atlas_hf) => tiles::extract_tile(atlas_hf, row, col, tile_size, inner_size_cells),
+            None => tiles::resample_tile(core, atlas_size, row, col, tile_size, inner_size_cells),
+        }
+    };
+    let cut_water = |i: usize| {
+        let (row, col) = (i / cols as usize, i % cols as usize);
+        match (&water_features, core_water) {
+            (Some(features), _) => Some(tiles::extract_water_tile(features, row, col, tile_size, inner_size_cells)),
+            (None, Some(features)) => Some(tiles::resample_water_tile(features, atlas_size, row, col, tile_size, inner_size_cells)),
+            (None, None) => None,
+        }
+    };
+    match &on_tile {
+        // Streaming: cut each tile in turn and hand it over before the next, in row-major order
+        Some(callback) => {
+            for (i, rect) in rects.iter().enumerate() {
+                let tile = cut_tile(i);
+                let water = cut_water(i);
+                measure_tile(&tile);
+                let obj = tile_object(&tile, water.as_ref());
+                callback.call3(&JsValue::NULL, &obj, &(i as u32).into(), rect)?;
+            }
+        }
+        // Every tile up front, cut in parallel with the `threads` feature
+        None if keep_tiles => {
+            let tiles = tiles::map_tiles(tile_count, cut_tile);
+            let water_tiles = core_water.map(|_| tiles::map_tiles(tile_count, cut_water));
+            for (i, tile) in tiles.iter().enumerate() {
+                measure_tile(tile);
+                tile_object(tile, water_tiles.as_ref().and_then(|water_tiles| water_tiles[i].as_ref()));
+            }
+        }
+        // Only the LOD metadata is needed: cut one tile at a time and let it go
+        None => {
+            for i in 0..tile_count {
+                measure_tile(&cut_tile(i));
+            }
+        }
+    }
+    
+    let extraction_time = utils::now_ms() - extraction_start;
+    utils::console_log!("📦 Tile extraction: {:.2}ms", extraction_time);
+    
+    // Create atlas directly from the generated heightfield
+    let atlas_array = atlas_hf.as_ref().filter(|_| output != TileGridOutput::TilesOnly).map(|atlas_hf| {
+        let atlas_build_start = utils::now_ms();
+        let atlas = tiles::build_atlas(atlas_hf, atlas_w, atlas_h);
+        let atlas_build_time = utils::now_ms() - atlas_build_start;
+        utils::console_log!("🖼️  Atlas building: {:.2}ms", atlas_build_time);
+
+        // Convert atlas to Float32Array
+        let atlas_array = js_sys::Float32Array::new_with_length(atlas.len() as u32);
+        atlas_array.copy_from(&atlas);
+        atlas_array
+    });
+    drop(atlas_hf);
 
     // Convert rects to JS array
     let rects_array = js_sys::Array::new();
@@ -268,26 +787,51 @@ pub fn generate_continuous_tile_grid(
         rects_array.push(&rect);
     }
 
-    // Convert tiles to JS array
-    let tiles_array = js_sys::Array::new();
-    for tile in tiles {
-        tiles_array.push(&tile.to_js_object());
-    }
+    let skirt_array = js_sys::Float32Array::new_with_length(skirt_depths.len() as u32);
+    skirt_array.copy_from(&skirt_depths);
+    let edge_error_array = js_sys::Float32Array::new_with_length(edge_errors.len() as u32);
+    edge_error_array.copy_from(&edge_errors);
 
     // Create result object
     let result = js_sys::Object::new();
     js_sys::Reflect::set(&result, &"tiles".into(), &tiles_array).unwrap();
     js_sys::Reflect::set(&result, &"innerSize".into(), &(inner_size as f32).into()).unwrap();
-    js_sys::Reflect::set(&result, &"atlas".into(), &atlas_array).unwrap();
+    if let Some(atlas_array) = &atlas_array {
+        js_sys::Reflect::set(&result, &"atlas".into(), atlas_array).unwrap();
+    }
     js_sys::Reflect::set(&result, &"atlasSize".into(), &(std::cmp::max(atlas_w, atlas_h) as f32).into()).unwrap();
     js_sys::Reflect::set(&result, &"rects".into(), &rects_array).unwrap();
+    js_sys::Reflect::set(&result, &"skirtDepths".into(), &skirt_array).unwrap();
+    js_sys::Reflect::set(&result, &"edgeErrors".into(), &edge_error_array).unwrap();
 
-    if let Some(water_features) = terrain_result.water_features {
-        js_sys::Reflect::set(&result, &"waterFeatures".into(), &water_features.to_js_object()).unwrap();
+    js_sys::Reflect::set(&result, &"atlasWidth".into(), &(atlas_w as f32).into()).unwrap();
+    js_sys::Reflect::set(&result, &"atlasHeight".into(), &(atlas_h as f32).into()).unwrap();
+    // Grid and terrain parameters brought into range, as on TerrainGenerationResult
+    js_sys::Reflect::set(&result, &"adjustments".into(), &bounds::to_js_array(&adjustments)).unwrap();
+    
+    // Water masks laid out exactly like `atlas` (atlasWidth × atlasHeight), so `rects` apply to
+    // them too; each tile also carries its own slice under tiles[i].waterFeatures
+    if let Some(water_features) = water_features.filter(|_| atlas_array.is_some()) {
+        let water_atlas = tiles::water_atlas_object(&water_features, atlas_w, atlas_h);
+        js_sys::Reflect::set(&result, &"waterFeatures".into(), &water_atlas).unwrap();
     }
 
-    let total_time = js_sys::Date::now() - start_time;
-    console::log_1(&format!("🎯 Total WASM time: {:.2}ms", total_time).into());
+    let total_time = utils::now_ms() - start_time;
+    utils::console_log!("🎯 Total WASM time: {:.2}ms", total_time);
+
+    Ok(result)
+}
 
-    result
-}
\ No newline at end of file
+// Most steps auto_steps picks for the tile grid's core terrain
+const AUTO_TILE_GRID_STEPS: u32 = 6;
+
+// Steps that bring `base_size` up to at least `atlas_size` cells, capped at AUTO_TILE_GRID_STEPS
+fn auto_tile_grid_steps(atlas_size: usize, base_size: u32) -> u32 {
+    let doublings = (atlas_size as f32 / base_size.max(1) as f32).log2().ceil().max(0.0) as u32;
+    (doublings + 1).min(AUTO_TILE_GRID_STEPS)
+}
+
+// Bilinear resample of a square per-cell raster onto resample_to's grid
+fn resample_raster(raster: &[f32], size: usize, new_size: usize) -> Vec<f32> {
+    resample::resample_region(raster, size, new_size, [0, 0, new_size, new_size])
+}
@@ -0,0 +1,86 @@
+use crate::config::TerrainConfig;
+use crate::tiles::TileGridOutput;
+use wasm_bindgen::prelude::*;
+
+// Bytes per cell of an f32 raster
+const CELL_BYTES: f64 = 4.0;
+// Full-size rasters alive at the pipeline's peak, counted from what each phase allocates:
+// the height field and its resampled copy between relief steps
+const HEIGHT_LAYERS: f64 = 2.0;
+// Lava, permafrost and oasis rasters kept on the result
+const RESULT_LAYERS: f64 = 3.0;
+// Water masks, flow accumulation, terrace and surface age on WaterFeatures
+const WATER_FEATURE_LAYERS: f64 = 6.0;
+// Flow, river, carve, beach and distance rasters inside one water system run
+const WATER_SYSTEM_SCRATCH_LAYERS: f64 = 10.0;
+// Erosion and deposition masks, renewal, bedrock, rainfall and the double buffers of the passes
+const EROSION_SCRATCH_LAYERS: f64 = 7.0;
+// Stages captured at the full size (ridge through oases), and the per-step fbm and filters
+// stages at every size up to it, 4/3 of a full raster each
+const FULL_SIZE_STAGES: f64 = 9.0;
+const STEP_STAGE_LAYERS: f64 = 2.0 * 4.0 / 3.0;
+
+// Peak bytes of the pipeline for `cells` cells
+fn pipeline_bytes(cells: f64, erosion: bool, capture_stages: bool) -> f64 {
+    let mut layers = HEIGHT_LAYERS + RESULT_LAYERS;
+    if erosion {
+        layers += WATER_FEATURE_LAYERS + WATER_SYSTEM_SCRATCH_LAYERS + EROSION_SCRATCH_LAYERS;
+    }
+    if capture_stages {
+        layers += FULL_SIZE_STAGES + STEP_STAGE_LAYERS;
+    }
+    cells * layers * CELL_BYTES
+}
+
+// Expected peak WASM memory (bytes) for generate_terrain_with_config, after the same clamping,
+// so apps can warn or downscale before generation runs out of memory. An estimate from the
+// rasters each stage allocates; the module's own code and data come on top.
+#[wasm_bindgen]
+pub fn estimate_memory(config: &TerrainConfig) -> f64 {
+    let mut config = *config;
+    config.clamp_bounds();
+    let full_size = (config.base_size as f64) * 2f64.powi(config.steps as i32 - 1);
+    pipeline_bytes(full_size * full_size, config.erosion_years > 0.0, config.capture_stages)
+}
+
+// Expected peak WASM memory (bytes) for generate_continuous_tile_grid with the same arguments:
+// the core terrain, the atlas-sized field, whichever of the atlas and the tiles `output` keeps
+// and, with erosion, their water masks. Throws for grids the generator rejects.
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn estimate_tile_grid_memory(
+    rows: u32,
+    cols: u32,
+    tile_size: u32,
+    overlap: u32,
+    base_size: u32,
+    steps: u32,
+    auto_steps: bool,
+    erosion_years: f32,
+    recompute_water: bool,
+    output: TileGridOutput,
+) -> Result<f64, JsValue> {
+    let overlap = crate::bounds::tile_grid_overlap(rows, cols, tile_size, overlap, &mut Vec::new())?;
+    let inner_size = (tile_size - 2 * overlap) as f64;
+    let (atlas_w, atlas_h) = (cols as f64 * inner_size, rows as f64 * inner_size);
+    let atlas_size = atlas_w.max(atlas_h);
+    let steps = if auto_steps { crate::auto_tile_grid_steps(atlas_size as usize, base_size) } else { steps };
+
+    let mut config = TerrainConfig::new(base_size, steps, 0, crate::biomes::BiomeType::Temperate, 0.0, erosion_years);
+    config.clamp_bounds();
+    let erosion = config.erosion_years > 0.0;
+    let core = estimate_memory(&config);
+
+    let atlas_cells = atlas_size * atlas_size;
+    let cropped_cells = if output == TileGridOutput::TilesOnly { 0.0 } else { atlas_w * atlas_h };
+    let tile_cells = if output == TileGridOutput::AtlasOnly { 0.0 } else { (rows * cols) as f64 * (tile_size as f64).powi(2) };
+    // Resampled atlas field, the cropped atlas and the tiles
+    let mut layers = atlas_cells + cropped_cells + tile_cells;
+    if erosion {
+        layers += WATER_FEATURE_LAYERS * (atlas_cells + cropped_cells + tile_cells);
+        if recompute_water {
+            layers += WATER_SYSTEM_SCRATCH_LAYERS * atlas_cells;
+        }
+    }
+    Ok(core + layers * CELL_BYTES)
+}
@@ -0,0 +1,97 @@
+use crate::config::TerrainConfig;
+use crate::units::VerticalUnits;
+use crate::TerrainGenerationResult;
+use wasm_bindgen::prelude::*;
+
+// Morph between the terrains two seeds give for the same config. Both ends are generated once,
+// so scrubbing `t` in an editor only costs a blend per frame.
+#[wasm_bindgen]
+pub struct TerrainMorph {
+    a: TerrainGenerationResult,
+    b: TerrainGenerationResult,
+}
+
+// Exact at both ends, so t = 0 and t = 1 reproduce the two terrains bit for bit
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a * (1.0 - t) + b * t
+}
+
+fn lerp_raster(a: &[f32], b: &[f32], t: f32) -> Vec<f32> {
+    a.iter().zip(b).map(|(&x, &y)| lerp(x, y, t)).collect()
+}
+
+fn lerp_rasters(a: Option<&Vec<f32>>, b: Option<&Vec<f32>>, t: f32) -> Option<Vec<f32>> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(lerp_raster(a, b, t)),
+        _ => None,
+    }
+}
+
+#[wasm_bindgen]
+impl TerrainMorph {
+    // Generate `config` with `seed_a` and with `seed_b`; its own seed is ignored. Throws a
+    // TerrainError like generate_terrain_with_config.
+    #[wasm_bindgen(constructor)]
+    pub fn new(config: &TerrainConfig, seed_a: u32, seed_b: u32) -> Result<TerrainMorph, JsValue> {
+        let a = crate::generate_terrain_with_config(&TerrainConfig { seed: seed_a, ..*config })?;
+        let b = crate::generate_terrain_with_config(&TerrainConfig { seed: seed_b, ..*config })?;
+        Ok(TerrainMorph { a, b })
+    }
+
+    // The terrain `t` (0-1) of the way from seed_a to seed_b. Heights, sea level, water masks and
+    // the other per-cell layers are blended as generated, so nothing is carved again and the ends
+    // match the two terrains exactly; oases come from the nearer end.
+    #[wasm_bindgen]
+    pub fn at(&self, t: f32) -> TerrainGenerationResult {
+        let t = if t.is_finite() { t.clamp(0.0, 1.0) } else { 0.0 };
+        let (a, b) = (&self.a, &self.b);
+        let nearer = if t < 0.5 { a } else { b };
+
+        let mut height_field = a.height_field.clone();
+        height_field.data_mut().copy_from_slice(&lerp_raster(a.height_field.data(), b.height_field.data(), t));
+        let units = VerticalUnits::new(a.units.height_scale_m, lerp(a.units.sea_level_m, b.units.sea_level_m, t));
+        let water_features = match (&a.water_features, &b.water_features) {
+            (Some(features_a), Some(features_b)) => Some(features_a.zip_rasters(features_b, |x, y| lerp_raster(x, y, t))),
+            _ => None,
+        };
+
+        TerrainGenerationResult {
+            height_field,
+            water_features,
+            lava_mask: lerp_rasters(a.lava_mask.as_ref(), b.lava_mask.as_ref(), t),
+            permafrost_depth: lerp_rasters(a.permafrost_depth.as_ref(), b.permafrost_depth.as_ref(), t),
+            oases: nearer.oases.clone(),
+            biome_type: a.biome_type,
+            biome_id: a.biome_id.clone(),
+            biome_params: a.biome_params,
+            units,
+            stages: Vec::new(),
+            completed_stages: nearer.completed_stages.clone(),
+            skipped_stages: nearer.skipped_stages.clone(),
+            adjustments: a.adjustments.clone(),
+            erosion_params: a.erosion_params,
+        }
+    }
+}
+
+// One-off morph `t` (0-1) of the way from the terrain of `seed_a` to that of `seed_b` for
+// `config`; keep a TerrainMorph instead when scrubbing through several values of `t`
+#[wasm_bindgen]
+pub fn morph_terrains(config: &TerrainConfig, seed_a: u32, seed_b: u32, t: f32) -> Result<TerrainGenerationResult, JsValue> {
+    Ok(TerrainMorph::new(config, seed_a, seed_b)?.at(t))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::biomes::BiomeType;
+
+    #[test]
+    fn ends_reproduce_both_seeds() {
+        let config = TerrainConfig::new(16, 3, 0, BiomeType::Temperate, 300.0, 2000.0);
+        let morph = TerrainMorph::new(&config, 11, 12).ok().unwrap();
+        assert_eq!(morph.at(0.0).determinism_checksum(), morph.a.determinism_checksum());
+        assert_eq!(morph.at(1.0).determinism_checksum(), morph.b.determinism_checksum());
+        assert_ne!(morph.a.determinism_checksum(), morph.b.determinism_checksum());
+    }
+}
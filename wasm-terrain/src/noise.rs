@@ -1,6 +1,9 @@
 use crate::height_field::HeightField;
 use wasm_bindgen::prelude::*;
 
+// Octaves beyond this add detail far below a cell at any supported map size and only cost time
+pub(crate) const MAX_OCTAVES: u32 = 12;
+
 #[wasm_bindgen]
 #[derive(Clone, Copy)]
 pub struct FBMParams {
@@ -35,18 +38,44 @@ impl FBMParams {
             seed,
         }
     }
+
+    // Cap `octaves` at the most apply_fbm evaluates, listing the change as
+    // {field, requested, applied, reason} objects
+    #[wasm_bindgen]
+    pub fn clamp_to_bounds(&mut self) -> js_sys::Array {
+        let mut adjustments = Vec::new();
+        let reason = format!("at most {} octaves are evaluated", MAX_OCTAVES);
+        crate::bounds::clamp_u32(&mut adjustments, "octaves", &mut self.octaves, 0, MAX_OCTAVES, &reason);
+        crate::bounds::to_js_array(&adjustments)
+    }
 }
 
 // Hash function for deterministic noise
 fn hash(n: f32) -> f32 {
     // More deterministic hash - round input to avoid precision issues
     let rounded = (n * 1_000_000.0).round() / 1_000_000.0;
-    let x = (rounded.sin()) * 43758.5453123;
+    #[allow(clippy::excessive_precision)]
+    let x = crate::det_math::sin(rounded) * 43758.5453123;
     x - x.floor()
 }
 
+// Integer hash of a world-space cell, identical for every tile that evaluates it
+pub(crate) fn hash_cell(x: i32, y: i32, salt: u32) -> u32 {
+    let mut h = (x as u32).wrapping_mul(0x8da6_b343) ^ (y as u32).wrapping_mul(0xd816_3841) ^ salt.wrapping_mul(0xcb1a_b31f);
+    h ^= h >> 16;
+    h = h.wrapping_mul(0x7feb_352d);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x846c_a68b);
+    h ^ (h >> 16)
+}
+
+// Map a hash to [0, 1)
+pub(crate) fn hash_to_unit(h: u32) -> f32 {
+    (h >> 8) as f32 / (1u32 << 24) as f32
+}
+
 // 2D value noise implementation
-fn value_noise_2d(x: f32, y: f32) -> f32 {
+pub(crate) fn value_noise_2d(x: f32, y: f32) -> f32 {
     // Round coordinates to ensure identical sampling at tile borders
     let px = (x * 1_000_000.0).round() / 1_000_000.0;
     let py = (y * 1_000_000.0).round() / 1_000_000.0;
@@ -76,7 +105,6 @@ fn value_noise_2d(x: f32, y: f32) -> f32 {
 pub type WorldUVFunc = Option<fn(x: usize, y: usize, size: usize) -> (f32, f32)>;
 
 // Default world UV mapping for tile continuity
-#[allow(dead_code)]
 fn default_world_uv(x: usize, y: usize, size: usize, tile_col: f32, tile_row: f32, world_scale: f32) -> (f32, f32) {
     let n = size as f32;
     let u = x as f32 / n;
@@ -104,9 +132,7 @@ pub fn apply_fbm(
         warp,
         seed: _,
     } = *params;
-    
-    // Limit octaves for performance - cap at 6
-    let max_octaves = octaves.min(6);
+    let octaves = octaves.min(MAX_OCTAVES);
     
     let seed_f = seed as f32;
     
@@ -145,7 +171,6 @@ pub fn apply_fbm(
 }
 
 // Specialized version for tile generation with explicit tile coordinates
-#[allow(dead_code)]
 pub fn apply_fbm_for_tile(
     height_field: &mut HeightField,
     params: &FBMParams,
@@ -164,6 +189,7 @@ pub fn apply_fbm_for_tile(
         warp,
         seed: _,
     } = *params;
+    let octaves = octaves.min(MAX_OCTAVES);
     
     let seed_f = seed as f32;
     
@@ -193,4 +219,87 @@ pub fn apply_fbm_for_tile(
             height_field.set(x, y, new_height);
         }
     }
-}
\ No newline at end of file
+}
+// Each extra detail octave is this much weaker than the one before (H ≈ 0.85 roughness)
+const DETAIL_GAIN: f32 = 0.55;
+// First detail octave amplitude relative to the source's RMS cell-to-cell relief
+const DETAIL_START: f32 = 0.5;
+
+// Catmull-Rom weights for the four taps around a sample at fraction t
+fn catmull_rom(t: f32) -> [f32; 4] {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    [
+        (-t3 + 2.0 * t2 - t) * 0.5,
+        (3.0 * t3 - 5.0 * t2 + 2.0) * 0.5,
+        (-3.0 * t3 + 4.0 * t2 + t) * 0.5,
+        (t3 - t2) * 0.5,
+    ]
+}
+
+pub(crate) fn sample_bicubic(height_field: &HeightField, x: f32, y: f32) -> f32 {
+    let (x0, y0) = (x.floor(), y.floor());
+    let wx = catmull_rom(x - x0);
+    let wy = catmull_rom(y - y0);
+    let (x0, y0) = (x0 as i32, y0 as i32);
+    let mut sum = 0.0;
+    for (j, wyj) in wy.iter().enumerate() {
+        for (i, wxi) in wx.iter().enumerate() {
+            sum += height_field.get_clamped(x0 + i as i32 - 1, y0 + j as i32 - 1) * wxi * wyj;
+        }
+    }
+    sum
+}
+
+// Upsample the cells [x, x + w) × [y, y + h) to `target` × `target` with a bicubic filter,
+// then fill in the frequencies the source could not hold: FBM octaves from the source
+// Nyquist limit up to the target's, evaluated at world coordinates (fractions of the full
+// map) so overlapping zooms at any resolution show the same detail. Amplitude continues the
+// source's own roughness.
+pub(crate) fn upsample_with_detail(height_field: &HeightField, region: [usize; 4], target: usize, seed: u32) -> HeightField {
+    let n = height_field.size();
+    let [x0, y0, w, h] = region;
+    let mut out = HeightField::new(target);
+    if target < 2 || w < 2 || h < 2 {
+        return out;
+    }
+
+    // RMS cell-to-cell relief inside the region
+    let mut sum_sq = 0.0f32;
+    let mut count = 0usize;
+    for y in y0..y0 + h {
+        for x in x0..x0 + w - 1 {
+            sum_sq += (height_field.get(x + 1, y) - height_field.get(x, y)).powi(2);
+            count += 1;
+        }
+    }
+    let roughness = (sum_sq / count.max(1) as f32).sqrt();
+
+    let step_x = (w - 1) as f32 / (target - 1) as f32;
+    let step_y = (h - 1) as f32 / (target - 1) as f32;
+    // Finest frequency (cycles per map) the output grid can represent
+    let target_nyquist = n as f32 / (2.0 * step_x.min(step_y));
+    let seed_f = seed as f32;
+
+    for j in 0..target {
+        for i in 0..target {
+            let sx = x0 as f32 + i as f32 * step_x;
+            let sy = y0 as f32 + j as f32 * step_y;
+            let (u, v) = (sx / n as f32, sy / n as f32);
+
+            let mut value = sample_bicubic(height_field, sx, sy);
+            let mut freq = n as f32 * 0.5;
+            let mut amp = roughness * DETAIL_START;
+            let mut octave = 0.0;
+            while freq <= target_nyquist {
+                let noise = value_noise_2d(u * freq + seed_f * 1.7 + octave * 7.31, v * freq - seed_f * 2.1 - octave * 3.17);
+                value += (noise * 2.0 - 1.0) * amp;
+                freq *= 2.0;
+                amp *= DETAIL_GAIN;
+                octave += 1.0;
+            }
+            out.set(i, j, value);
+        }
+    }
+    out
+}
@@ -0,0 +1,210 @@
+use crate::height_field::HeightField;
+use wasm_bindgen::prelude::*;
+
+// Radius of the regional water table smoothing, as a fraction of the map size
+const WATER_TABLE_RADIUS: f32 = 1.0 / 16.0;
+// Weight of upstream catchment (recharge) against depth to the water table
+const RECHARGE_WEIGHT: f32 = 0.5;
+// Fraction of the oasis radius taken up by open water
+const POOL_FRACTION: f32 = 0.35;
+// Dune softening reaches this many oasis radii out from the pool
+const SOFTEN_REACH: f32 = 2.0;
+
+#[wasm_bindgen]
+#[derive(Clone, Copy)]
+pub struct OasisParams {
+    pub count: u32,
+    pub radius: f32,         // cells, extent of the vegetated ring around the pool
+    pub pool_depth: f32,     // how far the pool floor sits below the site (height units)
+    pub dune_softening: f32, // 0-1 blend of nearby dunes toward their smoothed shape
+}
+
+#[wasm_bindgen]
+impl OasisParams {
+    #[wasm_bindgen(constructor)]
+    pub fn new(count: u32, radius: f32, pool_depth: f32, dune_softening: f32) -> Self {
+        Self {
+            count,
+            radius,
+            pool_depth,
+            dune_softening,
+        }
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Clone)]
+pub struct OasisField {
+    water_mask: Vec<f32>,
+    vegetation_mask: Vec<f32>,
+    sites: Vec<u32>,
+    size: usize,
+}
+
+#[wasm_bindgen]
+impl OasisField {
+    #[wasm_bindgen(getter)]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    // 1.0 over oasis pools
+    #[wasm_bindgen]
+    pub fn get_water_mask(&self) -> js_sys::Float32Array {
+        let array = js_sys::Float32Array::new_with_length(self.water_mask.len() as u32);
+        array.copy_from(&self.water_mask);
+        array
+    }
+
+    // 1.0 at the pool edge, fading to 0 at the oasis radius
+    #[wasm_bindgen]
+    pub fn get_vegetation_mask(&self) -> js_sys::Float32Array {
+        let array = js_sys::Float32Array::new_with_length(self.vegetation_mask.len() as u32);
+        array.copy_from(&self.vegetation_mask);
+        array
+    }
+
+    // Oasis centers as flat x, y pairs
+    #[wasm_bindgen]
+    pub fn get_sites(&self) -> js_sys::Uint32Array {
+        let array = js_sys::Uint32Array::new_with_length(self.sites.len() as u32);
+        array.copy_from(&self.sites);
+        array
+    }
+
+    // Internal methods for Rust use
+    pub(crate) fn water_mask(&self) -> &[f32] {
+        &self.water_mask
+    }
+}
+
+// Separable box blur with clamped edges
+fn box_blur(data: &[f32], n: usize, radius: usize) -> Vec<f32> {
+    let mut tmp = vec![0.0f32; n * n];
+    let mut out = vec![0.0f32; n * n];
+    for y in 0..n {
+        for x in 0..n {
+            let x0 = x.saturating_sub(radius);
+            let x1 = (x + radius).min(n - 1);
+            let sum: f32 = data[y * n + x0..=y * n + x1].iter().sum();
+            tmp[y * n + x] = sum / (x1 - x0 + 1) as f32;
+        }
+    }
+    for y in 0..n {
+        let y0 = y.saturating_sub(radius);
+        let y1 = (y + radius).min(n - 1);
+        for x in 0..n {
+            let sum: f32 = (y0..=y1).map(|j| tmp[j * n + x]).sum();
+            out[y * n + x] = sum / (y1 - y0 + 1) as f32;
+        }
+    }
+    out
+}
+
+// Groundwater score per land cell: high where the ground dips below the smoothed regional
+// water table and where a large catchment drains through (recharge)
+fn groundwater_potential(height_field: &HeightField, sea_level: f32) -> Vec<f32> {
+    let n = height_field.size();
+    let data = height_field.data();
+    let table = box_blur(data, n, ((n as f32 * WATER_TABLE_RADIUS) as usize).max(2));
+    let flow = crate::water_system::calculate_flow_accumulation(height_field);
+
+    let min = data.iter().fold(f32::INFINITY, |m, &h| m.min(h));
+    let max = data.iter().fold(f32::NEG_INFINITY, |m, &h| m.max(h));
+    let relief = ((max - min) * 0.05).max(f32::EPSILON);
+    let max_recharge = crate::det_math::ln((n * n) as f32).max(1.0);
+
+    (0..n * n)
+        .map(|idx| {
+            if data[idx] <= sea_level {
+                return f32::NEG_INFINITY;
+            }
+            (table[idx] - data[idx]) / relief + RECHARGE_WEIGHT * crate::det_math::ln(flow[idx]) / max_recharge
+        })
+        .collect()
+}
+
+// Desert oases: pick the land cells with the shallowest groundwater, sink a small pool there,
+// grow a vegetation ring around it and soften the dunes nearby so the pool sits in a hollow.
+pub fn place_oases(height_field: &mut HeightField, sea_level: f32, params: &OasisParams) -> OasisField {
+    let n = height_field.size();
+    let mut water_mask = vec![0.0f32; n * n];
+    let mut vegetation_mask = vec![0.0f32; n * n];
+    if n < 3 || params.count == 0 {
+        return OasisField { water_mask, vegetation_mask, sites: Vec::new(), size: n };
+    }
+
+    let potential = groundwater_potential(height_field, sea_level);
+    let mut order: Vec<usize> = (0..n * n).filter(|&idx| potential[idx].is_finite()).collect();
+    order.sort_by(|&a, &b| potential[b].partial_cmp(&potential[a]).unwrap_or(std::cmp::Ordering::Equal));
+
+    let radius = params.radius.max(1.0);
+    let spacing = (n as f32 / 6.0).max(radius * 2.0 * SOFTEN_REACH);
+    // x, y pairs of at most one oasis per cell
+    let wanted = (params.count as usize).min(order.len()) * 2;
+    let mut sites: Vec<u32> = Vec::with_capacity(wanted);
+    for idx in order {
+        if sites.len() >= wanted {
+            break;
+        }
+        let x = (idx % n) as f32;
+        let y = (idx / n) as f32;
+        let too_close = sites.chunks(2).any(|s| {
+            let dx = s[0] as f32 - x;
+            let dy = s[1] as f32 - y;
+            (dx * dx + dy * dy).sqrt() < spacing
+        });
+        if !too_close {
+            sites.push(x as u32);
+            sites.push(y as u32);
+        }
+    }
+
+    let smoothed = box_blur(height_field.data(), n, (radius * 0.5).ceil() as usize);
+    let pool_radius = radius * POOL_FRACTION;
+    let reach = radius * SOFTEN_REACH;
+    for site in sites.chunks(2) {
+        let (sx, sy) = (site[0] as f32, site[1] as f32);
+        let pool_level = height_field.get(site[0] as usize, site[1] as usize) - params.pool_depth;
+        let x0 = (sx - reach).floor().max(0.0) as usize;
+        let y0 = (sy - reach).floor().max(0.0) as usize;
+        let x1 = ((sx + reach).ceil() as usize).min(n - 1);
+        let y1 = ((sy + reach).ceil() as usize).min(n - 1);
+        for y in y0..=y1 {
+            for x in x0..=x1 {
+                let d = ((x as f32 - sx).powi(2) + (y as f32 - sy).powi(2)).sqrt();
+                if d > reach {
+                    continue;
+                }
+                let idx = y * n + x;
+                let h = height_field.get(x, y);
+                // Dunes relax toward their smoothed shape, most strongly next to the pool
+                let soften = params.dune_softening.clamp(0.0, 1.0) * (1.0 - d / reach);
+                let mut value = h + (smoothed[idx] - h) * soften;
+                if d <= pool_radius {
+                    value = value.min(pool_level);
+                    water_mask[idx] = 1.0;
+                } else if d <= radius {
+                    // Banks ease up from the waterline so the pool sits in a shallow bowl
+                    let t = (d - pool_radius) / (radius - pool_radius).max(f32::EPSILON);
+                    value = value.min(pool_level + params.pool_depth * (1.0 + t) * t);
+                    vegetation_mask[idx] = vegetation_mask[idx].max(1.0 - t * t);
+                }
+                height_field.set(x, y, value);
+            }
+        }
+    }
+
+    OasisField {
+        water_mask,
+        vegetation_mask,
+        sites,
+        size: n,
+    }
+}
+
+// sea_level is in heightfield units
+#[wasm_bindgen]
+pub fn apply_oases(height_field: &mut HeightField, sea_level: f32, params: &OasisParams) -> OasisField {
+    place_oases(height_field, sea_level, params)
+}
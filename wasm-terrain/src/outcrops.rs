@@ -0,0 +1,163 @@
+use crate::filters::{gradient_at, slope_at};
+use crate::height_field::HeightField;
+use crate::noise::{hash_cell, hash_to_unit};
+use wasm_bindgen::prelude::*;
+
+// Relief (height units per map width) over which regolith thins to about a third
+const REGOLITH_RELIEF_SCALE: f32 = 4.0;
+// How strongly hollows (concave cells) collect extra regolith
+const HOLLOW_ACCUMULATION: f32 = 400.0;
+// Outcrop score above which a cell is mapped as bare rock
+const OUTCROP_THRESHOLD: f32 = 0.35;
+// Farthest a boulder rolls downhill from its source outcrop (cells)
+const TALUS_REACH: usize = 12;
+// Boulder radius (cells) at the foot of the outcrop and growth per cell of travel:
+// the biggest blocks roll farthest
+const BOULDER_MIN_RADIUS: f32 = 0.15;
+const BOULDER_RADIUS_GROWTH: f32 = 0.04;
+
+#[wasm_bindgen]
+#[derive(Clone)]
+pub struct RockOutcrops {
+    outcrop_mask: Vec<f32>,
+    regolith: Vec<f32>,
+    boulders: Vec<f32>,
+    size: usize,
+}
+
+#[wasm_bindgen]
+impl RockOutcrops {
+    #[wasm_bindgen(getter)]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    // 0-1, how exposed the bedrock is
+    #[wasm_bindgen]
+    pub fn get_outcrop_mask(&self) -> js_sys::Float32Array {
+        let array = js_sys::Float32Array::new_with_length(self.outcrop_mask.len() as u32);
+        array.copy_from(&self.outcrop_mask);
+        array
+    }
+
+    // Relative regolith (soil and weathered rock) thickness, 0 = bare rock, 1 = deep soil
+    #[wasm_bindgen]
+    pub fn get_regolith_thickness(&self) -> js_sys::Float32Array {
+        let array = js_sys::Float32Array::new_with_length(self.regolith.len() as u32);
+        array.copy_from(&self.regolith);
+        array
+    }
+
+    // Talus boulders as flat x, y, radius triples in cell units
+    #[wasm_bindgen]
+    pub fn get_boulders(&self) -> js_sys::Float32Array {
+        let array = js_sys::Float32Array::new_with_length(self.boulders.len() as u32);
+        array.copy_from(&self.boulders);
+        array
+    }
+}
+
+// Relative regolith thickness: soil production keeps pace with transport on gentle ground and
+// collects in hollows, while steep ground and convex noses are stripped. The generator has no
+// stratigraphy layer, so this stands in for one.
+pub(crate) fn regolith_thickness(height_field: &HeightField) -> Vec<f32> {
+    let n = height_field.size();
+    (0..n * n)
+        .map(|idx| {
+            let (x, y) = (idx % n, idx / n);
+            let relief = slope_at(height_field, x, y) * n as f32;
+            let curvature = laplacian(height_field, x, y);
+            let base = crate::det_math::exp(-relief / REGOLITH_RELIEF_SCALE);
+            (base * (1.0 + curvature * HOLLOW_ACCUMULATION)).clamp(0.0, 1.0)
+        })
+        .collect()
+}
+
+// Positive in hollows, negative on convex noses and ridges
+fn laplacian(height_field: &HeightField, x: usize, y: usize) -> f32 {
+    let (x, y) = (x as i32, y as i32);
+    height_field.get_clamped(x - 1, y)
+        + height_field.get_clamped(x + 1, y)
+        + height_field.get_clamped(x, y - 1)
+        + height_field.get_clamped(x, y + 1)
+        - 4.0 * height_field.get_clamped(x, y)
+}
+
+// Bare rock where slope is above `slope_threshold` (height units per cell), the surface is
+// convex and regolith is thin. Boulders are scattered along the steepest-descent path below
+// each outcrop cell with a probability of `boulder_density` per talus cell, thinning out and
+// coarsening with distance.
+pub fn rock_outcrops(height_field: &HeightField, slope_threshold: f32, boulder_density: f32, seed: u32) -> RockOutcrops {
+    let n = height_field.size();
+    let regolith = regolith_thickness(height_field);
+    let threshold = slope_threshold.max(f32::EPSILON);
+
+    let outcrop_mask: Vec<f32> = (0..n * n)
+        .map(|idx| {
+            let (x, y) = (idx % n, idx / n);
+            let steep = (slope_at(height_field, x, y) / threshold).min(2.0) * 0.5;
+            let convex = if laplacian(height_field, x, y) < 0.0 { 1.0 } else { 0.5 };
+            let score = steep * convex * (1.0 - regolith[idx]);
+            ((score - OUTCROP_THRESHOLD) / (1.0 - OUTCROP_THRESHOLD)).clamp(0.0, 1.0)
+        })
+        .collect();
+
+    // Roll debris downhill: each talus cell remembers the shortest travel distance reaching it
+    let mut travel = vec![usize::MAX; n * n];
+    for (idx, &rock) in outcrop_mask.iter().enumerate() {
+        if rock <= 0.0 {
+            continue;
+        }
+        let (mut x, mut y) = (idx % n, idx / n);
+        for step in 1..=TALUS_REACH {
+            let (gx, gy) = gradient_at(height_field, x, y);
+            let slope = (gx * gx + gy * gy).sqrt();
+            if slope <= f32::EPSILON {
+                break;
+            }
+            let nx = (x as f32 - gx / slope).round();
+            let ny = (y as f32 - gy / slope).round();
+            if nx < 0.0 || ny < 0.0 || nx >= n as f32 || ny >= n as f32 {
+                break;
+            }
+            let (nx, ny) = (nx as usize, ny as usize);
+            if height_field.get(nx, ny) >= height_field.get(x, y) {
+                break;
+            }
+            (x, y) = (nx, ny);
+            let cell = y * n + x;
+            if outcrop_mask[cell] <= 0.0 {
+                travel[cell] = travel[cell].min(step);
+            }
+        }
+    }
+
+    let mut boulders = Vec::new();
+    for (idx, &distance) in travel.iter().enumerate() {
+        if distance == usize::MAX {
+            continue;
+        }
+        let (x, y) = ((idx % n) as i32, (idx / n) as i32);
+        let h = hash_cell(x, y, seed);
+        let chance = boulder_density * (1.0 - distance as f32 / (TALUS_REACH + 1) as f32);
+        if hash_to_unit(h) >= chance {
+            continue;
+        }
+        let jitter = h.rotate_left(12);
+        boulders.push(x as f32 + hash_to_unit(jitter));
+        boulders.push(y as f32 + hash_to_unit(jitter.rotate_left(10)));
+        boulders.push(BOULDER_MIN_RADIUS + BOULDER_RADIUS_GROWTH * distance as f32);
+    }
+
+    RockOutcrops {
+        outcrop_mask,
+        regolith,
+        boulders,
+        size: n,
+    }
+}
+
+#[wasm_bindgen]
+pub fn find_rock_outcrops(height_field: &HeightField, slope_threshold: f32, boulder_density: f32, seed: u32) -> RockOutcrops {
+    rock_outcrops(height_field, slope_threshold, boulder_density, seed)
+}
@@ -0,0 +1,220 @@
+use crate::biomes::{BiomeParams, BiomeType};
+use crate::filters::gradient_at;
+use crate::height_field::HeightField;
+use crate::noise::{hash_cell, hash_to_unit, value_noise_2d};
+use crate::seasons::{season_temperature, Season};
+use crate::supersample::SUBSAMPLE_OFFSETS;
+use wasm_bindgen::prelude::*;
+
+// Relief (height units per map width) above which ground is too steep for sorted polygons
+const POLYGON_MAX_RELIEF: f32 = 3.0;
+// Half width (cells) of the ice-wedge trough along polygon edges
+const CRACK_HALF_WIDTH: f32 = 1.5;
+// Rim height next to the trough, relative to the trough depth (low-centered polygons)
+const RIM_RATIO: f32 = 0.35;
+// Relief at which solifluction lobes are most pronounced; they fade on flats and cliffs
+const LOBE_PEAK_RELIEF: f32 = 5.0;
+// Downslope spacing of lobe fronts and cross-slope width of individual tongues (cells)
+const LOBE_SPACING: f32 = 10.0;
+const LOBE_WIDTH: f32 = 6.0;
+// Active-layer thickness (m) per sqrt(°C) of summer warmth, a Stefan-style thaw depth
+const THAW_COEFFICIENT: f32 = 0.35;
+
+#[wasm_bindgen]
+#[derive(Clone, Copy)]
+pub struct PatternedGroundParams {
+    pub polygon_size: f32, // average ice-wedge polygon diameter in cells
+    pub crack_depth: f32,  // trough depth along polygon edges (height units)
+    pub pingo_count: u32,
+    pub pingo_radius: f32, // cells
+    pub pingo_height: f32,
+    pub lobe_amplitude: f32, // solifluction lobe relief on slopes
+    pub supersample: bool, // average 4 samples per cell so trough edges, lobe fronts and craters don't stair-step
+}
+
+#[wasm_bindgen]
+impl PatternedGroundParams {
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        polygon_size: f32,
+        crack_depth: f32,
+        pingo_count: u32,
+        pingo_radius: f32,
+        pingo_height: f32,
+        lobe_amplitude: f32,
+    ) -> Self {
+        Self {
+            polygon_size,
+            crack_depth,
+            pingo_count,
+            pingo_radius,
+            pingo_height,
+            lobe_amplitude,
+            supersample: false,
+        }
+    }
+}
+
+// Distance (cells) from a point to the nearest polygon edge: half the gap between the
+// nearest and second-nearest jittered cell centers
+fn polygon_edge_distance(x: f32, y: f32, size: f32, seed: u32) -> f32 {
+    let px = x / size;
+    let py = y / size;
+    let cx = px.floor() as i32;
+    let cy = py.floor() as i32;
+    let mut f1 = f32::INFINITY;
+    let mut f2 = f32::INFINITY;
+    for j in cy - 1..=cy + 1 {
+        for i in cx - 1..=cx + 1 {
+            let h = hash_cell(i, j, seed);
+            let fx = i as f32 + hash_to_unit(h);
+            let fy = j as f32 + hash_to_unit(h.rotate_left(16));
+            let d = ((fx - px) * (fx - px) + (fy - py) * (fy - py)).sqrt();
+            if d < f1 {
+                f2 = f1;
+                f1 = d;
+            } else if d < f2 {
+                f2 = d;
+            }
+        }
+    }
+    (f2 - f1) * 0.5 * size
+}
+
+// Pingo sites: the flattest low land cells, kept apart so mounds don't merge
+fn pick_pingo_sites(height_field: &HeightField, sea_level: f32, count: usize, spacing: f32) -> Vec<(f32, f32)> {
+    let n = height_field.size();
+    let data = height_field.data();
+    let mut candidates: Vec<(usize, f32)> = (0..n * n)
+        .filter(|&idx| data[idx] > sea_level)
+        .map(|idx| {
+            let (gx, gy) = gradient_at(height_field, idx % n, idx / n);
+            let relief = (gx * gx + gy * gy).sqrt() * n as f32;
+            (idx, relief + (data[idx] - sea_level) * 4.0)
+        })
+        .collect();
+    candidates.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+
+    let mut sites: Vec<(f32, f32)> = Vec::with_capacity(count.min(candidates.len()));
+    for (idx, _) in candidates {
+        if sites.len() >= count {
+            break;
+        }
+        let x = (idx % n) as f32;
+        let y = (idx / n) as f32;
+        if sites.iter().all(|&(sx, sy)| ((sx - x).powi(2) + (sy - y).powi(2)).sqrt() >= spacing) {
+            sites.push((x, y));
+        }
+    }
+    sites
+}
+
+// Periglacial microrelief: ice-wedge polygon troughs with raised rims on flat ground,
+// solifluction lobes with steep fronts on moderate slopes, and pingo mounds (with a
+// collapsed summit crater) on the flattest lowlands. Cells at or below sea level are untouched.
+#[wasm_bindgen]
+pub fn apply_patterned_ground(height_field: &mut HeightField, params: &PatternedGroundParams, sea_level: f32, seed: u32) {
+    let n = height_field.size();
+    if n < 3 {
+        return;
+    }
+    let polygon_size = params.polygon_size.max(2.0);
+    let sites = pick_pingo_sites(height_field, sea_level, params.pingo_count as usize, n as f32 / 8.0);
+    let source = height_field.clone();
+    let seed_f = seed as f32;
+
+    for y in 0..n {
+        for x in 0..n {
+            let h = source.get(x, y);
+            if h <= sea_level {
+                continue;
+            }
+            let (gx, gy) = gradient_at(&source, x, y);
+            let slope = (gx * gx + gy * gy).sqrt();
+            let relief = slope * n as f32;
+            // Microrelief at (fx, fy) given this cell's slope
+            let offset_at = |fx: f32, fy: f32| {
+                let mut offset = 0.0;
+
+                // Polygon troughs only form on near-flat ground
+                let flatness = (1.0 - relief / POLYGON_MAX_RELIEF).max(0.0);
+                if flatness > 0.0 {
+                    let d = polygon_edge_distance(fx, fy, polygon_size, seed);
+                    let trough = (1.0 - d / CRACK_HALF_WIDTH).max(0.0);
+                    let rim = (1.0 - (d - 2.0 * CRACK_HALF_WIDTH).abs() / CRACK_HALF_WIDTH).max(0.0);
+                    offset += params.crack_depth * (rim * RIM_RATIO - trough) * flatness;
+                }
+
+                // Lobes: a sawtooth along the downslope axis (gentle tread, steep front) whose
+                // phase wanders across the slope so fronts break into separate tongues
+                if slope > f32::EPSILON {
+                    let r = relief / LOBE_PEAK_RELIEF;
+                    let weight = r * crate::det_math::exp(1.0 - r);
+                    let (dx, dy) = (-gx / slope, -gy / slope);
+                    let along = fx * dx + fy * dy;
+                    let across = fy * dx - fx * dy;
+                    let wander = value_noise_2d(across / LOBE_WIDTH + seed_f * 0.7, seed_f * 1.9) * 2.0;
+                    let phase = along / LOBE_SPACING + wander;
+                    let tread = phase - phase.floor();
+                    offset += params.lobe_amplitude * weight * (tread - 0.5);
+                }
+
+                for &(sx, sy) in &sites {
+                    let d = ((fx - sx).powi(2) + (fy - sy).powi(2)).sqrt() / params.pingo_radius.max(1.0);
+                    if d < 1.0 {
+                        let mound = crate::det_math::powf(1.0 - d * d, 1.5);
+                        let crater = (1.0 - d / 0.25).max(0.0) * 0.3;
+                        offset += params.pingo_height * (mound - crater);
+                    }
+                }
+                offset
+            };
+
+            let (fx, fy) = (x as f32, y as f32);
+            let offset = if params.supersample {
+                SUBSAMPLE_OFFSETS.iter().map(|&(ox, oy)| offset_at(fx + ox, fy + oy)).sum::<f32>() / SUBSAMPLE_OFFSETS.len() as f32
+            } else {
+                offset_at(fx, fy)
+            };
+
+            height_field.set(x, y, h + offset);
+        }
+    }
+}
+
+// Depth (m) of the permafrost table, i.e. the summer thaw (active layer) thickness, from
+// summer temperatures with elevation lapse. 0 where there is no permafrost: open water
+// (taliks) and cells whose mean annual temperature is above freezing.
+pub(crate) fn permafrost_depth(height_field: &HeightField, sea_level: f32, height_scale: f32, biome: &BiomeParams) -> Vec<f32> {
+    let data = height_field.data();
+    let mean = season_temperature(data, sea_level, height_scale, biome, Season::Spring);
+    let summer = season_temperature(data, sea_level, height_scale, biome, Season::Summer);
+    data.iter()
+        .zip(mean.iter().zip(&summer))
+        .map(|(&h, (&t_mean, &t_summer))| {
+            if h <= sea_level || t_mean >= 0.0 {
+                0.0
+            } else {
+                // Thaw depth grows with the square root of summer warmth
+                (THAW_COEFFICIENT * t_summer.max(0.0).sqrt()).max(0.05)
+            }
+        })
+        .collect()
+}
+
+// sea_level is in heightfield units. For a built-in biome; registered biomes and generated
+// terrain go through compute_permafrost_depth_for_params with their own parameters.
+#[wasm_bindgen]
+pub fn compute_permafrost_depth(height_field: &HeightField, sea_level: f32, biome_type: BiomeType) -> js_sys::Float32Array {
+    compute_permafrost_depth_for_params(height_field, sea_level, &BiomeParams::new(biome_type))
+}
+
+// compute_permafrost_depth with `biome`'s parameters, e.g. BiomeParams.for_id or a result's
+// biome_params
+#[wasm_bindgen]
+pub fn compute_permafrost_depth_for_params(height_field: &HeightField, sea_level: f32, biome: &BiomeParams) -> js_sys::Float32Array {
+    let depth = permafrost_depth(height_field, sea_level, biome.height_scale(), biome);
+    let array = js_sys::Float32Array::new_with_length(depth.len() as u32);
+    array.copy_from(&depth);
+    array
+}
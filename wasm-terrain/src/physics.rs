@@ -0,0 +1,136 @@
+use crate::height_field::HeightField;
+use wasm_bindgen::prelude::*;
+
+// Largest i16 magnitude used, leaving headroom so rounding never overflows
+const I16_RANGE: f32 = i16::MAX as f32 - 1.0;
+
+// Heights in world units plus everything the common physics engines need to build the same
+// surface the renderer draws. The field spans (size - 1) · cell_spacing on X and Z with
+// cell (x, y) at world (x · cell_spacing, height, y · cell_spacing), Y up.
+#[wasm_bindgen]
+#[derive(Clone)]
+pub struct PhysicsHeightfield {
+    size: usize,
+    cell_spacing: f32,
+    heights: Vec<f32>, // world units, row-major (x fastest)
+    min_height: f32,
+    max_height: f32,
+}
+
+#[wasm_bindgen]
+impl PhysicsHeightfield {
+    #[wasm_bindgen(getter)]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn cell_spacing(&self) -> f32 {
+        self.cell_spacing
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn min_height(&self) -> f32 {
+        self.min_height
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn max_height(&self) -> f32 {
+        self.max_height
+    }
+
+    // Extent of the field on X and Z
+    #[wasm_bindgen(getter)]
+    pub fn extent(&self) -> f32 {
+        self.size.saturating_sub(1) as f32 * self.cell_spacing
+    }
+
+    // World-unit heights, row-major with x fastest. Bullet: btHeightfieldTerrainShape with
+    // PHY_FLOAT, width = length = size, heightScale 1, min/max_height, up axis 1.
+    #[wasm_bindgen]
+    pub fn get_f32_row_major(&self) -> js_sys::Float32Array {
+        let array = js_sys::Float32Array::new_with_length(self.heights.len() as u32);
+        array.copy_from(&self.heights);
+        array
+    }
+
+    // World height of one i16 step: height = sample · i16_height_scale
+    #[wasm_bindgen(getter)]
+    pub fn i16_height_scale(&self) -> f32 {
+        let magnitude = self.min_height.abs().max(self.max_height.abs());
+        if magnitude > 0.0 { magnitude / I16_RANGE } else { 1.0 }
+    }
+
+    // Quantized heights, row-major with x fastest. Bullet: PHY_SHORT with
+    // heightScale = i16_height_scale.
+    #[wasm_bindgen]
+    pub fn get_i16_row_major(&self) -> js_sys::Int16Array {
+        let samples = self.quantized(|x, y| y * self.size + x);
+        let array = js_sys::Int16Array::new_with_length(samples.len() as u32);
+        array.copy_from(&samples);
+        array
+    }
+
+    // PhysX PxHeightFieldSample heights: rows run along X, so sample (x, y) sits at x · size + y.
+    // Use heightScale = i16_height_scale and rowScale = columnScale = cell_spacing.
+    #[wasm_bindgen]
+    pub fn get_physx_samples(&self) -> js_sys::Int16Array {
+        let samples = self.quantized(|x, y| x * self.size + y);
+        let array = js_sys::Int16Array::new_with_length(samples.len() as u32);
+        array.copy_from(&samples);
+        array
+    }
+
+    // Rapier HeightField matrix data: nrows = ncols = size, rows along Z, stored column-major,
+    // with scale [extent, 1, extent]. Rapier centers the field on the origin, so translate
+    // the collider by [extent / 2, 0, extent / 2] to line it up with the other layouts.
+    #[wasm_bindgen]
+    pub fn get_rapier_heights(&self) -> js_sys::Float32Array {
+        let n = self.size;
+        let mut column_major = vec![0.0f32; n * n];
+        for y in 0..n {
+            for x in 0..n {
+                column_major[x * n + y] = self.heights[y * n + x];
+            }
+        }
+        let array = js_sys::Float32Array::new_with_length(column_major.len() as u32);
+        array.copy_from(&column_major);
+        array
+    }
+
+    // Bullet places its heightfield with the vertical midpoint at the origin: translate the
+    // rigid body up by this to match world heights
+    #[wasm_bindgen(getter)]
+    pub fn bullet_center_offset(&self) -> f32 {
+        (self.min_height + self.max_height) * 0.5
+    }
+}
+
+impl PhysicsHeightfield {
+    pub(crate) fn from_height_field(height_field: &HeightField, height_scale: f32, cell_spacing: f32) -> Self {
+        let heights: Vec<f32> = height_field.data().iter().map(|&h| h * height_scale).collect();
+        let min_height = heights.iter().fold(f32::INFINITY, |m, &h| m.min(h));
+        let max_height = heights.iter().fold(f32::NEG_INFINITY, |m, &h| m.max(h));
+        let (min_height, max_height) = if heights.is_empty() { (0.0, 0.0) } else { (min_height, max_height) };
+        Self {
+            size: height_field.size(),
+            cell_spacing,
+            heights,
+            min_height,
+            max_height,
+        }
+    }
+
+    // Heights as i16 samples, written at index(x, y)
+    fn quantized(&self, index: impl Fn(usize, usize) -> usize) -> Vec<i16> {
+        let n = self.size;
+        let step = self.i16_height_scale();
+        let mut samples = vec![0i16; n * n];
+        for y in 0..n {
+            for x in 0..n {
+                samples[index(x, y)] = (self.heights[y * n + x] / step).round() as i16;
+            }
+        }
+        samples
+    }
+}
@@ -0,0 +1,323 @@
+use crate::biomes::BiomeType;
+use crate::filters::slope_at;
+use crate::height_field::HeightField;
+use crate::noise::{hash_cell, hash_to_unit, value_noise_2d};
+use wasm_bindgen::prelude::*;
+
+// Values per instance in Placements::get_instances
+const INSTANCE_STRIDE: usize = 6;
+// Instance scale varies by up to this fraction either way
+const SCALE_JITTER: f32 = 0.2;
+
+// One kind of object to scatter. Candidates are drawn at `density` per cell, kept with a
+// probability of 1 where every range matches (0 elsewhere) times the cluster noise, then
+// thinned so no two instances of the rule are closer than `min_spacing` cells.
+#[wasm_bindgen]
+#[derive(Clone, Copy)]
+pub struct PlacementRule {
+    pub density: f32,     // candidates per cell
+    pub min_spacing: f32, // cells between instances of this rule
+    pub min_slope: f32,   // height units per cell
+    pub max_slope: f32,
+    pub min_altitude: f32,
+    pub max_altitude: f32,
+    pub min_moisture: f32, // only checked when a moisture raster is supplied
+    pub max_moisture: f32,
+    pub cluster_scale: f32,    // clump size in cells
+    pub cluster_strength: f32, // 0 = even spread, 1 = only inside clumps
+    pub biomes: u32,           // bit (1 << BiomeType) per allowed biome, 0 = any
+}
+
+#[wasm_bindgen]
+impl PlacementRule {
+    #[wasm_bindgen(constructor)]
+    pub fn new(density: f32, min_spacing: f32) -> Self {
+        Self {
+            density,
+            min_spacing,
+            min_slope: 0.0,
+            max_slope: f32::MAX,
+            min_altitude: f32::MIN,
+            max_altitude: f32::MAX,
+            min_moisture: f32::MIN,
+            max_moisture: f32::MAX,
+            cluster_scale: 0.0,
+            cluster_strength: 0.0,
+            biomes: 0,
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn allow_biome(&mut self, biome_type: BiomeType) {
+        self.biomes |= 1 << biome_type as u32;
+    }
+}
+
+impl PlacementRule {
+    // 0-1 chance that a candidate at this cell survives, before spacing
+    fn suitability(&self, slope: f32, altitude: f32, moisture: Option<f32>, cluster: f32) -> f32 {
+        let in_range = (self.min_slope..=self.max_slope).contains(&slope)
+            && (self.min_altitude..=self.max_altitude).contains(&altitude)
+            && moisture.is_none_or(|m| (self.min_moisture..=self.max_moisture).contains(&m));
+        if !in_range {
+            return 0.0;
+        }
+        1.0 - self.cluster_strength.clamp(0.0, 1.0) * (1.0 - cluster)
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Default)]
+pub struct PlacementRules {
+    rules: Vec<PlacementRule>,
+}
+
+#[wasm_bindgen]
+impl PlacementRules {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Returns the rule's index, reported with each of its instances
+    #[wasm_bindgen]
+    pub fn add_rule(&mut self, rule: PlacementRule) -> usize {
+        self.rules.push(rule);
+        self.rules.len() - 1
+    }
+
+    #[wasm_bindgen]
+    pub fn clear(&mut self) {
+        self.rules.clear();
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn rule_count(&self) -> usize {
+        self.rules.len()
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Clone)]
+pub struct Placements {
+    instances: Vec<f32>,
+}
+
+#[wasm_bindgen]
+impl Placements {
+    #[wasm_bindgen(getter)]
+    pub fn instance_count(&self) -> usize {
+        self.instances.len() / INSTANCE_STRIDE
+    }
+
+    // Flat x, y, height, rule index, rotation (radians), scale per instance; x and y are
+    // fractional cells with cell centers on integers
+    #[wasm_bindgen]
+    pub fn get_instances(&self) -> js_sys::Float32Array {
+        let array = js_sys::Float32Array::new_with_length(self.instances.len() as u32);
+        array.copy_from(&self.instances);
+        array
+    }
+}
+
+struct Candidate {
+    x: f32, // world cells
+    y: f32,
+    height: f32,
+    priority: u32,
+    hash: u32,
+}
+
+// Candidates of one rule for the local cells [x0, x1) × [y0, y1) of a field whose cell (0, 0)
+// is world cell `origin`. Everything derives from hashes of world cell coordinates, so
+// overlapping regions, in this field or a neighboring tile, produce identical candidates.
+fn candidates(
+    height_field: &HeightField,
+    rule: &PlacementRule,
+    salt: u32,
+    moisture: &[f32],
+    exclusion: &[f32],
+    origin: [i32; 2],
+    [x0, y0, x1, y1]: [usize; 4],
+) -> Vec<Candidate> {
+    let n = height_field.size();
+    let cluster_offset = hash_to_unit(hash_cell(0, 0, salt)) * 1000.0;
+    let whole = rule.density.max(0.0).floor() as u32;
+    let fraction = rule.density.max(0.0).fract();
+    let mut result = Vec::new();
+    for y in y0..y1 {
+        for x in x0..x1 {
+            let idx = y * n + x;
+            if exclusion.get(idx).is_some_and(|&e| e > 0.5) {
+                continue;
+            }
+            let (cx, cy) = (x as i32 + origin[0], y as i32 + origin[1]);
+            let count = whole + u32::from(hash_to_unit(hash_cell(cx, cy, salt)) < fraction);
+            if count == 0 {
+                continue;
+            }
+            let cluster = if rule.cluster_scale > 0.0 {
+                value_noise_2d(cx as f32 / rule.cluster_scale + cluster_offset, cy as f32 / rule.cluster_scale)
+            } else {
+                1.0
+            };
+            let chance = rule.suitability(slope_at(height_field, x, y), height_field.get(x, y), moisture.get(idx).copied(), cluster);
+            for k in 0..count {
+                let h = hash_cell(cx, cy, salt.wrapping_add(k.wrapping_mul(0x9e37_79b9)).wrapping_add(1));
+                if hash_to_unit(h) >= chance {
+                    continue;
+                }
+                let (jx, jy) = (hash_to_unit(h.rotate_left(8)) - 0.5, hash_to_unit(h.rotate_left(16)) - 0.5);
+                result.push(Candidate {
+                    x: cx as f32 + jx,
+                    y: cy as f32 + jy,
+                    height: height_at(height_field, x, y, jx, jy),
+                    priority: h.rotate_left(24),
+                    hash: h,
+                });
+            }
+        }
+    }
+    result
+}
+
+// Bilinear height at an offset in [-0.5, 0.5) from a cell center
+fn height_at(height_field: &HeightField, x: usize, y: usize, jx: f32, jy: f32) -> f32 {
+    let (x, y) = (x as i32, y as i32);
+    let (x0, fx) = if jx < 0.0 { (x - 1, 1.0 + jx) } else { (x, jx) };
+    let (y0, fy) = if jy < 0.0 { (y - 1, 1.0 + jy) } else { (y, jy) };
+    crate::query::bilinear_cell(height_field, x0, y0, fx, fy)
+}
+
+// Deterministic instances inside `region` ([x, y, width, height] in cells). Candidates within
+// `min_spacing` of the region are taken into account, so adjacent regions agree along their
+// shared edge: an instance is dropped if any candidate with a higher priority lies within
+// `min_spacing`. `moisture` and `exclusion` (cells > 0.5 are skipped) are full-map rasters or
+// empty.
+pub fn place_objects(
+    height_field: &HeightField,
+    rules: &PlacementRules,
+    biome_type: BiomeType,
+    moisture: &[f32],
+    exclusion: &[f32],
+    region: [usize; 4],
+    seed: u32,
+) -> Placements {
+    let region = region.map(|v| v.min(i32::MAX as usize) as i32);
+    place_objects_in_world(height_field, rules, biome_type, moisture, exclusion, [0, 0], region, seed)
+}
+
+// place_objects for one tile of a larger world: the field's cell (0, 0) is world cell
+// `origin`, and `world_rect` ([x, y, width, height]) and the returned positions are in world
+// cells. Tiles that both cover a point and `min_spacing` + 1 cells around it (e.g. through
+// their overlap) agree on it exactly, whichever of them generates it.
+#[allow(clippy::too_many_arguments)]
+pub fn place_objects_in_world(
+    height_field: &HeightField,
+    rules: &PlacementRules,
+    biome_type: BiomeType,
+    moisture: &[f32],
+    exclusion: &[f32],
+    origin: [i32; 2],
+    world_rect: [i32; 4],
+    seed: u32,
+) -> Placements {
+    let n = height_field.size() as i64;
+    let [wx, wy, ww, wh] = world_rect.map(|v| v as i64);
+    let (ox, oy) = (origin[0] as i64, origin[1] as i64);
+    // The region in world cells, clipped to the field
+    let (rx0, ry0) = (wx.max(ox), wy.max(oy));
+    let (rx1, ry1) = ((wx + ww.max(0)).min(ox + n), (wy + wh.max(0)).min(oy + n));
+    let mut instances = Vec::new();
+    if rx0 >= rx1 || ry0 >= ry1 {
+        return Placements { instances };
+    }
+
+    for (index, rule) in rules.rules.iter().enumerate() {
+        if rule.biomes != 0 && rule.biomes & (1 << biome_type as u32) == 0 {
+            continue;
+        }
+        let salt = seed ^ hash_cell(index as i32, 0, 0x0091_ace5);
+        let spacing = rule.min_spacing.max(0.0);
+        let halo = spacing.ceil() as i64 + 1;
+        let local = |v: i64, o: i64| (v - o).clamp(0, n) as usize;
+        let bounds = [local(rx0 - halo, ox), local(ry0 - halo, oy), local(rx1 + halo, ox), local(ry1 + halo, oy)];
+        let found = candidates(height_field, rule, salt, moisture, exclusion, origin, bounds);
+
+        // Bucket by spacing-sized cells so the neighbor check only looks next door
+        let bucket = spacing.max(1.0);
+        let (bx0, by0) = ((bounds[0] as i64 + ox) as f32, (bounds[1] as i64 + oy) as f32);
+        let columns = ((bounds[2] - bounds[0]) as f32 / bucket).ceil() as usize + 2;
+        let rows = ((bounds[3] - bounds[1]) as f32 / bucket).ceil() as usize + 2;
+        let bucket_of = |c: &Candidate| {
+            let bx = ((c.x - bx0 + 1.0) / bucket).floor().max(0.0) as usize;
+            let by = ((c.y - by0 + 1.0) / bucket).floor().max(0.0) as usize;
+            (bx.min(columns - 1), by.min(rows - 1))
+        };
+        let mut buckets: Vec<Vec<usize>> = vec![Vec::new(); columns * rows];
+        for (i, c) in found.iter().enumerate() {
+            let (bx, by) = bucket_of(c);
+            buckets[by * columns + bx].push(i);
+        }
+
+        for c in &found {
+            if c.x < rx0 as f32 - 0.5 || c.y < ry0 as f32 - 0.5 || c.x >= rx1 as f32 - 0.5 || c.y >= ry1 as f32 - 0.5 {
+                continue;
+            }
+            let (bx, by) = bucket_of(c);
+            let beaten = (by.saturating_sub(1)..(by + 2).min(rows))
+                .flat_map(|y| (bx.saturating_sub(1)..(bx + 2).min(columns)).map(move |x| (x, y)))
+                .flat_map(|(x, y)| buckets[y * columns + x].iter())
+                .map(|&i| &found[i])
+                .any(|o| {
+                    let d2 = (o.x - c.x).powi(2) + (o.y - c.y).powi(2);
+                    d2 < spacing * spacing && (o.priority, o.hash) > (c.priority, c.hash)
+                });
+            if beaten {
+                continue;
+            }
+            let rotation = hash_to_unit(c.hash.rotate_left(4)) * std::f32::consts::TAU;
+            let scale = 1.0 + SCALE_JITTER * (2.0 * hash_to_unit(c.hash.rotate_left(12)) - 1.0);
+            instances.extend_from_slice(&[c.x, c.y, c.height, index as f32, rotation, scale]);
+        }
+    }
+
+    Placements { instances }
+}
+
+// Scatter objects by rule over `region` ([x, y, width, height] in cells, e.g. one tile).
+// Pass an empty array for `moisture` or `exclusion` to skip them.
+#[wasm_bindgen]
+pub fn scatter_objects(
+    height_field: &HeightField,
+    rules: &PlacementRules,
+    biome_type: BiomeType,
+    moisture: &[f32],
+    exclusion: &[f32],
+    region: &[u32],
+    seed: u32,
+) -> Option<Placements> {
+    let region = <[u32; 4]>::try_from(region).ok()?.map(|v| v as usize);
+    Some(place_objects(height_field, rules, biome_type, moisture, exclusion, region, seed))
+}
+
+// Scatter over `world_rect` ([x, y, width, height] in world cells) using a tile whose first
+// cell sits at world cell `tile_origin` ([x, y]). Instances come back in world cells and match
+// those of any other tile covering the same spot, as long as both tiles extend min_spacing + 1
+// cells past it (tile overlap is enough for the usual spacings).
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn scatter_tile(
+    height_field: &HeightField,
+    rules: &PlacementRules,
+    biome_type: BiomeType,
+    moisture: &[f32],
+    exclusion: &[f32],
+    tile_origin: &[i32],
+    world_rect: &[i32],
+    seed: u32,
+) -> Option<Placements> {
+    let origin = <[i32; 2]>::try_from(tile_origin).ok()?;
+    let world_rect = <[i32; 4]>::try_from(world_rect).ok()?;
+    Some(place_objects_in_world(height_field, rules, biome_type, moisture, exclusion, origin, world_rect, seed))
+}
@@ -0,0 +1,202 @@
+use std::collections::HashMap;
+
+// One closed boundary of a cell mask. Vertices are in cell units with cell centers on integer
+// coordinates, so edges run halfway between cells. Outer boundaries have positive signed area,
+// holes negative.
+struct Outline {
+    ring: Vec<(f32, f32)>,
+    cell: usize, // a mask cell just inside this boundary, for matching it to its region
+}
+
+// Every boundary loop of `mask`. Diagonal neighbors are treated as connected, matching
+// label_components, so each loop belongs to exactly one 8-connected region.
+fn trace_outlines(mask: &[bool], size: usize) -> Vec<Outline> {
+    let n = size as i32;
+    let inside = |x: i32, y: i32| x >= 0 && y >= 0 && x < n && y < n && mask[(y * n + x) as usize];
+    let vertex = |x: i32, y: i32| (y * (n + 1) + x) as usize;
+
+    // Directed edges between cell corners with the mask on their left (positive winding)
+    let mut outgoing: HashMap<usize, Vec<(usize, usize)>> = HashMap::new();
+    for y in 0..n {
+        for x in 0..n {
+            if !inside(x, y) {
+                continue;
+            }
+            let cell = (y * n + x) as usize;
+            let mut edge = |from: usize, to: usize| outgoing.entry(from).or_default().push((to, cell));
+            if !inside(x, y - 1) {
+                edge(vertex(x, y), vertex(x + 1, y));
+            }
+            if !inside(x + 1, y) {
+                edge(vertex(x + 1, y), vertex(x + 1, y + 1));
+            }
+            if !inside(x, y + 1) {
+                edge(vertex(x + 1, y + 1), vertex(x, y + 1));
+            }
+            if !inside(x - 1, y) {
+                edge(vertex(x, y + 1), vertex(x, y));
+            }
+        }
+    }
+
+    let corner = |v: usize| ((v % (size + 1)) as i32, (v / (size + 1)) as i32);
+    // Turn from prev -> current onto current -> next: negative is a right turn
+    let turn = |prev: usize, current: usize, next: usize| {
+        let ((px, py), (cx, cy), (nx, ny)) = (corner(prev), corner(current), corner(next));
+        (cx - px) * (ny - cy) - (cy - py) * (nx - cx)
+    };
+    let mut starts: Vec<usize> = outgoing.keys().copied().collect();
+    starts.sort_unstable();
+    let mut outlines = Vec::new();
+    for start in starts {
+        while let Some((first, cell)) = outgoing.get_mut(&start).and_then(|edges| edges.pop()) {
+            let mut ring = vec![start];
+            let (mut prev, mut current) = (start, first);
+            loop {
+                let edges = outgoing.get_mut(&current).map(|edges| &mut edges[..]).unwrap_or(&mut []);
+                // Where two regions touch at a corner, turn towards the diagonal neighbor
+                let pick = (0..edges.len()).min_by_key(|&i| turn(prev, current, edges[i].0));
+                // A loop may pass its start corner twice; it is closed once the first edge is next
+                if current == start && pick.is_none_or(|i| turn(prev, start, first) <= turn(prev, start, edges[i].0)) {
+                    break;
+                }
+                let Some(pick) = pick else {
+                    break;
+                };
+                ring.push(current);
+                let (next, _) = outgoing.get_mut(&current).map(|edges| edges.swap_remove(pick)).unwrap_or_default();
+                (prev, current) = (current, next);
+            }
+            outlines.push(Outline {
+                ring: ring
+                    .iter()
+                    .map(|&v| {
+                        let (x, y) = corner(v);
+                        (x as f32 - 0.5, y as f32 - 0.5)
+                    })
+                    .collect(),
+                cell,
+            });
+        }
+    }
+    outlines
+}
+
+// Shoelace area: positive for outer boundaries, negative for holes
+fn signed_area(ring: &[(f32, f32)]) -> f32 {
+    let mut sum = 0.0;
+    for (i, &(x0, y0)) in ring.iter().enumerate() {
+        let (x1, y1) = ring[(i + 1) % ring.len()];
+        sum += x0 * y1 - x1 * y0;
+    }
+    sum * 0.5
+}
+
+// Douglas-Peucker refinement: within each (a, b) span, keep the farthest vertex from the chord
+// while it lies farther than `tolerance`. `b` may equal the length to close a ring.
+fn refine_spans(points: &[(f32, f32)], keep: &mut [bool], mut spans: Vec<(usize, usize)>, tolerance: f32) {
+    let distance_sq = |a: (f32, f32), b: (f32, f32)| (a.0 - b.0).powi(2) + (a.1 - b.1).powi(2);
+    while let Some((a, b)) = spans.pop() {
+        let (start, end) = (points[a], points[b % points.len()]);
+        let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+        let length = (dx * dx + dy * dy).sqrt();
+        let mut worst = (0.0f32, 0);
+        for (i, &p) in points.iter().enumerate().take(b).skip(a + 1) {
+            let d = if length > f32::EPSILON {
+                ((p.0 - start.0) * dy - (p.1 - start.1) * dx).abs() / length
+            } else {
+                distance_sq(start, p).sqrt()
+            };
+            if d > worst.0 {
+                worst = (d, i);
+            }
+        }
+        if worst.0 > tolerance {
+            keep[worst.1] = true;
+            spans.push((a, worst.1));
+            spans.push((worst.1, b));
+        }
+    }
+}
+
+// Douglas-Peucker on a closed ring: no vertex of the original ring is farther than
+// `tolerance` (cells) from the simplified one
+fn simplify_ring(ring: &[(f32, f32)], tolerance: f32) -> Vec<(f32, f32)> {
+    if ring.len() <= 4 {
+        return ring.to_vec();
+    }
+    // Split at the vertex farthest from the first so both halves are open chains
+    let distance_sq = |a: (f32, f32), b: (f32, f32)| (a.0 - b.0).powi(2) + (a.1 - b.1).powi(2);
+    let far = (1..ring.len())
+        .max_by(|&a, &b| {
+            distance_sq(ring[0], ring[a])
+                .partial_cmp(&distance_sq(ring[0], ring[b]))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        })
+        .unwrap_or(ring.len() / 2);
+
+    let mut keep = vec![false; ring.len()];
+    keep[0] = true;
+    keep[far] = true;
+    refine_spans(ring, &mut keep, vec![(0, far), (far, ring.len())], tolerance);
+
+    let simplified: Vec<(f32, f32)> = ring.iter().zip(&keep).filter(|(_, &k)| k).map(|(&p, _)| p).collect();
+    // Too coarse a tolerance can collapse a small ring; keep it as drawn then
+    if simplified.len() < 3 { ring.to_vec() } else { simplified }
+}
+
+// Flatten a ring to x, y pairs for typed-array export
+fn flatten_ring(ring: &[(f32, f32)]) -> Vec<f32> {
+    ring.iter().flat_map(|&(x, y)| [x, y]).collect()
+}
+
+// Simplified outline and holes (flat x, y pairs) of each labeled region, indexed by label - 1
+pub(crate) fn region_polygons(mask: &[bool], labels: &[u32], count: u32, size: usize, tolerance: f32) -> Vec<(Vec<f32>, Vec<Vec<f32>>)> {
+    let mut regions = vec![(Vec::new(), Vec::new()); count as usize];
+    for outline in trace_outlines(mask, size) {
+        let (boundary, holes) = &mut regions[labels[outline.cell] as usize - 1];
+        let ring = flatten_ring(&simplify_ring(&outline.ring, tolerance));
+        if signed_area(&outline.ring) > 0.0 {
+            *boundary = ring;
+        } else {
+            holes.push(ring);
+        }
+    }
+    regions
+}
+
+// Stretches of the boundary of `mask` whose vertices all satisfy `along` (given in the outline's
+// cell coordinates), simplified to `tolerance` and flattened to x, y pairs. A loop that
+// satisfies it everywhere comes back closed, with its first vertex repeated at the end.
+pub(crate) fn boundary_polylines(mask: &[bool], size: usize, along: impl Fn(f32, f32) -> bool, tolerance: f32) -> Vec<Vec<f32>> {
+    let mut polylines = Vec::new();
+    for outline in trace_outlines(mask, size) {
+        let ring = outline.ring;
+        let selected: Vec<bool> = ring.iter().map(|&(x, y)| along(x, y)).collect();
+        let runs: Vec<Vec<(f32, f32)>> = match selected.iter().position(|&s| !s) {
+            None => vec![ring.iter().chain(ring.first()).copied().collect()],
+            Some(gap) => {
+                // Start right after a gap so no run wraps around the ring's start
+                let mut runs = vec![Vec::new()];
+                for i in 1..=ring.len() {
+                    let i = (gap + i) % ring.len();
+                    match runs.last_mut() {
+                        Some(run) if selected[i] => run.push(ring[i]),
+                        Some(run) if !run.is_empty() => runs.push(Vec::new()),
+                        _ => {}
+                    }
+                }
+                runs
+            }
+        };
+        for run in runs.into_iter().filter(|run| run.len() >= 2) {
+            let mut keep = vec![false; run.len()];
+            keep[0] = true;
+            keep[run.len() - 1] = true;
+            refine_spans(&run, &mut keep, vec![(0, run.len() - 1)], tolerance);
+            let simplified: Vec<(f32, f32)> = run.iter().zip(&keep).filter(|(_, &k)| k).map(|(&p, _)| p).collect();
+            polylines.push(flatten_ring(&simplified));
+        }
+    }
+    polylines
+}
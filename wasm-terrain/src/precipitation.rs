@@ -0,0 +1,223 @@
+use crate::height_field::HeightField;
+use wasm_bindgen::prelude::*;
+
+// Fraction of the air's moisture rained out while crossing the whole map on flat ground
+const BASE_RAINOUT: f32 = 1.5;
+// Extra rain-out per unit of windward uplift (height gained per cell, in map widths)
+const OROGRAPHIC_FACTOR: f32 = 2.0;
+// Moisture regained per cell of open water
+const SEA_RECHARGE: f32 = 0.05;
+const SMOOTHING_PASSES: u32 = 2;
+
+// Orographic precipitation: moist air blows in from the map edge along `wind_direction`
+// (radians, direction the wind blows towards), rains out faster where it is forced uphill
+// and leaves a rain shadow behind ridges. Returned per cell, normalized to a mean of 1.0.
+pub fn orographic_precipitation(height_field: &HeightField, wind_direction: f32, sea_level: f32) -> Vec<f32> {
+    let n = height_field.size();
+    if n == 0 {
+        return Vec::new();
+    }
+    let data = height_field.data();
+    let wx = crate::det_math::cos(wind_direction);
+    let wy = crate::det_math::sin(wind_direction);
+
+    // Visit cells upwind first so the (rounded) upwind cell is always already resolved
+    let mut order: Vec<usize> = (0..n * n).collect();
+    let projection = |idx: usize| (idx % n) as f32 * wx + (idx / n) as f32 * wy;
+    order.sort_by(|&a, &b| projection(a).partial_cmp(&projection(b)).unwrap_or(std::cmp::Ordering::Equal));
+
+    let mut moisture = vec![0.0f32; n * n];
+    let mut rain = vec![0.0f32; n * n];
+    let per_cell = 1.0 / n as f32;
+    for idx in order {
+        let x = (idx % n) as f32;
+        let y = (idx / n) as f32;
+        let ux = (x - wx).round();
+        let uy = (y - wy).round();
+        let (mut m, upwind_height) = if ux < 0.0 || uy < 0.0 || ux >= n as f32 || uy >= n as f32 {
+            (1.0, data[idx].max(sea_level))
+        } else {
+            let u_idx = uy as usize * n + ux as usize;
+            (moisture[u_idx], data[u_idx].max(sea_level))
+        };
+
+        let h = data[idx];
+        if h <= sea_level {
+            m = (m + SEA_RECHARGE).min(1.0);
+            rain[idx] = m * BASE_RAINOUT * per_cell;
+        } else {
+            let uplift = (h - upwind_height).max(0.0) * n as f32;
+            let fraction = ((BASE_RAINOUT + OROGRAPHIC_FACTOR * uplift) * per_cell).min(1.0);
+            rain[idx] = m * fraction;
+            m -= rain[idx];
+        }
+        moisture[idx] = m;
+    }
+
+    // Nearest-cell advection leaves streaks along the wind; soften them
+    let mut field = HeightField::new(n);
+    field.data_mut().copy_from_slice(&rain);
+    crate::filters::apply_smoothing(&mut field, SMOOTHING_PASSES, 1.0);
+
+    let mut rain = field.data().to_vec();
+    let mean = rain.iter().sum::<f32>() / rain.len() as f32;
+    if mean > 0.0 {
+        rain.iter_mut().for_each(|r| *r /= mean);
+    }
+    rain
+}
+
+#[wasm_bindgen]
+pub fn compute_precipitation(height_field: &HeightField, wind_direction: f32, sea_level: f32) -> Vec<f32> {
+    orographic_precipitation(height_field, wind_direction, sea_level)
+}
+
+// Coarse cells the advection grid gets when the caller passes 0
+const DEFAULT_CLIMATE_GRID: usize = 64;
+// Humidity (0-1) of the air blowing in over the upwind map edge
+const INFLOW_HUMIDITY: f32 = 1.0;
+// Share of the missing humidity open water makes up per step
+const EVAPORATION_RATE: f32 = 0.08;
+// Share of the humidity that rains out per step over flat land, and the extra per height unit
+// the air is lifted in that step
+const BACKGROUND_RAINOUT: f32 = 0.02;
+const UPLIFT_RAINOUT: f32 = 4.0;
+// Saturation humidity halves for every this much height (height units) above the sea; the
+// share below of any excess condenses per step
+const SATURATION_HALVING: f32 = 0.35;
+const CONDENSATION_RATE: f32 = 0.3;
+// Mixing between neighboring coarse cells per step, for turbulent spreading across the wind
+const HUMIDITY_DIFFUSION: f32 = 0.2;
+
+// Mean of the `n` × `n` field over each cell of a `g` × `g` grid
+fn coarse_average(data: &[f32], n: usize, g: usize) -> Vec<f32> {
+    let mut sum = vec![0.0f32; g * g];
+    let mut count = vec![0u32; g * g];
+    for y in 0..n {
+        let cy = y * g / n;
+        for x in 0..n {
+            let c = cy * g + x * g / n;
+            sum[c] += data[y * n + x];
+            count[c] += 1;
+        }
+    }
+    sum.iter().zip(&count).map(|(&s, &c)| if c > 0 { s / c as f32 } else { 0.0 }).collect()
+}
+
+// Bilinear value of a `g` × `g` grid at fine cell (x, y) of an `n` × `n` field; grid cell
+// centers sit over the middle of the fine cells they cover
+pub(crate) fn grid_lookup(grid: &[f32], g: usize, n: usize, x: usize, y: usize) -> f32 {
+    let scale = g as f32 / n as f32;
+    let max = (g - 1) as f32;
+    let gx = ((x as f32 + 0.5) * scale - 0.5).clamp(0.0, max);
+    let gy = ((y as f32 + 0.5) * scale - 0.5).clamp(0.0, max);
+    let (x0, y0) = (gx.floor() as usize, gy.floor() as usize);
+    let (x1, y1) = ((x0 + 1).min(g - 1), (y0 + 1).min(g - 1));
+    let (tx, ty) = (gx - x0 as f32, gy - y0 as f32);
+    let top = grid[y0 * g + x0] + (grid[y0 * g + x1] - grid[y0 * g + x0]) * tx;
+    let bottom = grid[y1 * g + x0] + (grid[y1 * g + x1] - grid[y1 * g + x0]) * tx;
+    top + (bottom - top) * ty
+}
+
+// Coarse cells per side of a climate grid over a `size` map; 0 asks for the default
+pub(crate) fn climate_grid_size(size: usize, grid_size: usize) -> usize {
+    if grid_size == 0 { DEFAULT_CLIMATE_GRID } else { grid_size }.clamp(2, size.max(2))
+}
+
+// Moisture advection on a `g` × `g` grid: humidity blows in over the upwind edge along
+// `wind_direction` (radians, direction the wind blows towards), is carried one cell per step,
+// rains out where the air is lifted or rises past what colder air can hold, and is recharged
+// over open water. Stepped until air from the inflow edge has crossed the map twice, so the
+// field is settled; returns the mean rain per step of the second crossing.
+pub(crate) fn advected_rain_grid(height_field: &HeightField, wind_direction: f32, sea_level: f32, g: usize) -> Vec<f32> {
+    let n = height_field.size();
+    let data = height_field.data();
+    let land: Vec<f32> = data.iter().map(|&h| (h - sea_level).max(0.0)).collect();
+    let water: Vec<f32> = data.iter().map(|&h| if h <= sea_level { 1.0 } else { 0.0 }).collect();
+    let height = coarse_average(&land, n, g);
+    let water = coarse_average(&water, n, g);
+
+    let wx = crate::det_math::cos(wind_direction);
+    let wy = crate::det_math::sin(wind_direction);
+    let at = |field: &[f32], fx: f32, fy: f32| -> Option<f32> {
+        if fx < 0.0 || fy < 0.0 || fx > (g - 1) as f32 || fy > (g - 1) as f32 {
+            return None;
+        }
+        let (x0, y0) = (fx.floor() as usize, fy.floor() as usize);
+        let (x1, y1) = ((x0 + 1).min(g - 1), (y0 + 1).min(g - 1));
+        let (tx, ty) = (fx - x0 as f32, fy - y0 as f32);
+        let top = field[y0 * g + x0] + (field[y0 * g + x1] - field[y0 * g + x0]) * tx;
+        let bottom = field[y1 * g + x0] + (field[y1 * g + x1] - field[y1 * g + x0]) * tx;
+        Some(top + (bottom - top) * ty)
+    };
+
+    let mut humidity = vec![INFLOW_HUMIDITY; g * g];
+    let mut rain = vec![0.0f32; g * g];
+    for step in 0..2 * g {
+        let mut next = vec![0.0f32; g * g];
+        for y in 0..g {
+            for x in 0..g {
+                let c = y * g + x;
+                // Semi-Lagrangian step: the air here came from one cell upwind
+                let (ux, uy) = (x as f32 - wx, y as f32 - wy);
+                let saturation = crate::det_math::powf(0.5, height[c] / SATURATION_HALVING);
+                // Inflow has already dropped what the ground at the edge would wring out of it
+                let q = at(&humidity, ux, uy).unwrap_or(INFLOW_HUMIDITY.min(saturation));
+                let upwind_height = at(&height, ux, uy).unwrap_or(height[c]);
+
+                let q = q + EVAPORATION_RATE * (1.0 - q).max(0.0) * water[c];
+                let uplift = (height[c] - upwind_height).max(0.0);
+                let condensed = (q - saturation).max(0.0) * CONDENSATION_RATE;
+                let rained = condensed + (q - condensed) * (BACKGROUND_RAINOUT + UPLIFT_RAINOUT * uplift).min(1.0);
+                // Rain is averaged over the second crossing, once the field has settled
+                if step >= g {
+                    rain[c] += rained;
+                }
+                next[c] = q - rained;
+            }
+        }
+        // Turbulent mixing with the four neighbors
+        for y in 0..g {
+            for x in 0..g {
+                let c = y * g + x;
+                let mut sum = 0.0;
+                let mut count = 0.0;
+                for (dx, dy) in [(-1i32, 0i32), (1, 0), (0, -1), (0, 1)] {
+                    let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+                    if nx >= 0 && ny >= 0 && (nx as usize) < g && (ny as usize) < g {
+                        sum += next[ny as usize * g + nx as usize];
+                        count += 1.0;
+                    }
+                }
+                humidity[c] = next[c] + HUMIDITY_DIFFUSION * (sum / count - next[c]);
+            }
+        }
+    }
+
+    rain.iter_mut().for_each(|r| *r /= g as f32);
+    rain
+}
+
+// advected_rain_grid on a coarse `grid_size` grid (0 = default), looked up bilinearly per cell
+// and normalized to a mean of 1.0 like orographic_precipitation
+pub fn advected_precipitation(height_field: &HeightField, wind_direction: f32, sea_level: f32, grid_size: usize) -> Vec<f32> {
+    let n = height_field.size();
+    if n == 0 {
+        return Vec::new();
+    }
+    let g = climate_grid_size(n, grid_size);
+    let rain = advected_rain_grid(height_field, wind_direction, sea_level, g);
+    let mut precipitation: Vec<f32> = (0..n * n).map(|idx| grid_lookup(&rain, g, n, idx % n, idx / n)).collect();
+    let mean = precipitation.iter().sum::<f32>() / precipitation.len() as f32;
+    if mean > 0.0 {
+        precipitation.iter_mut().for_each(|r| *r /= mean);
+    }
+    precipitation
+}
+
+// Smoother, climate-style alternative to compute_precipitation; `grid_size` coarse cells per
+// side (0 = 64) keeps it cheap at any resolution
+#[wasm_bindgen]
+pub fn compute_advected_precipitation(height_field: &HeightField, wind_direction: f32, sea_level: f32, grid_size: usize) -> Vec<f32> {
+    advected_precipitation(height_field, wind_direction, sea_level, grid_size)
+}
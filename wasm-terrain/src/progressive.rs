@@ -0,0 +1,195 @@
+use crate::codec::{for_each_block, unzigzag, zigzag, BitReader, BitWriter};
+use crate::error::TerrainError;
+use crate::height_field::HeightField;
+
+// Serialized form tag, bumped whenever the byte layout changes
+const PROGRESSIVE_MAGIC: &[u8; 4] = b"HFP2";
+// Side (cells) at or below which the pyramid stops halving; the coarsest level is sent whole
+const COARSEST_SIZE: usize = 8;
+// Finest quantization step relative to the height range, as in the block codec
+const MAX_LEVELS: f32 = (1u32 << 24) as f32;
+// Detail coefficients per 2×2 block of a level: horizontal, vertical and diagonal
+const DETAILS: usize = 3;
+
+// Sides of the pyramid levels, finest (the map itself) first
+fn level_sizes(n: usize) -> Vec<usize> {
+    let mut sizes = vec![n];
+    while let Some(&s) = sizes.last().filter(|&&s| s > COARSEST_SIZE) {
+        sizes.push(s / 2);
+    }
+    sizes
+}
+
+// Integer Haar step (the S-transform): floored mean and difference, undone exactly by unlift
+fn lift(a: i64, b: i64) -> (i64, i64) {
+    let h = a - b;
+    (b + (h >> 1), h)
+}
+
+fn unlift(l: i64, h: i64) -> (i64, i64) {
+    let b = l - (h >> 1);
+    (b + h, b)
+}
+
+// One level of the 2D Haar transform of an s × s level: the s/2 × s/2 approximation and the
+// three details of each 2×2 block
+fn haar_forward(levels: &[i64], s: usize) -> (Vec<i64>, Vec<[i64; DETAILS]>) {
+    let c = s / 2;
+    let mut coarse = Vec::with_capacity(c * c);
+    let mut details = Vec::with_capacity(c * c);
+    for cy in 0..c {
+        for cx in 0..c {
+            let at = |dx: usize, dy: usize| levels[(2 * cy + dy) * s + 2 * cx + dx];
+            let (l0, h0) = lift(at(0, 0), at(1, 0));
+            let (l1, h1) = lift(at(0, 1), at(1, 1));
+            let (ll, lh) = lift(l0, l1);
+            let (hl, hh) = lift(h0, h1);
+            coarse.push(ll);
+            details.push([hl, lh, hh]);
+        }
+    }
+    (coarse, details)
+}
+
+// Write the 2×2 block of the s × s `levels` whose approximation is `ll`
+fn haar_inverse(levels: &mut [i64], s: usize, (cx, cy): (usize, usize), ll: i64, [hl, lh, hh]: [i64; DETAILS]) {
+    let (l0, l1) = unlift(ll, lh);
+    let (h0, h1) = unlift(hl, hh);
+    let (a, b) = unlift(l0, h0);
+    let (c, d) = unlift(l1, h1);
+    let row = 2 * cy * s + 2 * cx;
+    levels[row..row + 2].copy_from_slice(&[a, b]);
+    levels[row + s..row + s + 2].copy_from_slice(&[c, d]);
+}
+
+// Progressive encoding: heights are quantized to steps of 2 · max_error like compress and
+// decomposed into an integer Haar wavelet pyramid. The stream holds the coarsest approximation
+// and then, for each finer level, the three detail coefficients of every 2×2 block, n² values
+// in all. Every level is a length-prefixed chunk of Rice-coded values, so any prefix of the
+// stream that ends on a chunk boundary decodes. Sides must be powers of two.
+pub(crate) fn encode_progressive(height_field: &HeightField, max_error: f32) -> Result<Vec<u8>, TerrainError> {
+    let n = height_field.size();
+    if !n.is_power_of_two() {
+        return Err(TerrainError::InvalidParameter(format!("progressive encoding needs a power-of-two map size, got {}", n)));
+    }
+    let data = height_field.data();
+    let min = data.iter().fold(f32::INFINITY, |m, &h| m.min(h));
+    let max = data.iter().fold(f32::NEG_INFINITY, |m, &h| m.max(h));
+    let step = (2.0 * max_error).max((max - min) / MAX_LEVELS).max(f32::MIN_POSITIVE);
+
+    let sizes = level_sizes(n);
+    let mut approximation: Vec<i64> = data.iter().map(|&h| ((h - min) / step).round() as i64).collect();
+    // Details of each level, finest first
+    let mut details = Vec::with_capacity(sizes.len() - 1);
+    for &s in &sizes[..sizes.len() - 1] {
+        let (coarse, level_details) = haar_forward(&approximation, s);
+        approximation = coarse;
+        details.push(level_details);
+    }
+
+    let mut bytes = Vec::with_capacity(16 + n * n / 2);
+    bytes.extend_from_slice(PROGRESSIVE_MAGIC);
+    bytes.extend_from_slice(&(n as u32).to_le_bytes());
+    bytes.extend_from_slice(&min.to_le_bytes());
+    bytes.extend_from_slice(&step.to_le_bytes());
+    let mut push_chunk = |chunk: Vec<u8>| {
+        bytes.extend_from_slice(&(chunk.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&chunk);
+    };
+
+    let coarsest = sizes[sizes.len() - 1];
+    let mut writer = BitWriter::new(Vec::new());
+    for_each_block([0, 0, coarsest, coarsest], |cells| {
+        let values: Vec<u32> = cells.iter().map(|&(x, y)| zigzag(approximation[y * coarsest + x])).collect();
+        writer.write_block(&values);
+    });
+    push_chunk(writer.finish());
+
+    for (level, level_details) in details.iter().enumerate().rev() {
+        let c = sizes[level] / 2;
+        let mut writer = BitWriter::new(Vec::new());
+        for_each_block([0, 0, c, c], |cells| {
+            let values: Vec<u32> = cells.iter().flat_map(|&(x, y)| level_details[y * c + x].map(zigzag)).collect();
+            writer.write_block(&values);
+        });
+        push_chunk(writer.finish());
+    }
+    Ok(bytes)
+}
+
+// Decode as many levels of an encode_progressive stream as have fully arrived, returning the
+// finest of them at its own resolution; None when not even the coarsest level is complete or
+// the bytes are malformed
+pub(crate) fn decode_progressive(bytes: &[u8]) -> Option<HeightField> {
+    if bytes.get(0..4)? != PROGRESSIVE_MAGIC {
+        return None;
+    }
+    let word = |at: usize| <[u8; 4]>::try_from(bytes.get(at..at.checked_add(4)?)?).ok();
+    let n = u32::from_le_bytes(word(4)?) as usize;
+    let min = f32::from_le_bytes(word(8)?);
+    let step = f32::from_le_bytes(word(12)?);
+    if !n.is_power_of_two() || n > crate::config::MAX_MAP_SIZE as usize {
+        return None;
+    }
+
+    // The next complete chunk holding at least `values` values; every value takes a bit
+    let mut at = 16;
+    let mut next_chunk = |values: usize| -> Option<&[u8]> {
+        let len = u32::from_le_bytes(word(at)?) as usize;
+        let start = at.checked_add(4)?;
+        let chunk = bytes.get(start..start.checked_add(len)?)?;
+        if chunk.len().checked_mul(8)? < values {
+            return None;
+        }
+        at = start + len;
+        Some(chunk)
+    };
+
+    let sizes = level_sizes(n);
+    let coarsest = sizes[sizes.len() - 1];
+    let mut reader = BitReader::new(next_chunk(coarsest * coarsest)?);
+    let mut levels = vec![0i64; coarsest * coarsest];
+    let mut ok = true;
+    for_each_block([0, 0, coarsest, coarsest], |cells| {
+        let Some(values) = reader.read_block(cells.len()).filter(|_| ok) else {
+            ok = false;
+            return;
+        };
+        for (&(x, y), &v) in cells.iter().zip(&values) {
+            levels[y * coarsest + x] = unzigzag(v);
+        }
+    });
+    if !ok {
+        return None;
+    }
+
+    let mut s = coarsest;
+    for &finer in sizes.iter().rev().skip(1) {
+        let Some(chunk) = next_chunk(DETAILS * s * s) else {
+            break;
+        };
+        let mut reader = BitReader::new(chunk);
+        let mut refined = vec![0i64; finer * finer];
+        for_each_block([0, 0, s, s], |cells| {
+            let Some(values) = reader.read_block(DETAILS * cells.len()).filter(|_| ok) else {
+                ok = false;
+                return;
+            };
+            for (&(x, y), d) in cells.iter().zip(values.chunks_exact(DETAILS)) {
+                let details = [unzigzag(d[0]), unzigzag(d[1]), unzigzag(d[2])];
+                haar_inverse(&mut refined, finer, (x, y), levels[y * s + x], details);
+            }
+        });
+        if !ok {
+            return None;
+        }
+        levels = refined;
+        s = finer;
+    }
+
+    let mut height_field = HeightField::new(s);
+    for (h, &q) in height_field.data_mut().iter_mut().zip(&levels) {
+        *h = min + q as f32 * step;
+    }
+    Some(height_field)
+}
@@ -0,0 +1,121 @@
+use crate::height_field::HeightField;
+use wasm_bindgen::prelude::*;
+
+// Min/max height mipchain. Level 0 holds the heights themselves; each further level halves
+// the resolution (rounding up) and each texel bounds the up-to-2x2 texels below it, down to
+// a single texel bounding the whole field.
+#[wasm_bindgen]
+#[derive(Clone)]
+pub struct MinMaxPyramid {
+    sizes: Vec<usize>,
+    min_levels: Vec<Vec<f32>>,
+    max_levels: Vec<Vec<f32>>,
+}
+
+#[wasm_bindgen]
+impl MinMaxPyramid {
+    #[wasm_bindgen(getter)]
+    pub fn level_count(&self) -> usize {
+        self.sizes.len()
+    }
+
+    // Width (= height) of a level in texels, 0 past the last level
+    #[wasm_bindgen]
+    pub fn level_size(&self, level: usize) -> usize {
+        self.sizes.get(level).copied().unwrap_or(0)
+    }
+
+    #[wasm_bindgen]
+    pub fn get_min(&self, level: usize) -> Option<js_sys::Float32Array> {
+        self.min_levels.get(level).map(|data| {
+            let array = js_sys::Float32Array::new_with_length(data.len() as u32);
+            array.copy_from(data);
+            array
+        })
+    }
+
+    #[wasm_bindgen]
+    pub fn get_max(&self, level: usize) -> Option<js_sys::Float32Array> {
+        self.max_levels.get(level).map(|data| {
+            let array = js_sys::Float32Array::new_with_length(data.len() as u32);
+            array.copy_from(data);
+            array
+        })
+    }
+
+    // Exact height bounds [min, max] of the cells [x, x + width) × [y, y + height), mostly read
+    // from coarse levels; empty if the rectangle misses the field
+    #[wasm_bindgen]
+    pub fn bounds(&self, x: usize, y: usize, width: usize, height: usize) -> Vec<f32> {
+        let n = self.level_size(0);
+        let (x1, y1) = ((x + width).min(n), (y + height).min(n));
+        if x >= x1 || y >= y1 {
+            return Vec::new();
+        }
+        let (lo, hi) = self.range(0, x, y, x1, y1);
+        vec![lo, hi]
+    }
+}
+
+impl MinMaxPyramid {
+    pub(crate) fn build(height_field: &HeightField) -> Self {
+        let mut sizes = vec![height_field.size()];
+        let mut min_levels = vec![height_field.data().to_vec()];
+        let mut max_levels = vec![height_field.data().to_vec()];
+
+        while *sizes.last().unwrap_or(&0) > 1 {
+            let n = sizes[sizes.len() - 1];
+            let m = n.div_ceil(2);
+            let (prev_min, prev_max) = (&min_levels[min_levels.len() - 1], &max_levels[max_levels.len() - 1]);
+            let mut min = vec![f32::INFINITY; m * m];
+            let mut max = vec![f32::NEG_INFINITY; m * m];
+            for y in 0..n {
+                for x in 0..n {
+                    let (src, dst) = (y * n + x, (y / 2) * m + x / 2);
+                    min[dst] = min[dst].min(prev_min[src]);
+                    max[dst] = max[dst].max(prev_max[src]);
+                }
+            }
+            sizes.push(m);
+            min_levels.push(min);
+            max_levels.push(max);
+        }
+
+        Self { sizes, min_levels, max_levels }
+    }
+
+    // Bounds of the level-`level` texels [x0, x1) × [y0, y1): whole texels of the next level
+    // are taken from there, the ragged border from this level
+    fn range(&self, level: usize, x0: usize, y0: usize, x1: usize, y1: usize) -> (f32, f32) {
+        let n = self.sizes[level];
+        let (min, max) = (&self.min_levels[level], &self.max_levels[level]);
+        let mut lo = f32::INFINITY;
+        let mut hi = f32::NEG_INFINITY;
+        let mut take = |x: usize, y: usize| {
+            lo = lo.min(min[y * n + x]);
+            hi = hi.max(max[y * n + x]);
+        };
+
+        // Inner span aligned to the next level's 2x2 blocks
+        let (ix0, iy0) = (x0.div_ceil(2), y0.div_ceil(2));
+        let (ix1, iy1) = (x1 / 2, y1 / 2);
+        if level + 1 >= self.sizes.len() || ix0 >= ix1 || iy0 >= iy1 {
+            for y in y0..y1 {
+                for x in x0..x1 {
+                    take(x, y);
+                }
+            }
+            return (lo, hi);
+        }
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let inner = x >= ix0 * 2 && x < ix1 * 2 && y >= iy0 * 2 && y < iy1 * 2;
+                if !inner {
+                    take(x, y);
+                }
+            }
+        }
+        let (inner_lo, inner_hi) = self.range(level + 1, ix0, iy0, ix1, iy1);
+        (lo.min(inner_lo), hi.max(inner_hi))
+    }
+}
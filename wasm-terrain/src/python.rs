@@ -0,0 +1,210 @@
+// Python module for tuning parameters in notebooks before presets ship to the web app: the
+// generator, erosion and the water system on numpy arrays. Heights are 2D float32 arrays
+// (rows, columns) of the same square maps HeightField holds, sea levels are in height field
+// units throughout and biomes are named by id, as in the C API. Long calls release the GIL.
+use crate::config::{TerrainConfig, MAX_MAP_SIZE};
+use crate::units::LEGACY_SEA_LEVEL_SCALE;
+use crate::error::TerrainError;
+use crate::height_field::HeightField;
+use crate::water_system::{WaterFeatures, WaterSystemParams};
+use crate::TerrainGenerationResult;
+use numpy::ndarray::Array2;
+use numpy::{IntoPyArray, PyArray2, PyReadonlyArray2};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+impl From<TerrainError> for PyErr {
+    fn from(error: TerrainError) -> Self {
+        PyValueError::new_err(format!("{}: {}", error.code(), error))
+    }
+}
+
+fn biome(id: &str) -> Result<crate::biomes::BiomeType, TerrainError> {
+    crate::biome_registry::resolve(id).ok_or_else(|| TerrainError::InvalidParameter(format!("unknown biome id {:?}", id)))
+}
+
+fn to_array<'py>(py: Python<'py>, size: usize, values: &[f32]) -> Bound<'py, PyArray2<f32>> {
+    Array2::from_shape_vec((size, size), values.to_vec()).unwrap().into_pyarray(py)
+}
+
+fn finite(name: &str, value: f32) -> Result<f32, TerrainError> {
+    if !value.is_finite() {
+        return Err(TerrainError::InvalidParameter(format!("{} must be a finite number, got {}", name, value)));
+    }
+    Ok(value)
+}
+
+// Map from `rows` × `cols` row-major heights: square, of a size a config could generate, and
+// finite everywhere
+fn height_field_from(rows: usize, cols: usize, values: impl IntoIterator<Item = f32>) -> Result<HeightField, TerrainError> {
+    if rows != cols {
+        return Err(TerrainError::InvalidParameter(format!("heights must be a square array, got {}x{}", rows, cols)));
+    }
+    if rows == 0 || rows as u64 > MAX_MAP_SIZE {
+        return Err(TerrainError::InvalidParameter(format!("heights must be 1 to {} cells per side, got {}", MAX_MAP_SIZE, rows)));
+    }
+    let mut height_field = HeightField::new(rows);
+    for (h, value) in height_field.data_mut().iter_mut().zip(values) {
+        *h = finite("heights", value)?;
+    }
+    Ok(height_field)
+}
+
+fn to_height_field(heights: &PyReadonlyArray2<f32>) -> Result<HeightField, TerrainError> {
+    let heights = heights.as_array();
+    let (rows, cols) = heights.dim();
+    height_field_from(rows, cols, heights.iter().copied())
+}
+
+fn generate_terrain(
+    base_size: u32,
+    steps: u32,
+    seed: u32,
+    biome_id: &str,
+    sea_level: f32,
+    erosion_years: f32,
+) -> Result<TerrainGenerationResult, TerrainError> {
+    let mut config = TerrainConfig::for_biome_id(base_size, steps, seed, biome_id, sea_level, erosion_years)?;
+    let adjustments = config.clamp_bounds();
+    config.check()?;
+    Ok(crate::run_pipeline(&config, adjustments, crate::budget::StageBudget::new(None)))
+}
+
+// Water masks as a dict of arrays: water, river, beach, flow (accumulation) and surface_age
+fn water_dict<'py>(py: Python<'py>, features: &WaterFeatures) -> PyResult<Bound<'py, PyDict>> {
+    let size = features.size();
+    let dict = PyDict::new(py);
+    dict.set_item("water", to_array(py, size, features.water_mask()))?;
+    dict.set_item("river", to_array(py, size, features.river_mask()))?;
+    dict.set_item("beach", to_array(py, size, features.beach_mask()))?;
+    dict.set_item("flow", to_array(py, size, features.flow_accumulation()))?;
+    dict.set_item("surface_age", to_array(py, size, features.surface_age()))?;
+    Ok(dict)
+}
+
+// A generated terrain
+#[pyclass(name = "Terrain")]
+struct PyTerrain(TerrainGenerationResult);
+
+#[pymethods]
+impl PyTerrain {
+    #[getter]
+    fn size(&self) -> usize {
+        self.0.height_field_ref().size()
+    }
+
+    #[getter]
+    fn heights<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray2<f32>> {
+        let height_field = self.0.height_field_ref();
+        to_array(py, height_field.size(), height_field.data())
+    }
+
+    // Sea level in height field units
+    #[getter]
+    fn sea_level(&self) -> f32 {
+        self.0.vertical_units().sea_level_height()
+    }
+
+    #[getter]
+    fn erosion_years(&self) -> f32 {
+        self.0.erosion_years()
+    }
+
+    // None when the terrain was generated without erosion
+    #[getter]
+    fn water<'py>(&self, py: Python<'py>) -> PyResult<Option<Bound<'py, PyDict>>> {
+        self.0.water_features_ref().map(|features| water_dict(py, features)).transpose()
+    }
+
+    // Settings the generator clamped, as (field, requested, applied, reason) tuples
+    #[getter]
+    fn adjustments(&self) -> Vec<(&'static str, f64, f64, String)> {
+        self.0.adjustments.iter().map(|a| (a.field, a.requested, a.applied, a.reason.clone())).collect()
+    }
+}
+
+// generate_terrain_with_config with the config's constructor arguments; biome_id is a biome id
+// (see biome_ids) and sea_level is in height field units.
+// Out-of-range settings are clamped and listed in Terrain.adjustments; raises ValueError for
+// settings the pipeline can't run with.
+#[pyfunction]
+#[pyo3(signature = (base_size=64, steps=4, seed=0, biome_id="temperate", sea_level=0.0, erosion_years=0.0))]
+fn generate(py: Python<'_>, base_size: u32, steps: u32, seed: u32, biome_id: &str, sea_level: f32, erosion_years: f32) -> PyResult<PyTerrain> {
+    let result = py.detach(|| generate_terrain(base_size, steps, seed, biome_id, sea_level, erosion_years))?;
+    Ok(PyTerrain(result))
+}
+
+// `years` of the biome's geological erosion on a copy of `heights`, as the generator runs it;
+// sea_level is in height field units. Returns (heights, water dict).
+#[pyfunction]
+#[pyo3(signature = (heights, years, sea_level=0.0, biome_id="temperate", seed=0))]
+fn erode<'py>(
+    py: Python<'py>,
+    heights: PyReadonlyArray2<f32>,
+    years: f32,
+    sea_level: f32,
+    biome_id: &str,
+    seed: u32,
+) -> PyResult<(Bound<'py, PyArray2<f32>>, Bound<'py, PyDict>)> {
+    let mut height_field = to_height_field(&heights)?;
+    let legacy_sea_level = finite("sea_level", sea_level)? * LEGACY_SEA_LEVEL_SCALE;
+    let mut params = crate::erosion::ErosionParams::for_biome(biome(biome_id)?, finite("years", years)?.max(0.0), legacy_sea_level);
+    params.seed = seed;
+    let features = py.detach(|| crate::erosion::apply_geological_erosion(&mut height_field, &params));
+    Ok((to_array(py, height_field.size(), height_field.data()), water_dict(py, &features)?))
+}
+
+// Rivers, lakes and beaches for `heights` with the generator's water settings, carving the
+// rivers into a copy; sea_level is in height field units. Returns (heights, water dict).
+#[pyfunction]
+#[pyo3(signature = (heights, sea_level, river_threshold=0.08, river_width=8.0, river_depth=0.05))]
+fn water_system<'py>(
+    py: Python<'py>,
+    heights: PyReadonlyArray2<f32>,
+    sea_level: f32,
+    river_threshold: f32,
+    river_width: f32,
+    river_depth: f32,
+) -> PyResult<(Bound<'py, PyArray2<f32>>, Bound<'py, PyDict>)> {
+    let mut height_field = to_height_field(&heights)?;
+    let mut params = WaterSystemParams::new(sea_level, river_threshold, river_width, river_depth, 0.04, 8.0);
+    params.check(height_field.size())?;
+    let features = py.detach(|| crate::water_system::apply_water_system(&mut height_field, &params));
+    Ok((to_array(py, height_field.size(), height_field.data()), water_dict(py, &features)?))
+}
+
+#[pymodule]
+fn genesis_terrain_wasm(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    // Arrays need numpy's C API; fail the import rather than the first array access without it
+    module.py().import("numpy")?;
+    module.add_class::<PyTerrain>()?;
+    module.add_function(wrap_pyfunction!(generate, module)?)?;
+    module.add_function(wrap_pyfunction!(erode, module)?)?;
+    module.add_function(wrap_pyfunction!(water_system, module)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn heights_are_validated() {
+        assert!(height_field_from(2, 3, vec![0.0; 6]).is_err());
+        assert!(height_field_from(0, 0, Vec::new()).is_err());
+        let side = MAX_MAP_SIZE as usize + 1;
+        assert!(height_field_from(side, side, std::iter::empty()).is_err());
+        assert!(height_field_from(2, 2, [0.0, f32::NAN, 0.0, 0.0]).is_err());
+        let height_field = height_field_from(2, 2, [0.0, 1.0, 2.0, 3.0]).unwrap();
+        assert_eq!(height_field.data(), [0.0, 1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn sea_level_is_in_height_units() {
+        let result = generate_terrain(32, 1, 3, "tundra", 0.05, 0.0).unwrap();
+        assert!((result.vertical_units().sea_level_height() - 0.05).abs() < 1e-6);
+        assert_eq!(result.biome_id, "tundra");
+        assert!(generate_terrain(32, 1, 3, "swamp", 0.05, 0.0).is_err());
+    }
+}
@@ -0,0 +1,178 @@
+use crate::error::TerrainError;
+use crate::height_field::HeightField;
+use wasm_bindgen::prelude::*;
+
+const LEVELS: f32 = u16::MAX as f32;
+
+// 16-bit height field: value = min + q / 65535 * (max - min). Half the memory and
+// postMessage size of HeightField at ~1/65535 of the height range precision.
+#[wasm_bindgen]
+#[derive(Clone)]
+pub struct QuantizedHeightField {
+    size: usize,
+    min: f32,
+    max: f32,
+    data: Vec<u16>,
+}
+
+impl QuantizedHeightField {
+    fn quantize_value(value: f32, min: f32, max: f32) -> u16 {
+        let span = max - min;
+        if span > 0.0 {
+            (((value - min) / span).clamp(0.0, 1.0) * LEVELS).round() as u16
+        } else {
+            0
+        }
+    }
+
+    #[inline]
+    fn dequantize_value(&self, q: u16) -> f32 {
+        self.min + q as f32 / LEVELS * (self.max - self.min)
+    }
+
+    // Dequantize one row (clamped to the field) into `out`
+    fn read_row(&self, y: i32, out: &mut [f32]) {
+        let n = self.size;
+        let y = y.clamp(0, n as i32 - 1) as usize;
+        for (x, value) in out.iter_mut().enumerate() {
+            *value = self.dequantize_value(self.data[y * n + x]);
+        }
+    }
+
+    // Evaluate a 3x3 stencil over the field with a rolling window of three dequantized rows
+    fn stencil_pass<S, E>(&self, stencil: &S, mut emit: E)
+    where
+        S: Fn(&[f32], &[f32], &[f32], usize) -> f32,
+        E: FnMut(usize, f32),
+    {
+        let n = self.size;
+        let mut rows = [vec![0.0f32; n], vec![0.0f32; n], vec![0.0f32; n]];
+        self.read_row(-1, &mut rows[0]);
+        self.read_row(0, &mut rows[1]);
+        for y in 0..n {
+            self.read_row(y as i32 + 1, &mut rows[2]);
+            for x in 0..n {
+                emit(y * n + x, stencil(&rows[0], &rows[1], &rows[2], x));
+            }
+            rows.rotate_left(1);
+        }
+    }
+
+    // Apply a 3x3 stencil in place. The output range is found in a first pass so the result
+    // is requantized without clipping, and no full f32 copy is ever materialized.
+    fn map_3x3<S>(&mut self, stencil: S)
+    where
+        S: Fn(&[f32], &[f32], &[f32], usize) -> f32,
+    {
+        let n = self.size;
+        if n == 0 {
+            return;
+        }
+
+        let mut min = f32::INFINITY;
+        let mut max = f32::NEG_INFINITY;
+        self.stencil_pass(&stencil, |_, v| {
+            min = min.min(v);
+            max = max.max(v);
+        });
+
+        let mut out = vec![0u16; n * n];
+        self.stencil_pass(&stencil, |idx, v| out[idx] = Self::quantize_value(v, min, max));
+
+        self.data = out;
+        self.min = min;
+        self.max = max;
+    }
+}
+
+#[wasm_bindgen]
+impl QuantizedHeightField {
+    #[wasm_bindgen]
+    pub fn from_height_field(height_field: &HeightField) -> QuantizedHeightField {
+        let data = height_field.data();
+        let min = data.iter().fold(f32::INFINITY, |m, &h| m.min(h));
+        let max = data.iter().fold(f32::NEG_INFINITY, |m, &h| m.max(h));
+        let (min, max) = if data.is_empty() { (0.0, 0.0) } else { (min, max) };
+
+        QuantizedHeightField {
+            size: height_field.size(),
+            min,
+            max,
+            data: data.iter().map(|&h| Self::quantize_value(h, min, max)).collect(),
+        }
+    }
+
+    // Rebuild from transferred data; throws when the length doesn't match size²
+    #[wasm_bindgen]
+    pub fn from_raw(size: usize, min: f32, max: f32, data: Vec<u16>) -> Result<QuantizedHeightField, JsValue> {
+        if data.len() != size * size {
+            return Err(TerrainError::LengthMismatch { what: "quantized height data", expected: size * size, actual: data.len() }.into());
+        }
+        Ok(QuantizedHeightField { size, min, max, data })
+    }
+
+    #[wasm_bindgen]
+    pub fn dequantize(&self) -> HeightField {
+        let mut out = HeightField::new(self.size);
+        for (value, &q) in out.data_mut().iter_mut().zip(&self.data) {
+            *value = self.dequantize_value(q);
+        }
+        out
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn min(&self) -> f32 {
+        self.min
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn max(&self) -> f32 {
+        self.max
+    }
+
+    #[wasm_bindgen]
+    pub fn get(&self, x: usize, y: usize) -> f32 {
+        let n = self.size;
+        let x = x.min(n - 1);
+        let y = y.min(n - 1);
+        self.dequantize_value(self.data[y * n + x])
+    }
+
+    #[wasm_bindgen]
+    pub fn get_data(&self) -> js_sys::Uint16Array {
+        let array = js_sys::Uint16Array::new_with_length(self.data.len() as u32);
+        array.copy_from(&self.data);
+        array
+    }
+
+    // 3x3 box smoothing, same semantics as filters::apply_smoothing
+    #[wasm_bindgen]
+    pub fn smooth(&mut self, iterations: u32, strength: f32) {
+        let n = self.size;
+        for _iter in 0..iterations {
+            self.map_3x3(|up, mid, down, x| {
+                let l = x.saturating_sub(1);
+                let r = (x + 1).min(n - 1);
+                let sum = up[l] + up[x] + up[r] + mid[l] + mid[x] + mid[r] + down[l] + down[x] + down[r];
+                mid[x] + (sum / 9.0 - mid[x]) * strength
+            });
+        }
+    }
+
+    // Laplacian unsharp mask, same semantics as filters::apply_ridge_sharpen
+    #[wasm_bindgen]
+    pub fn ridge_sharpen(&mut self, strength: f32) {
+        let n = self.size;
+        self.map_3x3(|up, mid, down, x| {
+            let l = x.saturating_sub(1);
+            let r = (x + 1).min(n - 1);
+            let lap = mid[l] + mid[r] + up[x] + down[x] - 4.0 * mid[x];
+            mid[x] - strength * lap
+        });
+    }
+}
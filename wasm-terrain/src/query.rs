@@ -0,0 +1,151 @@
+use crate::height_field::HeightField;
+
+// Offset (cells) of the central differences behind sample_normal
+const NORMAL_STEP: f32 = 0.5;
+
+// Bilinear height at fractional cell coordinates (cell centers on integers), clamped to the field
+pub(crate) fn bilinear(height_field: &HeightField, x: f32, y: f32) -> f32 {
+    let n = height_field.size();
+    let x = x.clamp(0.0, (n - 1) as f32);
+    let y = y.clamp(0.0, (n - 1) as f32);
+    let (x0, y0) = (x.floor() as i32, y.floor() as i32);
+    bilinear_cell(height_field, x0, y0, x - x0 as f32, y - y0 as f32)
+}
+
+// Bilinear height at fraction (fx, fy) of the way from cell (x0, y0) to (x0 + 1, y0 + 1).
+// Taking the cell and fraction separately keeps the result bit-identical wherever the field
+// sits in a larger world.
+pub(crate) fn bilinear_cell(height_field: &HeightField, x0: i32, y0: i32, fx: f32, fy: f32) -> f32 {
+    let h00 = height_field.get_clamped(x0, y0);
+    let h10 = height_field.get_clamped(x0 + 1, y0);
+    let h01 = height_field.get_clamped(x0, y0 + 1);
+    let h11 = height_field.get_clamped(x0 + 1, y0 + 1);
+    let a = h00 + (h10 - h00) * fx;
+    let b = h01 + (h11 - h01) * fx;
+    a + (b - a) * fy
+}
+
+// Unit surface normal as [x, y, height] in cell and height units, from central differences of
+// the bicubic surface so it varies smoothly across cells
+pub(crate) fn normal(height_field: &HeightField, x: f32, y: f32) -> [f32; 3] {
+    let sample = |x: f32, y: f32| crate::noise::sample_bicubic(height_field, x, y);
+    let gx = (sample(x + NORMAL_STEP, y) - sample(x - NORMAL_STEP, y)) / (2.0 * NORMAL_STEP);
+    let gy = (sample(x, y + NORMAL_STEP) - sample(x, y - NORMAL_STEP)) / (2.0 * NORMAL_STEP);
+    let length = (gx * gx + gy * gy + 1.0).sqrt();
+    [-gx / length, -gy / length, 1.0 / length]
+}
+
+// First point where the ray origin + t · direction meets the bilinear surface, as
+// [x, y, height, t]. Coordinates are [x, y, height] in cell and height units; t is in units of
+// the direction's length. The ray is clipped to the field, walked cell by cell, and each cell
+// solved exactly (a bilinear patch along a line is a quadratic). An origin below the surface
+// hits at once.
+pub(crate) fn raycast(height_field: &HeightField, origin: [f64; 3], direction: [f64; 3]) -> Option<[f64; 4]> {
+    let n = height_field.size();
+    if n < 2 {
+        return None;
+    }
+    let [ox, oy, oz] = origin;
+    let [dx, dy, dz] = direction;
+    if dx == 0.0 && dy == 0.0 && dz == 0.0 {
+        return None;
+    }
+
+    // Clip to the field's footprint [0, n - 1]² (slab test)
+    let limit = (n - 1) as f64;
+    let (mut t_start, mut t_end) = (0.0f64, f64::INFINITY);
+    for (o, d) in [(ox, dx), (oy, dy)] {
+        if d == 0.0 {
+            if o < 0.0 || o > limit {
+                return None;
+            }
+        } else {
+            let (a, b) = ((0.0 - o) / d, (limit - o) / d);
+            t_start = t_start.max(a.min(b));
+            t_end = t_end.min(a.max(b));
+        }
+    }
+    if t_start > t_end {
+        return None;
+    }
+
+    // Grid walk (Amanatides-Woo) over the cells between sample points
+    let cell_of = |p: f64, d: f64| {
+        let c = if d < 0.0 { p.ceil() - 1.0 } else { p.floor() };
+        c.clamp(0.0, limit - 1.0) as usize
+    };
+    let (mut cx, mut cy) = (cell_of(ox + dx * t_start, dx), cell_of(oy + dy * t_start, dy));
+    let next_crossing = |c: usize, o: f64, d: f64| {
+        if d > 0.0 {
+            (c as f64 + 1.0 - o) / d
+        } else if d < 0.0 {
+            (c as f64 - o) / d
+        } else {
+            f64::INFINITY
+        }
+    };
+    let mut t = t_start;
+    loop {
+        let t_exit = next_crossing(cx, ox, dx).min(next_crossing(cy, oy, dy)).min(t_end);
+        if let Some(hit) = intersect_cell(height_field, cx, cy, origin, direction, t, t_exit) {
+            let [x, y, z] = [ox + dx * hit, oy + dy * hit, oz + dz * hit];
+            return Some([x, y, z, hit]);
+        }
+        if t_exit >= t_end {
+            return None;
+        }
+        // Step into the neighbor across whichever boundary came first
+        if next_crossing(cx, ox, dx) <= next_crossing(cy, oy, dy) {
+            if (dx > 0.0 && cx + 2 > n - 1) || (dx < 0.0 && cx == 0) {
+                return None;
+            }
+            cx = if dx > 0.0 { cx + 1 } else { cx - 1 };
+        } else {
+            if (dy > 0.0 && cy + 2 > n - 1) || (dy < 0.0 && cy == 0) {
+                return None;
+            }
+            cy = if dy > 0.0 { cy + 1 } else { cy - 1 };
+        }
+        t = t_exit;
+    }
+}
+
+// Smallest t in [t0, t1] where the ray reaches the bilinear patch of cell (cx, cy)
+fn intersect_cell(
+    height_field: &HeightField,
+    cx: usize,
+    cy: usize,
+    [ox, oy, oz]: [f64; 3],
+    [dx, dy, dz]: [f64; 3],
+    t0: f64,
+    t1: f64,
+) -> Option<f64> {
+    let h00 = height_field.get(cx, cy) as f64;
+    let h10 = height_field.get(cx + 1, cy) as f64;
+    let h01 = height_field.get(cx, cy + 1) as f64;
+    let h11 = height_field.get(cx + 1, cy + 1) as f64;
+    let (a, b, c) = (h10 - h00, h01 - h00, h00 - h10 - h01 + h11);
+    let (u0, v0) = (ox - cx as f64, oy - cy as f64);
+
+    // Ray height above the surface: g(t) = p0 + p1 · t + p2 · t²
+    let p0 = oz - (h00 + a * u0 + b * v0 + c * u0 * v0);
+    let p1 = dz - (a * dx + b * dy + c * (u0 * dy + v0 * dx));
+    let p2 = -c * dx * dy;
+    let g = |t: f64| p0 + p1 * t + p2 * t * t;
+    if g(t0) <= 0.0 {
+        return Some(t0);
+    }
+
+    let roots = if p2.abs() < 1e-12 {
+        if p1 == 0.0 { Vec::new() } else { vec![-p0 / p1] }
+    } else {
+        let discriminant = p1 * p1 - 4.0 * p2 * p0;
+        if discriminant < 0.0 {
+            Vec::new()
+        } else {
+            let root = discriminant.sqrt();
+            vec![(-p1 - root) / (2.0 * p2), (-p1 + root) / (2.0 * p2)]
+        }
+    };
+    roots.into_iter().filter(|&t| t >= t0 && t <= t1).min_by(|a, b| a.total_cmp(b))
+}
@@ -0,0 +1,234 @@
+use crate::beaches::river_mouths;
+use crate::error::TerrainError;
+use crate::filters::distance_to_mask;
+use crate::height_field::HeightField;
+use crate::noise::{hash_cell, hash_to_unit};
+use crate::polygons::boundary_polylines;
+use crate::water_system::WaterFeatures;
+use wasm_bindgen::prelude::*;
+
+// Sea surface temperature (°C) below which corals don't grow, and from which they thrive
+const MIN_REEF_TEMPERATURE: f32 = 18.0;
+const FULL_REEF_TEMPERATURE: f32 = 23.0;
+// Share of max_depth down to which corals grow at full vigor; they thin out below it
+const OPTIMAL_DEPTH_SHARE: f32 = 0.3;
+// Distance (cells) along the coast over which river sediment smothers reefs
+const SEDIMENT_REACH: f32 = 24.0;
+// Width (cells) of a barrier reef along the edge of the sunlit shallows
+const BARRIER_BAND: f32 = 2.0;
+// Reef crest polyline simplification tolerance (cells)
+const CREST_TOLERANCE: f32 = 0.75;
+// Atoll shape, as shares of max_depth: depth of the rim, of the central lagoon, and the drop
+// per cell of the outer flank
+const ATOLL_RIM_DEPTH: f32 = 0.15;
+const ATOLL_LAGOON_DEPTH: f32 = 0.7;
+const ATOLL_FLANK_DROP: f32 = 0.25;
+// Seeded candidate sites tried per requested atoll
+const ATOLL_CANDIDATES: u32 = 64;
+
+#[wasm_bindgen]
+#[derive(Clone, Copy)]
+pub struct ReefParams {
+    pub sea_temperature: f32, // °C, annual mean at the surface
+    pub max_depth: f32,       // height units below sea level; too dark for corals below
+    pub fringe_width: f32,    // cells out from the shore that fringing reefs reach
+    pub lagoon_width: f32,    // cells of lagoon a barrier reef keeps between itself and the shore
+    pub atoll_count: u32,     // submerged volcanoes stamped in open water, each ringed by an atoll
+    pub atoll_radius: f32,    // cells, to the atoll rim
+    pub seed: u32,
+}
+
+#[wasm_bindgen]
+impl ReefParams {
+    #[wasm_bindgen(constructor)]
+    pub fn new(sea_temperature: f32, max_depth: f32) -> Self {
+        Self {
+            sea_temperature,
+            max_depth,
+            fringe_width: 4.0,
+            lagoon_width: 6.0,
+            atoll_count: 0,
+            atoll_radius: 10.0,
+            seed: 0,
+        }
+    }
+}
+
+// Reef cover per cell (0-1) and the reef crests: polylines along the seaward edge of the reefs,
+// where the waves break, as flat x, y pairs with cell centers on integers
+#[wasm_bindgen]
+#[derive(Clone)]
+pub struct ReefField {
+    reef: Vec<f32>,
+    crests: Vec<Vec<f32>>,
+    atolls: Vec<u32>,
+    size: usize,
+}
+
+#[wasm_bindgen]
+impl ReefField {
+    #[wasm_bindgen(getter)]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    #[wasm_bindgen]
+    pub fn get_reef_mask(&self) -> js_sys::Float32Array {
+        let array = js_sys::Float32Array::new_with_length(self.reef.len() as u32);
+        array.copy_from(&self.reef);
+        array
+    }
+
+    // One Float32Array of x, y pairs per crest
+    #[wasm_bindgen]
+    pub fn get_crests(&self) -> js_sys::Array {
+        let crests = js_sys::Array::new();
+        for crest in &self.crests {
+            let line = js_sys::Float32Array::new_with_length(crest.len() as u32);
+            line.copy_from(crest);
+            crests.push(&line);
+        }
+        crests
+    }
+
+    // Centers of the stamped atolls as flat x, y pairs
+    #[wasm_bindgen]
+    pub fn get_atolls(&self) -> js_sys::Uint32Array {
+        let array = js_sys::Uint32Array::new_with_length(self.atolls.len() as u32);
+        array.copy_from(&self.atolls);
+        array
+    }
+}
+
+// Raise a drowned volcano under each of `params.atoll_count` open-water sites far from land
+// and each other: a rim just below the surface around a shallow lagoon, with steep outer flanks.
+// Heights only ever go up. Returns the centers.
+fn stamp_atolls(height_field: &mut HeightField, sea_level: f32, distance_to_land: &[f32], params: &ReefParams) -> Vec<u32> {
+    let n = height_field.size();
+    let radius = params.atoll_radius.max(2.0);
+    let mut centers: Vec<(f32, f32)> = Vec::new();
+    let candidates = params.atoll_count.saturating_mul(ATOLL_CANDIDATES).min(i32::MAX as u32) as i32;
+    for i in 0..candidates {
+        if centers.len() >= params.atoll_count as usize {
+            break;
+        }
+        let x = (hash_to_unit(hash_cell(i, 0, params.seed)) * n as f32) as usize;
+        let y = (hash_to_unit(hash_cell(i, 1, params.seed)) * n as f32) as usize;
+        let idx = y * n + x;
+        let clear_of_edges = [x, y, n - 1 - x, n - 1 - y].iter().all(|&d| d as f32 > radius);
+        let open_water = distance_to_land[idx] > 3.0 * radius && sea_level - height_field.data()[idx] > params.max_depth;
+        let spaced = centers.iter().all(|&(cx, cy)| crate::det_math::hypot(cx - x as f32, cy - y as f32) > 4.0 * radius);
+        if clear_of_edges && open_water && spaced {
+            centers.push((x as f32, y as f32));
+        }
+    }
+
+    // Far enough out for the flank to reach the deepest seafloor on the map
+    let floor = height_field.data().iter().fold(sea_level, |m, &h| m.min(h));
+    let reach = radius + ((sea_level - floor) / params.max_depth.max(f32::EPSILON) - ATOLL_RIM_DEPTH).max(0.0) / ATOLL_FLANK_DROP;
+    for &(cx, cy) in &centers {
+        let (x0, x1) = ((cx - reach).max(0.0) as usize, ((cx + reach) as usize).min(n - 1));
+        let (y0, y1) = ((cy - reach).max(0.0) as usize, ((cy + reach) as usize).min(n - 1));
+        for y in y0..=y1 {
+            for x in x0..=x1 {
+                let r = crate::det_math::hypot(x as f32 - cx, y as f32 - cy);
+                let share = if r <= radius {
+                    // Flat lagoon floor rising steeply to the rim
+                    let t = (r / radius).powi(4);
+                    ATOLL_LAGOON_DEPTH + (ATOLL_RIM_DEPTH - ATOLL_LAGOON_DEPTH) * t
+                } else {
+                    ATOLL_RIM_DEPTH + (r - radius) * ATOLL_FLANK_DROP
+                };
+                let target = sea_level - share * params.max_depth;
+                let h = height_field.get(x, y);
+                if target > h {
+                    height_field.set(x, y, target);
+                }
+            }
+        }
+    }
+    centers.iter().flat_map(|&(x, y)| [x as u32, y as u32]).collect()
+}
+
+// Corals need warm, clear, shallow water: cover falls off with cold water, depth and nearby
+// river mouths. Fringing reefs hug the shore; barrier reefs follow the seaward edge of the
+// shallows where a lagoon separates them from land, which also rings any stamped atolls.
+pub fn reefs(height_field: &mut HeightField, water_features: &WaterFeatures, sea_level: f32, params: &ReefParams) -> Result<ReefField, TerrainError> {
+    let n = height_field.size();
+    if water_features.size() != n {
+        return Err(TerrainError::SizeMismatch { expected: n, actual: water_features.size() });
+    }
+    if n == 0 {
+        return Ok(ReefField { reef: Vec::new(), crests: Vec::new(), atolls: Vec::new(), size: 0 });
+    }
+    let land: Vec<bool> = height_field.data().iter().map(|&h| h > sea_level).collect();
+    let distance_to_land = distance_to_mask(&land, n);
+    let atolls = if params.atoll_count > 0 { stamp_atolls(height_field, sea_level, &distance_to_land, params) } else { Vec::new() };
+
+    let warmth = ((params.sea_temperature - MIN_REEF_TEMPERATURE) / (FULL_REEF_TEMPERATURE - MIN_REEF_TEMPERATURE)).clamp(0.0, 1.0);
+    let data = height_field.data();
+    let depth: Vec<f32> = data.iter().map(|&h| sea_level - h).collect();
+    let optimal = params.max_depth * OPTIMAL_DEPTH_SHARE;
+    let deep: Vec<bool> = depth.iter().map(|&d| d >= params.max_depth).collect();
+    let distance_to_deep = distance_to_mask(&deep, n);
+    // Water below the sunlit shallows: open sea, but also atoll lagoons
+    let dim: Vec<bool> = depth.iter().map(|&d| d > optimal).collect();
+    let distance_to_dim = distance_to_mask(&dim, n);
+    let sea: Vec<bool> = land.iter().map(|&l| !l).collect();
+    let distance_to_mouth = distance_to_mask(&river_mouths(water_features.river_mask(), &sea, n), n);
+
+    let reef: Vec<f32> = (0..n * n)
+        .map(|idx| {
+            if warmth <= 0.0 || land[idx] || deep[idx] {
+                return 0.0;
+            }
+            let fringing = distance_to_land[idx] <= params.fringe_width;
+            let barrier = !dim[idx] && distance_to_dim[idx] <= BARRIER_BAND && distance_to_land[idx] >= params.lagoon_width;
+            if !fringing && !barrier {
+                return 0.0;
+            }
+            let light = 1.0 - ((depth[idx] - optimal) / (params.max_depth - optimal).max(f32::EPSILON)).clamp(0.0, 1.0);
+            let clarity = 1.0 - crate::det_math::exp(-distance_to_mouth[idx] / SEDIMENT_REACH);
+            warmth * light * clarity
+        })
+        .collect();
+
+    // Crests run along the reef edges that face the open sea: where the water beside the reef
+    // lies closer to deep water than the reef itself, which leaves out shores and lagoons
+    let mask: Vec<bool> = reef.iter().map(|&r| r > 0.5).collect();
+    let seaward = |x: f32, y: f32| {
+        // Outline vertices sit on cell corners; compare the four cells around the corner
+        let (cx, cy) = ((x + 0.5) as i32, (y + 0.5) as i32);
+        let (mut reef_side, mut water_side) = (f32::INFINITY, f32::INFINITY);
+        for (dx, dy) in [(-1, -1), (0, -1), (-1, 0), (0, 0)] {
+            let (px, py) = (cx + dx, cy + dy);
+            if px < 0 || py < 0 || px as usize >= n || py as usize >= n {
+                continue;
+            }
+            let idx = py as usize * n + px as usize;
+            if mask[idx] {
+                reef_side = reef_side.min(distance_to_deep[idx]);
+            } else if !land[idx] {
+                water_side = water_side.min(distance_to_deep[idx]);
+            }
+        }
+        water_side < reef_side
+    };
+    let crests = boundary_polylines(&mask, n, seaward, CREST_TOLERANCE);
+
+    Ok(ReefField { reef, crests, atolls, size: n })
+}
+
+// Fringing and barrier reefs for warm seas, plus `params.atoll_count` atolls stamped into open
+// water. River mouths in `water_features` keep reefs away from their sediment; pass an empty
+// `new WaterFeatures(size)` to ignore rivers. Throws when the water features are for a
+// different map size.
+#[wasm_bindgen]
+pub fn generate_reefs(
+    height_field: &mut HeightField,
+    water_features: &WaterFeatures,
+    sea_level: f32,
+    params: &ReefParams,
+) -> Result<ReefField, JsValue> {
+    Ok(reefs(height_field, water_features, sea_level, params)?)
+}
@@ -0,0 +1,91 @@
+use crate::config::TerrainConfig;
+use crate::error::TerrainError;
+use crate::height_field::HeightField;
+use crate::units::WorldScale;
+use crate::TerrainGenerationResult;
+use wasm_bindgen::prelude::*;
+
+// Window of the world a config generates, in the noise coordinates of an unwindowed map: the
+// top-left corner (u, v) and the side, as fractions of that map's side
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub(crate) struct WorldRegion {
+    pub u: f32,
+    pub v: f32,
+    pub extent: f32,
+}
+
+impl WorldRegion {
+    pub(crate) const FULL: WorldRegion = WorldRegion { u: 0.0, v: 0.0, extent: 1.0 };
+
+    // The part of this region covered by `size` cells at (x, y) of an `n`-cell map of it
+    fn sub_region(&self, x: u32, y: u32, size: u32, n: usize) -> WorldRegion {
+        let cell = self.extent / n as f32;
+        WorldRegion { u: self.u + x as f32 * cell, v: self.v + y as f32 * cell, extent: size as f32 * cell }
+    }
+}
+
+// A window cut out of a generated map together with the config that regenerates it on its own
+#[wasm_bindgen]
+pub struct RegionExport {
+    config: TerrainConfig,
+    height_field: HeightField,
+}
+
+#[wasm_bindgen]
+impl RegionExport {
+    #[wasm_bindgen(getter)]
+    pub fn config(&self) -> TerrainConfig {
+        self.config
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn height_field(&self) -> HeightField {
+        self.height_field.clone()
+    }
+}
+
+// Crop `rect` ([x, y, width, height] in cells, square) out of `result`, generated from `config`,
+// and derive a config for just that part of the world: same seed and biome, the window as its
+// world region, the resolved sea level as explicit vertical units and the cell size shrunk to
+// match. Generating the derived config gives the region at the full map size, so it can be
+// zoomed into and refined; the relief noise lines up with the crop exactly, while erosion,
+// water and later stages only see the region. Throws if the rect is not a square on the map.
+#[wasm_bindgen]
+pub fn extract_world_region(result: &TerrainGenerationResult, config: &TerrainConfig, rect: &[u32]) -> Result<RegionExport, JsValue> {
+    let source = result.height_field_ref();
+    let n = source.size();
+    let [x, y, width, height] = <[u32; 4]>::try_from(rect).map_err(|_| {
+        TerrainError::InvalidParameter(format!("rect must be 4 numbers [x, y, width, height], got {:?}", rect))
+    })?;
+    let inside = x as usize + width as usize <= n && y as usize + height as usize <= n;
+    if width == 0 || width != height || !inside {
+        return Err(TerrainError::InvalidParameter(format!(
+            "rect must be a non-empty square within the {}x{} map, got {:?}",
+            n, n, rect
+        ))
+        .into());
+    }
+
+    let size = width as usize;
+    let mut height_field = HeightField::new(size);
+    for row in 0..size {
+        let start = (y as usize + row) * n + x as usize;
+        height_field.data_mut()[row * size..(row + 1) * size].copy_from_slice(&source.data()[start..start + size]);
+    }
+
+    let units = result.vertical_units();
+    let parent_cell_size = match config.cell_size_m {
+        Some(m) => Some(m),
+        None if config.auto_scale => Some(WorldScale::for_resolution(n, units.height_scale_m).cell_size_m),
+        None => None,
+    };
+    let region = config.region.unwrap_or(WorldRegion::FULL).sub_region(x, y, width, n);
+    let derived = TerrainConfig {
+        land_fraction: None,
+        vertical_units: Some(units),
+        cell_size_m: parent_cell_size.map(|m| m * size as f32 / n as f32),
+        region: Some(region),
+        ..*config
+    };
+    Ok(RegionExport { config: derived, height_field })
+}
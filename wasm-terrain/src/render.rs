@@ -0,0 +1,304 @@
+use crate::filters::gradient_at;
+use crate::height_field::HeightField;
+use crate::water_system::WaterFeatures;
+use wasm_bindgen::prelude::*;
+
+// Sun from the north-west at 45° elevation, the cartographic convention
+const SUN_DIRECTION: [f32; 3] = [-0.5, -0.5, std::f32::consts::FRAC_1_SQRT_2];
+// How much of the hillshade darkening is applied on top of the tint
+const HILLSHADE_MIX: f32 = 0.6;
+
+const SEA_SHALLOW: [f32; 3] = [70.0, 140.0, 190.0];
+const SEA_DEEP: [f32; 3] = [20.0, 50.0, 110.0];
+const RIVER_COLOR: [f32; 3] = [60.0, 130.0, 210.0];
+
+// Hypsometric tint stops as [height, r, g, b] relative to sea level (0) and the highest point (1)
+const DEFAULT_STOPS: [[f32; 4]; 6] = [
+    [0.0, 214.0, 200.0, 150.0],
+    [0.05, 110.0, 160.0, 80.0],
+    [0.3, 70.0, 125.0, 55.0],
+    [0.55, 140.0, 120.0, 80.0],
+    [0.8, 125.0, 115.0, 110.0],
+    [1.0, 245.0, 245.0, 250.0],
+];
+
+// Default palette as flat [height, r, g, b, ...] stops spanning sea level to the field maximum
+#[wasm_bindgen]
+pub fn default_relief_palette(height_field: &HeightField, sea_level: f32) -> Vec<f32> {
+    let max = height_field.data().iter().fold(sea_level, |m, &h| m.max(h));
+    let span = (max - sea_level).max(f32::EPSILON);
+    DEFAULT_STOPS
+        .iter()
+        .flat_map(|stop| [sea_level + stop[0] * span, stop[1], stop[2], stop[3]])
+        .collect()
+}
+
+// Linear interpolation between palette stops (heights must be ascending)
+fn palette_color(stops: &[[f32; 4]], height: f32) -> [f32; 3] {
+    let first = stops[0];
+    if height <= first[0] {
+        return [first[1], first[2], first[3]];
+    }
+    for pair in stops.windows(2) {
+        let (a, b) = (pair[0], pair[1]);
+        if height <= b[0] {
+            let t = if b[0] > a[0] { (height - a[0]) / (b[0] - a[0]) } else { 1.0 };
+            return [
+                a[1] + (b[1] - a[1]) * t,
+                a[2] + (b[2] - a[2]) * t,
+                a[3] + (b[3] - a[3]) * t,
+            ];
+        }
+    }
+    let last = stops[stops.len() - 1];
+    [last[1], last[2], last[3]]
+}
+
+fn mix(a: [f32; 3], b: [f32; 3], t: f32) -> [f32; 3] {
+    [a[0] + (b[0] - a[0]) * t, a[1] + (b[1] - a[1]) * t, a[2] + (b[2] - a[2]) * t]
+}
+
+// Lambertian hillshade in [0, 1]; `z_scale` multiplies height gradients to exaggerate relief
+pub(crate) fn hillshade_at(height_field: &HeightField, x: usize, y: usize, z_scale: f32) -> f32 {
+    let (gx, gy) = gradient_at(height_field, x, y);
+    let nx = -gx * z_scale;
+    let ny = -gy * z_scale;
+    let len = (nx * nx + ny * ny + 1.0).sqrt();
+    ((nx * SUN_DIRECTION[0] + ny * SUN_DIRECTION[1] + SUN_DIRECTION[2]) / len).max(0.0)
+}
+
+pub fn color_relief_pixels(
+    height_field: &HeightField,
+    palette: &[f32],
+    water_features: Option<&WaterFeatures>,
+    sea_level: f32,
+    z_scale: f32,
+) -> Vec<u8> {
+    let n = height_field.size();
+    let data = height_field.data();
+
+    let stops: Vec<[f32; 4]> = if palette.len() >= 4 {
+        palette.chunks_exact(4).map(|c| [c[0], c[1], c[2], c[3]]).collect()
+    } else {
+        let fallback = default_relief_palette(height_field, sea_level);
+        fallback.chunks_exact(4).map(|c| [c[0], c[1], c[2], c[3]]).collect()
+    };
+    let min = data.iter().fold(sea_level, |m, &h| m.min(h));
+    let depth_span = (sea_level - min).max(f32::EPSILON);
+    let flat_shade = SUN_DIRECTION[2];
+
+    let mut pixels = vec![0u8; n * n * 4];
+    for y in 0..n {
+        for x in 0..n {
+            let idx = y * n + x;
+            let h = data[idx];
+            let is_sea = h <= sea_level;
+
+            let mut color = if is_sea {
+                mix(SEA_SHALLOW, SEA_DEEP, ((sea_level - h) / depth_span).min(1.0))
+            } else {
+                // Shade relative to flat ground so plains keep their palette color
+                let shade = hillshade_at(height_field, x, y, z_scale) / flat_shade;
+                let light = 1.0 + (shade - 1.0) * HILLSHADE_MIX;
+                palette_color(&stops, h).map(|c| c * light)
+            };
+
+            if let Some(features) = water_features {
+                let water = features.water_mask().get(idx).copied().unwrap_or(0.0);
+                let river = features.river_mask().get(idx).copied().unwrap_or(0.0);
+                if !is_sea && water > 0.5 {
+                    color = mix(color, SEA_SHALLOW, 0.8);
+                }
+                if river > 0.0 {
+                    color = mix(color, RIVER_COLOR, river.min(1.0));
+                }
+            }
+
+            let p = idx * 4;
+            pixels[p] = color[0].clamp(0.0, 255.0) as u8;
+            pixels[p + 1] = color[1].clamp(0.0, 255.0) as u8;
+            pixels[p + 2] = color[2].clamp(0.0, 255.0) as u8;
+            pixels[p + 3] = 255;
+        }
+    }
+
+    pixels
+}
+
+// Minimap texture: hypsometric tint + hillshade + water/river overlay as RGBA8 rows.
+// `palette` is flat [height, r, g, b, ...] stops (0-255 colors); empty uses the default.
+// Pass an empty `new WaterFeatures(size)` to render without water overlays.
+#[wasm_bindgen]
+pub fn render_color_relief(
+    height_field: &HeightField,
+    palette: &[f32],
+    water_features: &WaterFeatures,
+    sea_level: f32,
+    z_scale: f32,
+) -> js_sys::Uint8Array {
+    let pixels = color_relief_pixels(height_field, palette, Some(water_features), sea_level, z_scale);
+    let array = js_sys::Uint8Array::new_with_length(pixels.len() as u32);
+    array.copy_from(&pixels);
+    array
+}
+
+// Plan curvature (per cell: one over the radius of the contour bend) that saturates the alpha
+// channel of the flow map
+const FLOW_CURVATURE_SCALE: f32 = 0.25;
+
+// Byte for a value in [-1, 1], 128 at 0
+fn signed_byte(v: f32) -> u8 {
+    (128.0 + v.clamp(-1.0, 1.0) * 127.0).round() as u8
+}
+
+// RGBA8 cliff flow map. RG is the unit downslope direction (x, y mapped from [-1, 1] to 0-255,
+// 128 = none), B how much of a cliff the cell is (slope above `slope_threshold`, feathered by
+// `feather`), and A the plan curvature (below 128 flow converges into gullies, above it spreads
+// over noses). Cells with no cliff weight get a neutral 128, 128, 0, 128.
+pub fn cliff_flow_pixels(height_field: &HeightField, slope_threshold: f32, feather: f32) -> Vec<u8> {
+    let n = height_field.size();
+    let at = |x: i32, y: i32| height_field.get_clamped(x, y);
+
+    let mut pixels = vec![0u8; n * n * 4];
+    for y in 0..n {
+        for x in 0..n {
+            let p = (y * n + x) * 4;
+            let (fx, fy) = gradient_at(height_field, x, y);
+            let slope = (fx * fx + fy * fy).sqrt();
+            let cliff = crate::analysis::band_weight(slope, slope_threshold, f32::MAX, feather);
+            if cliff <= 0.0 || slope <= f32::EPSILON {
+                pixels[p..p + 4].copy_from_slice(&[128, 128, 0, 128]);
+                continue;
+            }
+
+            // Second derivatives for the plan curvature, taken in the same pass as the aspect
+            let (xi, yi) = (x as i32, y as i32);
+            let h = at(xi, yi);
+            let fxx = at(xi + 1, yi) - 2.0 * h + at(xi - 1, yi);
+            let fyy = at(xi, yi + 1) - 2.0 * h + at(xi, yi - 1);
+            let fxy = (at(xi + 1, yi + 1) - at(xi + 1, yi - 1) - at(xi - 1, yi + 1) + at(xi - 1, yi - 1)) * 0.25;
+            let plan = (fxx * fy * fy - 2.0 * fxy * fx * fy + fyy * fx * fx) / (slope * slope * slope);
+
+            pixels[p] = signed_byte(-fx / slope);
+            pixels[p + 1] = signed_byte(-fy / slope);
+            pixels[p + 2] = (cliff * 255.0).round() as u8;
+            pixels[p + 3] = signed_byte(-plan / FLOW_CURVATURE_SCALE);
+        }
+    }
+
+    pixels
+}
+
+// Flow / orientation texture for cliff shaders: rock striations and water streaks follow RG,
+// streaks gather where A is low. See cliff_flow_pixels for the channel layout.
+#[wasm_bindgen]
+pub fn export_cliff_flow_map(height_field: &HeightField, slope_threshold: f32, feather: f32) -> js_sys::Uint8Array {
+    let pixels = cliff_flow_pixels(height_field, slope_threshold, feather);
+    let array = js_sys::Uint8Array::new_with_length(pixels.len() as u32);
+    array.copy_from(&pixels);
+    array
+}
+
+// Byte layouts for uploading heights as an RGBA8 texture where float textures aren't available
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum HeightEncoding {
+    // The f32 bit pattern, little-endian: R holds the lowest byte, A the sign and exponent
+    Float32Bits = 0,
+    // Height scaled to 16 bits over [min_height, max_height]: R high byte, G low byte, B 0, A 255
+    Rg16 = 1,
+    // Height scaled to 24 bits over [min_height, max_height], split from high to low over R, G
+    // and B, A 255; decode as (R * 65536 + G * 256 + B) / 16777215
+    SplitHiLo = 2,
+}
+
+// RGBA8 pixels of the heights in `encoding`; the range only matters to the scaled encodings,
+// where heights outside it are clamped
+pub fn height_rgba_pixels(height_field: &HeightField, encoding: HeightEncoding, min_height: f32, max_height: f32) -> Vec<u8> {
+    let span = (max_height - min_height).max(f32::EPSILON);
+    let scaled = |h: f32, levels: f64| (((h - min_height) / span).clamp(0.0, 1.0) as f64 * levels).round() as u32;
+    let mut pixels = Vec::with_capacity(height_field.data().len() * 4);
+    for &h in height_field.data() {
+        match encoding {
+            HeightEncoding::Float32Bits => pixels.extend_from_slice(&h.to_le_bytes()),
+            HeightEncoding::Rg16 => {
+                let v = scaled(h, 65535.0);
+                pixels.extend_from_slice(&[(v >> 8) as u8, v as u8, 0, 255]);
+            }
+            HeightEncoding::SplitHiLo => {
+                let v = scaled(h, 16_777_215.0);
+                pixels.extend_from_slice(&[(v >> 16) as u8, (v >> 8) as u8, v as u8, 255]);
+            }
+        }
+    }
+    pixels
+}
+
+fn check_height_range(min_height: f32, max_height: f32) -> Result<(), crate::error::TerrainError> {
+    if min_height.is_finite() && max_height.is_finite() && max_height > min_height {
+        return Ok(());
+    }
+    Err(crate::error::TerrainError::InvalidParameter(format!(
+        "height range must be finite with max above min, got [{}, {}]",
+        min_height, max_height
+    )))
+}
+
+// Heights as RGBA8 rows ready for texImage2D on WebGL1, without re-encoding in JS. The scaled
+// encodings map [min_height, max_height] onto their full range; throws if it is empty or not
+// finite. Float32Bits ignores the range.
+#[wasm_bindgen]
+pub fn encode_rgba_height(
+    height_field: &HeightField,
+    encoding: HeightEncoding,
+    min_height: f32,
+    max_height: f32,
+) -> Result<js_sys::Uint8Array, JsValue> {
+    if encoding != HeightEncoding::Float32Bits {
+        check_height_range(min_height, max_height)?;
+    }
+    let pixels = height_rgba_pixels(height_field, encoding, min_height, max_height);
+    let array = js_sys::Uint8Array::new_with_length(pixels.len() as u32);
+    array.copy_from(&pixels);
+    Ok(array)
+}
+
+// RGBA8 pixels with the height scaled to 16 bits over [min_height, max_height] in R (high byte)
+// and A (low byte), and the surface normal octahedron-encoded in G and B. Normals of a height
+// field always face up, so the upper half of the octahedron is enough: G and B hold
+// p = (nx, ny) / (|nx| + |ny| + nz) as signed bytes (128 = 0), and the shader rebuilds
+// n = normalize(p.x, p.y, 1 - |p.x| - |p.y|). `z_scale` multiplies the height gradients, like
+// the hillshade's.
+pub fn normal_height_pixels(height_field: &HeightField, min_height: f32, max_height: f32, z_scale: f32) -> Vec<u8> {
+    let n = height_field.size();
+    let span = (max_height - min_height).max(f32::EPSILON);
+    let mut pixels = vec![0u8; n * n * 4];
+    for y in 0..n {
+        for x in 0..n {
+            let p = (y * n + x) * 4;
+            let h = (((height_field.get(x, y) - min_height) / span).clamp(0.0, 1.0) * 65535.0).round() as u32;
+            let (gx, gy) = gradient_at(height_field, x, y);
+            let (nx, ny) = (-gx * z_scale, -gy * z_scale);
+            let l1 = nx.abs() + ny.abs() + 1.0;
+            pixels[p..p + 4].copy_from_slice(&[(h >> 8) as u8, signed_byte(nx / l1), signed_byte(ny / l1), h as u8]);
+        }
+    }
+    pixels
+}
+
+// Height and normals packed into one RGBA8 texture in a single pass, for terrain shaders that
+// would otherwise sample a height texture and a normal map. See normal_height_pixels for the
+// layout; throws if the height range is empty or not finite.
+#[wasm_bindgen]
+pub fn export_normal_height_map(
+    height_field: &HeightField,
+    min_height: f32,
+    max_height: f32,
+    z_scale: f32,
+) -> Result<js_sys::Uint8Array, JsValue> {
+    check_height_range(min_height, max_height)?;
+    let pixels = normal_height_pixels(height_field, min_height, max_height, z_scale);
+    let array = js_sys::Uint8Array::new_with_length(pixels.len() as u32);
+    array.copy_from(&pixels);
+    Ok(array)
+}
@@ -0,0 +1,163 @@
+use crate::height_field::HeightField;
+use crate::water_system::{calculate_flow_accumulation, flow_receivers};
+use wasm_bindgen::prelude::*;
+
+// Depth (height units) channels are cut below the resampled ground, so the bilinear
+// surface beside a re-drawn channel does not capture its flow
+const CHANNEL_INCISION: f32 = 1e-3;
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ResampleKernel {
+    Nearest = 0,
+    Bilinear = 1,
+    Bicubic = 2, // Catmull-Rom; may overshoot slightly at sharp breaks
+}
+
+impl ResampleKernel {
+    // Half width of the kernel at unit scale (cells)
+    fn radius(self) -> f32 {
+        match self {
+            ResampleKernel::Nearest => 0.5,
+            ResampleKernel::Bilinear => 1.0,
+            ResampleKernel::Bicubic => 2.0,
+        }
+    }
+
+    fn weight(self, t: f32) -> f32 {
+        let t = t.abs();
+        match self {
+            ResampleKernel::Nearest => 1.0,
+            ResampleKernel::Bilinear => (1.0 - t).max(0.0),
+            ResampleKernel::Bicubic if t < 1.0 => 1.5 * t * t * t - 2.5 * t * t + 1.0,
+            ResampleKernel::Bicubic if t < 2.0 => -0.5 * t * t * t + 2.5 * t * t - 4.0 * t + 2.0,
+            ResampleKernel::Bicubic => 0.0,
+        }
+    }
+}
+
+// First source cell and normalized weights of the samples behind one output cell centered on
+// `center` (source cells). When minifying, `scale` (source cells per output cell, >= 1)
+// stretches the kernel so every source cell contributes instead of aliasing.
+fn taps(kernel: ResampleKernel, center: f32, scale: f32) -> (i32, Vec<f32>) {
+    if kernel == ResampleKernel::Nearest {
+        return (center.round() as i32, vec![1.0]);
+    }
+    let support = kernel.radius() * scale;
+    let first = (center - support).ceil() as i32;
+    let last = (center + support).floor() as i32;
+    let mut weights: Vec<f32> = (first..=last).map(|i| kernel.weight((i as f32 - center) / scale)).collect();
+    let sum: f32 = weights.iter().sum();
+    if sum.abs() > f32::EPSILON {
+        weights.iter_mut().for_each(|w| *w /= sum);
+    }
+    (first, weights)
+}
+
+// Kernel-weighted samples of `data` (a size × size raster) at the cell-center coordinates
+// xs × ys in one separable pass, row-major with xs.len() values per row. `scale` (source cells
+// per output cell along x and y) widens the kernel when minifying. Samples past the field edge
+// repeat the edge.
+fn sample_grid(data: &[f32], size: usize, xs: &[f32], ys: &[f32], (scale_x, scale_y): (f32, f32), kernel: ResampleKernel) -> Vec<f32> {
+    let (width, height) = (xs.len(), ys.len());
+    let mut out = vec![0.0f32; width * height];
+    if size == 0 || width == 0 || height == 0 {
+        return out;
+    }
+    let column_taps: Vec<(i32, Vec<f32>)> = xs.iter().map(|&x| taps(kernel, x, scale_x)).collect();
+    let row_taps: Vec<(i32, Vec<f32>)> = ys.iter().map(|&y| taps(kernel, y, scale_y)).collect();
+    let clamp = |i: i32| (i.max(0) as usize).min(size - 1);
+
+    // Horizontal pass over just the source rows the vertical taps reach
+    let row_min = row_taps.iter().map(|(first, _)| *first).min().unwrap_or(0);
+    let row_max = row_taps.iter().map(|(first, w)| first + w.len() as i32 - 1).max().unwrap_or(0);
+    let rows = (row_max - row_min + 1).max(0) as usize;
+    let mut horizontal = vec![0.0f32; rows * width];
+    for r in 0..rows {
+        let row = &data[clamp(row_min + r as i32) * size..][..size];
+        for (i, (first, weights)) in column_taps.iter().enumerate() {
+            horizontal[r * width + i] = weights.iter().enumerate().map(|(k, w)| row[clamp(first + k as i32)] * w).sum();
+        }
+    }
+
+    for (j, (first, weights)) in row_taps.iter().enumerate() {
+        for i in 0..width {
+            out[j * width + i] = weights
+                .iter()
+                .enumerate()
+                .map(|(k, w)| horizontal[(first + k as i32 - row_min) as usize * width + i] * w)
+                .sum();
+        }
+    }
+    out
+}
+
+// Resample [x, x + width] × [y, y + height] (cell-center coordinates, fractional allowed) to a
+// `size` × `size` field in one separable pass. Output corners land on the rect corners, so the
+// rect [0, 0, n - 1, n - 1] with Bilinear matches resample_to. The rect is clamped to the
+// field first, which also bounds the kernel footprint when minifying.
+pub(crate) fn resample_window(height_field: &HeightField, [x, y, width, height]: [f32; 4], size: usize, kernel: ResampleKernel) -> HeightField {
+    let n = height_field.size();
+    let mut out = HeightField::new(size);
+    if size == 0 || n == 0 {
+        return out;
+    }
+    let edge = (n - 1) as f32;
+    let (x0, y0) = (x.clamp(0.0, edge), y.clamp(0.0, edge));
+    let (x1, y1) = ((x + width).clamp(0.0, edge), (y + height).clamp(0.0, edge));
+    let steps = (size.max(2) - 1) as f32;
+    let (step_x, step_y) = ((x1 - x0) / steps, (y1 - y0) / steps);
+    let xs: Vec<f32> = (0..size).map(|i| x0 + i as f32 * step_x).collect();
+    let ys: Vec<f32> = (0..size).map(|j| y0 + j as f32 * step_y).collect();
+    let scale = (step_x.abs().max(1.0), step_y.abs().max(1.0));
+    out.data_mut().copy_from_slice(&sample_grid(height_field.data(), n, &xs, &ys, scale, kernel));
+    out
+}
+
+// Cells [x, x + width) × [y, y + height) of the `size` × `size` raster resampled to
+// `new_size` × `new_size` (the cell grid of resample_to), row-major with `width` values per
+// row. Only the region is computed, so tiles and crops never need the whole resampled field;
+// shrinking filters like resample_window.
+pub(crate) fn resample_region(data: &[f32], size: usize, new_size: usize, [x, y, width, height]: [usize; 4]) -> Vec<f32> {
+    if size == 0 {
+        return vec![0.0; width * height];
+    }
+    let last = (new_size.max(2) - 1) as f32;
+    let coords = |start: usize, len: usize| -> Vec<f32> { (start..start + len).map(|i| (i * (size - 1)) as f32 / last).collect() };
+    let scale = ((size - 1) as f32 / last).max(1.0);
+    sample_grid(data, size, &coords(x, width), &coords(y, height), (scale, scale), ResampleKernel::Bilinear)
+}
+
+// Upscale with resample_to, then re-inscribe the D8 channel network of the source (cells
+// draining at least `channel_area` source cells) as one-cell lines at the new resolution.
+// Every link runs from a cell center to its receiver with the bed interpolated between their
+// heights (less CHANNEL_INCISION), so channels keep falling and stay 8-connected instead of being blurred into
+// closed hollows. Downscaling is plain resample_to.
+pub(crate) fn resample_preserving_drainage(height_field: &HeightField, new_size: usize, channel_area: f32) -> HeightField {
+    let n = height_field.size();
+    let mut out = height_field.resample_to(new_size);
+    if new_size <= n || n < 2 {
+        return out;
+    }
+    let data = height_field.data();
+    let receivers = flow_receivers(height_field);
+    let flow = calculate_flow_accumulation(height_field);
+    let scale = (new_size - 1) as f32 / (n - 1) as f32;
+
+    let out_data = out.data_mut();
+    for idx in (0..n * n).filter(|&i| flow[i] >= channel_area && receivers[i] != usize::MAX) {
+        let to = receivers[idx];
+        let (ax, ay) = ((idx % n) as f32 * scale, (idx / n) as f32 * scale);
+        let (bx, by) = ((to % n) as f32 * scale, (to / n) as f32 * scale);
+        let steps = (bx - ax).abs().max((by - ay).abs()).ceil().max(1.0) as usize;
+        for s in 0..=steps {
+            let t = s as f32 / steps as f32;
+            let x = (ax + (bx - ax) * t).round() as usize;
+            let y = (ay + (by - ay) * t).round() as usize;
+            let bed = data[idx] + (data[to] - data[idx]) * t - CHANNEL_INCISION;
+            let cell = &mut out_data[y * new_size + x];
+            *cell = cell.min(bed);
+        }
+    }
+    out
+}
@@ -0,0 +1,320 @@
+use crate::analysis::label_components;
+use crate::height_field::HeightField;
+use crate::water_system::{river_paths, WaterFeatures};
+use crate::water_volumes::{probe_bank, standing_water};
+use std::collections::HashMap;
+use wasm_bindgen::prelude::*;
+
+// Channels this short that end in another channel are side arms of a wide river, not reaches
+const MIN_REACH_CELLS: usize = 4;
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum RiverNodeKind {
+    Source = 0,
+    Confluence = 1,
+    Lake = 2,
+    Mouth = 3,  // a river reaching the sea
+    Outlet = 4, // a river leaving the map or ending in a pit
+}
+
+impl RiverNodeKind {
+    fn name(self) -> &'static str {
+        match self {
+            RiverNodeKind::Source => "source",
+            RiverNodeKind::Confluence => "confluence",
+            RiverNodeKind::Lake => "lake",
+            RiverNodeKind::Mouth => "mouth",
+            RiverNodeKind::Outlet => "outlet",
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct RiverNode {
+    pub kind: RiverNodeKind,
+    pub x: f32,
+    pub y: f32,
+    pub height: f32, // bed height; roughly the surface for lakes
+}
+
+impl RiverNode {
+    // Convert to JS object for interop
+    pub fn to_js_object(&self) -> js_sys::Object {
+        let obj = js_sys::Object::new();
+
+        js_sys::Reflect::set(&obj, &"kind".into(), &(self.kind as u8).into()).unwrap();
+        js_sys::Reflect::set(&obj, &"x".into(), &self.x.into()).unwrap();
+        js_sys::Reflect::set(&obj, &"y".into(), &self.y.into()).unwrap();
+        js_sys::Reflect::set(&obj, &"height".into(), &self.height.into()).unwrap();
+
+        obj
+    }
+}
+
+// One navigable reach between two nodes, pointing downstream
+#[derive(Clone)]
+pub struct RiverEdge {
+    pub from: u32,
+    pub to: u32,
+    pub length: f32,   // cells along the channel
+    pub width: f32,    // mean channel width (cells)
+    pub depth: f32,    // mean depth below the lower bank (height units)
+    pub gradient: f32, // mean bed drop per cell of length
+    points: Vec<f32>,  // centerline as flat x, y pairs, upstream first
+}
+
+impl RiverEdge {
+    // Convert to JS object for interop
+    pub fn to_js_object(&self) -> js_sys::Object {
+        let obj = js_sys::Object::new();
+
+        let points = js_sys::Float32Array::new_with_length(self.points.len() as u32);
+        points.copy_from(&self.points);
+
+        js_sys::Reflect::set(&obj, &"from".into(), &self.from.into()).unwrap();
+        js_sys::Reflect::set(&obj, &"to".into(), &self.to.into()).unwrap();
+        js_sys::Reflect::set(&obj, &"length".into(), &self.length.into()).unwrap();
+        js_sys::Reflect::set(&obj, &"width".into(), &self.width.into()).unwrap();
+        js_sys::Reflect::set(&obj, &"depth".into(), &self.depth.into()).unwrap();
+        js_sys::Reflect::set(&obj, &"gradient".into(), &self.gradient.into()).unwrap();
+        js_sys::Reflect::set(&obj, &"points".into(), &points).unwrap();
+
+        obj
+    }
+}
+
+// Waterways as a graph for boats and AI pathing. Coordinates are in cells with cell centers on
+// integers; edges follow the flow, so upstream travel walks them backwards.
+#[wasm_bindgen]
+#[derive(Clone)]
+pub struct RiverGraph {
+    nodes: Vec<RiverNode>,
+    edges: Vec<RiverEdge>,
+}
+
+#[wasm_bindgen]
+impl RiverGraph {
+    #[wasm_bindgen(getter)]
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn edge_count(&self) -> usize {
+        self.edges.len()
+    }
+
+    #[wasm_bindgen]
+    pub fn get_nodes(&self) -> js_sys::Array {
+        let nodes = js_sys::Array::new();
+        for node in &self.nodes {
+            nodes.push(&node.to_js_object());
+        }
+        nodes
+    }
+
+    #[wasm_bindgen]
+    pub fn get_edges(&self) -> js_sys::Array {
+        let edges = js_sys::Array::new();
+        for edge in &self.edges {
+            edges.push(&edge.to_js_object());
+        }
+        edges
+    }
+
+    // {"nodes": [{kind, x, y, height}], "edges": [{from, to, length, width, depth, gradient,
+    // points}]} with kind as a name ("source", "confluence", "lake", "mouth", "outlet")
+    #[wasm_bindgen]
+    pub fn to_json(&self) -> String {
+        let nodes: Vec<String> = self
+            .nodes
+            .iter()
+            .map(|node| {
+                format!(
+                    "{{\"kind\":\"{}\",\"x\":{},\"y\":{},\"height\":{}}}",
+                    node.kind.name(),
+                    json_number(node.x),
+                    json_number(node.y),
+                    json_number(node.height)
+                )
+            })
+            .collect();
+        let edges: Vec<String> = self
+            .edges
+            .iter()
+            .map(|edge| {
+                let points: Vec<String> = edge.points.iter().map(|&v| json_number(v)).collect();
+                format!(
+                    "{{\"from\":{},\"to\":{},\"length\":{},\"width\":{},\"depth\":{},\"gradient\":{},\"points\":[{}]}}",
+                    edge.from,
+                    edge.to,
+                    json_number(edge.length),
+                    json_number(edge.width),
+                    json_number(edge.depth),
+                    json_number(edge.gradient),
+                    points.join(",")
+                )
+            })
+            .collect();
+        format!("{{\"nodes\":[{}],\"edges\":[{}]}}", nodes.join(","), edges.join(","))
+    }
+}
+
+fn json_number(v: f32) -> String {
+    if v.is_finite() { v.to_string() } else { "null".to_string() }
+}
+
+// Channels are traced from their sources as in the water volumes and cut into reaches at
+// confluences. Each lake is a single node joining its inflows and outflow; every river entering
+// the sea gets its own mouth node.
+pub fn river_graph(height_field: &HeightField, water_features: &WaterFeatures, sea_level: f32) -> RiverGraph {
+    let n = height_field.size();
+    let data = height_field.data();
+    let river_mask = water_features.river_mask();
+    let standing = standing_water(height_field, water_features, sea_level);
+    let river: Vec<bool> = (0..n * n).map(|i| river_mask[i] > 0.5 && !standing[i]).collect();
+    let (labels, count) = label_components(&standing, n);
+
+    // Lakes become one node each, placed on the lake cell nearest the centroid
+    let mut is_sea = vec![false; count as usize + 1];
+    let mut sums = vec![(0.0f64, 0.0f64, 0u32, f32::NEG_INFINITY); count as usize + 1];
+    for (idx, &label) in labels.iter().enumerate() {
+        if label > 0 {
+            let s = &mut sums[label as usize];
+            *s = (s.0 + (idx % n) as f64, s.1 + (idx / n) as f64, s.2 + 1, s.3.max(data[idx]));
+            is_sea[label as usize] |= data[idx] <= sea_level;
+        }
+    }
+    let mut lake_anchor = vec![(f64::INFINITY, 0usize); count as usize + 1];
+    for (idx, &label) in labels.iter().enumerate() {
+        if label > 0 {
+            let (sx, sy, cells, _) = sums[label as usize];
+            let (cx, cy) = (sx / cells as f64, sy / cells as f64);
+            let d = ((idx % n) as f64 - cx).powi(2) + ((idx / n) as f64 - cy).powi(2);
+            if d < lake_anchor[label as usize].0 {
+                lake_anchor[label as usize] = (d, idx);
+            }
+        }
+    }
+
+    let paths: Vec<Vec<usize>> = river_paths(n, &river, &standing, water_features.flow_accumulation())
+        .into_iter()
+        .filter(|path| path.len() > MIN_REACH_CELLS || !river[path[path.len() - 1]])
+        .collect();
+    // A path ending inside another one marks a confluence there
+    let mut interior = vec![false; n * n];
+    for path in &paths {
+        for &idx in &path[..path.len() - 1] {
+            interior[idx] = true;
+        }
+    }
+    let mut confluence = vec![false; n * n];
+    for path in &paths {
+        let end = path[path.len() - 1];
+        confluence[end] = interior[end];
+    }
+
+    let mut nodes: Vec<RiverNode> = Vec::new();
+    let mut cell_nodes: HashMap<usize, u32> = HashMap::new();
+    let mut lake_nodes: HashMap<u32, u32> = HashMap::new();
+    let mut node_at = |nodes: &mut Vec<RiverNode>, idx: usize, kind: RiverNodeKind| -> u32 {
+        let label = labels[idx];
+        if label > 0 && !is_sea[label as usize] {
+            return *lake_nodes.entry(label).or_insert_with(|| {
+                let anchor = lake_anchor[label as usize].1;
+                let height = sums[label as usize].3;
+                nodes.push(RiverNode { kind: RiverNodeKind::Lake, x: (anchor % n) as f32, y: (anchor / n) as f32, height });
+                nodes.len() as u32 - 1
+            });
+        }
+        *cell_nodes.entry(idx).or_insert_with(|| {
+            nodes.push(RiverNode { kind, x: (idx % n) as f32, y: (idx / n) as f32, height: data[idx] });
+            nodes.len() as u32 - 1
+        })
+    };
+
+    let mut edges = Vec::new();
+    for path in &paths {
+        // Channels leaving a lake start at the lake
+        let outflow_of = neighbors(n, path[0]).find(|&i| labels[i] > 0 && !is_sea[labels[i] as usize]);
+        let mut from = match outflow_of {
+            Some(lake_cell) => node_at(&mut nodes, lake_cell, RiverNodeKind::Lake),
+            None => node_at(&mut nodes, path[0], RiverNodeKind::Source),
+        };
+        let mut start = 0;
+        let last = path.len() - 1;
+        for i in 1..=last {
+            if i < last && !confluence[path[i]] {
+                continue;
+            }
+            let idx = path[i];
+            let kind = if confluence[idx] {
+                RiverNodeKind::Confluence
+            } else if standing[idx] {
+                RiverNodeKind::Mouth
+            } else {
+                RiverNodeKind::Outlet
+            };
+            let to = node_at(&mut nodes, idx, kind);
+            edges.push(reach(height_field, river_mask, &river, &path[start..=i], from, to));
+            from = to;
+            start = i;
+        }
+    }
+
+    RiverGraph { nodes, edges }
+}
+
+fn neighbors(n: usize, idx: usize) -> impl Iterator<Item = usize> {
+    let (x, y) = ((idx % n) as i32, (idx / n) as i32);
+    (-1i32..=1)
+        .flat_map(move |dy| (-1i32..=1).map(move |dx| (x + dx, y + dy)))
+        .filter(move |&(nx, ny)| (nx, ny) != (x, y) && nx >= 0 && ny >= 0 && (nx as usize) < n && (ny as usize) < n)
+        .map(move |(nx, ny)| ny as usize * n + nx as usize)
+}
+
+// Length, mean width and depth of the channel cells, and bed gradient of one reach
+fn reach(height_field: &HeightField, river_mask: &[f32], river: &[bool], cells: &[usize], from: u32, to: u32) -> RiverEdge {
+    let n = height_field.size();
+    let data = height_field.data();
+    let mut length = 0.0f32;
+    let mut points = Vec::with_capacity(cells.len() * 2);
+    let (mut width, mut depth, mut samples) = (0.0f32, 0.0f32, 0u32);
+    for (i, &idx) in cells.iter().enumerate() {
+        let (x, y) = (idx % n, idx / n);
+        points.extend_from_slice(&[x as f32, y as f32]);
+        let (ax, ay) = (cells[i.saturating_sub(1)] % n, cells[i.saturating_sub(1)] / n);
+        if i > 0 {
+            length += ((x as f32 - ax as f32).powi(2) + (y as f32 - ay as f32).powi(2)).sqrt();
+        }
+        if !river[idx] {
+            continue;
+        }
+        let next = cells[(i + 1).min(cells.len() - 1)];
+        let (dx, dy) = ((next % n) as f32 - ax as f32, (next / n) as f32 - ay as f32);
+        let d = (dx * dx + dy * dy).sqrt().max(f32::EPSILON);
+        let (left, left_bank) = probe_bank(height_field, river_mask, x, y, -dy / d, dx / d);
+        let (right, right_bank) = probe_bank(height_field, river_mask, x, y, dy / d, -dx / d);
+        width += left + right;
+        depth += (left_bank.min(right_bank) - data[idx]).max(0.0);
+        samples += 1;
+    }
+    let samples = samples.max(1) as f32;
+    let drop = data[cells[0]] - data[cells[cells.len() - 1]];
+    RiverEdge {
+        from,
+        to,
+        length,
+        width: width / samples,
+        depth: depth / samples,
+        gradient: if length > 0.0 { drop.max(0.0) / length } else { 0.0 },
+        points,
+    }
+}
+
+// Navigation graph of the river network for pathing boats and AI along waterways
+#[wasm_bindgen]
+pub fn extract_river_graph(height_field: &HeightField, water_features: &WaterFeatures, sea_level: f32) -> RiverGraph {
+    river_graph(height_field, water_features, sea_level)
+}
@@ -0,0 +1,304 @@
+use crate::biomes::BiomeType;
+use crate::filters::slope_at;
+use crate::height_field::HeightField;
+use crate::noise::{hash_cell, hash_to_unit};
+use crate::outcrops::regolith_thickness;
+use wasm_bindgen::prelude::*;
+
+// Bare-rock share (1 - regolith) a site needs; softer ground weathers away before it stands up
+const MIN_RESISTANCE: f32 = 0.6;
+// Curvature (height units, times size²) at which a crest counts as fully convex
+const CREST_CURVATURE: f32 = 150.0;
+// Slope (height units per cell) at which ground counts as steep for hoodoos and fins
+const STEEP_SLOPE: f32 = 0.02;
+// Arch span (cells between the feet) and crest height (height units) before jitter
+const ARCH_SPAN: f32 = 6.0;
+const ARCH_HEIGHT: f32 = 0.015;
+// Hoodoo clusters: pillars spread over this radius (cells), each this thick and tall
+const HOODOO_CLUSTER_RADIUS: f32 = 4.0;
+const HOODOO_PILLAR_RADIUS: f32 = 0.8;
+const HOODOO_HEIGHT: f32 = 0.01;
+// Balanced rock: pedestal radius (cells) and height to the top of the boulder
+const BALANCED_ROCK_RADIUS: f32 = 1.5;
+const BALANCED_ROCK_HEIGHT: f32 = 0.008;
+// Size varies by up to this fraction either way
+const SIZE_JITTER: f32 = 0.3;
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum RockFeatureKind {
+    Arch = 0,
+    Hoodoo = 1,
+    BalancedRock = 2,
+}
+
+// One placed feature: where the client puts a mesh instead of (or on top of) the stamp
+#[derive(Clone)]
+pub struct RockFeature {
+    pub kind: RockFeatureKind,
+    pub site: usize, // index of the site; the pillars of one hoodoo cluster share it
+    pub x: f32,      // cells
+    pub y: f32,
+    pub height: f32,   // ground height under the feature
+    pub rotation: f32, // radians; arches span along this direction
+    pub scale: f32,    // cells: arch span, pillar or pedestal radius
+    pub feature_height: f32, // height units above the ground
+}
+
+impl RockFeature {
+    // Convert to JS object for interop
+    pub fn to_js_object(&self) -> js_sys::Object {
+        let obj = js_sys::Object::new();
+
+        js_sys::Reflect::set(&obj, &"kind".into(), &(self.kind as u8).into()).unwrap();
+        js_sys::Reflect::set(&obj, &"site".into(), &(self.site as u32).into()).unwrap();
+        js_sys::Reflect::set(&obj, &"x".into(), &self.x.into()).unwrap();
+        js_sys::Reflect::set(&obj, &"y".into(), &self.y.into()).unwrap();
+        js_sys::Reflect::set(&obj, &"height".into(), &self.height.into()).unwrap();
+        js_sys::Reflect::set(&obj, &"rotation".into(), &self.rotation.into()).unwrap();
+        js_sys::Reflect::set(&obj, &"scale".into(), &self.scale.into()).unwrap();
+        js_sys::Reflect::set(&obj, &"featureHeight".into(), &self.feature_height.into()).unwrap();
+
+        obj
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Site {
+    kind: RockFeatureKind,
+    x: usize,
+    y: usize,
+    rotation: f32,
+    score: f32,
+}
+
+// How much each biome favors arches, hoodoos and balanced rocks; None where none form
+fn kind_weights(biome_type: BiomeType) -> Option<[f32; 3]> {
+    match biome_type {
+        BiomeType::Badlands => Some([0.6, 1.0, 0.4]),
+        BiomeType::Desert => Some([1.0, 0.3, 0.8]),
+        _ => None,
+    }
+}
+
+// Ranked feature sites. Every kind needs bare, resistant rock; arches want narrow fins (sharply
+// convex across, flat along a steep-sided crest), hoodoos steep convex slopes being cut back,
+// balanced rocks isolated knobs on gentle ground.
+fn rock_feature_sites(height_field: &HeightField, biome_type: BiomeType, sea_level: f32, max_count: usize, min_spacing: f32) -> Vec<Site> {
+    let n = height_field.size();
+    let Some(weights) = kind_weights(biome_type) else {
+        return Vec::new();
+    };
+    let data = height_field.data();
+    let regolith = regolith_thickness(height_field);
+    let norm = (n * n) as f32 / CREST_CURVATURE;
+
+    let mut candidates = Vec::new();
+    for y in 1..n.saturating_sub(1) {
+        for x in 1..n.saturating_sub(1) {
+            let idx = y * n + x;
+            let h = data[idx];
+            let resistance = 1.0 - regolith[idx];
+            if h <= sea_level || resistance < MIN_RESISTANCE {
+                continue;
+            }
+
+            // Convexity along the two axes and the two diagonals (diagonal steps are √2 long)
+            let across = [
+                (2.0 * h - data[idx - 1] - data[idx + 1], 0.0f32),
+                (2.0 * h - data[idx - n] - data[idx + n], std::f32::consts::FRAC_PI_2),
+                ((2.0 * h - data[idx - n - 1] - data[idx + n + 1]) * 0.5, std::f32::consts::FRAC_PI_4),
+                ((2.0 * h - data[idx - n + 1] - data[idx + n - 1]) * 0.5, 3.0 * std::f32::consts::FRAC_PI_4),
+            ];
+            let (max_c, max_dir) = across.iter().fold((f32::MIN, 0.0), |m, &(c, d)| if c > m.0 { (c, d) } else { m });
+            let min_c = across.iter().fold(f32::MAX, |m, &(c, _)| m.min(c));
+            if max_c <= 0.0 {
+                continue;
+            }
+            let crest = (max_c * norm).min(1.0);
+            let slope = slope_at(height_field, x, y);
+            let steep = (slope / STEEP_SLOPE).min(1.0);
+
+            // A fin is convex across and nearly straight along its crest
+            let fin = crest * (1.0 - (min_c.max(0.0) / max_c)) * (0.5 + 0.5 * steep);
+            let hoodoo = crest * steep;
+            let knob = crest * (min_c.max(0.0) / max_c) * (1.0 - steep);
+            for (kind, score) in [(RockFeatureKind::Arch, fin), (RockFeatureKind::Hoodoo, hoodoo), (RockFeatureKind::BalancedRock, knob)] {
+                if score > 0.0 {
+                    candidates.push(Site {
+                        kind,
+                        x,
+                        y,
+                        // The arch spans along the crest, across the direction of strongest convexity
+                        rotation: max_dir + std::f32::consts::FRAC_PI_2,
+                        score: score * resistance,
+                    });
+                }
+            }
+        }
+    }
+
+    candidates.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+
+    // The biome weights share out the sites between the kinds; places a kind can't fill go to
+    // the best remaining sites of any kind
+    let total: f32 = weights.iter().sum();
+    let quota = weights.map(|w| (max_count as f32 * w / total).round() as usize);
+    let min_spacing_sq = min_spacing * min_spacing;
+    let mut sites: Vec<Site> = Vec::with_capacity(max_count.min(candidates.len()));
+    let mut taken = vec![false; candidates.len()];
+    for use_quota in [true, false] {
+        let mut counts = [0usize; 3];
+        for site in &sites {
+            counts[site.kind as usize] += 1;
+        }
+        for (i, candidate) in candidates.iter().enumerate() {
+            if sites.len() >= max_count {
+                break;
+            }
+            if taken[i] || (use_quota && counts[candidate.kind as usize] >= quota[candidate.kind as usize]) {
+                continue;
+            }
+            let too_close = sites.iter().any(|s| {
+                let dx = s.x as f32 - candidate.x as f32;
+                let dy = s.y as f32 - candidate.y as f32;
+                dx * dx + dy * dy < min_spacing_sq
+            });
+            if !too_close {
+                taken[i] = true;
+                counts[candidate.kind as usize] += 1;
+                sites.push(*candidate);
+            }
+        }
+    }
+
+    sites
+}
+
+// Transforms of the features at each site: one per arch and balanced rock, several pillars per
+// hoodoo cluster. Sizes and cluster layouts are fixed by the seed and the site position.
+fn site_features(height_field: &HeightField, sites: &[Site], seed: u32) -> Vec<RockFeature> {
+    let mut features = Vec::new();
+    for (index, site) in sites.iter().enumerate() {
+        let hash = hash_cell(site.x as i32, site.y as i32, seed);
+        let jitter = 1.0 + SIZE_JITTER * (2.0 * hash_to_unit(hash) - 1.0);
+        let (x, y) = (site.x as f32, site.y as f32);
+        let feature = |x: f32, y: f32, scale: f32, feature_height: f32| RockFeature {
+            kind: site.kind,
+            site: index,
+            x,
+            y,
+            height: height_field.get_clamped(x.round() as i32, y.round() as i32),
+            rotation: site.rotation,
+            scale,
+            feature_height,
+        };
+        match site.kind {
+            RockFeatureKind::Arch => features.push(feature(x, y, ARCH_SPAN * jitter, ARCH_HEIGHT * jitter)),
+            RockFeatureKind::BalancedRock => features.push(feature(x, y, BALANCED_ROCK_RADIUS * jitter, BALANCED_ROCK_HEIGHT * jitter)),
+            RockFeatureKind::Hoodoo => {
+                let count = 3 + (hash_to_unit(hash.rotate_left(8)) * 5.0) as u32;
+                for i in 0..count {
+                    let h = hash_cell(site.x as i32, site.y as i32, seed ^ (i + 1).wrapping_mul(0x9e37_79b9));
+                    let angle = hash_to_unit(h) * std::f32::consts::TAU;
+                    let radius = HOODOO_CLUSTER_RADIUS * jitter * hash_to_unit(h.rotate_left(16)).sqrt();
+                    let height = HOODOO_HEIGHT * (0.6 + 0.8 * hash_to_unit(h.rotate_left(24)));
+                    let max = (height_field.size() - 1) as f32;
+                    features.push(feature(
+                        (x + radius * crate::det_math::cos(angle)).clamp(0.0, max),
+                        (y + radius * crate::det_math::sin(angle)).clamp(0.0, max),
+                        HOODOO_PILLAR_RADIUS * jitter,
+                        height,
+                    ));
+                }
+            }
+        }
+    }
+    features
+}
+
+// Flat-topped pillar of `radius` cells rising `height` at (cx, cy), kept where it stands
+// higher than what `lift` already holds so overlapping stamps don't stack
+fn stamp_pillar(lift: &mut [f32], n: usize, cx: f32, cy: f32, radius: f32, height: f32) {
+    let n = n as i32;
+    let reach = radius.ceil() as i32 + 1;
+    for y in (cy.round() as i32 - reach).max(0)..=(cy.round() as i32 + reach).min(n - 1) {
+        for x in (cx.round() as i32 - reach).max(0)..=(cx.round() as i32 + reach).min(n - 1) {
+            let d = ((x as f32 - cx).powi(2) + (y as f32 - cy).powi(2)).sqrt() / radius.max(f32::EPSILON);
+            if d < 1.0 {
+                let idx = (y * n + x) as usize;
+                lift[idx] = lift[idx].max(height * (1.0 - d.powi(4)));
+            }
+        }
+    }
+}
+
+// Height field stand-ins for the features. A height field can't hold the opening under an arch,
+// so an arch stamps its two feet joined by a low saddle; a balanced rock is one pillar.
+fn stamp_features(height_field: &mut HeightField, features: &[RockFeature]) {
+    let n = height_field.size();
+    let mut lift = vec![0.0f32; n * n];
+    for feature in features {
+        match feature.kind {
+            RockFeatureKind::Arch => {
+                let (dx, dy) = (crate::det_math::cos(feature.rotation), crate::det_math::sin(feature.rotation));
+                let foot = feature.scale * 0.2;
+                let steps = (feature.scale * 2.0).ceil() as usize;
+                for s in 0..=steps {
+                    let t = s as f32 / steps as f32 - 0.5;
+                    let (px, py) = (feature.x + dx * feature.scale * t, feature.y + dy * feature.scale * t);
+                    let (radius, height) = if s == 0 || s == steps {
+                        (foot, feature.feature_height)
+                    } else {
+                        (foot * 0.5, feature.feature_height * 0.4)
+                    };
+                    stamp_pillar(&mut lift, n, px, py, radius, height);
+                }
+            }
+            RockFeatureKind::Hoodoo | RockFeatureKind::BalancedRock => {
+                stamp_pillar(&mut lift, n, feature.x, feature.y, feature.scale, feature.feature_height);
+            }
+        }
+    }
+    for (h, l) in height_field.data_mut().iter_mut().zip(&lift) {
+        *h += l;
+    }
+}
+
+pub fn rock_features(
+    height_field: &mut HeightField,
+    biome_type: BiomeType,
+    sea_level: f32,
+    max_count: usize,
+    min_spacing: f32,
+    seed: u32,
+    stamp: bool,
+) -> Vec<RockFeature> {
+    let sites = rock_feature_sites(height_field, biome_type, sea_level, max_count, min_spacing);
+    let features = site_features(height_field, &sites, seed);
+    if stamp {
+        stamp_features(height_field, &features);
+    }
+    features
+}
+
+// Arches, hoodoo clusters and balanced rocks for badlands and desert maps (none elsewhere),
+// placed on the most erosion-resistant fins, slopes and knobs. With `stamp` they are pressed
+// into the height field as simple shapes; either way their transforms are returned so the
+// client can put real meshes there.
+#[wasm_bindgen]
+pub fn place_rock_features(
+    height_field: &mut HeightField,
+    biome_type: BiomeType,
+    sea_level: f32,
+    max_count: u32,
+    min_spacing: f32,
+    seed: u32,
+    stamp: bool,
+) -> js_sys::Array {
+    let result = js_sys::Array::new();
+    for feature in rock_features(height_field, biome_type, sea_level, max_count as usize, min_spacing, seed, stamp) {
+        result.push(&feature.to_js_object());
+    }
+    result
+}
@@ -0,0 +1,193 @@
+use crate::climate::ClimateGrid;
+use crate::error::TerrainError;
+use crate::filters::distance_to_mask;
+use crate::height_field::HeightField;
+use crate::noise::{hash_cell, hash_to_unit};
+use wasm_bindgen::prelude::*;
+
+// Degrees (°C) below the freezing point over which sea ice goes from first floes to full cover
+const FREEZE_RAMP: f32 = 4.0;
+// Sheltered water near the coast freezes this much (°C) earlier, fading out over FAST_ICE_REACH
+// cells; open water churned by waves stays clear longer
+const FAST_ICE_BONUS: f32 = 3.0;
+const FAST_ICE_REACH: f32 = 8.0;
+// Radius (cells) of the window whose land share measures how enclosed a bay is
+const ENCLOSURE_RADIUS: usize = 12;
+// Land share of that window at which a bay holds an ice shelf out to its full reach
+const FULL_ENCLOSURE: f32 = 0.5;
+// Degrees (°C) below shelf_temperature over which a shelf grows to full thickness
+const SHELF_RAMP: f32 = 4.0;
+// Distance (cells) out from the shelf front over which icebergs drift, and their spacing
+const ICEBERG_DRIFT: f32 = 16.0;
+const ICEBERG_SPACING: f32 = 4.0;
+// Iceberg radius (cells) range
+const ICEBERG_MIN_RADIUS: f32 = 0.5;
+const ICEBERG_MAX_RADIUS: f32 = 2.5;
+// Seeded candidates tried per requested iceberg
+const ICEBERG_CANDIDATES: u32 = 32;
+
+#[wasm_bindgen]
+#[derive(Clone, Copy)]
+pub struct SeaIceParams {
+    pub freeze_temperature: f32, // °C, where sea water starts to freeze
+    pub shelf_temperature: f32,  // °C, coasts colder than this push glaciers out over the sea
+    pub shelf_reach: f32,        // cells an ice shelf extends from the coast in a closed bay
+    pub shelf_max_depth: f32,    // height units; shelves stay grounded on shallower water only
+    pub iceberg_count: u32,
+    pub seed: u32,
+}
+
+#[wasm_bindgen]
+impl SeaIceParams {
+    #[wasm_bindgen(constructor)]
+    pub fn new(shelf_reach: f32, shelf_max_depth: f32, iceberg_count: u32, seed: u32) -> Self {
+        Self {
+            freeze_temperature: -1.8,
+            shelf_temperature: -5.0,
+            shelf_reach,
+            shelf_max_depth,
+            iceberg_count,
+            seed,
+        }
+    }
+}
+
+// Sea ice cover and ice shelves (0-1 per cell, both 0 on land) and icebergs calved off the
+// shelf fronts
+#[wasm_bindgen]
+#[derive(Clone)]
+pub struct SeaIceField {
+    sea_ice: Vec<f32>,
+    ice_shelf: Vec<f32>,
+    icebergs: Vec<f32>,
+    size: usize,
+}
+
+#[wasm_bindgen]
+impl SeaIceField {
+    #[wasm_bindgen(getter)]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    #[wasm_bindgen]
+    pub fn get_sea_ice(&self) -> js_sys::Float32Array {
+        let array = js_sys::Float32Array::new_with_length(self.sea_ice.len() as u32);
+        array.copy_from(&self.sea_ice);
+        array
+    }
+
+    #[wasm_bindgen]
+    pub fn get_ice_shelf(&self) -> js_sys::Float32Array {
+        let array = js_sys::Float32Array::new_with_length(self.ice_shelf.len() as u32);
+        array.copy_from(&self.ice_shelf);
+        array
+    }
+
+    // Iceberg spawn points as flat x, y, radius triples (cells)
+    #[wasm_bindgen]
+    pub fn get_icebergs(&self) -> js_sys::Float32Array {
+        let array = js_sys::Float32Array::new_with_length(self.icebergs.len() as u32);
+        array.copy_from(&self.icebergs);
+        array
+    }
+}
+
+// Share of land in the (2r+1)² window around every cell, from a summed-area table
+fn land_share(land: &[bool], n: usize, r: usize) -> Vec<f32> {
+    let mut table = vec![0u32; (n + 1) * (n + 1)];
+    for y in 0..n {
+        for x in 0..n {
+            table[(y + 1) * (n + 1) + x + 1] =
+                land[y * n + x] as u32 + table[y * (n + 1) + x + 1] + table[(y + 1) * (n + 1) + x] - table[y * (n + 1) + x];
+        }
+    }
+    (0..n * n)
+        .map(|idx| {
+            let (x, y) = (idx % n, idx / n);
+            let (x0, y0, x1, y1) = (x.saturating_sub(r), y.saturating_sub(r), (x + r + 1).min(n), (y + r + 1).min(n));
+            let count = table[y1 * (n + 1) + x1] + table[y0 * (n + 1) + x0] - table[y0 * (n + 1) + x1] - table[y1 * (n + 1) + x0];
+            count as f32 / ((x1 - x0) * (y1 - y0)) as f32
+        })
+        .collect()
+}
+
+// Sea ice forms where the climate grid's temperature falls below freezing, earliest in sheltered
+// coastal water. Where the coast is colder still, glaciers flow out as ice shelves over shallow
+// water, reaching farthest in enclosed bays; icebergs calve off their seaward fronts.
+pub fn sea_ice(height_field: &HeightField, climate: &ClimateGrid, sea_level: f32, params: &SeaIceParams) -> Result<SeaIceField, TerrainError> {
+    let n = height_field.size();
+    if climate.size() != n {
+        return Err(TerrainError::SizeMismatch { expected: n, actual: climate.size() });
+    }
+    let data = height_field.data();
+    let land: Vec<bool> = data.iter().map(|&h| h > sea_level).collect();
+    let distance_to_land = distance_to_mask(&land, n);
+    let enclosure = land_share(&land, n, ENCLOSURE_RADIUS);
+
+    let mut sea_ice = vec![0.0f32; n * n];
+    let mut ice_shelf = vec![0.0f32; n * n];
+    for idx in 0..n * n {
+        if land[idx] {
+            continue;
+        }
+        let temperature = climate.temperature_at(idx % n, idx / n);
+        // Without any land the distance is infinite and there is no shelter
+        let shelter = FAST_ICE_BONUS * crate::det_math::exp(-distance_to_land[idx] / FAST_ICE_REACH);
+        sea_ice[idx] = ((params.freeze_temperature - temperature + shelter) / FREEZE_RAMP).clamp(0.0, 1.0);
+
+        let cold = ((params.shelf_temperature - temperature) / SHELF_RAMP).clamp(0.0, 1.0);
+        let reach = params.shelf_reach * (enclosure[idx] / FULL_ENCLOSURE).min(1.0);
+        if cold > 0.0 && distance_to_land[idx] <= reach && sea_level - data[idx] <= params.shelf_max_depth {
+            ice_shelf[idx] = cold;
+        }
+    }
+
+    // Icebergs drift out into open water from the shelf fronts
+    let shelf: Vec<bool> = ice_shelf.iter().map(|&s| s > 0.5).collect();
+    let distance_to_shelf = distance_to_mask(&shelf, n);
+    let mut icebergs: Vec<f32> = Vec::new();
+    let candidates = params.iceberg_count.saturating_mul(ICEBERG_CANDIDATES).min(i32::MAX as u32) as i32;
+    for i in 0..candidates {
+        if icebergs.len() / 3 >= params.iceberg_count as usize || n == 0 {
+            break;
+        }
+        let x = (hash_to_unit(hash_cell(i, 0, params.seed)) * n as f32) as usize;
+        let y = (hash_to_unit(hash_cell(i, 1, params.seed)) * n as f32) as usize;
+        let idx = y * n + x;
+        let drift = distance_to_shelf[idx];
+        // Most bergs stay near the front they calved from
+        let keep = hash_to_unit(hash_cell(i, 2, params.seed)) < 1.0 - drift / ICEBERG_DRIFT;
+        let open = !land[idx] && !shelf[idx];
+        let spaced = icebergs.chunks_exact(3).all(|b| crate::det_math::hypot(b[0] - x as f32, b[1] - y as f32) > ICEBERG_SPACING);
+        if keep && open && spaced {
+            let radius = ICEBERG_MIN_RADIUS + (ICEBERG_MAX_RADIUS - ICEBERG_MIN_RADIUS) * hash_to_unit(hash_cell(i, 3, params.seed));
+            icebergs.extend([x as f32, y as f32, radius]);
+        }
+    }
+
+    Ok(SeaIceField { sea_ice, ice_shelf, icebergs, size: n })
+}
+
+// Sea ice, ice shelves and iceberg spawn points for cold seas, from the annual temperatures of
+// `climate`, computed for this height field (see compute_climate_grid); throws when the grid
+// is for a different map size
+#[wasm_bindgen]
+pub fn generate_sea_ice(height_field: &HeightField, climate: &ClimateGrid, sea_level: f32, params: &SeaIceParams) -> Result<SeaIceField, JsValue> {
+    Ok(sea_ice(height_field, climate, sea_level, params)?)
+}
+
+#[cfg(all(test, feature = "strict_determinism"))]
+mod tests {
+    use crate::biomes::BiomeType;
+    use crate::det_math::tests::{hex, terrain};
+
+    #[test]
+    fn sea_ice_is_pinned() {
+        let base = terrain(BiomeType::Tundra);
+        let climate = crate::climate::climate_grid(&base.height_field, &base.units, BiomeType::Tundra, 0.7, 0);
+        let params = super::SeaIceParams::new(8.0, 0.2, 6, 4);
+        let ice = super::sea_ice(&base.height_field, &climate, base.units.sea_level_height(), &params).unwrap();
+        assert_eq!(hex(&[&ice.sea_ice, &ice.ice_shelf, &ice.icebergs]), "117f9c68be9c817e");
+    }
+}
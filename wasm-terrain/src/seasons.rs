@@ -0,0 +1,259 @@
+use crate::biomes::BiomeParams;
+use crate::filters::{distance_to_mask, gradient_at};
+use crate::height_field::HeightField;
+use crate::TerrainGenerationResult;
+use wasm_bindgen::prelude::*;
+
+// Temperature drop per 1000m of elevation (standard atmosphere)
+pub(crate) const LAPSE_RATE: f32 = 6.5;
+// Distance (cells) over which nearby water still boosts vegetation
+const MOISTURE_RADIUS: f32 = 24.0;
+// Slope (relief units per map width) at which the aspect offset reaches full strength
+const ASPECT_FULL_RELIEF: f32 = 8.0;
+// Fraction of soil moisture lost per °C of extra warmth on sun-facing slopes
+const EVAPORATION_PER_DEGREE: f32 = 0.08;
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Hemisphere {
+    Northern = 0, // equator-facing slopes face south (+y, map rows grow southward)
+    Southern = 1,
+}
+
+// Slope-aspect temperature modifier: equator-facing slopes are warmed and pole-facing slopes
+// cooled by up to `strength` °C, which shifts the snowline, vegetation vigor and evaporation
+#[wasm_bindgen]
+#[derive(Clone, Copy)]
+pub struct Microclimate {
+    pub hemisphere: Hemisphere,
+    pub strength: f32,
+}
+
+#[wasm_bindgen]
+impl Microclimate {
+    #[wasm_bindgen(constructor)]
+    pub fn new(hemisphere: Hemisphere, strength: f32) -> Self {
+        Self { hemisphere, strength }
+    }
+}
+
+// Per-cell temperature offset (°C) from slope aspect
+fn aspect_offsets(height_field: &HeightField, microclimate: &Microclimate) -> Vec<f32> {
+    let n = height_field.size();
+    let toward_equator = match microclimate.hemisphere {
+        Hemisphere::Northern => 1.0,
+        Hemisphere::Southern => -1.0,
+    };
+    let mut offsets = vec![0.0f32; n * n];
+    for y in 0..n {
+        for x in 0..n {
+            let (gx, gy) = gradient_at(height_field, x, y);
+            let slope = (gx * gx + gy * gy).sqrt();
+            if slope <= f32::EPSILON {
+                continue;
+            }
+            // Slopes face downhill; only the north/south component of that matters
+            let facing = -gy / slope * toward_equator;
+            let steepness = (slope * n as f32 / ASPECT_FULL_RELIEF).min(1.0);
+            offsets[y * n + x] = microclimate.strength * facing * steepness;
+        }
+    }
+    offsets
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Season {
+    Spring = 0,
+    Summer = 1,
+    Autumn = 2,
+    Winter = 3,
+}
+
+impl Season {
+    pub fn from_index(index: u8) -> Option<Season> {
+        match index {
+            0 => Some(Season::Spring),
+            1 => Some(Season::Summer),
+            2 => Some(Season::Autumn),
+            3 => Some(Season::Winter),
+            _ => None,
+        }
+    }
+
+    // Offset from the mean temperature in units of the biome's seasonal range
+    fn temperature_offset(self) -> f32 {
+        match self {
+            Season::Spring => 0.0,
+            Season::Summer => 1.0,
+            Season::Autumn => -0.2,
+            Season::Winter => -1.0,
+        }
+    }
+
+    // Base foliage tint (linear RGB)
+    fn vegetation_tint(self) -> [f32; 3] {
+        match self {
+            Season::Spring => [0.45, 0.75, 0.30],
+            Season::Summer => [0.30, 0.60, 0.20],
+            Season::Autumn => [0.75, 0.50, 0.18],
+            Season::Winter => [0.42, 0.38, 0.30],
+        }
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Clone)]
+pub struct SeasonalVariant {
+    pub season: Season,
+    pub river_flow_multiplier: f32,
+    pub tint_r: f32,
+    pub tint_g: f32,
+    pub tint_b: f32,
+    snow_mask: Vec<f32>,
+    frozen_lake_mask: Vec<f32>,
+    vegetation_vigor: Vec<f32>,
+    size: usize,
+}
+
+#[wasm_bindgen]
+impl SeasonalVariant {
+    #[wasm_bindgen(getter)]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    #[wasm_bindgen]
+    pub fn get_snow_mask(&self) -> js_sys::Float32Array {
+        let array = js_sys::Float32Array::new_with_length(self.snow_mask.len() as u32);
+        array.copy_from(&self.snow_mask);
+        array
+    }
+
+    #[wasm_bindgen]
+    pub fn get_frozen_lake_mask(&self) -> js_sys::Float32Array {
+        let array = js_sys::Float32Array::new_with_length(self.frozen_lake_mask.len() as u32);
+        array.copy_from(&self.frozen_lake_mask);
+        array
+    }
+
+    // 0 = dormant/brown, 1 = lush; multiply with the tint for a per-cell color hint
+    #[wasm_bindgen]
+    pub fn get_vegetation_vigor(&self) -> js_sys::Float32Array {
+        let array = js_sys::Float32Array::new_with_length(self.vegetation_vigor.len() as u32);
+        array.copy_from(&self.vegetation_vigor);
+        array
+    }
+}
+
+// Per-cell air temperature (°C) for a season from elevation above sea level
+pub(crate) fn season_temperature(
+    data: &[f32],
+    sea_level: f32,
+    height_scale: f32, // meters per height unit
+    biome: &BiomeParams,
+    season: Season,
+) -> Vec<f32> {
+    let base = biome.mean_temperature() + season.temperature_offset() * biome.seasonal_temperature_range();
+    data.iter()
+        .map(|&h| base - (h - sea_level).max(0.0) * height_scale / 1000.0 * LAPSE_RATE)
+        .collect()
+}
+
+pub fn seasonal_variant(result: &TerrainGenerationResult, season: Season) -> SeasonalVariant {
+    seasonal_variant_with_microclimate(result, season, None)
+}
+
+pub fn seasonal_variant_with_microclimate(
+    result: &TerrainGenerationResult,
+    season: Season,
+    microclimate: Option<&Microclimate>,
+) -> SeasonalVariant {
+    let height_field = result.height_field_ref();
+    let n = height_field.size();
+    let data = height_field.data();
+    let biome = result.biome_params();
+    let units = result.vertical_units();
+    let sea_level = units.sea_level_height();
+
+    let water: Vec<bool> = match result.water_features_ref() {
+        Some(features) => features.water_mask().iter().map(|&w| w > 0.5).collect(),
+        None => data.iter().map(|&h| h <= sea_level).collect(),
+    };
+    let dist_water = distance_to_mask(&water, n);
+    let mut temperature = season_temperature(data, sea_level, units.height_scale_m, &biome, season);
+    let aspect = microclimate.map(|m| aspect_offsets(height_field, m));
+    if let Some(offsets) = &aspect {
+        for (t, &o) in temperature.iter_mut().zip(offsets) {
+            *t += o;
+        }
+    }
+
+    let snow_mask = temperature.iter().map(|&t| (-t / 4.0).clamp(0.0, 1.0)).collect();
+    let frozen_lake_mask = temperature
+        .iter()
+        .zip(&water)
+        .map(|(&t, &w)| if w { ((-t - 2.0) / 4.0).clamp(0.0, 1.0) } else { 0.0 })
+        .collect();
+
+    let dryness = if season == Season::Summer { biome.summer_dryness() } else { 0.0 };
+    let vegetation_vigor = (0..n * n)
+        .map(|idx| {
+            if water[idx] {
+                return 0.0;
+            }
+            let warmth = ((temperature[idx] - 2.0) / 15.0).clamp(0.0, 1.0);
+            let mut moisture = 1.0 - (dist_water[idx] / MOISTURE_RADIUS).min(1.0) * dryness.max(0.3);
+            // Sunny slopes dry out faster, shady ones keep their moisture
+            if let Some(offsets) = &aspect {
+                moisture = (moisture * (1.0 - offsets[idx] * EVAPORATION_PER_DEGREE)).clamp(0.0, 1.0);
+            }
+            warmth * moisture
+        })
+        .collect();
+
+    // Snowmelt swells spring rivers; frozen winters and dry summers shrink them
+    let river_flow_multiplier = match season {
+        Season::Spring => 1.5,
+        Season::Summer => 1.0 - biome.summer_dryness(),
+        Season::Autumn => 1.0,
+        Season::Winter => 0.6,
+    };
+    let [tint_r, tint_g, tint_b] = season.vegetation_tint();
+
+    SeasonalVariant {
+        season,
+        river_flow_multiplier,
+        tint_r,
+        tint_g,
+        tint_b,
+        snow_mask,
+        frozen_lake_mask,
+        vegetation_vigor,
+        size: n,
+    }
+}
+
+// Same as generate_seasonal_variants with slope-aspect temperature modifiers applied
+#[wasm_bindgen]
+pub fn generate_seasonal_variants_with_microclimate(
+    result: &TerrainGenerationResult,
+    seasons: &[u8],
+    microclimate: &Microclimate,
+) -> js_sys::Array {
+    let variants = js_sys::Array::new();
+    for season in seasons.iter().filter_map(|&s| Season::from_index(s)) {
+        variants.push(&JsValue::from(seasonal_variant_with_microclimate(result, season, Some(microclimate))));
+    }
+    variants
+}
+
+// `seasons` holds Season values (0 = Spring .. 3 = Winter); unknown values are skipped
+#[wasm_bindgen]
+pub fn generate_seasonal_variants(result: &TerrainGenerationResult, seasons: &[u8]) -> js_sys::Array {
+    let variants = js_sys::Array::new();
+    for season in seasons.iter().filter_map(|&s| Season::from_index(s)) {
+        variants.push(&JsValue::from(seasonal_variant(result, season)));
+    }
+    variants
+}
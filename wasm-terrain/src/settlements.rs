@@ -0,0 +1,310 @@
+use crate::error::TerrainError;
+use crate::filters::{distance_to_mask, slope_at};
+use crate::height_field::HeightField;
+use crate::water_system::WaterFeatures;
+use wasm_bindgen::prelude::*;
+
+// Number of rays used to trace the footprint polygon around a site
+const FOOTPRINT_VERTICES: usize = 16;
+
+// Relative importance of each scoring term
+const FLATNESS_WEIGHT: f32 = 0.35;
+const WATER_WEIGHT: f32 = 0.3;
+const COAST_WEIGHT: f32 = 0.15;
+const AREA_WEIGHT: f32 = 0.2;
+
+#[wasm_bindgen]
+#[derive(Clone, Copy)]
+pub struct SettlementConstraints {
+    pub footprint_radius: f32, // cells
+    pub max_slope: f32,
+    pub min_spacing: f32, // cells between site centers
+    pub sea_level: f32,
+    pub search_radius: f32, // cells; water/coast further away scores zero
+    pub flatten: bool,
+}
+
+#[wasm_bindgen]
+impl SettlementConstraints {
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        footprint_radius: f32,
+        max_slope: f32,
+        min_spacing: f32,
+        sea_level: f32,
+        search_radius: f32,
+        flatten: bool,
+    ) -> Self {
+        Self {
+            footprint_radius,
+            max_slope,
+            min_spacing,
+            sea_level,
+            search_radius,
+            flatten,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct SettlementSite {
+    pub x: usize,
+    pub y: usize,
+    pub height: f32,
+    pub score: f32,
+    pub flatness: f32,
+    pub water_access: f32,
+    pub coastal_access: f32,
+    pub buildable_area: f32,
+    pub footprint: Vec<f32>, // x0, y0, x1, y1, ... in cell coordinates
+}
+
+impl SettlementSite {
+    // Convert to JS object for interop
+    pub fn to_js_object(&self) -> js_sys::Object {
+        let obj = js_sys::Object::new();
+
+        js_sys::Reflect::set(&obj, &"x".into(), &(self.x as f32).into()).unwrap();
+        js_sys::Reflect::set(&obj, &"y".into(), &(self.y as f32).into()).unwrap();
+        js_sys::Reflect::set(&obj, &"height".into(), &self.height.into()).unwrap();
+        js_sys::Reflect::set(&obj, &"score".into(), &self.score.into()).unwrap();
+        js_sys::Reflect::set(&obj, &"flatness".into(), &self.flatness.into()).unwrap();
+        js_sys::Reflect::set(&obj, &"waterAccess".into(), &self.water_access.into()).unwrap();
+        js_sys::Reflect::set(&obj, &"coastalAccess".into(), &self.coastal_access.into()).unwrap();
+        js_sys::Reflect::set(&obj, &"buildableArea".into(), &self.buildable_area.into()).unwrap();
+
+        let footprint = js_sys::Float32Array::new_with_length(self.footprint.len() as u32);
+        footprint.copy_from(&self.footprint);
+        js_sys::Reflect::set(&obj, &"footprint".into(), &footprint).unwrap();
+
+        obj
+    }
+}
+
+// Trace the buildable footprint around a site: each ray walks outwards until it hits
+// water, steep ground or the footprint radius.
+fn trace_footprint(
+    height_field: &HeightField,
+    slopes: &[f32],
+    water: &[bool],
+    cx: usize,
+    cy: usize,
+    constraints: &SettlementConstraints,
+) -> Vec<f32> {
+    let n = height_field.size();
+    let mut polygon = Vec::with_capacity(FOOTPRINT_VERTICES * 2);
+
+    for k in 0..FOOTPRINT_VERTICES {
+        let angle = k as f32 / FOOTPRINT_VERTICES as f32 * std::f32::consts::PI * 2.0;
+        let (dy, dx) = (crate::det_math::sin(angle), crate::det_math::cos(angle));
+
+        let mut reach = 0.0f32;
+        while reach < constraints.footprint_radius {
+            let step = reach + 1.0;
+            let px = (cx as f32 + dx * step).round() as i32;
+            let py = (cy as f32 + dy * step).round() as i32;
+            if px < 0 || py < 0 || px as usize >= n || py as usize >= n {
+                break;
+            }
+            let idx = py as usize * n + px as usize;
+            if water[idx] || slopes[idx] > constraints.max_slope {
+                break;
+            }
+            reach = step.min(constraints.footprint_radius);
+        }
+
+        polygon.push(cx as f32 + dx * reach);
+        polygon.push(cy as f32 + dy * reach);
+    }
+
+    polygon
+}
+
+// Level the ground under a site to its mean height, feathering the outer ring
+fn flatten_site(height_field: &mut HeightField, site: &SettlementSite, radius: f32) {
+    let n = height_field.size() as i32;
+    let r = radius.ceil() as i32;
+    let blend = (radius * 0.5).max(1.0);
+    let outer = radius + blend;
+    let reach = outer.ceil() as i32;
+
+    let mut sum = 0.0;
+    let mut count = 0;
+    for j in -r..=r {
+        for i in -r..=r {
+            if ((i * i + j * j) as f32).sqrt() <= radius {
+                sum += height_field.get_clamped(site.x as i32 + i, site.y as i32 + j);
+                count += 1;
+            }
+        }
+    }
+    let target = sum / count.max(1) as f32;
+
+    for j in -reach..=reach {
+        for i in -reach..=reach {
+            let x = site.x as i32 + i;
+            let y = site.y as i32 + j;
+            if x < 0 || y < 0 || x >= n || y >= n {
+                continue;
+            }
+            let d = ((i * i + j * j) as f32).sqrt();
+            if d > outer {
+                continue;
+            }
+            let w = if d <= radius {
+                1.0
+            } else {
+                let t = 1.0 - (d - radius) / blend;
+                t * t * (3.0 - 2.0 * t)
+            };
+            let current = height_field.get(x as usize, y as usize);
+            height_field.set(x as usize, y as usize, current + (target - current) * w);
+        }
+    }
+}
+
+// Score candidate cells and greedily pick the best sites that respect the spacing rule.
+// `water_features` must match the height field's size.
+pub fn find_settlement_sites(
+    height_field: &HeightField,
+    water_features: &WaterFeatures,
+    count: usize,
+    constraints: &SettlementConstraints,
+) -> Result<Vec<SettlementSite>, TerrainError> {
+    let n = height_field.size();
+    if water_features.size() != n {
+        return Err(TerrainError::SizeMismatch { expected: n, actual: water_features.size() });
+    }
+    if n == 0 || count == 0 {
+        return Ok(Vec::new());
+    }
+
+    let data = height_field.data();
+    let water_mask = water_features.water_mask();
+    let river_mask = water_features.river_mask();
+
+    let mut slopes = vec![0.0f32; n * n];
+    for y in 0..n {
+        for x in 0..n {
+            slopes[y * n + x] = slope_at(height_field, x, y);
+        }
+    }
+
+    let water: Vec<bool> = (0..n * n)
+        .map(|i| data[i] <= constraints.sea_level || water_mask.get(i).is_some_and(|&w| w > 0.5))
+        .collect();
+    let sea: Vec<bool> = data.iter().map(|&h| h <= constraints.sea_level).collect();
+    let fresh: Vec<bool> = river_mask.iter().map(|&r| r > 0.5).collect();
+
+    let dist_sea = distance_to_mask(&sea, n);
+    let dist_fresh = distance_to_mask(&fresh, n);
+
+    let radius = constraints.footprint_radius.max(1.0);
+    let r = radius.ceil() as i32;
+    let search = constraints.search_radius.max(1.0);
+    let stride = ((radius * 0.5) as usize).max(1);
+
+    let mut candidates = Vec::new();
+    for y in (0..n).step_by(stride) {
+        for x in (0..n).step_by(stride) {
+            let idx = y * n + x;
+            if water[idx] || slopes[idx] > constraints.max_slope {
+                continue;
+            }
+
+            // Sample the footprint disc for flatness and buildable fraction
+            let mut slope_sum = 0.0;
+            let mut buildable = 0;
+            let mut total = 0;
+            for j in -r..=r {
+                for i in -r..=r {
+                    if ((i * i + j * j) as f32).sqrt() > radius {
+                        continue;
+                    }
+                    total += 1;
+                    let px = x as i32 + i;
+                    let py = y as i32 + j;
+                    if px < 0 || py < 0 || px as usize >= n || py as usize >= n {
+                        continue;
+                    }
+                    let p_idx = py as usize * n + px as usize;
+                    slope_sum += slopes[p_idx];
+                    if !water[p_idx] && slopes[p_idx] <= constraints.max_slope {
+                        buildable += 1;
+                    }
+                }
+            }
+
+            let buildable_area = buildable as f32 / total.max(1) as f32;
+            let mean_slope = slope_sum / total.max(1) as f32;
+            let flatness = if constraints.max_slope > 0.0 {
+                (1.0 - mean_slope / constraints.max_slope).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let water_access = (1.0 - dist_fresh[idx] / search).clamp(0.0, 1.0);
+            let coastal_access = (1.0 - dist_sea[idx] / search).clamp(0.0, 1.0);
+
+            let score = flatness * FLATNESS_WEIGHT
+                + water_access * WATER_WEIGHT
+                + coastal_access * COAST_WEIGHT
+                + buildable_area * AREA_WEIGHT;
+
+            candidates.push(SettlementSite {
+                x,
+                y,
+                height: data[idx],
+                score,
+                flatness,
+                water_access,
+                coastal_access,
+                buildable_area,
+                footprint: Vec::new(),
+            });
+        }
+    }
+
+    candidates.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+
+    let min_spacing_sq = constraints.min_spacing * constraints.min_spacing;
+    let mut sites: Vec<SettlementSite> = Vec::with_capacity(count.min(candidates.len()));
+    for mut candidate in candidates {
+        if sites.len() >= count {
+            break;
+        }
+        let too_close = sites.iter().any(|s| {
+            let dx = s.x as f32 - candidate.x as f32;
+            let dy = s.y as f32 - candidate.y as f32;
+            dx * dx + dy * dy < min_spacing_sq
+        });
+        if too_close {
+            continue;
+        }
+        candidate.footprint = trace_footprint(height_field, &slopes, &water, candidate.x, candidate.y, constraints);
+        sites.push(candidate);
+    }
+
+    Ok(sites)
+}
+
+#[wasm_bindgen]
+pub fn place_settlements(
+    height_field: &mut HeightField,
+    water_features: &WaterFeatures,
+    count: u32,
+    constraints: &SettlementConstraints,
+) -> Result<js_sys::Array, JsValue> {
+    let sites = find_settlement_sites(height_field, water_features, count as usize, constraints)?;
+
+    if constraints.flatten {
+        for site in &sites {
+            flatten_site(height_field, site, constraints.footprint_radius.max(1.0));
+        }
+    }
+
+    let result = js_sys::Array::new();
+    for site in &sites {
+        result.push(&site.to_js_object());
+    }
+    Ok(result)
+}
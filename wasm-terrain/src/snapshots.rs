@@ -0,0 +1,67 @@
+use crate::delta::HeightFieldDelta;
+use crate::error::TerrainError;
+use crate::height_field::HeightField;
+use wasm_bindgen::prelude::*;
+
+// Labeled full copies of height fields, oldest first, for A/B comparison of pipeline stages.
+// Kept apart from HeightField so cloning a map doesn't copy its history.
+#[wasm_bindgen]
+#[derive(Clone, Default)]
+pub struct SnapshotStack {
+    entries: Vec<(String, HeightField)>,
+}
+
+#[wasm_bindgen]
+impl SnapshotStack {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Save the current heights of `height_field` under `label`, replacing any snapshot with
+    // the same label
+    #[wasm_bindgen]
+    pub fn push(&mut self, label: &str, height_field: &HeightField) {
+        self.entries.retain(|(l, _)| l != label);
+        self.entries.push((label.to_string(), height_field.clone()));
+    }
+
+    // Roll `height_field` back to a snapshot; false if the label is unknown or the snapshot
+    // has a different size
+    #[wasm_bindgen]
+    pub fn restore(&self, label: &str, height_field: &mut HeightField) -> bool {
+        match self.get(label) {
+            Some(snapshot) if snapshot.size() == height_field.size() => {
+                height_field.data_mut().copy_from_slice(snapshot.data());
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // Delta turning snapshot `label_a` into snapshot `label_b`; throws for an unknown label or
+    // snapshots of different sizes
+    #[wasm_bindgen]
+    pub fn diff(&self, label_a: &str, label_b: &str) -> Result<HeightFieldDelta, JsValue> {
+        let snapshot = |label: &str| {
+            self.get(label).ok_or_else(|| TerrainError::InvalidParameter(format!("no snapshot labeled \"{}\"", label)))
+        };
+        HeightFieldDelta::between(snapshot(label_a)?, snapshot(label_b)?)
+    }
+
+    #[wasm_bindgen]
+    pub fn labels(&self) -> Vec<String> {
+        self.entries.iter().map(|(l, _)| l.clone()).collect()
+    }
+
+    #[wasm_bindgen]
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+impl SnapshotStack {
+    fn get(&self, label: &str) -> Option<&HeightField> {
+        self.entries.iter().find(|(l, _)| l == label).map(|(_, h)| h)
+    }
+}
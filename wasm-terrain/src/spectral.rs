@@ -0,0 +1,266 @@
+use crate::height_field::HeightField;
+use crate::noise::{hash_cell, hash_to_unit};
+use wasm_bindgen::prelude::*;
+
+// Butterworth order used by the low-pass and detrend filters (gain falls as f^-2·order)
+const FILTER_ORDER: i32 = 4;
+
+// 2D spectrum of a heightfield. The field is mirrored out to (at least) twice its size before
+// the transform, which avoids the seam a plain wrap-around would put at the map edges.
+pub(crate) struct Spectrum {
+    re: Vec<f32>,
+    im: Vec<f32>,
+    size: usize,   // padded transform size
+    source: usize, // original heightfield size
+}
+
+impl Spectrum {
+    // Radial frequency of a bin in cycles across the original map
+    fn frequency(&self, idx: usize) -> f32 {
+        let n = self.size;
+        let signed = |i: usize| if i <= n / 2 { i as f32 } else { i as f32 - n as f32 };
+        let fx = signed(idx % n);
+        let fy = signed(idx / n);
+        (fx * fx + fy * fy).sqrt() * self.source as f32 / n as f32
+    }
+
+    fn scale_bins(&mut self, gain: impl Fn(f32) -> f32) {
+        for idx in 0..self.re.len() {
+            let g = gain(self.frequency(idx));
+            self.re[idx] *= g;
+            self.im[idx] *= g;
+        }
+    }
+}
+
+// In-place iterative radix-2 FFT; the length must be a power of two. Unnormalized both ways.
+fn fft_1d(re: &mut [f32], im: &mut [f32], inverse: bool) {
+    let n = re.len();
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+
+    let sign = if inverse { 1.0 } else { -1.0 };
+    let mut len = 2;
+    while len <= n {
+        let angle = sign * 2.0 * std::f32::consts::PI / len as f32;
+        let twiddles: Vec<(f32, f32)> = (0..len / 2)
+            .map(|k| (crate::det_math::cos(angle * k as f32), crate::det_math::sin(angle * k as f32)))
+            .collect();
+        for start in (0..n).step_by(len) {
+            for (k, &(w_re, w_im)) in twiddles.iter().enumerate() {
+                let a = start + k;
+                let b = a + len / 2;
+                let t_re = re[b] * w_re - im[b] * w_im;
+                let t_im = re[b] * w_im + im[b] * w_re;
+                re[b] = re[a] - t_re;
+                im[b] = im[a] - t_im;
+                re[a] += t_re;
+                im[a] += t_im;
+            }
+        }
+        len <<= 1;
+    }
+}
+
+fn fft_2d(re: &mut [f32], im: &mut [f32], n: usize, inverse: bool) {
+    for row in 0..n {
+        let span = row * n..(row + 1) * n;
+        fft_1d(&mut re[span.clone()], &mut im[span], inverse);
+    }
+    let mut col_re = vec![0.0f32; n];
+    let mut col_im = vec![0.0f32; n];
+    for col in 0..n {
+        for row in 0..n {
+            col_re[row] = re[row * n + col];
+            col_im[row] = im[row * n + col];
+        }
+        fft_1d(&mut col_re, &mut col_im, inverse);
+        for row in 0..n {
+            re[row * n + col] = col_re[row];
+            im[row * n + col] = col_im[row];
+        }
+    }
+}
+
+pub(crate) fn forward(data: &[f32], source: usize) -> Spectrum {
+    let size = (2 * source).next_power_of_two();
+    // Even (mirrored) extension with period 2·source, seamless when size is exactly 2·source
+    let mirror = |i: usize| {
+        let p = i % (2 * source);
+        if p < source { p } else { 2 * source - 1 - p }
+    };
+    let mut re = vec![0.0f32; size * size];
+    for y in 0..size {
+        for x in 0..size {
+            re[y * size + x] = data[mirror(y) * source + mirror(x)];
+        }
+    }
+    let mut im = vec![0.0f32; size * size];
+    fft_2d(&mut re, &mut im, size, false);
+    Spectrum { re, im, size, source }
+}
+
+// Back to heights, cropped to the original size (the real part; imaginary residue is dropped)
+pub(crate) fn inverse(mut spectrum: Spectrum) -> Vec<f32> {
+    let n = spectrum.size;
+    fft_2d(&mut spectrum.re, &mut spectrum.im, n, true);
+    let scale = 1.0 / (n * n) as f32;
+    let m = spectrum.source;
+    (0..m * m).map(|idx| spectrum.re[(idx / m) * n + idx % m] * scale).collect()
+}
+
+fn filter(height_field: &mut HeightField, gain: impl Fn(f32) -> f32) {
+    let n = height_field.size();
+    if n < 2 {
+        return;
+    }
+    let mut spectrum = forward(height_field.data(), n);
+    spectrum.scale_bins(gain);
+    height_field.data_mut().copy_from_slice(&inverse(spectrum));
+}
+
+fn low_pass_gain(f: f32, cutoff: f32) -> f32 {
+    1.0 / (1.0 + (f / cutoff.max(f32::EPSILON)).powi(2 * FILTER_ORDER))
+}
+
+// Remove detail finer than `cutoff` cycles across the map (smooth Butterworth roll-off)
+#[wasm_bindgen]
+pub fn spectral_low_pass(height_field: &mut HeightField, cutoff: f32) {
+    filter(height_field, |f| low_pass_gain(f, cutoff));
+}
+
+// Remove regional trends coarser than `cutoff` cycles across the map, keeping the mean height.
+// Useful for flattening tilt and broad warping out of imported DEMs before further shaping.
+// A least-squares plane is taken out first since a pure tilt leaks into every band.
+#[wasm_bindgen]
+pub fn spectral_detrend(height_field: &mut HeightField, cutoff: f32) {
+    remove_plane(height_field);
+    filter(height_field, |f| if f == 0.0 { 1.0 } else { 1.0 - low_pass_gain(f, cutoff) });
+}
+
+// Subtract the best-fit plane through the heights, keeping the mean
+fn remove_plane(height_field: &mut HeightField) {
+    let n = height_field.size();
+    if n < 2 {
+        return;
+    }
+    // On a full grid x and y are uncorrelated, so each slope is a 1D regression
+    let center = (n - 1) as f64 / 2.0;
+    let mut sxz = 0.0f64;
+    let mut syz = 0.0f64;
+    let mut sxx = 0.0f64;
+    for (idx, &h) in height_field.data().iter().enumerate() {
+        let x = (idx % n) as f64 - center;
+        let y = (idx / n) as f64 - center;
+        sxz += x * h as f64;
+        syz += y * h as f64;
+        sxx += x * x;
+    }
+    let (ax, ay) = (sxz / sxx, syz / sxx);
+    for (idx, h) in height_field.data_mut().iter_mut().enumerate() {
+        let x = (idx % n) as f64 - center;
+        let y = (idx / n) as f64 - center;
+        *h -= (ax * x + ay * y) as f32;
+    }
+}
+
+// Rescale every frequency band so the power spectrum follows f^-(2H + 2), the spectrum of a
+// fractal surface with roughness (Hurst) exponent `hurst` in [0, 1]; phases are kept, so
+// features stay where they are. Total variance (everything but the mean) is preserved.
+#[wasm_bindgen]
+pub fn spectral_shape(height_field: &mut HeightField, hurst: f32) {
+    let n = height_field.size();
+    if n < 2 {
+        return;
+    }
+    let exponent = hurst.clamp(0.0, 1.0) + 1.0;
+    let mut spectrum = forward(height_field.data(), n);
+
+    let mut power_before = 0.0f64;
+    let mut power_after = 0.0f64;
+    for idx in 1..spectrum.re.len() {
+        let f = spectrum.frequency(idx);
+        let magnitude = (spectrum.re[idx].powi(2) + spectrum.im[idx].powi(2)).sqrt();
+        power_before += (magnitude as f64).powi(2);
+        if magnitude > 0.0 {
+            let target = crate::det_math::powf(f, -exponent);
+            spectrum.re[idx] *= target / magnitude;
+            spectrum.im[idx] *= target / magnitude;
+            power_after += (target as f64).powi(2);
+        }
+    }
+    if power_after > 0.0 {
+        let scale = (power_before / power_after).sqrt() as f32;
+        for idx in 1..spectrum.re.len() {
+            spectrum.re[idx] *= scale;
+            spectrum.im[idx] *= scale;
+        }
+    }
+    height_field.data_mut().copy_from_slice(&inverse(spectrum));
+}
+
+// Fractal terrain by spectral synthesis: random phases with amplitudes falling as
+// f^-(H + 1), so the roughness exponent is exactly `hurst`. Heights are normalized to [0, 1].
+#[wasm_bindgen]
+pub fn synthesize_fractal(size: usize, hurst: f32, seed: u32) -> HeightField {
+    let mut height_field = HeightField::new(size);
+    if size < 2 {
+        return height_field;
+    }
+    let exponent = hurst.clamp(0.0, 1.0) + 1.0;
+    // Synthesized directly in the frequency domain, so no mirroring: just crop a periodic tile
+    let n = size.next_power_of_two();
+    let mut spectrum = Spectrum {
+        re: vec![0.0; n * n],
+        im: vec![0.0; n * n],
+        size: n,
+        source: size,
+    };
+    for idx in 1..n * n {
+        let f = spectrum.frequency(idx);
+        let phase = hash_to_unit(hash_cell((idx % n) as i32, (idx / n) as i32, seed)) * 2.0 * std::f32::consts::PI;
+        let amplitude = crate::det_math::powf(f, -exponent);
+        spectrum.re[idx] = amplitude * crate::det_math::cos(phase);
+        spectrum.im[idx] = amplitude * crate::det_math::sin(phase);
+    }
+    height_field.data_mut().copy_from_slice(&inverse(spectrum));
+    height_field.normalize();
+    height_field
+}
+
+// Radially averaged power spectrum: entry k is the mean power of frequencies between k and
+// k + 1 cycles across the map, up to the Nyquist limit. A straight line on log-log axes with
+// slope -(2H + 2) indicates a fractal surface with Hurst exponent H.
+#[wasm_bindgen]
+pub fn radial_power_spectrum(height_field: &HeightField) -> Vec<f32> {
+    let n = height_field.size();
+    if n < 2 {
+        return Vec::new();
+    }
+    let spectrum = forward(height_field.data(), n);
+    let bins = n / 2;
+    let mut sum = vec![0.0f64; bins];
+    let mut count = vec![0u32; bins];
+    for idx in 0..spectrum.re.len() {
+        let bin = spectrum.frequency(idx) as usize;
+        if bin < bins {
+            sum[bin] += (spectrum.re[idx].powi(2) + spectrum.im[idx].powi(2)) as f64;
+            count[bin] += 1;
+        }
+    }
+    sum.iter()
+        .zip(&count)
+        .map(|(&s, &c)| if c > 0 { (s / c as f64) as f32 } else { 0.0 })
+        .collect()
+}
@@ -0,0 +1,187 @@
+use crate::height_field::HeightField;
+use crate::water_system::{apply_water_system, WaterFeatures, WaterSystemParams};
+use wasm_bindgen::prelude::*;
+
+// Successive over-relaxation factor for the membrane solve
+const SOR_OMEGA: f32 = 1.85;
+
+// Copy `patch` into `target` with its top-left corner at (x, y), replacing heights outright
+#[wasm_bindgen]
+pub fn blit(target: &mut HeightField, patch: &HeightField, x: i32, y: i32) {
+    let n = target.size() as i32;
+    let m = patch.size() as i32;
+    for j in 0..m {
+        for i in 0..m {
+            let tx = x + i;
+            let ty = y + j;
+            if tx >= 0 && ty >= 0 && tx < n && ty < n {
+                target.set(tx as usize, ty as usize, patch.get(i as usize, j as usize));
+            }
+        }
+    }
+}
+
+// Gradient-domain paste: keeps the patch's slopes (its Laplacian) while its outline takes the
+// heights of the surrounding terrain, so authored or DEM patches sit in without seams. Solved
+// as a membrane correction c = result - patch with c = target - patch on the patch border and
+// ∇²c = 0 inside. Cells hanging off the target are left out (zero-flux boundary there).
+#[wasm_bindgen]
+pub fn blit_seamless(target: &mut HeightField, patch: &HeightField, x: i32, y: i32, iterations: u32) {
+    let n = target.size() as i32;
+    let m = patch.size() as i32;
+    if m < 3 {
+        blit(target, patch, x, y);
+        return;
+    }
+    let mu = m as usize;
+
+    let inside = |i: i32, j: i32| {
+        let tx = x + i;
+        let ty = y + j;
+        tx >= 0 && ty >= 0 && tx < n && ty < n
+    };
+    let on_border = |i: i32, j: i32| i == 0 || j == 0 || i == m - 1 || j == m - 1;
+
+    // Dirichlet values on the border; start the interior at their mean so SOR converges fast
+    let mut correction = vec![0.0f32; mu * mu];
+    let mut border_sum = 0.0;
+    let mut border_count = 0;
+    for j in 0..m {
+        for i in 0..m {
+            if on_border(i, j) && inside(i, j) {
+                let d = target.get((x + i) as usize, (y + j) as usize) - patch.get(i as usize, j as usize);
+                correction[j as usize * mu + i as usize] = d;
+                border_sum += d;
+                border_count += 1;
+            }
+        }
+    }
+    if border_count == 0 {
+        // Patch covers the whole target: nothing to match against
+        blit(target, patch, x, y);
+        return;
+    }
+    let mean = border_sum / border_count as f32;
+    for j in 1..m - 1 {
+        for i in 1..m - 1 {
+            correction[j as usize * mu + i as usize] = mean;
+        }
+    }
+
+    for _iter in 0..iterations {
+        for j in 1..m - 1 {
+            for i in 1..m - 1 {
+                if !inside(i, j) {
+                    continue;
+                }
+                let mut sum = 0.0;
+                let mut count = 0;
+                for (di, dj) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+                    if inside(i + di, j + dj) {
+                        sum += correction[(j + dj) as usize * mu + (i + di) as usize];
+                        count += 1;
+                    }
+                }
+                if count == 0 {
+                    continue;
+                }
+                let idx = j as usize * mu + i as usize;
+                let relaxed = sum / count as f32;
+                correction[idx] += SOR_OMEGA * (relaxed - correction[idx]);
+            }
+        }
+    }
+
+    for j in 0..m {
+        for i in 0..m {
+            if inside(i, j) {
+                let value = patch.get(i as usize, j as usize) + correction[j as usize * mu + i as usize];
+                target.set((x + i) as usize, (y + j) as usize, value);
+            }
+        }
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum MergeAxis {
+    Horizontal = 0, // b lies east of a
+    Vertical = 1,   // b lies south of a
+}
+
+// (x, y) in a and b of the cell `depth` cells away from the shared seam at position `along`
+fn seam_cells(axis: MergeAxis, n: usize, along: usize, depth: usize) -> ((usize, usize), (usize, usize)) {
+    match axis {
+        MergeAxis::Horizontal => ((n - 1 - depth, along), (depth, along)),
+        MergeAxis::Vertical => ((along, n - 1 - depth), (along, depth)),
+    }
+}
+
+// Stitch two independently generated, equally sized neighbors in place. Per seam position the
+// step across the seam is compared with the slope on either side; the excess is split between
+// both fields and faded out over `blend_width` cells, so heights and gradients both continue.
+// The water system is then re-run on an n x n window centered on the seam (written back into
+// both fields) so rivers can cross it; its WaterFeatures are returned. None if sizes differ.
+#[wasm_bindgen]
+pub fn merge_adjacent(
+    a: &mut HeightField,
+    b: &mut HeightField,
+    axis: MergeAxis,
+    blend_width: u32,
+    water_params: &WaterSystemParams,
+) -> Option<WaterFeatures> {
+    let n = a.size();
+    if n != b.size() || n < 2 {
+        return None;
+    }
+    let blend = (blend_width as usize).clamp(1, n);
+
+    for along in 0..n {
+        let (a0, b0) = seam_cells(axis, n, along, 0);
+        let (a1, b1) = seam_cells(axis, n, along, 1);
+        let a_edge = a.get(a0.0, a0.1);
+        let b_edge = b.get(b0.0, b0.1);
+        let slope = ((a_edge - a.get(a1.0, a1.1)) + (b.get(b1.0, b1.1) - b_edge)) * 0.5;
+        let excess = (b_edge - a_edge) - slope;
+
+        for depth in 0..blend {
+            let t = 1.0 - depth as f32 / blend as f32;
+            let w = t * t * (3.0 - 2.0 * t) * 0.5 * excess;
+            let ((ax, ay), (bx, by)) = seam_cells(axis, n, along, depth);
+            a.set(ax, ay, a.get(ax, ay) + w);
+            b.set(bx, by, b.get(bx, by) - w);
+        }
+    }
+
+    // Seam window: the far half of a followed by the near half of b
+    let split = n - n / 2;
+    let window_cell = |i: usize, j: usize| -> (bool, usize, usize) {
+        let (across, along) = match axis {
+            MergeAxis::Horizontal => (i, j),
+            MergeAxis::Vertical => (j, i),
+        };
+        let (in_a, depth) = if across < split { (true, across + n / 2) } else { (false, across - split) };
+        match axis {
+            MergeAxis::Horizontal => (in_a, depth, along),
+            MergeAxis::Vertical => (in_a, along, depth),
+        }
+    };
+
+    let mut window = HeightField::new(n);
+    for j in 0..n {
+        for i in 0..n {
+            let (in_a, x, y) = window_cell(i, j);
+            window.set(i, j, if in_a { a.get(x, y) } else { b.get(x, y) });
+        }
+    }
+    let water_features = apply_water_system(&mut window, water_params);
+    for j in 0..n {
+        for i in 0..n {
+            let (in_a, x, y) = window_cell(i, j);
+            let target = if in_a { &mut *a } else { &mut *b };
+            target.set(x, y, window.get(i, j));
+        }
+    }
+
+    Some(water_features)
+}
@@ -0,0 +1,114 @@
+use crate::height_field::HeightField;
+use crate::noise::{hash_cell, hash_to_unit};
+use wasm_bindgen::prelude::*;
+
+// Thickness of one sedimentary bed (height units)
+const LAYER_THICKNESS: f32 = 0.012;
+// Erodibility of soft mudstone beds vs hard sandstone caps
+const SOFT_BED: f32 = 1.0;
+pub(crate) const HARD_BED: f32 = 0.2;
+
+// Tilted bedding planes, one orientation per fault block. Blocks are the Worley cells of
+// jittered feature points, so the planes break along the block edges the way faulted strata do.
+#[wasm_bindgen]
+#[derive(Clone)]
+pub struct StrataField {
+    dip: Vec<f32>,    // per cell: x, y gradient of the bedding plane (height units per cell)
+    offset: Vec<f32>, // per cell: height of the block's bedding plane above the horizontal
+    size: usize,
+}
+
+#[wasm_bindgen]
+impl StrataField {
+    #[wasm_bindgen(getter)]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    // Flat x, y gradient of the bedding plane per cell; beds dip towards -gradient
+    #[wasm_bindgen]
+    pub fn get_dip(&self) -> js_sys::Float32Array {
+        let array = js_sys::Float32Array::new_with_length(self.dip.len() as u32);
+        array.copy_from(&self.dip);
+        array
+    }
+
+    // Position in the layer stack of every cell of `height_field`: heights with the tilt of the
+    // local bedding plane removed. Banding this instead of raw height draws the beds on cliff
+    // faces. Empty if the sizes differ.
+    #[wasm_bindgen]
+    pub fn bedding_heights(&self, height_field: &HeightField) -> Vec<f32> {
+        if height_field.size() != self.size {
+            return Vec::new();
+        }
+        height_field.data().iter().zip(&self.offset).map(|(&h, &o)| h - o).collect()
+    }
+}
+
+impl StrataField {
+    // Height in the layer stack of a point at height `h` over cell (x, y)
+    fn bed_height(&self, x: usize, y: usize, h: f32) -> f32 {
+        h - self.offset[y * self.size + x]
+    }
+}
+
+// Relative erodibility of the bed at height h over cell (x, y): alternating soft/hard strata
+// whose order is fixed by the seed. Without a strata field the beds are horizontal, so the same
+// elevation is equally resistant everywhere on the map; with one they follow its tilt.
+pub(crate) fn bed_erodibility(h: f32, x: usize, y: usize, strata: Option<&StrataField>, seed: u32) -> f32 {
+    let bed = strata.map_or(h, |strata| strata.bed_height(x, y, h));
+    let layer = (bed / LAYER_THICKNESS).floor() as i32;
+    if hash_to_unit(hash_cell(layer, 0, seed)) < 0.35 {
+        HARD_BED
+    } else {
+        SOFT_BED
+    }
+}
+
+// Fault blocks of about `block_size` cells, each with bedding tilted up to `max_dip` height
+// units per cell in a seeded direction. Planes pass through their block's feature point, so
+// neighboring blocks agree near their centers and step apart towards shared edges.
+pub fn strata_field(size: usize, block_size: f32, max_dip: f32, seed: u32) -> StrataField {
+    let block_size = block_size.max(1.0);
+    let mut dip = vec![0.0f32; size * size * 2];
+    let mut offset = vec![0.0f32; size * size];
+    let feature = |bx: i32, by: i32| {
+        let h = hash_cell(bx, by, seed);
+        let fx = (bx as f32 + hash_to_unit(h)) * block_size;
+        let fy = (by as f32 + hash_to_unit(h.rotate_left(16))) * block_size;
+        (fx, fy, h)
+    };
+
+    for y in 0..size {
+        for x in 0..size {
+            let (px, py) = (x as f32, y as f32);
+            let (bx, by) = ((px / block_size).floor() as i32, (py / block_size).floor() as i32);
+            let mut nearest = (f32::INFINITY, 0.0, 0.0, 0u32);
+            for dy in -1..=1 {
+                for dx in -1..=1 {
+                    let (fx, fy, h) = feature(bx + dx, by + dy);
+                    let d2 = (px - fx).powi(2) + (py - fy).powi(2);
+                    if d2 < nearest.0 {
+                        nearest = (d2, fx, fy, h);
+                    }
+                }
+            }
+            let (_, fx, fy, h) = nearest;
+            let azimuth = hash_to_unit(h.rotate_left(8)) * std::f32::consts::TAU;
+            let magnitude = max_dip * hash_to_unit(h.rotate_left(24));
+            let (gx, gy) = (crate::det_math::cos(azimuth) * magnitude, crate::det_math::sin(azimuth) * magnitude);
+
+            let idx = y * size + x;
+            dip[idx * 2] = gx;
+            dip[idx * 2 + 1] = gy;
+            offset[idx] = gx * (px - fx) + gy * (py - fy);
+        }
+    }
+
+    StrataField { dip, offset, size }
+}
+
+#[wasm_bindgen]
+pub fn generate_strata(size: usize, block_size: f32, max_dip: f32, seed: u32) -> StrataField {
+    strata_field(size, block_size, max_dip, seed)
+}
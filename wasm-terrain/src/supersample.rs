@@ -0,0 +1,59 @@
+use crate::height_field::HeightField;
+
+// Offsets of the four subsamples of a cell at twice the resolution, in cells
+pub(crate) const SUBSAMPLE_OFFSETS: [(f32, f32); 4] = [(-0.25, -0.25), (0.25, -0.25), (-0.25, 0.25), (0.25, 0.25)];
+
+// Bilinear upsample of an n x n raster to 2n x 2n; each cell splits into four subcells whose
+// centers sit a quarter cell from the original center
+fn upsample2(data: &[f32], n: usize) -> Vec<f32> {
+    let m = 2 * n;
+    let mut out = vec![0.0f32; m * m];
+    let max = (n - 1) as f32;
+    for y in 0..m {
+        let sy = ((y as f32 + 0.5) / 2.0 - 0.5).clamp(0.0, max);
+        let y0 = sy.floor() as usize;
+        let y1 = (y0 + 1).min(n - 1);
+        let ty = sy - y0 as f32;
+        for x in 0..m {
+            let sx = ((x as f32 + 0.5) / 2.0 - 0.5).clamp(0.0, max);
+            let x0 = sx.floor() as usize;
+            let x1 = (x0 + 1).min(n - 1);
+            let tx = sx - x0 as f32;
+            let top = data[y0 * n + x0] + (data[y0 * n + x1] - data[y0 * n + x0]) * tx;
+            let bottom = data[y1 * n + x0] + (data[y1 * n + x1] - data[y1 * n + x0]) * tx;
+            out[y * m + x] = top + (bottom - top) * ty;
+        }
+    }
+    out
+}
+
+// Mean of each 2 x 2 block of a 2n x 2n raster
+fn downsample2(data: &[f32], n: usize) -> Vec<f32> {
+    let m = 2 * n;
+    (0..n * n)
+        .map(|idx| {
+            let (x, y) = (2 * (idx % n), 2 * (idx / n));
+            (data[y * m + x] + data[y * m + x + 1] + data[(y + 1) * m + x] + data[(y + 1) * m + x + 1]) * 0.25
+        })
+        .collect()
+}
+
+// Run a carve or stamp at twice the resolution and add back only the change it made, averaged
+// over each cell, so diagonal edges get partial depths instead of one-cell stairs. `mask` is
+// the operation's per-cell input, upsampled with the heights.
+pub(crate) fn carve_supersampled(height_field: &mut HeightField, mask: &[f32], op: impl FnOnce(&mut HeightField, &[f32])) {
+    let n = height_field.size();
+    if n < 2 {
+        op(height_field, mask);
+        return;
+    }
+    let fine_before = upsample2(height_field.data(), n);
+    let mut fine = HeightField::new(2 * n);
+    fine.data_mut().copy_from_slice(&fine_before);
+    op(&mut fine, &upsample2(mask, n));
+
+    let change: Vec<f32> = fine.data().iter().zip(&fine_before).map(|(&after, &before)| after - before).collect();
+    for (h, d) in height_field.data_mut().iter_mut().zip(downsample2(&change, n)) {
+        *h += d;
+    }
+}
@@ -0,0 +1,148 @@
+use crate::brush::CellRect;
+use crate::height_field::HeightField;
+use wasm_bindgen::prelude::*;
+
+fn smoothstep(t: f32) -> f32 {
+    let t = t.clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+// Distance from p to segment ab, and the clamped position t along it
+fn segment_distance(px: f32, py: f32, ax: f32, ay: f32, bx: f32, by: f32) -> (f32, f32) {
+    let (dx, dy) = (bx - ax, by - ay);
+    let len_sq = dx * dx + dy * dy;
+    let t = if len_sq > 0.0 { (((px - ax) * dx + (py - ay) * dy) / len_sq).clamp(0.0, 1.0) } else { 0.0 };
+    let (cx, cy) = (ax + dx * t - px, ay + dy * t - py);
+    ((cx * cx + cy * cy).sqrt(), t)
+}
+
+// Even-odd rule point in polygon test over flat [x0, y0, x1, y1, ...] vertices
+fn point_in_polygon(px: f32, py: f32, polygon: &[f32]) -> bool {
+    let count = polygon.len() / 2;
+    let mut inside = false;
+    let mut j = count - 1;
+    for i in 0..count {
+        let (xi, yi) = (polygon[i * 2], polygon[i * 2 + 1]);
+        let (xj, yj) = (polygon[j * 2], polygon[j * 2 + 1]);
+        if (yi > py) != (yj > py) && px < (xj - xi) * (py - yi) / (yj - yi) + xi {
+            inside = !inside;
+        }
+        j = i;
+    }
+    inside
+}
+
+// Cell rectangle covering [min, max] on both axes, clipped to the field
+fn clip_rect(n: usize, min_x: f32, min_y: f32, max_x: f32, max_y: f32) -> Option<CellRect> {
+    let x0 = min_x.floor().max(0.0) as usize;
+    let y0 = min_y.floor().max(0.0) as usize;
+    let x1 = (max_x.ceil() + 1.0).clamp(0.0, n as f32) as usize;
+    let y1 = (max_y.ceil() + 1.0).clamp(0.0, n as f32) as usize;
+    (x0 < x1 && y0 < y1).then_some([x0, y0, x1, y1])
+}
+
+// Level the area inside `polygon` (flat x, y cell coordinates, e.g. a settlement footprint)
+// to `height`, blending back to the original terrain over `blend_margin` cells outside it
+#[wasm_bindgen]
+pub fn flatten_region(height_field: &mut HeightField, polygon: &[f32], height: f32, blend_margin: f32) {
+    let n = height_field.size();
+    if polygon.len() < 6 || n == 0 {
+        return;
+    }
+    let polygon = &polygon[..polygon.len() / 2 * 2];
+    let margin = blend_margin.max(0.0);
+
+    let (mut min_x, mut min_y) = (f32::INFINITY, f32::INFINITY);
+    let (mut max_x, mut max_y) = (f32::NEG_INFINITY, f32::NEG_INFINITY);
+    for v in polygon.chunks_exact(2) {
+        min_x = min_x.min(v[0]);
+        max_x = max_x.max(v[0]);
+        min_y = min_y.min(v[1]);
+        max_y = max_y.max(v[1]);
+    }
+    let Some([x0, y0, x1, y1]) = clip_rect(n, min_x - margin, min_y - margin, max_x + margin, max_y + margin) else {
+        return;
+    };
+
+    let count = polygon.len() / 2;
+    for y in y0..y1 {
+        for x in x0..x1 {
+            let (px, py) = (x as f32, y as f32);
+            let w = if point_in_polygon(px, py, polygon) {
+                1.0
+            } else if margin > 0.0 {
+                let mut d = f32::INFINITY;
+                for i in 0..count {
+                    let j = (i + 1) % count;
+                    let (dist, _) =
+                        segment_distance(px, py, polygon[i * 2], polygon[i * 2 + 1], polygon[j * 2], polygon[j * 2 + 1]);
+                    d = d.min(dist);
+                }
+                smoothstep(1.0 - d / margin)
+            } else {
+                0.0
+            };
+            if w > 0.0 {
+                let h = height_field.get(x, y);
+                height_field.set(x, y, h + (height - h) * w);
+            }
+        }
+    }
+
+    height_field.mark_dirty([x0, y0, x1, y1]);
+}
+
+// Grade a straight ramp from (start_x, start_y) to (end_x, end_y): a `width`-cell wide strip
+// whose height runs linearly between the terrain heights at both ends, blended into the
+// surroundings over `blend` cells on the sides and ends. Chain ramps for switchbacks.
+#[wasm_bindgen]
+pub fn create_ramp(
+    height_field: &mut HeightField,
+    start_x: f32,
+    start_y: f32,
+    end_x: f32,
+    end_y: f32,
+    width: f32,
+    blend: f32,
+) {
+    let n = height_field.size();
+    if n == 0 {
+        return;
+    }
+    let half = (width * 0.5).max(0.5);
+    let blend = blend.max(0.0);
+    let reach = half + blend;
+
+    let start_h = height_field.get_clamped(start_x.round() as i32, start_y.round() as i32);
+    let end_h = height_field.get_clamped(end_x.round() as i32, end_y.round() as i32);
+
+    let Some([x0, y0, x1, y1]) = clip_rect(
+        n,
+        start_x.min(end_x) - reach,
+        start_y.min(end_y) - reach,
+        start_x.max(end_x) + reach,
+        start_y.max(end_y) + reach,
+    ) else {
+        return;
+    };
+
+    for y in y0..y1 {
+        for x in x0..x1 {
+            let (d, t) = segment_distance(x as f32, y as f32, start_x, start_y, end_x, end_y);
+            let w = if d <= half {
+                1.0
+            } else if blend > 0.0 {
+                smoothstep(1.0 - (d - half) / blend)
+            } else {
+                0.0
+            };
+            if w > 0.0 {
+                let target = start_h + (end_h - start_h) * t;
+                let h = height_field.get(x, y);
+                height_field.set(x, y, h + (target - h) * w);
+            }
+        }
+    }
+
+    height_field.mark_dirty([x0, y0, x1, y1]);
+}
@@ -0,0 +1,181 @@
+use crate::height_field::HeightField;
+use crate::water_system::WaterFeatures;
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "threads")]
+use rayon::prelude::*;
+
+// What generate_continuous_tile_grid returns the heights as. Tiles and atlas hold the same
+// data, so large grids can skip one of them to halve peak memory.
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TileGridOutput {
+    TilesAndAtlas = 0,
+    TilesOnly = 1, // no `atlas` and no atlas-wide `waterFeatures`
+    AtlasOnly = 2, // `tiles` is empty; cut tiles from the atlas with `rects`
+}
+
+// Copy one tile out of a square atlas-sized raster row by row; cells past the atlas edge stay 0.0
+fn extract_tile_cells(data: &[f32], atlas_size: usize, row: usize, col: usize, tile_size: usize, inner_size: usize) -> Vec<f32> {
+    let mut tile = vec![0.0f32; tile_size * tile_size];
+    let src_x = col * inner_size;
+    let src_y = row * inner_size;
+
+    if src_x < atlas_size {
+        let width = tile_size.min(atlas_size - src_x);
+        for y in 0..tile_size.min(atlas_size.saturating_sub(src_y)) {
+            let src = (src_y + y) * atlas_size + src_x;
+            tile[y * tile_size..y * tile_size + width].copy_from_slice(&data[src..src + width]);
+        }
+    }
+
+    tile
+}
+
+pub(crate) fn extract_tile(atlas: &HeightField, row: usize, col: usize, tile_size: usize, inner_size: usize) -> HeightField {
+    let mut tile = HeightField::new(tile_size);
+    tile.data_mut()
+        .copy_from_slice(&extract_tile_cells(atlas.data(), atlas.size(), row, col, tile_size, inner_size));
+    tile
+}
+
+// The cells extract_tile_cells would cut from `data` (a size × size raster) resampled to
+// atlas_size, computed from the tile's own window of the source so the atlas-sized raster is
+// never built
+fn resample_tile_cells(data: &[f32], size: usize, atlas_size: usize, row: usize, col: usize, tile_size: usize, inner_size: usize) -> Vec<f32> {
+    let mut tile = vec![0.0f32; tile_size * tile_size];
+    let (src_x, src_y) = (col * inner_size, row * inner_size);
+    let width = tile_size.min(atlas_size.saturating_sub(src_x));
+    let height = tile_size.min(atlas_size.saturating_sub(src_y));
+    if width == 0 || height == 0 {
+        return tile;
+    }
+    let cells = crate::resample::resample_region(data, size, atlas_size, [src_x, src_y, width, height]);
+    for (y, cells_row) in cells.chunks_exact(width).enumerate() {
+        tile[y * tile_size..y * tile_size + width].copy_from_slice(cells_row);
+    }
+    tile
+}
+
+// Tile of `core` resampled to atlas_size, equal to extract_tile on that atlas
+pub(crate) fn resample_tile(core: &HeightField, atlas_size: usize, row: usize, col: usize, tile_size: usize, inner_size: usize) -> HeightField {
+    let mut tile = HeightField::new(tile_size);
+    tile.data_mut()
+        .copy_from_slice(&resample_tile_cells(core.data(), core.size(), atlas_size, row, col, tile_size, inner_size));
+    tile
+}
+
+// Cut `count` tiles in row-major order (in parallel with the `threads` feature)
+pub(crate) fn map_tiles<T: Send>(count: usize, cut: impl Fn(usize) -> T + Sync + Send) -> Vec<T> {
+    #[cfg(feature = "threads")]
+    let tiles = (0..count).into_par_iter().map(cut).collect();
+    #[cfg(not(feature = "threads"))]
+    let tiles = (0..count).map(cut).collect();
+
+    tiles
+}
+
+// One tile of atlas-wide water features, cut like extract_tile, overlap included, so rivers
+// carry on across tile seams exactly as in the atlas
+pub(crate) fn extract_water_tile(
+    water_features: &WaterFeatures,
+    row: usize,
+    col: usize,
+    tile_size: usize,
+    inner_size: usize,
+) -> WaterFeatures {
+    let atlas_size = water_features.size();
+    water_features.map_rasters(tile_size, |raster| extract_tile_cells(raster, atlas_size, row, col, tile_size, inner_size))
+}
+
+// One tile of the core terrain's water features resampled to atlas_size, like resample_tile
+pub(crate) fn resample_water_tile(
+    water_features: &WaterFeatures,
+    atlas_size: usize,
+    row: usize,
+    col: usize,
+    tile_size: usize,
+    inner_size: usize,
+) -> WaterFeatures {
+    let size = water_features.size();
+    water_features.map_rasters(tile_size, |raster| resample_tile_cells(raster, size, atlas_size, row, col, tile_size, inner_size))
+}
+
+// Crop the square atlas-sized field to atlas_w x atlas_h, one row slice at a time
+pub(crate) fn build_atlas(atlas: &HeightField, atlas_w: usize, atlas_h: usize) -> Vec<f32> {
+    crop_atlas(atlas.data(), atlas.size(), atlas_w, atlas_h)
+}
+
+// Crop atlas-wide water features the same way as build_atlas, so each mask lines up with the
+// atlas and its UV rects: the keys of WaterFeatures::to_js_object, each raster atlas_w × atlas_h
+// row-major, plus `width` and `height`. WaterFeatures itself stays square.
+pub(crate) fn water_atlas_object(water_features: &WaterFeatures, atlas_w: usize, atlas_h: usize) -> js_sys::Object {
+    let size = water_features.size();
+    let obj = js_sys::Object::new();
+    for (key, raster) in water_features.named_rasters() {
+        let cropped = crop_atlas(raster, size, atlas_w, atlas_h);
+        js_sys::Reflect::set(&obj, &key.into(), &js_sys::Float32Array::from(&cropped[..])).unwrap();
+    }
+    js_sys::Reflect::set(&obj, &"width".into(), &(atlas_w as f32).into()).unwrap();
+    js_sys::Reflect::set(&obj, &"height".into(), &(atlas_h as f32).into()).unwrap();
+    obj
+}
+
+fn crop_atlas(data: &[f32], size: usize, atlas_w: usize, atlas_h: usize) -> Vec<f32> {
+    let mut out = vec![0.0f32; atlas_w * atlas_h];
+    if atlas_w == 0 {
+        return out;
+    }
+
+    let copy_row = |(y, row): (usize, &mut [f32])| {
+        let width = atlas_w.min(size);
+        if y < size {
+            row[..width].copy_from_slice(&data[y * size..y * size + width]);
+        }
+    };
+
+    #[cfg(feature = "threads")]
+    out.par_chunks_mut(atlas_w).enumerate().for_each(copy_row);
+    #[cfg(not(feature = "threads"))]
+    out.chunks_mut(atlas_w).enumerate().for_each(copy_row);
+
+    out
+}
+
+// Skirts hang this much further than the worst edge error, and never less than the floor
+const SKIRT_MARGIN: f32 = 1.5;
+const MIN_SKIRT_DEPTH: f32 = 1e-3;
+
+// Largest height jump along one edge when every other vertex is dropped (the next LOD
+// level interpolates odd vertices linearly from their even neighbors)
+fn edge_lod_error(tile: &HeightField, vertex: impl Fn(usize) -> (usize, usize)) -> f32 {
+    let n = tile.size();
+    let mut worst = 0.0f32;
+    let mut i = 1;
+    while i + 1 < n {
+        let (ax, ay) = vertex(i - 1);
+        let (bx, by) = vertex(i);
+        let (cx, cy) = vertex(i + 1);
+        let coarse = (tile.get(ax, ay) + tile.get(cx, cy)) * 0.5;
+        worst = worst.max((tile.get(bx, by) - coarse).abs());
+        i += 2;
+    }
+    worst
+}
+
+// Per-edge max error vs. the next LOD level, in N, E, S, W order
+pub(crate) fn lod_edge_errors(tile: &HeightField) -> [f32; 4] {
+    let last = tile.size().saturating_sub(1);
+    [
+        edge_lod_error(tile, |i| (i, 0)),
+        edge_lod_error(tile, |i| (last, i)),
+        edge_lod_error(tile, |i| (i, last)),
+        edge_lod_error(tile, |i| (0, i)),
+    ]
+}
+
+// Skirt depth that hides any crack against a neighbor one LOD level coarser
+pub(crate) fn skirt_depth(edge_errors: &[f32; 4]) -> f32 {
+    let worst = edge_errors.iter().fold(0.0f32, |m, &e| m.max(e));
+    (worst * SKIRT_MARGIN).max(MIN_SKIRT_DEPTH)
+}
@@ -0,0 +1,124 @@
+use crate::biomes::{BiomeParams, BiomeType};
+use wasm_bindgen::prelude::*;
+
+// The generator's historical sea level unit: thousandths of the 0-1 height range
+pub(crate) const LEGACY_SEA_LEVEL_SCALE: f32 = 1000.0;
+
+// What height field values mean in the world: a height of 1 is `height_scale_m` meters above
+// the zero datum, and the sea stands at `sea_level_m` meters on the same scale
+#[wasm_bindgen]
+#[derive(Clone, Copy)]
+pub struct VerticalUnits {
+    pub height_scale_m: f32,
+    pub sea_level_m: f32,
+}
+
+#[wasm_bindgen]
+impl VerticalUnits {
+    #[wasm_bindgen(constructor)]
+    pub fn new(height_scale_m: f32, sea_level_m: f32) -> Self {
+        Self { height_scale_m, sea_level_m }
+    }
+
+    // The biome's own vertical scale with the sea at `sea_level_m`
+    #[wasm_bindgen]
+    pub fn for_biome(biome_type: BiomeType, sea_level_m: f32) -> Self {
+        Self::new(BiomeParams::new(biome_type).height_scale(), sea_level_m)
+    }
+
+    // Height field value of `meters`
+    #[wasm_bindgen]
+    pub fn to_height(&self, meters: f32) -> f32 {
+        meters / self.height_scale_m
+    }
+
+    // Meters of height field value `height`
+    #[wasm_bindgen]
+    pub fn to_meters(&self, height: f32) -> f32 {
+        height * self.height_scale_m
+    }
+
+    // Sea level as a height field value
+    #[wasm_bindgen]
+    pub fn sea_level_height(&self) -> f32 {
+        self.to_height(self.sea_level_m)
+    }
+
+    // Sea level in the legacy 1/1000 units of TerrainConfig::sea_level and ErosionParams
+    #[wasm_bindgen]
+    pub fn legacy_sea_level(&self) -> f32 {
+        self.sea_level_height() * LEGACY_SEA_LEVEL_SCALE
+    }
+
+    // Units for a legacy sea level (1/1000 units) on the biome's vertical scale
+    pub(crate) fn from_legacy(biome_type: BiomeType, sea_level: f32) -> Self {
+        let height_scale_m = BiomeParams::new(biome_type).height_scale();
+        Self::new(height_scale_m, sea_level / LEGACY_SEA_LEVEL_SCALE * height_scale_m)
+    }
+}
+
+// Cell size the built-in defaults and biome presets were tuned at. With a world scale set,
+// their per-cell widths and slopes are taken to mean what they meant at this cell size.
+pub(crate) const REFERENCE_CELL_SIZE_M: f32 = 30.0;
+// Map size (cells) auto-scaling treats as the reference: other sizes cover the same world
+pub(crate) const REFERENCE_MAP_SIZE: usize = 512;
+// Most an iteration count may grow over its reference value; keeps large maps affordable
+const MAX_ITERATION_GROWTH: f32 = 4.0;
+
+// Horizontal and vertical size of a height field cell in the world, so widths can be given in
+// meters and slopes as gradients (rise over run) and mean the same at any resolution
+#[wasm_bindgen]
+#[derive(Clone, Copy)]
+pub struct WorldScale {
+    pub cell_size_m: f32,
+    pub height_scale_m: f32,
+}
+
+#[wasm_bindgen]
+impl WorldScale {
+    #[wasm_bindgen(constructor)]
+    pub fn new(cell_size_m: f32, height_scale_m: f32) -> Self {
+        Self { cell_size_m, height_scale_m }
+    }
+
+    // Cells spanned by `meters`
+    #[wasm_bindgen]
+    pub fn to_cells(&self, meters: f32) -> f32 {
+        meters / self.cell_size_m
+    }
+
+    // Height units per cell of a `gradient` (rise over run)
+    #[wasm_bindgen]
+    pub fn slope_per_cell(&self, gradient: f32) -> f32 {
+        gradient * self.cell_size_m / self.height_scale_m
+    }
+
+    // Internal methods for Rust use
+    // Width in cells of a built-in default of `cells` at the reference cell size
+    pub(crate) fn reference_cells(&self, cells: f32) -> f32 {
+        cells * REFERENCE_CELL_SIZE_M / self.cell_size_m
+    }
+
+    // Per-cell slope of a built-in default of `per_cell` at the reference cell size
+    pub(crate) fn reference_slope(&self, per_cell: f32) -> f32 {
+        per_cell * self.cell_size_m / REFERENCE_CELL_SIZE_M
+    }
+
+    // The reference world at `size` cells: the REFERENCE_MAP_SIZE map at the reference cell size
+    pub(crate) fn for_resolution(size: usize, height_scale_m: f32) -> Self {
+        Self::new(REFERENCE_CELL_SIZE_M * REFERENCE_MAP_SIZE as f32 / size.max(1) as f32, height_scale_m)
+    }
+
+    // Iterations that cover the same world distance as `iterations` at the reference cell size:
+    // processes that spread one cell per iteration need `exponent` 1, diffusion 2
+    pub(crate) fn scale_iterations(&self, iterations: u32, exponent: i32) -> u32 {
+        let scaled = iterations as f32 * (REFERENCE_CELL_SIZE_M / self.cell_size_m).powi(exponent);
+        // max(1) keeps the bounds ordered when iterations is 0
+        scaled.round().clamp(1.0, (iterations as f32 * MAX_ITERATION_GROWTH).max(1.0)) as u32
+    }
+
+    // The same world at `size` cells instead of `full_size`
+    pub(crate) fn at_size(&self, size: usize, full_size: usize) -> Self {
+        Self::new(self.cell_size_m * full_size as f32 / size.max(1) as f32, self.height_scale_m)
+    }
+}
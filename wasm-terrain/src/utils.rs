@@ -18,8 +18,32 @@ extern "C" {
     pub fn log(s: &str);
 }
 
+// Progress logging goes to the JS console on wasm. Builds with the `ffi` feature may run
+// under a wasm runtime without JS, and native builds (the C FFI, Python) have no console to
+// import, so the messages are dropped there.
+pub(crate) fn log_message(s: &str) {
+    #[cfg(all(target_arch = "wasm32", not(feature = "ffi")))]
+    log(s);
+    #[cfg(any(not(target_arch = "wasm32"), feature = "ffi"))]
+    let _ = s;
+}
+
+// Milliseconds since the epoch, for stage timing and generation budgets. Date is a JS import,
+// so native builds read the system clock instead; wasm builds with the `ffi` feature have
+// neither and report 0, which leaves timings at zero and budgets never running out.
+pub(crate) fn now_ms() -> f64 {
+    #[cfg(all(target_arch = "wasm32", not(feature = "ffi")))]
+    return js_sys::Date::now();
+    #[cfg(all(target_arch = "wasm32", feature = "ffi"))]
+    return 0.0;
+    #[cfg(not(target_arch = "wasm32"))]
+    return std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0.0, |elapsed| elapsed.as_secs_f64() * 1000.0);
+}
+
 macro_rules! console_log {
-    ($($t:tt)*) => (crate::utils::log(&format_args!($($t)*).to_string()))
+    ($($t:tt)*) => (crate::utils::log_message(&format_args!($($t)*).to_string()))
 }
 
 pub(crate) use console_log;
\ No newline at end of file
@@ -0,0 +1,150 @@
+use crate::config::TerrainConfig;
+use crate::TerrainGenerationResult;
+use wasm_bindgen::prelude::*;
+
+// Share of cells pinned at exactly 0 beyond which carving is assumed to have hit the clamp
+const CLAMPED_FLOOR_LIMIT: f32 = 0.05;
+// Height range below which the map is reported as flat
+const FLAT_RANGE: f32 = 1e-4;
+// How far (fraction of the map) the land share may miss a set_sea_level_by_fraction target
+const LAND_FRACTION_TOLERANCE: f32 = 0.05;
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ValidationSeverity {
+    Info = 0,
+    Warning = 1,
+    Error = 2,
+}
+
+#[derive(Clone)]
+pub struct ValidationIssue {
+    pub code: &'static str,
+    pub severity: ValidationSeverity,
+    pub message: String,
+    pub value: f32, // the measured quantity behind the issue
+}
+
+impl ValidationIssue {
+    fn new(code: &'static str, severity: ValidationSeverity, value: f32, message: String) -> Self {
+        Self { code, severity, message, value }
+    }
+
+    // Convert to JS object for interop
+    pub fn to_js_object(&self) -> js_sys::Object {
+        let obj = js_sys::Object::new();
+
+        js_sys::Reflect::set(&obj, &"code".into(), &self.code.into()).unwrap();
+        js_sys::Reflect::set(&obj, &"severity".into(), &(self.severity as u8).into()).unwrap();
+        js_sys::Reflect::set(&obj, &"message".into(), &self.message.as_str().into()).unwrap();
+        js_sys::Reflect::set(&obj, &"value".into(), &self.value.into()).unwrap();
+
+        obj
+    }
+}
+
+// Sanity checks on a finished generation, most severe first. An empty list means nothing
+// looked off; problems that used to show up as silently weird terrain get a stable code.
+pub fn validate_result(result: &TerrainGenerationResult, config: &TerrainConfig) -> Vec<ValidationIssue> {
+    use ValidationSeverity::*;
+
+    let height_field = result.height_field_ref();
+    let data = height_field.data();
+    let cells = data.len().max(1) as f32;
+    let sea = result.vertical_units().sea_level_height();
+    let mut issues = Vec::new();
+
+    let non_finite = data.iter().filter(|h| !h.is_finite()).count();
+    if non_finite > 0 {
+        issues.push(ValidationIssue::new(
+            "non_finite_heights",
+            Error,
+            non_finite as f32,
+            format!("{} heights are NaN or infinite", non_finite),
+        ));
+    }
+
+    let finite = || data.iter().copied().filter(|h| h.is_finite());
+    let min = finite().fold(f32::INFINITY, f32::min);
+    let max = finite().fold(f32::NEG_INFINITY, f32::max);
+    if max - min < FLAT_RANGE {
+        issues.push(ValidationIssue::new(
+            "flat_terrain",
+            Error,
+            (max - min).max(0.0),
+            "height range is effectively zero".to_string(),
+        ));
+    }
+
+    let at_zero = data.iter().filter(|&&h| h == 0.0).count() as f32 / cells;
+    if at_zero > CLAMPED_FLOOR_LIMIT {
+        issues.push(ValidationIssue::new(
+            "clamped_floor",
+            Warning,
+            at_zero,
+            format!("{:.1}% of the map sits at exactly 0, likely carved into the clamp", at_zero * 100.0),
+        ));
+    }
+
+    let land = data.iter().filter(|&&h| h > sea).count() as f32 / cells;
+    if land == 0.0 {
+        issues.push(ValidationIssue::new(
+            "no_land",
+            Error,
+            land,
+            format!("no cell is above sea level {:.3}", sea),
+        ));
+    } else if land == 1.0 {
+        issues.push(ValidationIssue::new(
+            "no_sea",
+            Info,
+            land,
+            format!("every cell is above sea level {:.3}", sea),
+        ));
+    }
+    if let Some(target) = config.land_fraction.map(|f| f.clamp(0.0, 1.0)) {
+        if (land - target).abs() > LAND_FRACTION_TOLERANCE {
+            issues.push(ValidationIssue::new(
+                "land_fraction_missed",
+                Warning,
+                land,
+                format!("land covers {:.1}% of the map, {:.1}% was requested", land * 100.0, target * 100.0),
+            ));
+        }
+    }
+
+    match result.water_features_ref() {
+        None if config.erosion_years > 0.0 => issues.push(ValidationIssue::new(
+            "missing_water_features",
+            Error,
+            0.0,
+            "erosion was requested but produced no water features".to_string(),
+        )),
+        None => {}
+        Some(features) => {
+            // On real relief drainage converges: the biggest catchment spans many map widths.
+            // Flow that never gathers more than a map width means flats or pits everywhere.
+            let flow = features.flow_accumulation();
+            let max_flow = flow.iter().copied().fold(0.0f32, f32::max);
+            let n = height_field.size() as f32;
+            if flow.iter().any(|f| !f.is_finite()) || (land > 0.0 && max_flow < n) {
+                issues.push(ValidationIssue::new(
+                    "degenerate_flow",
+                    Warning,
+                    max_flow,
+                    format!("largest catchment is only {:.0} cells on a {}-cell-wide map", max_flow, n),
+                ));
+            } else if land > 0.0 && !features.river_mask().iter().any(|&r| r > 0.5) {
+                issues.push(ValidationIssue::new(
+                    "no_rivers",
+                    Info,
+                    0.0,
+                    "drainage is fine but no river passed the threshold".to_string(),
+                ));
+            }
+        }
+    }
+
+    issues.sort_by_key(|issue| std::cmp::Reverse(issue.severity as u8));
+    issues
+}
@@ -0,0 +1,225 @@
+use crate::height_field::HeightField;
+use wasm_bindgen::prelude::*;
+
+// Lava thinner than this counts as solidified / absent
+const LAVA_EPSILON: f32 = 1e-5;
+
+#[wasm_bindgen]
+#[derive(Clone, Copy)]
+pub struct LavaFlowParams {
+    pub vent_count: u32,     // vents picked automatically when none are supplied
+    pub volume: f32,         // total lava erupted per vent (height units x cells)
+    pub steps: u32,
+    pub yield_strength: f32, // lava thinner than this stops flowing (self-levee)
+    pub cooling_rate: f32,   // fraction of mobile lava solidifying per step
+}
+
+#[wasm_bindgen]
+impl LavaFlowParams {
+    #[wasm_bindgen(constructor)]
+    pub fn new(vent_count: u32, volume: f32, steps: u32, yield_strength: f32, cooling_rate: f32) -> Self {
+        Self {
+            vent_count,
+            volume,
+            steps,
+            yield_strength,
+            cooling_rate,
+        }
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Clone)]
+pub struct LavaField {
+    lava_mask: Vec<f32>,
+    thickness: Vec<f32>,
+    vents: Vec<u32>,
+    size: usize,
+}
+
+#[wasm_bindgen]
+impl LavaField {
+    #[wasm_bindgen(getter)]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    // 1.0 where hardened lava covers the ground
+    #[wasm_bindgen]
+    pub fn get_lava_mask(&self) -> js_sys::Float32Array {
+        let array = js_sys::Float32Array::new_with_length(self.lava_mask.len() as u32);
+        array.copy_from(&self.lava_mask);
+        array
+    }
+
+    // Solidified lava thickness added to the terrain
+    #[wasm_bindgen]
+    pub fn get_thickness(&self) -> js_sys::Float32Array {
+        let array = js_sys::Float32Array::new_with_length(self.thickness.len() as u32);
+        array.copy_from(&self.thickness);
+        array
+    }
+
+    // Vent positions as flat x, y pairs
+    #[wasm_bindgen]
+    pub fn get_vents(&self) -> js_sys::Uint32Array {
+        let array = js_sys::Uint32Array::new_with_length(self.vents.len() as u32);
+        array.copy_from(&self.vents);
+        array
+    }
+
+    // Internal methods for Rust use
+    pub(crate) fn lava_mask(&self) -> &[f32] {
+        &self.lava_mask
+    }
+}
+
+// Pick the highest cells as vents, keeping them a quarter of the map apart
+fn pick_vents(height_field: &HeightField, count: usize) -> Vec<u32> {
+    let n = height_field.size();
+    let data = height_field.data();
+    let spacing = (n as f32 * 0.25).max(1.0);
+
+    let mut order: Vec<usize> = (0..data.len()).collect();
+    order.sort_by(|&a, &b| data[b].partial_cmp(&data[a]).unwrap_or(std::cmp::Ordering::Equal));
+
+    // x, y pairs of at most one vent per cell
+    let wanted = count.min(order.len()) * 2;
+    let mut vents: Vec<u32> = Vec::with_capacity(wanted);
+    for idx in order {
+        if vents.len() >= wanted {
+            break;
+        }
+        let x = (idx % n) as f32;
+        let y = (idx / n) as f32;
+        let too_close = vents.chunks(2).any(|v| {
+            let dx = v[0] as f32 - x;
+            let dy = v[1] as f32 - y;
+            (dx * dx + dy * dy).sqrt() < spacing
+        });
+        if !too_close {
+            vents.push(x as u32);
+            vents.push(y as u32);
+        }
+    }
+    vents
+}
+
+// Route lava downhill from vents as a Bingham-like fluid: only lava thicker than the yield
+// strength moves, flow margins cool faster and build levees, and everything left solidifies.
+pub fn simulate_lava_flows(height_field: &mut HeightField, vents: &[u32], params: &LavaFlowParams) -> LavaField {
+    let n = height_field.size();
+    let vents = if vents.len() >= 2 {
+        vents.to_vec()
+    } else {
+        pick_vents(height_field, params.vent_count as usize)
+    };
+
+    let vent_cells: Vec<usize> = vents
+        .chunks_exact(2)
+        .filter(|v| (v[0] as usize) < n && (v[1] as usize) < n)
+        .map(|v| v[1] as usize * n + v[0] as usize)
+        .collect();
+
+    let mut lava = vec![0.0f32; n * n];
+    let mut solid = vec![0.0f32; n * n];
+    let mut delta = vec![0.0f32; n * n];
+    let effusion = if params.steps > 0 { params.volume / params.steps as f32 } else { 0.0 };
+
+    for _step in 0..params.steps {
+        for &v in &vent_cells {
+            lava[v] += effusion;
+        }
+
+        // Distribute mobile lava to lower neighbors in proportion to the surface drop
+        delta.iter_mut().for_each(|d| *d = 0.0);
+        let data = height_field.data();
+        for y in 0..n {
+            for x in 0..n {
+                let idx = y * n + x;
+                let mobile = lava[idx] - params.yield_strength;
+                if mobile <= 0.0 {
+                    continue;
+                }
+                let surface = data[idx] + lava[idx];
+
+                let mut drops = [0.0f32; 8];
+                let mut targets = [0usize; 8];
+                let mut total_drop = 0.0;
+                let mut max_drop = 0.0f32;
+                let mut k = 0;
+                for dy in -1i32..=1 {
+                    for dx in -1i32..=1 {
+                        if dx == 0 && dy == 0 {
+                            continue;
+                        }
+                        let nx = x as i32 + dx;
+                        let ny = y as i32 + dy;
+                        if nx < 0 || ny < 0 || nx as usize >= n || ny as usize >= n {
+                            continue;
+                        }
+                        let n_idx = ny as usize * n + nx as usize;
+                        let drop = surface - (data[n_idx] + lava[n_idx]);
+                        if drop > 0.0 {
+                            drops[k] = drop;
+                            targets[k] = n_idx;
+                            total_drop += drop;
+                            max_drop = max_drop.max(drop);
+                            k += 1;
+                        }
+                    }
+                }
+                if k == 0 {
+                    continue;
+                }
+
+                let moved = mobile.min(max_drop * 0.5);
+                delta[idx] -= moved;
+                for j in 0..k {
+                    delta[targets[j]] += moved * drops[j] / total_drop;
+                }
+            }
+        }
+
+        // Cool: margins (cells bordering bare ground) solidify twice as fast
+        let data = height_field.data_mut();
+        for idx in 0..n * n {
+            lava[idx] = (lava[idx] + delta[idx]).max(0.0);
+            if lava[idx] <= LAVA_EPSILON {
+                continue;
+            }
+            let x = idx % n;
+            let y = idx / n;
+            let margin = (x > 0 && lava[idx - 1] <= LAVA_EPSILON)
+                || (x + 1 < n && lava[idx + 1] <= LAVA_EPSILON)
+                || (y > 0 && lava[idx - n] <= LAVA_EPSILON)
+                || (y + 1 < n && lava[idx + n] <= LAVA_EPSILON);
+            let rate = if margin { params.cooling_rate * 2.0 } else { params.cooling_rate };
+            let cooled = lava[idx] * rate.min(1.0);
+            lava[idx] -= cooled;
+            solid[idx] += cooled;
+            data[idx] += cooled;
+        }
+    }
+
+    // Whatever is still molten hardens in place
+    let data = height_field.data_mut();
+    for idx in 0..n * n {
+        data[idx] += lava[idx];
+        solid[idx] += lava[idx];
+    }
+
+    let lava_mask = solid.iter().map(|&t| if t > LAVA_EPSILON { 1.0 } else { 0.0 }).collect();
+
+    LavaField {
+        lava_mask,
+        thickness: solid,
+        vents,
+        size: n,
+    }
+}
+
+#[wasm_bindgen]
+pub fn apply_lava_flows(height_field: &mut HeightField, vents: &[u32], params: &LavaFlowParams) -> LavaField {
+    simulate_lava_flows(height_field, vents, params)
+}
@@ -1,4 +1,8 @@
+use crate::error::TerrainError;
+use crate::filters::{distance_to_mask, distance_to_mask_avoiding, slope_at};
 use crate::height_field::HeightField;
+use crate::supersample::carve_supersampled;
+use crate::units::WorldScale;
 use wasm_bindgen::prelude::*;
 
 #[wasm_bindgen]
@@ -10,6 +14,62 @@ pub struct WaterSystemParams {
     pub river_depth: f32,
     pub coastal_erosion: f32,
     pub beach_width: f32,
+    pub beach_max_slope: f32, // shores steeper than this (cliffs) get no sand; Infinity = no limit
+    pub use_wave_exposure: bool, // modulate beaches and coastal erosion by wind fetch
+    pub wind_direction: f32,     // radians, direction the prevailing wind blows towards
+    pub levee_height: f32,       // natural levee crest along major rivers (height units), 0 = none
+    pub terrace_count: u32,      // floodplain steps beside major rivers, 0 = flat valley floors
+    // Lowest height river carving and coastal erosion may cut to. Unset: a channel depth
+    // (rivers) or one erosion step (coast) below sea level; -Infinity: no bound.
+    pub carve_floor: Option<f32>,
+    pub antialias_rivers: bool, // redraw channels as smooth strokes along their centerlines
+    pub min_channel_width: f32, // cells; narrower stretches of channel are widened, <= 1 = off
+    pub supersample_carving: bool, // carve channels at 2x and average back, for smooth diagonal banks
+    // Set: river_width, beach_width and min_channel_width are meters and beach_max_slope is a
+    // gradient (rise over run), so the same values hold at any resolution
+    pub world_scale: Option<WorldScale>,
+    river_constraints: Vec<RiverConstraint>,
+    lake_constraints: Vec<LakeConstraint>,
+}
+
+// Shore slope limit for beaches at the reference cell size, in height units per cell. Generation
+// applies it only with a world scale, where it holds at every resolution; plain parameters
+// leave beaches unlimited, as before the limit existed.
+pub(crate) const DEFAULT_BEACH_MAX_SLOPE: f32 = 0.01;
+
+// Named hydrology settings for common climates
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum WaterPreset {
+    WetTemperate = 0, // dense network of moderate rivers with terraced valleys
+    Monsoon = 1,      // many wide, shallow rivers with high levees and wave-worked coasts
+    Arid = 2,         // few deeply incised wadis, broad sand beaches
+    Glacial = 3,      // wide braided outwash channels, narrow gravel shores, stepped terraces
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum MapEdge {
+    North = 0,
+    East = 1,
+    South = 2,
+    West = 3,
+}
+
+// A river that must rise at (source_x, source_y) and leave the map through `exit`
+#[derive(Clone, Copy)]
+struct RiverConstraint {
+    source_x: usize,
+    source_y: usize,
+    exit: MapEdge,
+}
+
+// A lake of at least `min_area` cells that must exist around (x, y)
+#[derive(Clone, Copy)]
+struct LakeConstraint {
+    x: usize,
+    y: usize,
+    min_area: f32,
 }
 
 #[wasm_bindgen]
@@ -30,7 +90,156 @@ impl WaterSystemParams {
             river_depth,
             coastal_erosion,
             beach_width,
+            beach_max_slope: f32::INFINITY,
+            use_wave_exposure: false,
+            wind_direction: 0.0,
+            levee_height: 0.0,
+            terrace_count: 0,
+            carve_floor: None,
+            antialias_rivers: false,
+            min_channel_width: 0.0,
+            supersample_carving: false,
+            world_scale: None,
+            river_constraints: Vec::new(),
+            lake_constraints: Vec::new(),
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn preset(preset: WaterPreset, sea_level: f32) -> Self {
+        match preset {
+            WaterPreset::WetTemperate => Self {
+                levee_height: 0.003,
+                terrace_count: 3,
+                ..Self::new(sea_level, 0.05, 8.0, 0.05, 0.04, 8.0)
+            },
+            WaterPreset::Monsoon => Self {
+                levee_height: 0.005,
+                terrace_count: 4,
+                ..Self::new(sea_level, 0.03, 12.0, 0.04, 0.06, 10.0)
+            },
+            WaterPreset::Arid => Self {
+                levee_height: 0.0,
+                terrace_count: 1,
+                ..Self::new(sea_level, 0.2, 4.0, 0.08, 0.02, 12.0)
+            },
+            WaterPreset::Glacial => Self {
+                beach_max_slope: 0.006,
+                terrace_count: 5,
+                ..Self::new(sea_level, 0.1, 10.0, 0.03, 0.03, 4.0)
+            },
+        }
+    }
+
+    // Check the parameters against a map of `map_size` cells before running the water system.
+    // Settings that can only produce empty or meaningless masks are an error naming the field;
+    // widths larger than the map are clamped to it.
+    #[wasm_bindgen]
+    pub fn validate(&mut self, map_size: usize) -> Result<(), JsValue> {
+        self.check(map_size).map_err(JsValue::from)
+    }
+
+    // Guarantee a river from near (source_x, source_y) to the given map edge. A drainage
+    // corridor is pre-carved along the cheapest mostly-downhill route before flow accumulation.
+    #[wasm_bindgen]
+    pub fn require_river(&mut self, source_x: usize, source_y: usize, exit: MapEdge) {
+        self.river_constraints.push(RiverConstraint { source_x, source_y, exit });
+    }
+
+    #[wasm_bindgen]
+    pub fn clear_river_constraints(&mut self) {
+        self.river_constraints.clear();
+    }
+
+    // Guarantee a lake of at least `min_area` cells at (x, y): a basin with a noise-perturbed
+    // outline is deepened below its rim so it holds water up to the lowest rim cell
+    #[wasm_bindgen]
+    pub fn require_lake(&mut self, x: usize, y: usize, min_area: f32) {
+        self.lake_constraints.push(LakeConstraint { x, y, min_area });
+    }
+
+    #[wasm_bindgen]
+    pub fn clear_lake_constraints(&mut self) {
+        self.lake_constraints.clear();
+    }
+
+    // Internal methods for Rust use
+    // These parameters with widths in cells and slopes in height units per cell
+    pub(crate) fn in_cells(&self) -> WaterSystemParams {
+        let mut params = self.clone();
+        if let Some(scale) = params.world_scale.take() {
+            params.river_width = scale.to_cells(self.river_width);
+            params.beach_width = scale.to_cells(self.beach_width);
+            params.min_channel_width = scale.to_cells(self.min_channel_width);
+            params.beach_max_slope = scale.slope_per_cell(self.beach_max_slope);
+        }
+        params
+    }
+
+    pub(crate) fn check(&mut self, map_size: usize) -> Result<(), TerrainError> {
+        let finite = [
+            ("sea_level", self.sea_level),
+            ("river_threshold", self.river_threshold),
+            ("river_width", self.river_width),
+            ("river_depth", self.river_depth),
+            ("coastal_erosion", self.coastal_erosion),
+            ("beach_width", self.beach_width),
+            ("wind_direction", self.wind_direction),
+            ("levee_height", self.levee_height),
+            ("min_channel_width", self.min_channel_width),
+        ];
+        if let Some((name, value)) = finite.iter().find(|(_, v)| !v.is_finite()) {
+            return Err(TerrainError::InvalidParameter(format!("{} must be a finite number, got {}", name, value)));
+        }
+        if self.river_threshold <= 0.0 || self.river_threshold >= 1.0 {
+            return Err(TerrainError::InvalidParameter(format!(
+                "river_threshold is a share of the largest flow and must lie strictly between 0 and 1, got {}; \
+                 0 turns every cell into river and 1 or more leaves no river at all",
+                self.river_threshold
+            )));
+        }
+        let non_negative = [
+            ("river_width", self.river_width),
+            ("river_depth", self.river_depth),
+            ("coastal_erosion", self.coastal_erosion),
+            ("beach_width", self.beach_width),
+            ("levee_height", self.levee_height),
+            ("min_channel_width", self.min_channel_width),
+        ];
+        if let Some((name, value)) = non_negative.iter().find(|(_, v)| *v < 0.0) {
+            return Err(TerrainError::InvalidParameter(format!("{} must not be negative, got {}", name, value)));
+        }
+        if let Some(scale) = self.world_scale {
+            if !(scale.cell_size_m > 0.0 && scale.height_scale_m > 0.0) {
+                return Err(TerrainError::InvalidParameter(format!(
+                    "world_scale needs a positive cell size and height scale, got {}m and {}m",
+                    scale.cell_size_m, scale.height_scale_m
+                )));
+            }
+        }
+        if self.beach_max_slope.is_nan() || self.beach_max_slope <= 0.0 {
+            return Err(TerrainError::InvalidParameter(format!(
+                "beach_max_slope must be positive, got {}; no shore would get sand",
+                self.beach_max_slope
+            )));
+        }
+        if let Some(c) = self.river_constraints.iter().find(|c| c.source_x >= map_size || c.source_y >= map_size) {
+            return Err(TerrainError::InvalidParameter(format!(
+                "required river source ({}, {}) lies outside the {}x{} map",
+                c.source_x, c.source_y, map_size, map_size
+            )));
         }
+        if let Some(c) = self.lake_constraints.iter().find(|c| c.x >= map_size || c.y >= map_size) {
+            return Err(TerrainError::InvalidParameter(format!(
+                "required lake at ({}, {}) lies outside the {}x{} map",
+                c.x, c.y, map_size, map_size
+            )));
+        }
+
+        let limit = map_size as f32 * self.world_scale.map_or(1.0, |scale| scale.cell_size_m);
+        self.beach_width = self.beach_width.min(limit);
+        self.river_width = self.river_width.min(limit);
+        Ok(())
     }
 }
 
@@ -41,11 +250,15 @@ pub struct WaterFeatures {
     river_mask: Vec<f32>,
     beach_mask: Vec<f32>,
     flow_accumulation: Vec<f32>,
+    terrace_age: Vec<f32>,
+    surface_age: Vec<f32>,
     size: usize,
 }
 
 #[wasm_bindgen]
 impl WaterFeatures {
+    // Empty masks for a size × size map, for APIs that take water features when there is no water
+    #[wasm_bindgen(constructor)]
     pub fn new(size: usize) -> Self {
         let len = size * size;
         Self {
@@ -53,6 +266,8 @@ impl WaterFeatures {
             river_mask: vec![0.0; len],
             beach_mask: vec![0.0; len],
             flow_accumulation: vec![0.0; len],
+            terrace_age: vec![0.0; len],
+            surface_age: vec![0.0; len],
             size,
         }
     }
@@ -90,77 +305,350 @@ impl WaterFeatures {
         array
     }
 
+    // Valley-floor steps along major rivers: 0 off the valley floor, otherwise rising from the
+    // active floodplain to 1 on the highest (oldest) terrace
+    #[wasm_bindgen]
+    pub fn get_terrace_age(&self) -> js_sys::Float32Array {
+        let array = js_sys::Float32Array::new_with_length(self.terrace_age.len() as u32);
+        array.copy_from(&self.terrace_age);
+        array
+    }
+
+    // Years since erosion or deposition last renewed each cell's surface; 0 everywhere unless
+    // the features came from geological erosion
+    #[wasm_bindgen]
+    pub fn get_surface_age(&self) -> js_sys::Float32Array {
+        let array = js_sys::Float32Array::new_with_length(self.surface_age.len() as u32);
+        array.copy_from(&self.surface_age);
+        array
+    }
+
+    // Rivers, lakes and drainage summary
+    #[wasm_bindgen]
+    pub fn report(&self) -> crate::analysis::HydrologyReport {
+        crate::analysis::hydrology_report(self)
+    }
+
     // Convert to JS object for interop
     pub fn to_js_object(&self) -> js_sys::Object {
         let obj = js_sys::Object::new();
         
-        js_sys::Reflect::set(&obj, &"waterMask".into(), &self.get_water_mask()).unwrap();
-        js_sys::Reflect::set(&obj, &"riverMask".into(), &self.get_river_mask()).unwrap();
-        js_sys::Reflect::set(&obj, &"beachMask".into(), &self.get_beach_mask()).unwrap();
-        js_sys::Reflect::set(&obj, &"flowAccumulation".into(), &self.get_flow_accumulation()).unwrap();
+        for (key, raster) in self.named_rasters() {
+            js_sys::Reflect::set(&obj, &key.into(), &js_sys::Float32Array::from(raster)).unwrap();
+        }
         
         obj
     }
+
+    // Internal methods for Rust use
+    // Every raster with the key to_js_object exports it under
+    pub(crate) fn named_rasters(&self) -> [(&'static str, &[f32]); 6] {
+        [
+            ("waterMask", &self.water_mask),
+            ("riverMask", &self.river_mask),
+            ("beachMask", &self.beach_mask),
+            ("flowAccumulation", &self.flow_accumulation),
+            ("terraceAge", &self.terrace_age),
+            ("surfaceAge", &self.surface_age),
+        ]
+    }
+
+    pub(crate) fn water_mask(&self) -> &[f32] {
+        &self.water_mask
+    }
+
+    pub(crate) fn river_mask(&self) -> &[f32] {
+        &self.river_mask
+    }
+
+    pub(crate) fn beach_mask(&self) -> &[f32] {
+        &self.beach_mask
+    }
+
+    pub(crate) fn flow_accumulation(&self) -> &[f32] {
+        &self.flow_accumulation
+    }
+
+    pub(crate) fn surface_age(&self) -> &[f32] {
+        &self.surface_age
+    }
+
+    pub(crate) fn set_surface_age(&mut self, age: Vec<f32>) {
+        self.surface_age = age;
+    }
+
+    pub(crate) fn terrace_age(&self) -> &[f32] {
+        &self.terrace_age
+    }
+
+    pub(crate) fn set_terrace_age(&mut self, age: Vec<f32>) {
+        self.terrace_age = age;
+    }
+
+    // The same features with every raster replaced by `f` of it, as a `size`-wide grid
+    pub(crate) fn map_rasters(&self, size: usize, f: impl Fn(&[f32]) -> Vec<f32>) -> WaterFeatures {
+        WaterFeatures {
+            water_mask: f(&self.water_mask),
+            river_mask: f(&self.river_mask),
+            beach_mask: f(&self.beach_mask),
+            flow_accumulation: f(&self.flow_accumulation),
+            terrace_age: f(&self.terrace_age),
+            surface_age: f(&self.surface_age),
+            size,
+        }
+    }
+
+    // Raster by raster combination of these features with `other`, of the same size
+    pub(crate) fn zip_rasters(&self, other: &WaterFeatures, f: impl Fn(&[f32], &[f32]) -> Vec<f32>) -> WaterFeatures {
+        WaterFeatures {
+            water_mask: f(&self.water_mask, &other.water_mask),
+            river_mask: f(&self.river_mask, &other.river_mask),
+            beach_mask: f(&self.beach_mask, &other.beach_mask),
+            flow_accumulation: f(&self.flow_accumulation, &other.flow_accumulation),
+            terrace_age: f(&self.terrace_age, &other.terrace_age),
+            surface_age: f(&self.surface_age, &other.surface_age),
+            size: self.size,
+        }
+    }
+
+    // Merge water bodies placed after the water system ran (e.g. oasis pools)
+    pub(crate) fn add_standing_water(&mut self, mask: &[f32]) {
+        for (w, &m) in self.water_mask.iter_mut().zip(mask) {
+            *w = w.max(m);
+        }
+    }
 }
 
 // D8 flow directions: N, NE, E, SE, S, SW, W, NW
 const DX: [i32; 8] = [0, 1, 1, 1, 0, -1, -1, -1];
 const DY: [i32; 8] = [-1, -1, 0, 1, 1, 1, 0, -1];
 
-// Calculate flow accumulation using D8 algorithm
-fn calculate_flow_accumulation(height_field: &HeightField) -> Vec<f32> {
-    let size = height_field.size();
+// Cost of climbing one height unit, relative to walking one map width, when routing corridors
+const CORRIDOR_UPHILL_PENALTY: f32 = 50.0;
+// Minimum drop per cell along a carved corridor so D8 routing follows it
+const CORRIDOR_DROP: f32 = 1e-5;
+
+// Cheapest 8-connected path from the source to any cell of the exit edge (Dijkstra). Steps
+// cost their length plus a penalty for climbing, so the route follows existing valleys.
+fn route_corridor(height_field: &HeightField, constraint: &RiverConstraint) -> Vec<usize> {
+    use std::cmp::Reverse;
+    use std::collections::BinaryHeap;
+
+    let n = height_field.size();
     let data = height_field.data();
-    
-    if size == 0 || data.is_empty() {
-        return vec![0.0; size * size];
+    let source = constraint.source_y.min(n - 1) * n + constraint.source_x.min(n - 1);
+    let on_exit = |idx: usize| match constraint.exit {
+        MapEdge::North => idx / n == 0,
+        MapEdge::East => idx % n == n - 1,
+        MapEdge::South => idx / n == n - 1,
+        MapEdge::West => idx.is_multiple_of(n),
+    };
+
+    let climb_cost = CORRIDOR_UPHILL_PENALTY * n as f32;
+    let mut cost = vec![f32::INFINITY; n * n];
+    let mut previous = vec![usize::MAX; n * n];
+    let mut heap = BinaryHeap::new();
+    cost[source] = 0.0;
+    heap.push((Reverse(0u32), source));
+
+    let mut end = None;
+    while let Some((Reverse(c), idx)) = heap.pop() {
+        let c = f32::from_bits(c);
+        if c > cost[idx] {
+            continue;
+        }
+        if on_exit(idx) {
+            end = Some(idx);
+            break;
+        }
+        let (x, y) = ((idx % n) as i32, (idx / n) as i32);
+        for dir in 0..8 {
+            let nx = x + DX[dir];
+            let ny = y + DY[dir];
+            if nx < 0 || ny < 0 || nx as usize >= n || ny as usize >= n {
+                continue;
+            }
+            let n_idx = ny as usize * n + nx as usize;
+            let step = ((DX[dir] * DX[dir] + DY[dir] * DY[dir]) as f32).sqrt();
+            let next = c + step + (data[n_idx] - data[idx]).max(0.0) * climb_cost;
+            if next < cost[n_idx] {
+                cost[n_idx] = next;
+                previous[n_idx] = idx;
+                // Non-negative f32 bit patterns order like the values themselves
+                heap.push((Reverse(next.to_bits()), n_idx));
+            }
+        }
     }
-    
-    let mut flow = vec![1.0f32; size * size]; // Start with 1 unit of flow
-    let mut processed = vec![false; size * size];
-    
-    // Create height-sorted list of points (highest first)
-    let mut points: Vec<(usize, usize, f32, usize)> = Vec::new();
-    for y in 0..size {
-        for x in 0..size {
-            let idx = y * size + x;
-            points.push((x, y, data[idx], idx));
+
+    let mut path = Vec::new();
+    let mut idx = match end {
+        Some(idx) => idx,
+        None => return path,
+    };
+    while idx != usize::MAX {
+        path.push(idx);
+        idx = previous[idx];
+    }
+    path.reverse();
+    path
+}
+
+// Carve a strictly descending channel (with a one-cell feathered bank) along each required
+// river and return the corridor cells
+fn carve_required_rivers(height_field: &mut HeightField, constraints: &[RiverConstraint]) -> Vec<usize> {
+    let n = height_field.size();
+    let mut corridor = Vec::new();
+    if n < 2 {
+        return corridor;
+    }
+
+    for constraint in constraints {
+        let path = route_corridor(height_field, constraint);
+        let data = height_field.data_mut();
+        let mut level = f32::INFINITY;
+        for &idx in &path {
+            level = data[idx].min(level - CORRIDOR_DROP);
+            data[idx] = level;
+
+            // Lower the banks halfway so water on either side drains into the channel
+            let (x, y) = ((idx % n) as i32, (idx / n) as i32);
+            for dir in 0..8 {
+                let nx = x + DX[dir];
+                let ny = y + DY[dir];
+                if nx >= 0 && ny >= 0 && (nx as usize) < n && (ny as usize) < n {
+                    let n_idx = ny as usize * n + nx as usize;
+                    if data[n_idx] > level {
+                        data[n_idx] = level + (data[n_idx] - level) * 0.5;
+                    }
+                }
+            }
         }
+        corridor.extend(path);
     }
-    points.sort_by(|a, b| b.2.partial_cmp(&a.2).unwrap_or(std::cmp::Ordering::Equal));
-    
-    // Process from highest to lowest
-    for (x, y, _height, idx) in points {
-        if processed[idx] {
+    corridor
+}
+
+// Deepest point of a required lake basin below its rim, in height units
+const LAKE_DEPTH: f32 = 0.02;
+// Shoreline radius varies by up to this fraction above the minimum radius
+const LAKE_SHORE_JITTER: f32 = 0.35;
+
+// Carve each required lake basin and return the cells that end up under water
+fn carve_required_lakes(height_field: &mut HeightField, constraints: &[LakeConstraint]) -> Vec<usize> {
+    let n = height_field.size();
+    let mut lake_cells = Vec::new();
+    if n < 3 {
+        return lake_cells;
+    }
+
+    for (k, lake) in constraints.iter().enumerate() {
+        let cx = lake.x.min(n - 1) as f32;
+        let cy = lake.y.min(n - 1) as f32;
+        let base_radius = (lake.min_area.max(1.0) / std::f32::consts::PI).sqrt();
+        // Shore radius in a given direction; sampled on a circle so it wraps around seamlessly
+        let shore = |dx: f32, dy: f32| {
+            let d = (dx * dx + dy * dy).sqrt().max(f32::EPSILON);
+            let (ux, uy) = (dx / d, dy / d);
+            let noise = crate::noise::value_noise_2d(ux * 1.7 + cx * 0.37 + k as f32 * 13.1, uy * 1.7 + cy * 0.37);
+            base_radius * (1.0 + LAKE_SHORE_JITTER * noise)
+        };
+
+        let reach = (base_radius * (1.0 + LAKE_SHORE_JITTER) + 2.0).ceil() as i32;
+        let x0 = (cx as i32 - reach).max(0);
+        let y0 = (cy as i32 - reach).max(0);
+        let x1 = (cx as i32 + reach).min(n as i32 - 1);
+        let y1 = (cy as i32 + reach).min(n as i32 - 1);
+
+        // The rim (cells just outside the shoreline) sets the spill level
+        let data = height_field.data_mut();
+        let mut rim = f32::INFINITY;
+        for y in y0..=y1 {
+            for x in x0..=x1 {
+                let (dx, dy) = (x as f32 - cx, y as f32 - cy);
+                let d = (dx * dx + dy * dy).sqrt();
+                let r = shore(dx, dy);
+                if d > r && d <= r + 1.5 {
+                    rim = rim.min(data[y as usize * n + x as usize]);
+                }
+            }
+        }
+        if !rim.is_finite() {
             continue;
         }
-        
-        let mut steepest_slope = 0.0;
-        let mut flow_to_idx = None;
-        
-        // Find steepest downhill neighbor
-        for dir in 0..8 {
-            let nx = x as i32 + DX[dir];
-            let ny = y as i32 + DY[dir];
+
+        for y in y0..=y1 {
+            for x in x0..=x1 {
+                let (dx, dy) = (x as f32 - cx, y as f32 - cy);
+                let t = (dx * dx + dy * dy).sqrt() / shore(dx, dy);
+                if t <= 1.0 {
+                    let idx = y as usize * n + x as usize;
+                    let floor = rim - CORRIDOR_DROP - LAKE_DEPTH * (1.0 - t * t);
+                    data[idx] = data[idx].min(floor);
+                    lake_cells.push(idx);
+                }
+            }
+        }
+    }
+    lake_cells
+}
+
+// Steepest-descent (D8) receiver of every cell, usize::MAX for pits and flats
+pub(crate) fn flow_receivers(height_field: &HeightField) -> Vec<usize> {
+    let size = height_field.size();
+    let data = height_field.data();
+    let mut receivers = vec![usize::MAX; size * size];
+    
+    for y in 0..size {
+        for x in 0..size {
+            let idx = y * size + x;
+            let mut steepest_slope = 0.0;
             
-            if nx >= 0 && (nx as usize) < size && ny >= 0 && (ny as usize) < size {
-                let n_idx = (ny as usize) * size + (nx as usize);
-                let distance = ((DX[dir] * DX[dir] + DY[dir] * DY[dir]) as f32).sqrt();
-                let slope = (data[idx] - data[n_idx]) / distance;
+            // Find steepest downhill neighbor
+            for dir in 0..8 {
+                let nx = x as i32 + DX[dir];
+                let ny = y as i32 + DY[dir];
                 
-                if slope > steepest_slope {
-                    steepest_slope = slope;
-                    flow_to_idx = Some(n_idx);
+                if nx >= 0 && (nx as usize) < size && ny >= 0 && (ny as usize) < size {
+                    let n_idx = (ny as usize) * size + (nx as usize);
+                    let distance = ((DX[dir] * DX[dir] + DY[dir] * DY[dir]) as f32).sqrt();
+                    let slope = (data[idx] - data[n_idx]) / distance;
+                    
+                    if slope > steepest_slope {
+                        steepest_slope = slope;
+                        receivers[idx] = n_idx;
+                    }
                 }
             }
         }
-        
-        // Accumulate flow to steepest neighbor
-        if let Some(target_idx) = flow_to_idx {
-            flow[target_idx] += flow[idx];
+    }
+    
+    receivers
+}
+
+// Cell indices from highest to lowest, so every cell comes before its receiver
+pub(crate) fn cells_by_height(height_field: &HeightField) -> Vec<usize> {
+    let data = height_field.data();
+    let mut order: Vec<usize> = (0..data.len()).collect();
+    order.sort_by(|&a, &b| data[b].partial_cmp(&data[a]).unwrap_or(std::cmp::Ordering::Equal));
+    order
+}
+
+// Calculate flow accumulation using D8 algorithm
+pub(crate) fn calculate_flow_accumulation(height_field: &HeightField) -> Vec<f32> {
+    let size = height_field.size();
+    
+    if size == 0 || height_field.data().is_empty() {
+        return vec![0.0; size * size];
+    }
+    
+    let mut flow = vec![1.0f32; size * size]; // Start with 1 unit of flow
+    let receivers = flow_receivers(height_field);
+    
+    // Process from highest to lowest, passing flow to the steepest neighbor
+    for idx in cells_by_height(height_field) {
+        if receivers[idx] != usize::MAX {
+            flow[receivers[idx]] += flow[idx];
         }
-        
-        processed[idx] = true;
     }
     
     flow
@@ -227,53 +715,312 @@ fn generate_river_mask(
     smoothed
 }
 
-// Generate beach mask around water areas
-fn generate_beach_mask(height_field: &HeightField, sea_level: f32, beach_width: f32) -> Vec<f32> {
+// Channel paths (cell indices) from each source down to a `sink` cell or a confluence, where
+// each channel cell drains into the neighboring channel or sink cell with the most flow.
+// Paths include the cell they end in; the longest are traced first so main stems stay whole.
+pub(crate) fn river_paths(size: usize, river: &[bool], sink: &[bool], flow: &[f32]) -> Vec<Vec<usize>> {
+    let n = size;
+    let mut downstream = vec![usize::MAX; n * n];
+    let mut has_upstream = vec![false; n * n];
+    for idx in (0..n * n).filter(|&i| river[i]) {
+        let (x, y) = ((idx % n) as i32, (idx / n) as i32);
+        let mut best = flow[idx];
+        for dy in -1i32..=1 {
+            for dx in -1i32..=1 {
+                let (nx, ny) = (x + dx, y + dy);
+                if (dx == 0 && dy == 0) || nx < 0 || ny < 0 || nx as usize >= n || ny as usize >= n {
+                    continue;
+                }
+                let n_idx = ny as usize * n + nx as usize;
+                if (river[n_idx] || sink[n_idx]) && flow[n_idx] > best {
+                    best = flow[n_idx];
+                    downstream[idx] = n_idx;
+                }
+            }
+        }
+        if downstream[idx] != usize::MAX {
+            has_upstream[downstream[idx]] = true;
+        }
+    }
+
+    // Cells to the end of the channel, resolved downstream first (flow rises downstream)
+    let mut order: Vec<usize> = (0..n * n).filter(|&i| river[i]).collect();
+    order.sort_by(|&a, &b| flow[b].partial_cmp(&flow[a]).unwrap_or(std::cmp::Ordering::Equal));
+    let mut length = vec![0u32; n * n];
+    for &idx in &order {
+        let down = downstream[idx];
+        length[idx] = 1 + if down != usize::MAX && river[down] { length[down] } else { 0 };
+    }
+    let mut sources: Vec<usize> = order.into_iter().filter(|&i| !has_upstream[i]).collect();
+    sources.sort_by_key(|&i| std::cmp::Reverse(length[i]));
+
+    let mut visited = vec![false; n * n];
+    let mut paths = Vec::new();
+    for source in sources {
+        let mut path = Vec::new();
+        let mut idx = source;
+        loop {
+            path.push(idx);
+            if !river[idx] || visited[idx] {
+                break;
+            }
+            visited[idx] = true;
+            if downstream[idx] == usize::MAX {
+                break;
+            }
+            idx = downstream[idx];
+        }
+        if path.len() >= 2 {
+            paths.push(path);
+        }
+    }
+    paths
+}
+
+// Corner-cutting passes applied to river centerlines before they are re-drawn
+const CENTERLINE_SMOOTHING: usize = 2;
+
+// Re-draw the channels of a river mask as anti-aliased strokes along smoothed centerlines.
+// Stroke width grows with flow up to `river_width` cells and edges get a one-cell coverage
+// ramp, so the mask reads as a smooth texture at 1:1. Returns the new mask and the carving
+// strength, which follows a rounded cross-section instead of the flat mask.
+fn antialias_river_mask(
+    river_mask: &[f32],
+    flow_accumulation: &[f32],
+    size: usize,
+    threshold: f32,
+    river_width: f32,
+) -> (Vec<f32>, Vec<f32>) {
+    let max_flow = flow_accumulation.iter().fold(0.0f32, |max, &val| max.max(val));
+    if max_flow == 0.0 {
+        return (river_mask.to_vec(), river_mask.to_vec());
+    }
+    let strength = |idx: usize| ((flow_accumulation[idx] / max_flow - threshold) / (1.0 - threshold)).clamp(0.0, 1.0);
+    let channel: Vec<bool> = flow_accumulation.iter().map(|&f| f / max_flow > threshold).collect();
+
+    // Banks and faint tributaries stay as they are; the channels are replaced by strokes
+    let mut mask: Vec<f32> = (0..size * size).map(|i| if channel[i] { 0.0 } else { river_mask[i] }).collect();
+    let mut carve = mask.clone();
+
+    for path in river_paths(size, &channel, &channel, flow_accumulation) {
+        // x, y, half width, strength per vertex
+        let mut line: Vec<[f32; 4]> = path
+            .iter()
+            .map(|&idx| {
+                let s = strength(idx);
+                let width = 1.0 + (river_width - 1.0).max(0.0) * s.sqrt();
+                [(idx % size) as f32, (idx / size) as f32, width * 0.5, s]
+            })
+            .collect();
+        for _ in 0..CENTERLINE_SMOOTHING {
+            line = chaikin(&line);
+        }
+
+        for segment in line.windows(2) {
+            draw_segment(&mut mask, &mut carve, size, segment[0], segment[1]);
+        }
+    }
+    (mask, carve)
+}
+
+// Chaikin corner cutting, keeping both end points in place
+fn chaikin(line: &[[f32; 4]]) -> Vec<[f32; 4]> {
+    if line.len() < 3 {
+        return line.to_vec();
+    }
+    let lerp = |a: &[f32; 4], b: &[f32; 4], t: f32| std::array::from_fn(|k| a[k] + (b[k] - a[k]) * t);
+    let mut smoothed = vec![line[0]];
+    for pair in line.windows(2) {
+        smoothed.push(lerp(&pair[0], &pair[1], 0.25));
+        smoothed.push(lerp(&pair[0], &pair[1], 0.75));
+    }
+    smoothed.push(line[line.len() - 1]);
+    smoothed
+}
+
+// Stamp one tapered capsule: coverage ramps from 1 to 0 across the half-cell either side of
+// the stroke edge, and the carving strength is a semicircular cross-section
+fn draw_segment(mask: &mut [f32], carve: &mut [f32], size: usize, a: [f32; 4], b: [f32; 4]) {
+    let reach = a[2].max(b[2]) + 1.0;
+    let x0 = (a[0].min(b[0]) - reach).floor().max(0.0) as usize;
+    let y0 = (a[1].min(b[1]) - reach).floor().max(0.0) as usize;
+    let x1 = ((a[0].max(b[0]) + reach).ceil().max(0.0) as usize).min(size - 1);
+    let y1 = ((a[1].max(b[1]) + reach).ceil().max(0.0) as usize).min(size - 1);
+    let (dx, dy) = (b[0] - a[0], b[1] - a[1]);
+    let length_sq = dx * dx + dy * dy;
+
+    for y in y0..=y1 {
+        for x in x0..=x1 {
+            let (px, py) = (x as f32 - a[0], y as f32 - a[1]);
+            let t = if length_sq > 0.0 { ((px * dx + py * dy) / length_sq).clamp(0.0, 1.0) } else { 0.0 };
+            let distance = ((px - dx * t).powi(2) + (py - dy * t).powi(2)).sqrt();
+            let half_width = a[2] + (b[2] - a[2]) * t;
+            let coverage = (half_width + 0.5 - distance).clamp(0.0, 1.0);
+            if coverage <= 0.0 {
+                continue;
+            }
+            let s = a[3] + (b[3] - a[3]) * t;
+            let profile = (1.0 - (distance / (half_width + 0.5)).powi(2)).max(0.0).sqrt();
+            let idx = y * size + x;
+            mask[idx] = mask[idx].max(coverage * s);
+            carve[idx] = carve[idx].max(profile * s);
+        }
+    }
+}
+
+// Generate beach mask around water areas: fades from 1.0 at the waterline to 0.0 at
+// `beach_width` cells inland. Ground steeper than `max_slope` gets no sand and blocks the
+// distance transform, so cliff tops behind a steep shore stay bare too.
+// With `exposure`, sheltered shores get wide flats and exposed headlands narrow strips.
+pub(crate) fn generate_beach_mask(
+    height_field: &HeightField,
+    sea_level: f32,
+    beach_width: f32,
+    max_slope: f32,
+    exposure: Option<&[f32]>,
+) -> Vec<f32> {
     let size = height_field.size();
     let data = height_field.data();
-    let mut beach_mask = vec![0.0f32; size * size];
-    let mut water_mask = vec![0.0f32; size * size];
-    
-    // First pass: identify water areas
-    for i in 0..data.len() {
-        water_mask[i] = if data[i] <= sea_level { 1.0 } else { 0.0 };
+
+    let water: Vec<bool> = data.iter().map(|&h| h <= sea_level).collect();
+    let mut steep = vec![false; size * size];
+    for y in 0..size {
+        for x in 0..size {
+            let idx = y * size + x;
+            steep[idx] = !water[idx] && slope_at(height_field, x, y) > max_slope;
+        }
     }
-    
-    // Second pass: expand water areas to create beaches
-    let beach_pixels = beach_width.ceil() as i32;
+    let distance = distance_to_mask_avoiding(&water, &steep, size);
+
+    let mut beach_mask = vec![0.0f32; size * size];
     for y in 0..size {
         for x in 0..size {
             let idx = y * size + x;
-            
-            if water_mask[idx] > 0.0 {
+            if water[idx] {
                 beach_mask[idx] = 1.0; // Water areas are also beaches
+            } else {
+                let width = match exposure {
+                    Some(e) => beach_width * (SHELTERED_BEACH_SCALE + (EXPOSED_BEACH_SCALE - SHELTERED_BEACH_SCALE) * e[idx]),
+                    None => beach_width,
+                };
+                if distance[idx] <= width {
+                    beach_mask[idx] = (1.0 - distance[idx] / width).max(0.0);
+                }
+            }
+        }
+    }
+
+    beach_mask
+}
+
+// Beach width multipliers for fully sheltered (mud flats) and fully exposed shores
+const SHELTERED_BEACH_SCALE: f32 = 1.6;
+const EXPOSED_BEACH_SCALE: f32 = 0.4;
+// Land cells this far from the shore still read the fetch of the water in front of them
+const FETCH_INLAND_REACH: usize = 16;
+
+// Open-water fetch along the prevailing wind, normalized to [0, 1]: for each coastal cell, the
+// number of water cells upwind before land (crossing at most FETCH_INLAND_REACH land cells
+// first), out of n/4. Water that runs off the map edge counts as open ocean (1.0). Only cells
+// within FETCH_INLAND_REACH of the shoreline trace the wind; land beyond it is sheltered (0.0)
+// and water beyond it open (1.0).
+pub fn wave_exposure(height_field: &HeightField, sea_level: f32, wind_direction: f32) -> Vec<f32> {
+    let n = height_field.size();
+    let data = height_field.data();
+    let wx = crate::det_math::cos(wind_direction);
+    let wy = crate::det_math::sin(wind_direction);
+    let max_fetch = (n / 4).max(1);
+
+    let water: Vec<bool> = data.iter().map(|&h| h <= sea_level).collect();
+    let land: Vec<bool> = water.iter().map(|&w| !w).collect();
+    let to_water = distance_to_mask(&water, n);
+    let to_land = distance_to_mask(&land, n);
+    // Slack for chamfer distances, which run up to 8% long, and the rounding of the ray's steps
+    let reach = FETCH_INLAND_REACH as f32 * 1.1 + 1.0;
+
+    let mut exposure = vec![0.0f32; n * n];
+    for y in 0..n {
+        for x in 0..n {
+            let idx = y * n + x;
+            if water[idx] && to_land[idx] > reach {
+                exposure[idx] = 1.0;
                 continue;
             }
-            
-            // Check distance to nearest water
-            let mut found_water = false;
-            for dy in -beach_pixels..=beach_pixels {
-                if found_water { break; }
-                for dx in -beach_pixels..=beach_pixels {
-                    let nx = x as i32 + dx;
-                    let ny = y as i32 + dy;
-                    
-                    if nx >= 0 && (nx as usize) < size && ny >= 0 && (ny as usize) < size {
-                        let n_idx = (ny as usize) * size + (nx as usize);
-                        let distance = ((dx * dx + dy * dy) as f32).sqrt();
-                        
-                        if water_mask[n_idx] > 0.0 && distance <= beach_width {
-                            beach_mask[idx] = (1.0 - distance / beach_width).max(0.0);
-                            found_water = true;
-                            break;
-                        }
+            if to_water[idx] > reach {
+                continue;
+            }
+            let mut fetch = 0usize;
+            let mut in_water = false;
+            for k in 0..=max_fetch + FETCH_INLAND_REACH {
+                let px = (x as f32 - wx * k as f32).round();
+                let py = (y as f32 - wy * k as f32).round();
+                if px < 0.0 || py < 0.0 || px >= n as f32 || py >= n as f32 {
+                    if in_water {
+                        fetch = max_fetch;
                     }
+                    break;
+                }
+                if data[py as usize * n + px as usize] <= sea_level {
+                    in_water = true;
+                    fetch += 1;
+                    if fetch >= max_fetch {
+                        break;
+                    }
+                } else if in_water || k > FETCH_INLAND_REACH {
+                    break;
                 }
             }
+            exposure[idx] = fetch as f32 / max_fetch as f32;
+        }
+    }
+    exposure
+}
+
+#[wasm_bindgen]
+pub fn compute_wave_exposure(height_field: &HeightField, sea_level: f32, wind_direction: f32) -> Vec<f32> {
+    wave_exposure(height_field, sea_level, wind_direction)
+}
+
+// Widen every stretch of channel narrower than `min_width` cells (rounded up to an odd
+// width). A morphological opening of the carve mask with a square keeps the parts wide enough
+// to hold it; what it removes are the thin slots, which get the strength of their nearest
+// slot cell out to the full width. One-cell gorges otherwise render as black seams and trap
+// navigation.
+fn widen_narrow_channels(river_mask: &mut [f32], river_carve: &mut [f32], size: usize, min_width: f32) {
+    let radius = ((min_width - 1.0) * 0.5).ceil() as usize;
+    let channel: Vec<bool> = river_carve.iter().map(|&c| c > 0.5).collect();
+    let opened = square_filter(&square_filter(&channel, size, radius, false), size, radius, true);
+    let thin: Vec<bool> = (0..size * size).map(|i| channel[i] && !opened[i]).collect();
+    if !thin.iter().any(|&t| t) {
+        return;
+    }
+
+    let (reach, carve) = nearest_channel(&thin, river_carve, size);
+    let (_, mask) = nearest_channel(&thin, river_mask, size);
+    for i in 0..size * size {
+        if reach[i] <= radius as f32 {
+            river_carve[i] = river_carve[i].max(carve[i]);
+            river_mask[i] = river_mask[i].max(mask[i]);
         }
     }
-    
-    beach_mask
+}
+
+// Dilation (`any`) or erosion of a mask by a (2·radius + 1)² square, one axis at a time.
+// Cells past the map edge count as set, so channels running off the map are not eroded there.
+fn square_filter(mask: &[bool], size: usize, radius: usize, any: bool) -> Vec<bool> {
+    let pass = |source: &[bool], step: usize, stride: usize| -> Vec<bool> {
+        let mut result = vec![false; size * size];
+        for line in 0..size {
+            for i in 0..size {
+                let window = i.saturating_sub(radius)..(i + radius + 1).min(size);
+                let mut values = window.map(|j| source[line * stride + j * step]);
+                result[line * stride + i * step] = if any { values.any(|v| v) } else { values.all(|v| v) };
+            }
+        }
+        result
+    };
+    let rows = pass(mask, 1, size);
+    pass(&rows, size, 1)
 }
 
 // Carve river channels into heightfield
@@ -282,6 +1029,8 @@ fn carve_rivers(
     river_mask: &[f32],
     depth: f32,
     _width: f32,
+    floor: f32,
+    fine_factor: f32, // subcells per cell side when carving supersampled; keeps slopes per original cell
 ) {
     let size = height_field.size();
     let data = height_field.data_mut();
@@ -303,7 +1052,7 @@ fn carve_rivers(
                     slope += (data[idx] - data[n_idx]).abs();
                 }
             }
-            slope /= 8.0; // Average slope
+            slope *= fine_factor / 8.0; // Average slope
             
             // Height also affects hardness
             let height_factor = (data[idx] + 0.3).max(0.0);
@@ -329,19 +1078,128 @@ fn carve_rivers(
             };
             
             let erosion = carve_depth * river_strength * 0.7;
-            data[i] = (data[i] - erosion).max(0.0);
+            // Cells already below the floor (deep basins) are never raised to it
+            data[i] = (data[i] - erosion).max(floor.min(data[i]));
         }
     }
 }
 
+// Rivers carrying at least this fraction of the largest flow get levees and terraces
+const MAJOR_RIVER_FLOW: f32 = 0.25;
+// How far (cells) from a major channel the valley floor can reach
+const FLOODPLAIN_REACH: f32 = 24.0;
+// Distance (cells) from the channel center to the levee crest (just outside the carved
+// banks), and the crest's half width
+const LEVEE_OFFSET: f32 = 4.0;
+const LEVEE_HALF_WIDTH: f32 = 2.5;
+// Height of one terrace step relative to the river depth
+const TERRACE_RISE: f32 = 0.5;
+// Fraction of each terrace step taken up by the riser; the rest is flat tread
+const TERRACE_RISER: f32 = 0.2;
+
+// Distance to the nearest `channel` cell and that cell's height (two-pass chamfer)
+fn nearest_channel(channel: &[bool], data: &[f32], size: usize) -> (Vec<f32>, Vec<f32>) {
+    const DIAG: f32 = std::f32::consts::SQRT_2;
+    let mut dist: Vec<f32> = channel.iter().map(|&c| if c { 0.0 } else { f32::INFINITY }).collect();
+    let mut bed: Vec<f32> = data.to_vec();
+    let forward = [(-1i32, 0i32, 1.0f32), (0, -1, 1.0), (-1, -1, DIAG), (1, -1, DIAG)];
+    let backward = [(1i32, 0i32, 1.0f32), (0, 1, 1.0), (1, 1, DIAG), (-1, 1, DIAG)];
+    let mut sweep = |ys: Vec<usize>, xs: Vec<usize>, taps: &[(i32, i32, f32)]| {
+        for &y in &ys {
+            for &x in &xs {
+                let idx = y * size + x;
+                for &(dx, dy, cost) in taps {
+                    let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+                    if nx < 0 || ny < 0 || nx >= size as i32 || ny >= size as i32 {
+                        continue;
+                    }
+                    let n_idx = ny as usize * size + nx as usize;
+                    if dist[n_idx] + cost < dist[idx] {
+                        dist[idx] = dist[n_idx] + cost;
+                        bed[idx] = bed[n_idx];
+                    }
+                }
+            }
+        }
+    };
+    sweep((0..size).collect(), (0..size).collect(), &forward);
+    sweep((0..size).rev().collect(), (0..size).rev().collect(), &backward);
+    (dist, bed)
+}
+
+// Natural levees and stepped terraces beside major rivers. Valley-floor cells (close to a
+// major channel and not far above it) are reshaped so their height above the channel bed
+// falls into flat treads separated by short risers, with a low levee ridge along each bank.
+// Returns the terrace age raster (see WaterFeatures::get_terrace_age).
+fn shape_floodplains(
+    height_field: &mut HeightField,
+    river_mask: &[f32],
+    flow_accumulation: &[f32],
+    params: &WaterSystemParams,
+) -> Vec<f32> {
+    let size = height_field.size();
+    let mut terrace_age = vec![0.0f32; size * size];
+    let max_flow = flow_accumulation.iter().fold(0.0f32, |m, &f| m.max(f));
+    if max_flow <= 0.0 || (params.terrace_count == 0 && params.levee_height <= 0.0) {
+        return terrace_age;
+    }
+
+    let channel: Vec<bool> = (0..size * size)
+        .map(|i| river_mask[i] > 0.0 && flow_accumulation[i] >= max_flow * MAJOR_RIVER_FLOW)
+        .collect();
+    let (dist, bed) = nearest_channel(&channel, height_field.data(), size);
+    let rise = (params.river_depth * TERRACE_RISE).max(f32::EPSILON);
+    let levels = params.terrace_count as f32 + 1.0;
+    let data = height_field.data_mut();
+
+    for i in 0..data.len() {
+        let d = dist[i];
+        if channel[i] || d > FLOODPLAIN_REACH || data[i] <= params.sea_level {
+            continue;
+        }
+        let above = data[i] - bed[i];
+        if above < 0.0 || above >= rise * levels {
+            continue; // valley walls and beyond keep their shape
+        }
+
+        // Terrace treads: flat at k·rise, with a smoothstep riser up to the next level
+        let t = above / rise;
+        let k = t.floor();
+        let f = ((t - k - (1.0 - TERRACE_RISER)) / TERRACE_RISER).clamp(0.0, 1.0);
+        let mut height = if params.terrace_count > 0 {
+            bed[i] + (k + f * f * (3.0 - 2.0 * f)) * rise
+        } else {
+            data[i]
+        };
+
+        // Levees are built by overbank deposition, so they sit on the active floodplain only
+        if k == 0.0 {
+            let crest = (1.0 - ((d - LEVEE_OFFSET) / LEVEE_HALF_WIDTH).abs()).max(0.0);
+            height += params.levee_height * crest * crest;
+        }
+        data[i] = height;
+        terrace_age[i] = (k + 1.0) / levels;
+    }
+
+    terrace_age
+}
+
 // Apply coastal erosion
-fn apply_coastal_erosion(height_field: &mut HeightField, beach_mask: &[f32], erosion_amount: f32) {
+// Exposed coasts (high fetch) erode up to 1.5x faster, sheltered bays at half the rate
+fn apply_coastal_erosion(
+    height_field: &mut HeightField,
+    beach_mask: &[f32],
+    erosion_amount: f32,
+    exposure: Option<&[f32]>,
+    floor: f32,
+) {
     let data = height_field.data_mut();
     
     for i in 0..data.len() {
         if beach_mask[i] > 0.0 {
-            let erosion = erosion_amount * beach_mask[i];
-            data[i] = (data[i] - erosion).max(data[i] * 0.3);
+            let wave_factor = exposure.map_or(1.0, |e| 0.5 + e[i]);
+            let erosion = erosion_amount * beach_mask[i] * wave_factor;
+            data[i] = (data[i] - erosion).max(floor.min(data[i]));
         }
     }
 }
@@ -352,24 +1210,46 @@ pub fn apply_water_system(
     params: &WaterSystemParams,
 ) -> WaterFeatures {
     let size = height_field.size();
+    let params = &params.in_cells();
+    
+    // Pre-carve corridors for user-required rivers so flow accumulation follows them
+    let corridor = carve_required_rivers(height_field, &params.river_constraints);
+    let lake_cells = carve_required_lakes(height_field, &params.lake_constraints);
     
     // Calculate flow accumulation
     let flow_accumulation = calculate_flow_accumulation(height_field);
     
     // Generate masks
-    let river_mask = generate_river_mask(height_field, &flow_accumulation, params.river_threshold);
-    let beach_mask = generate_beach_mask(height_field, params.sea_level, params.beach_width);
+    let (river_mask, river_carve) = river_masks(height_field, &flow_accumulation, &corridor, params);
+    let exposure = params
+        .use_wave_exposure
+        .then(|| wave_exposure(height_field, params.sea_level, params.wind_direction));
+    let beach_mask = generate_beach_mask(
+        height_field,
+        params.sea_level,
+        params.beach_width,
+        params.beach_max_slope,
+        exposure.as_deref(),
+    );
     
     // Apply erosion effects
-    carve_rivers(height_field, &river_mask, params.river_depth, params.river_width);
-    apply_coastal_erosion(height_field, &beach_mask, params.coastal_erosion);
+    // Floodplains are measured against the channel before it is cut down
+    let terrace_age = shape_floodplains(height_field, &river_mask, &flow_accumulation, params);
+    let river_floor = params.carve_floor.unwrap_or(params.sea_level - params.river_depth);
+    let coast_floor = params.carve_floor.unwrap_or(params.sea_level - params.coastal_erosion);
+    if params.supersample_carving {
+        carve_supersampled(height_field, &river_carve, |fine, carve| {
+            carve_rivers(fine, carve, params.river_depth, params.river_width * 2.0, river_floor, 2.0)
+        });
+    } else {
+        carve_rivers(height_field, &river_carve, params.river_depth, params.river_width, river_floor, 1.0);
+    }
+    apply_coastal_erosion(height_field, &beach_mask, params.coastal_erosion, exposure.as_deref(), coast_floor);
     
     // Generate final water mask (sea level + rivers)
-    let data = height_field.data();
-    let mut water_mask = vec![0.0f32; size * size];
-    for i in 0..water_mask.len() {
-        let below_sea_level = if data[i] <= params.sea_level { 1.0f32 } else { 0.0f32 };
-        water_mask[i] = below_sea_level.max(river_mask[i]);
+    let mut water_mask = final_water_mask(height_field, &river_mask, params.sea_level);
+    for &idx in &lake_cells {
+        water_mask[idx] = 1.0;
     }
     
     WaterFeatures {
@@ -377,6 +1257,63 @@ pub fn apply_water_system(
         river_mask,
         beach_mask,
         flow_accumulation,
+        terrace_age,
+        surface_age: vec![0.0; size * size],
         size,
     }
-}
\ No newline at end of file
+}
+
+// River mask and the carve strength per cell (the antialiased profile when enabled), with
+// required-river corridors forced on and narrow channels widened
+fn river_masks(height_field: &HeightField, flow: &[f32], corridor: &[usize], params: &WaterSystemParams) -> (Vec<f32>, Vec<f32>) {
+    let size = height_field.size();
+    let river_mask = generate_river_mask(height_field, flow, params.river_threshold);
+    let (mut river_mask, mut river_carve) = if params.antialias_rivers {
+        antialias_river_mask(&river_mask, flow, size, params.river_threshold, params.river_width)
+    } else {
+        (river_mask.clone(), river_mask)
+    };
+    for &idx in corridor {
+        river_mask[idx] = 1.0;
+        river_carve[idx] = 1.0;
+    }
+    if params.min_channel_width > 1.0 {
+        widen_narrow_channels(&mut river_mask, &mut river_carve, size, params.min_channel_width);
+    }
+    (river_mask, river_carve)
+}
+
+// Water wherever the ground is at or below sea level or a river runs
+fn final_water_mask(height_field: &HeightField, river_mask: &[f32], sea_level: f32) -> Vec<f32> {
+    let data = height_field.data();
+    data.iter().zip(river_mask).map(|(&h, &river)| if h <= sea_level { 1.0 } else { river }).collect()
+}
+
+// The masks apply_water_system would find on a height field whose channels and coasts are
+// already cut, e.g. one resampled from an eroded map, without carving it a second time.
+// Terrace and surface age come from the carving history and are left at zero for the caller.
+pub(crate) fn water_masks(height_field: &HeightField, params: &WaterSystemParams) -> WaterFeatures {
+    let size = height_field.size();
+    let params = &params.in_cells();
+    let flow_accumulation = calculate_flow_accumulation(height_field);
+    let (river_mask, _) = river_masks(height_field, &flow_accumulation, &[], params);
+    let exposure = params
+        .use_wave_exposure
+        .then(|| wave_exposure(height_field, params.sea_level, params.wind_direction));
+    let beach_mask = generate_beach_mask(
+        height_field,
+        params.sea_level,
+        params.beach_width,
+        params.beach_max_slope,
+        exposure.as_deref(),
+    );
+    WaterFeatures {
+        water_mask: final_water_mask(height_field, &river_mask, params.sea_level),
+        river_mask,
+        beach_mask,
+        flow_accumulation,
+        terrace_age: vec![0.0; size * size],
+        surface_age: vec![0.0; size * size],
+        size,
+    }
+}
@@ -0,0 +1,232 @@
+use crate::analysis::label_components;
+use crate::height_field::HeightField;
+use crate::polygons::region_polygons;
+use crate::water_system::{river_paths, WaterFeatures};
+use wasm_bindgen::prelude::*;
+
+// Largest distance (cells) a simplified shoreline may stray from the cell boundary
+const OUTLINE_TOLERANCE: f32 = 0.75;
+// Spacing of width/depth samples along a river ribbon (cells of channel)
+const RIBBON_SAMPLE_SPACING: usize = 4;
+// Farthest a width probe looks for the bank on either side of a channel (cells)
+pub(crate) const MAX_HALF_WIDTH: f32 = 16.0;
+
+// A lake or sea as a flat-topped volume: everything inside the outline and below the surface
+#[derive(Clone)]
+pub struct WaterBody {
+    pub surface_height: f32,
+    pub max_depth: f32,
+    pub area: u32, // cells
+    pub is_sea: bool,
+    outline: Vec<f32>,
+    holes: Vec<Vec<f32>>,
+}
+
+impl WaterBody {
+    // Convert to JS object for interop
+    pub fn to_js_object(&self) -> js_sys::Object {
+        let obj = js_sys::Object::new();
+
+        let outline = js_sys::Float32Array::new_with_length(self.outline.len() as u32);
+        outline.copy_from(&self.outline);
+        let holes = js_sys::Array::new();
+        for hole in &self.holes {
+            let ring = js_sys::Float32Array::new_with_length(hole.len() as u32);
+            ring.copy_from(hole);
+            holes.push(&ring);
+        }
+
+        js_sys::Reflect::set(&obj, &"surfaceHeight".into(), &self.surface_height.into()).unwrap();
+        js_sys::Reflect::set(&obj, &"maxDepth".into(), &self.max_depth.into()).unwrap();
+        js_sys::Reflect::set(&obj, &"area".into(), &self.area.into()).unwrap();
+        js_sys::Reflect::set(&obj, &"isSea".into(), &self.is_sea.into()).unwrap();
+        js_sys::Reflect::set(&obj, &"outline".into(), &outline).unwrap();
+        js_sys::Reflect::set(&obj, &"holes".into(), &holes).unwrap();
+
+        obj
+    }
+}
+
+// Simplified water volumes for buoyancy and swimming checks. Polygons are flat x, y pairs in
+// cell units (cell centers on integer coordinates); holes are islands.
+#[wasm_bindgen]
+#[derive(Clone)]
+pub struct WaterVolumes {
+    bodies: Vec<WaterBody>,
+    ribbons: Vec<Vec<f32>>,
+}
+
+#[wasm_bindgen]
+impl WaterVolumes {
+    #[wasm_bindgen(getter)]
+    pub fn body_count(&self) -> usize {
+        self.bodies.len()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn ribbon_count(&self) -> usize {
+        self.ribbons.len()
+    }
+
+    // Lakes and seas, largest first
+    #[wasm_bindgen]
+    pub fn get_bodies(&self) -> js_sys::Array {
+        let bodies = js_sys::Array::new();
+        for body in &self.bodies {
+            bodies.push(&body.to_js_object());
+        }
+        bodies
+    }
+
+    // River centerline from upstream to downstream as flat x, y, surface height, width, depth
+    // samples. Ribbons end at a lake, the sea or the river they join.
+    #[wasm_bindgen]
+    pub fn get_ribbon(&self, index: usize) -> js_sys::Float32Array {
+        let samples = self.ribbons.get(index).map(Vec::as_slice).unwrap_or(&[]);
+        let array = js_sys::Float32Array::new_with_length(samples.len() as u32);
+        array.copy_from(samples);
+        array
+    }
+}
+
+// Standing water (lakes and sea) is split into 8-connected bodies. Seas sit at `sea_level`;
+// a lake's surface is its lowest rim cell, the level it would spill at. Rivers become
+// ribbons along the channels, with the surface taken from the lower bank and never rising
+// downstream.
+pub fn water_volumes(height_field: &HeightField, water_features: &WaterFeatures, sea_level: f32) -> WaterVolumes {
+    let n = height_field.size();
+    let data = height_field.data();
+    let standing = standing_water(height_field, water_features, sea_level);
+    let (labels, count) = label_components(&standing, n);
+
+    let bodies_count = count as usize + 1;
+    let mut area = vec![0u32; bodies_count];
+    let mut floor = vec![f32::INFINITY; bodies_count];
+    let mut top = vec![f32::NEG_INFINITY; bodies_count];
+    let mut rim = vec![f32::INFINITY; bodies_count];
+    let mut is_sea = vec![false; bodies_count];
+    for (idx, &label) in labels.iter().enumerate() {
+        if label == 0 {
+            continue;
+        }
+        let l = label as usize;
+        area[l] += 1;
+        floor[l] = floor[l].min(data[idx]);
+        top[l] = top[l].max(data[idx]);
+        is_sea[l] |= data[idx] <= sea_level;
+        let (x, y) = ((idx % n) as i32, (idx / n) as i32);
+        for dy in -1i32..=1 {
+            for dx in -1i32..=1 {
+                let (nx, ny) = (x + dx, y + dy);
+                if nx < 0 || ny < 0 || nx as usize >= n || ny as usize >= n {
+                    continue;
+                }
+                let n_idx = ny as usize * n + nx as usize;
+                if labels[n_idx] != label {
+                    rim[l] = rim[l].min(data[n_idx]);
+                }
+            }
+        }
+    }
+
+    let mut bodies: Vec<WaterBody> = (1..bodies_count)
+        .map(|l| {
+            let surface_height = if is_sea[l] {
+                sea_level
+            } else if rim[l].is_finite() {
+                rim[l].max(floor[l])
+            } else {
+                top[l]
+            };
+            WaterBody {
+                surface_height,
+                max_depth: (surface_height - floor[l]).max(0.0),
+                area: area[l],
+                is_sea: is_sea[l],
+                outline: Vec::new(),
+                holes: Vec::new(),
+            }
+        })
+        .collect();
+    let polygons = region_polygons(&standing, &labels, count, n, OUTLINE_TOLERANCE);
+    for (body, (outline, holes)) in bodies.iter_mut().zip(polygons) {
+        body.outline = outline;
+        body.holes = holes;
+    }
+    bodies.sort_by_key(|body| std::cmp::Reverse(body.area));
+
+    let ribbons = river_ribbons(height_field, water_features, &standing);
+    WaterVolumes { bodies, ribbons }
+}
+
+// Lake and sea cells: water that isn't river channel, plus river cells already below the sea
+pub(crate) fn standing_water(height_field: &HeightField, water_features: &WaterFeatures, sea_level: f32) -> Vec<bool> {
+    let data = height_field.data();
+    let water_mask = water_features.water_mask();
+    let river_mask = water_features.river_mask();
+    (0..data.len())
+        .map(|i| water_mask[i] > 0.5 && (river_mask[i] <= 0.5 || data[i] <= sea_level))
+        .collect()
+}
+
+// Channel paths from each source down to standing water or a confluence, sampled at a fixed
+// spacing. The longest paths are traced first so main stems stay in one piece.
+fn river_ribbons(height_field: &HeightField, water_features: &WaterFeatures, standing: &[bool]) -> Vec<Vec<f32>> {
+    let n = height_field.size();
+    let river_mask = water_features.river_mask();
+    let flow = water_features.flow_accumulation();
+    let river: Vec<bool> = (0..n * n).map(|i| river_mask[i] > 0.5 && !standing[i]).collect();
+
+    let mut ribbons = Vec::new();
+    for path in river_paths(n, &river, standing, flow) {
+        // Short spurs into another channel are the banks of a wide river, not tributaries
+        if path.len() <= RIBBON_SAMPLE_SPACING && river[path[path.len() - 1]] {
+            continue;
+        }
+        let mut samples = Vec::new();
+        let mut surface_cap = f32::INFINITY;
+        let last = path.len() - 1;
+        for i in (0..last).step_by(RIBBON_SAMPLE_SPACING).chain(std::iter::once(last)) {
+            let (x, y) = (path[i] % n, path[i] / n);
+            let (ax, ay) = (path[i.saturating_sub(1)] % n, path[i.saturating_sub(1)] / n);
+            let (bx, by) = (path[(i + 1).min(last)] % n, path[(i + 1).min(last)] / n);
+            let (dx, dy) = (bx as f32 - ax as f32, by as f32 - ay as f32);
+            let d = (dx * dx + dy * dy).sqrt().max(f32::EPSILON);
+
+            let bed = height_field.get(x, y);
+            let (left, left_bank) = probe_bank(height_field, river_mask, x, y, -dy / d, dx / d);
+            let (right, right_bank) = probe_bank(height_field, river_mask, x, y, dy / d, -dx / d);
+            surface_cap = surface_cap.min(left_bank.min(right_bank));
+            let surface = surface_cap.max(bed);
+
+            samples.extend_from_slice(&[x as f32, y as f32, surface, left + right, surface - bed]);
+        }
+        ribbons.push(samples);
+    }
+    ribbons
+}
+
+// Distance (cells) from a channel cell to the first dry cell along a direction, and that
+// cell's height; the map edge counts as a bank at the channel height
+pub(crate) fn probe_bank(height_field: &HeightField, river_mask: &[f32], x: usize, y: usize, ux: f32, uy: f32) -> (f32, f32) {
+    let n = height_field.size();
+    let mut distance = 0.5;
+    while distance <= MAX_HALF_WIDTH {
+        let px = (x as f32 + ux * distance).round();
+        let py = (y as f32 + uy * distance).round();
+        if px < 0.0 || py < 0.0 || px >= n as f32 || py >= n as f32 {
+            return (distance, height_field.get(x, y));
+        }
+        let (px, py) = (px as usize, py as usize);
+        if river_mask[py * n + px] <= 0.5 {
+            return (distance, height_field.get(px, py));
+        }
+        distance += 0.5;
+    }
+    (MAX_HALF_WIDTH, height_field.get(x, y))
+}
+
+#[wasm_bindgen]
+pub fn extract_water_volumes(height_field: &HeightField, water_features: &WaterFeatures, sea_level: f32) -> WaterVolumes {
+    water_volumes(height_field, water_features, sea_level)
+}
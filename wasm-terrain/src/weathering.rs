@@ -0,0 +1,108 @@
+use crate::biomes::BiomeParams;
+use crate::height_field::HeightField;
+use crate::water_system::WaterFeatures;
+use wasm_bindgen::prelude::*;
+
+// Years of exposure after which an average surface counts as fully weathered
+const WEATHERING_TIMESCALE: f32 = 1000.0;
+// Convexity (height minus the mean of the 8 neighbors, times size²) at which a cell is fully
+// exposed; hollows as deep are fully sheltered
+const EXPOSURE_CURVATURE: f32 = 200.0;
+// How much wetter than the climate baseline the main drainage lines get
+const DRAINAGE_WETNESS: f32 = 0.5;
+
+// Surface weathering for rock texturing. `weathering` (0 fresh - 1 old) says how far a surface
+// has weathered; `moisture` (0-1) says whether that shows as lichen and moss (wet) or desert
+// varnish (dry). Both are 0 / 1 under water.
+#[wasm_bindgen]
+#[derive(Clone)]
+pub struct WeatheringMap {
+    weathering: Vec<f32>,
+    moisture: Vec<f32>,
+    size: usize,
+}
+
+#[wasm_bindgen]
+impl WeatheringMap {
+    #[wasm_bindgen(getter)]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    #[wasm_bindgen]
+    pub fn get_weathering(&self) -> js_sys::Float32Array {
+        let array = js_sys::Float32Array::new_with_length(self.weathering.len() as u32);
+        array.copy_from(&self.weathering);
+        array
+    }
+
+    #[wasm_bindgen]
+    pub fn get_moisture(&self) -> js_sys::Float32Array {
+        let array = js_sys::Float32Array::new_with_length(self.moisture.len() as u32);
+        array.copy_from(&self.moisture);
+        array
+    }
+}
+
+// Weathering advances with surface age (from the erosion history on `water_features`), faster
+// on exposed convex ground and in wet places, slower in sheltered hollows and dry climates.
+// Moisture is the biome's humidity raised along the drainage network.
+pub fn weathering_map(height_field: &HeightField, water_features: &WaterFeatures, biome: &BiomeParams) -> WeatheringMap {
+    let n = height_field.size();
+    let age = water_features.surface_age();
+    let water = water_features.water_mask();
+    let flow = water_features.flow_accumulation();
+    let max_flow = flow.iter().fold(0.0f32, |m, &f| m.max(f));
+    let flow_scale = crate::det_math::ln(1.0 + max_flow).max(f32::MIN_POSITIVE);
+    let humidity = biome.humidity();
+
+    let mut weathering = vec![0.0f32; n * n];
+    let mut moisture = vec![1.0f32; n * n];
+    if age.len() != n * n {
+        return WeatheringMap { weathering, moisture, size: n };
+    }
+    for y in 0..n {
+        for x in 0..n {
+            let idx = y * n + x;
+            if water[idx] > 0.5 {
+                continue;
+            }
+            let wetness = crate::det_math::ln(1.0 + flow[idx]) / flow_scale;
+            let wet = (humidity + (1.0 - humidity) * wetness * DRAINAGE_WETNESS).clamp(0.0, 1.0);
+
+            let mut neighbors = 0.0f32;
+            for (dx, dy) in [(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)] {
+                neighbors += height_field.get_clamped(x as i32 + dx, y as i32 + dy);
+            }
+            let convexity = (height_field.get(x, y) - neighbors / 8.0) * (n * n) as f32;
+            let exposure = (0.5 + convexity / EXPOSURE_CURVATURE).clamp(0.0, 1.0);
+
+            let rate = (0.5 + exposure) * (0.5 + wet);
+            weathering[idx] = 1.0 - crate::det_math::exp(-age[idx] * rate / WEATHERING_TIMESCALE);
+            moisture[idx] = wet;
+        }
+    }
+
+    WeatheringMap { weathering, moisture, size: n }
+}
+
+// Weathering for texturing fresh vs lichen-covered vs varnished rock; run on the features
+// returned by geological erosion, which carry the surface age
+#[wasm_bindgen]
+pub fn compute_weathering(height_field: &HeightField, water_features: &WaterFeatures, biome: &BiomeParams) -> WeatheringMap {
+    weathering_map(height_field, water_features, biome)
+}
+
+#[cfg(all(test, feature = "strict_determinism"))]
+mod tests {
+    use crate::biomes::{BiomeParams, BiomeType};
+    use crate::det_math::tests::{hex, terrain};
+
+    #[test]
+    fn weathering_is_pinned() {
+        let base = terrain(BiomeType::Temperate);
+        let features = base.water_features.as_ref().unwrap();
+        let map = super::weathering_map(&base.height_field, features, &BiomeParams::new(BiomeType::Temperate));
+        assert_eq!(hex(&[&map.weathering, &map.moisture]), "413f9711ff67b05c");
+    }
+}